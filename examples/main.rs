@@ -17,10 +17,8 @@ fn main() {
             drawing::QuickBox::new(buffer.clone())
                 .render(
                     state.window_size,
-                    RectBoundary {
-                        pos: (10, 5),
-                        size: (12, 5),
-                    },
+                    RectBoundary::percent_of(state.window_size, 50, 50)
+                        .centered_in(&RectBoundary::window(state.window_size)),
                 )
                 .unwrap()
                 .1,
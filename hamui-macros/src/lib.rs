@@ -0,0 +1,117 @@
+//! Derive macros for hamui
+//!
+//! Use them through the `hamui-macros` feature of hamui (`hamui::macros::Component`).
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Index, LitInt, Member};
+
+/// Implement `Component` for a struct whose fields are components. Every field is
+/// rendered in its own part of the rect, top to bottom (or left to right with `#[row]`).
+///
+/// Attributes:
+/// * `#[row]` / `#[column]` (on the struct) - lay the fields out left to right / top to bottom (default)
+/// * `#[fixed(n)]` (on a field) - give the field `n` cells, the others share what's left
+/// * `#[skip]` (on a field) - not a component, don't render it (like a `buffer` or some state)
+#[proc_macro_derive(Component, attributes(row, column, fixed, skip))]
+pub fn derive_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match component(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Build the `Component` impl
+fn component(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "Component can only be derived for structs",
+        ));
+    };
+
+    // direction
+    let mut row = false;
+
+    for attr in &input.attrs {
+        if attr.path().is_ident("row") {
+            row = true;
+        } else if attr.path().is_ident("column") {
+            row = false;
+        }
+    }
+
+    // fields
+    let fields: Vec<&syn::Field> = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    };
+
+    let mut members = Vec::new();
+    let mut sizes = Vec::new();
+
+    for (i, field) in fields.into_iter().enumerate() {
+        let mut size = quote!(::std::option::Option::None);
+        let mut skip = false;
+
+        for attr in &field.attrs {
+            if attr.path().is_ident("skip") {
+                skip = true;
+            } else if attr.path().is_ident("fixed") {
+                let cells: LitInt = attr.parse_args()?;
+                cells.base10_parse::<u16>()?;
+                size = quote!(::std::option::Option::Some(#cells));
+            }
+        }
+
+        if skip {
+            continue;
+        }
+
+        members.push(match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        });
+        sizes.push(size);
+    }
+
+    let split = if row {
+        quote!(columns_sized)
+    } else {
+        quote!(rows_sized)
+    };
+    let indices = 0..members.len();
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::hamui::drawing::Component for #name #ty_generics #where_clause {
+            fn render(
+                &mut self,
+                window_size: ::hamui::drawing::Vec2,
+                rect: ::hamui::drawing::RectBoundary,
+            ) -> ::hamui::drawing::DrawingResult {
+                #[allow(unused_variables)]
+                let rects = rect.#split(&[#(#sizes),*]);
+                #[allow(unused_mut)]
+                let mut changes = ::std::vec::Vec::new();
+
+                #(
+                    changes.extend(
+                        ::hamui::drawing::Component::render(
+                            &mut self.#members,
+                            window_size,
+                            rects[#indices].clone(),
+                        )?
+                        .1,
+                    );
+                )*
+
+                Ok((rect, changes))
+            }
+        }
+    })
+}
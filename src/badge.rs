@@ -0,0 +1,274 @@
+//! Badges and chips
+//!
+//! Small colored labels for statuses (`Badge`) and removable tags like active filters
+//! ([`Chip`], laid out by [`Chips`], which flows them across rows like words).
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{
+    Creatable, DrawingResult, RectBoundary, TextBackgroundColor, TextColor, Vec2,
+};
+use crate::style::Style;
+use crate::text::display_width;
+use crate::State;
+
+/// Get the text color matching a background color (for the rounded ends)
+fn fg_of(bg: TextBackgroundColor) -> TextColor {
+    match bg {
+        TextBackgroundColor::Black => TextColor::Black,
+        TextBackgroundColor::Red => TextColor::Red,
+        TextBackgroundColor::Green => TextColor::Green,
+        TextBackgroundColor::Yellow => TextColor::Yellow,
+        TextBackgroundColor::Blue => TextColor::Blue,
+        TextBackgroundColor::Magenta => TextColor::Magenta,
+        TextBackgroundColor::Cyan => TextColor::Cyan,
+        TextBackgroundColor::White => TextColor::White,
+        TextBackgroundColor::BrightBlack => TextColor::BrightBlack,
+        TextBackgroundColor::BrightRed => TextColor::BrightRed,
+        TextBackgroundColor::BrightGreen => TextColor::BrightGreen,
+        TextBackgroundColor::BrightYellow => TextColor::BrightYellow,
+        TextBackgroundColor::BrightBlue => TextColor::BrightBlue,
+        TextBackgroundColor::BrightMagenta => TextColor::BrightMagenta,
+        TextBackgroundColor::BrightCyan => TextColor::BrightCyan,
+        TextBackgroundColor::BrightWhite => TextColor::BrightWhite,
+    }
+}
+
+/// Draw `text` as a pill: ` text ` on the background of `style`, with half blocks
+/// in the background color at both ends (when `rounded`) so it looks rounded
+fn write_pill(
+    buffer: &mut PseudoBuffer,
+    pos: Vec2,
+    text: &str,
+    style: Style,
+    rounded: bool,
+) -> std::io::Result<u16> {
+    let mut x = pos.0;
+
+    let cap = style
+        .bg
+        .filter(|_| rounded)
+        .map(|bg| Style::DEFAULT.fg(fg_of(bg)));
+
+    if let Some(cap) = cap {
+        buffer.write_str_styled((x, pos.1), "▐", cap)?;
+        x += 1;
+    }
+
+    buffer.write_str_styled((x, pos.1), &format!(" {text} "), style)?;
+    x += display_width(text) as u16 + 2;
+
+    if let Some(cap) = cap {
+        buffer.write_str_styled((x, pos.1), "▌", cap)?;
+        x += 1;
+    }
+
+    Ok(x - pos.0)
+}
+
+/// Get the width of a pill drawn by [`write_pill`]
+fn pill_width(text: &str, style: Style, rounded: bool) -> u16 {
+    let caps = if rounded && style.bg.is_some() { 2 } else { 0 };
+    display_width(text) as u16 + 2 + caps
+}
+
+/// Colored status label: `▐ done ▌`
+pub struct Badge {
+    pub buffer: PseudoBuffer,
+    pub style: Style,
+    /// Draw rounded ends (half blocks in the background color)
+    pub rounded: bool,
+}
+
+impl Creatable for Badge {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Badge {
+            buffer,
+            style: Style::DEFAULT
+                .fg(TextColor::Black)
+                .bg(TextBackgroundColor::Cyan),
+            rounded: true,
+        }
+    }
+}
+
+impl Badge {
+    /// Get the width of a badge with `text`
+    pub fn width(&self, text: &str) -> u16 {
+        pill_width(text, self.style, self.rounded)
+    }
+
+    /// Draw a badge at a given [`Vec2`]
+    pub fn render(&mut self, pos: Vec2, text: &str) -> DrawingResult {
+        let width = write_pill(&mut self.buffer, pos, text, self.style, self.rounded)?;
+
+        // done
+        Ok((
+            RectBoundary::new(pos, (width, 1)),
+            self.buffer.get_changes(),
+        ))
+    }
+}
+
+/// Tag in a [`Chips`] row
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chip {
+    pub label: String,
+    pub style: Style,
+    /// Show a `×` to remove the chip
+    pub closable: bool,
+}
+
+impl Chip {
+    /// Create a new [`Chip`] (black on white, not closable)
+    pub fn new(label: &str) -> Self {
+        Chip {
+            label: label.to_string(),
+            style: Style::DEFAULT
+                .fg(TextColor::Black)
+                .bg(TextBackgroundColor::BrightWhite),
+            closable: false,
+        }
+    }
+
+    /// Set the style
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Show a `×` to remove the chip
+    pub fn closable(mut self) -> Self {
+        self.closable = true;
+        self
+    }
+
+    /// Get the text drawn inside of the chip
+    fn text(&self) -> String {
+        if self.closable {
+            format!("{} ×", self.label)
+        } else {
+            self.label.clone()
+        }
+    }
+}
+
+/// Where a click on [`Chips`] landed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChipClick {
+    /// On the chip at the index
+    Chip(usize),
+    /// On the `×` of the chip at the index
+    Close(usize),
+}
+
+/// Chips flowing left to right, wrapping into the next row when they don't fit
+pub struct Chips {
+    pub buffer: PseudoBuffer,
+    /// Draw rounded ends (half blocks in the background color)
+    pub rounded: bool,
+    /// Cells between chips
+    pub gap: u16,
+}
+
+impl Creatable for Chips {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Chips {
+            buffer,
+            rounded: true,
+            gap: 1,
+        }
+    }
+}
+
+impl Chips {
+    /// Get the rect of every chip which fits in `rect`
+    pub fn chip_rects(&self, rect: &RectBoundary, chips: &[Chip]) -> Vec<RectBoundary> {
+        let mut pos = rect.pos;
+        let mut rects = Vec::new();
+
+        for chip in chips {
+            let width = pill_width(&chip.text(), chip.style, self.rounded).min(rect.size.0);
+
+            // wrap (unless the row is still empty)
+            if (pos.0 > rect.pos.0) && (pos.0 + width > rect.right()) {
+                pos = (rect.pos.0, pos.1 + 1);
+            }
+
+            if pos.1 >= rect.pos.1 + rect.size.1 {
+                break;
+            }
+
+            rects.push(RectBoundary::new(pos, (width, 1)));
+            pos.0 = pos.0.saturating_add(width + self.gap);
+        }
+
+        rects
+    }
+
+    /// Get the rect of the `×` of a closable chip drawn at `rect`
+    pub fn close_rect(&self, rect: &RectBoundary, chip: &Chip) -> Option<RectBoundary> {
+        let cap = if self.rounded && chip.style.bg.is_some() {
+            1
+        } else {
+            0
+        };
+
+        chip.closable
+            .then(|| RectBoundary::new((rect.right().saturating_sub(2 + cap), rect.pos.1), (1, 1)))
+    }
+
+    /// Handle a click on the chips
+    pub fn handle_click(
+        &self,
+        state: &State,
+        rect: &RectBoundary,
+        chips: &[Chip],
+    ) -> Option<ChipClick> {
+        let click = state.click.as_ref()?;
+
+        let (i, chip_rect) = self
+            .chip_rects(rect, chips)
+            .into_iter()
+            .enumerate()
+            .find(|(_, r)| r.contains(click.pos))?;
+
+        match self.close_rect(&chip_rect, &chips[i]) {
+            Some(close) if close.contains(click.pos) => Some(ChipClick::Close(i)),
+            _ => Some(ChipClick::Chip(i)),
+        }
+    }
+
+    /// Draw the chips. Returns the rect they used (every row with chips).
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `chips`
+    pub fn render(&mut self, rect: RectBoundary, chips: &[Chip]) -> DrawingResult {
+        let rects = self.chip_rects(&rect, chips);
+
+        for (chip, chip_rect) in chips.iter().zip(&rects) {
+            let before = self.buffer.get_changes().len();
+            write_pill(
+                &mut self.buffer,
+                chip_rect.pos,
+                &chip.text(),
+                chip.style,
+                self.rounded,
+            )?;
+
+            // (chips wider than the rect are cut off)
+            let mut changes = self.buffer.get_changes();
+            let mut drawn = changes.split_off(before);
+            drawn.retain(|c| chip_rect.contains(c.loc));
+            changes.extend(drawn);
+            self.buffer.set_changes(changes);
+        }
+
+        let height = rects.last().map_or(0, |r| r.pos.1 + 1 - rect.pos.1);
+
+        // done
+        Ok((
+            RectBoundary::new(rect.pos, (rect.size.0, height)),
+            self.buffer.get_changes(),
+        ))
+    }
+}
@@ -0,0 +1,188 @@
+//! Breadcrumb widget
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{Creatable, DrawingResult, RectBoundary, TextAttribute};
+use crate::style::Style;
+use crate::text::{display_width, truncate_to_width, TruncateAt};
+use crate::State;
+
+/// Item of a [`Breadcrumb`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CrumbItem {
+    /// Screen at this level of the stack (0 is the root)
+    Level(usize),
+    /// `…` (levels which don't fit)
+    Gap,
+}
+
+/// Screen stack as clickable segments: `Home › Settings › Network`.
+/// The application keeps the stack (like a `Vec` of its screens) and pops back
+/// to the level [`Breadcrumb::handle_click`] returns (see [`Breadcrumb::pop_to`]).
+pub struct Breadcrumb {
+    pub buffer: PseudoBuffer,
+    pub style: Style,
+    /// Style of the current (last) level
+    pub current_style: Style,
+    /// Drawn between levels
+    pub separator: String,
+}
+
+impl Creatable for Breadcrumb {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Breadcrumb {
+            buffer,
+            style: Style::DEFAULT.attr(TextAttribute::Underline),
+            current_style: Style::DEFAULT.attr(TextAttribute::Bold),
+            separator: " › ".to_string(),
+        }
+    }
+}
+
+impl Breadcrumb {
+    /// Get the items to draw in `width` cells. The root and the current level are
+    /// always shown; levels after the root collapse into a gap until the rest fits.
+    pub fn items<S: AsRef<str>>(&self, levels: &[S], width: u16) -> Vec<CrumbItem> {
+        let separator = display_width(&self.separator);
+        let level_width = |i: usize| display_width(levels[i].as_ref());
+
+        let mut hidden = 0;
+
+        loop {
+            let mut items = Vec::new();
+            let mut total = 0;
+
+            for i in 0..levels.len() {
+                if (i > 0) && (i <= hidden) && (i + 1 < levels.len()) {
+                    if i == 1 {
+                        items.push(CrumbItem::Gap);
+                        total += 1 + separator;
+                    }
+
+                    continue;
+                }
+
+                items.push(CrumbItem::Level(i));
+                total += level_width(i) + if i > 0 { separator } else { 0 };
+            }
+
+            // (the current level is cut off if it doesn't fit on its own)
+            if (total <= width as usize) || (hidden + 2 >= levels.len()) {
+                return items;
+            }
+
+            hidden += 1;
+        }
+    }
+
+    /// Get every item with its text and rect
+    pub fn item_rects<S: AsRef<str>>(
+        &self,
+        rect: &RectBoundary,
+        levels: &[S],
+    ) -> Vec<(CrumbItem, String, RectBoundary)> {
+        let separator = display_width(&self.separator) as u16;
+        let mut x = rect.pos.0;
+        let mut rects = Vec::new();
+
+        for item in self.items(levels, rect.size.0) {
+            let text = match item {
+                CrumbItem::Level(i) => levels[i].as_ref().to_string(),
+                CrumbItem::Gap => "…".to_string(),
+            };
+
+            if !rects.is_empty() {
+                x = x.saturating_add(separator);
+            }
+
+            let width = display_width(&text) as u16;
+            rects.push((item, text, RectBoundary::new((x, rect.pos.1), (width, 1))));
+            x = x.saturating_add(width);
+        }
+
+        rects
+    }
+
+    /// Handle a click on the breadcrumb. Returns the level to pop back to
+    /// (`None` if the click missed, or hit the current level).
+    pub fn handle_click<S: AsRef<str>>(
+        &self,
+        state: &State,
+        rect: &RectBoundary,
+        levels: &[S],
+    ) -> Option<usize> {
+        let click = state.click.as_ref().filter(|c| rect.contains(c.pos))?;
+
+        let (item, _, _) = self
+            .item_rects(rect, levels)
+            .into_iter()
+            .find(|(_, _, r)| r.contains(click.pos))?;
+
+        match item {
+            CrumbItem::Level(level) if level + 1 < levels.len() => Some(level),
+            _ => None,
+        }
+    }
+
+    /// Handle a key event (Backspace goes back one level, Home to the root).
+    /// Returns the level to pop back to.
+    pub fn handle_key(event: KeyEvent, levels: usize) -> Option<usize> {
+        match event.code {
+            KeyCode::Backspace if levels > 1 => Some(levels - 2),
+            KeyCode::Home if levels > 1 => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Like [`Breadcrumb::handle_key`], with the key of this frame translated by the
+    /// navigation preset (see [`crate::State::nav_key`])
+    pub fn handle_nav_key(state: &mut crate::State, levels: usize) -> Option<usize> {
+        Breadcrumb::handle_key(state.nav_key()?, levels)
+    }
+
+    /// Pop `stack` back to `level` (the screen at `level` is the current one again)
+    pub fn pop_to<T>(stack: &mut Vec<T>, level: usize) {
+        stack.truncate(level + 1);
+    }
+
+    /// Draw the breadcrumb
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, _)
+    /// * `levels` - title of every screen in the stack (the root first)
+    pub fn render<S: AsRef<str>>(&mut self, rect: RectBoundary, levels: &[S]) -> DrawingResult {
+        let mut width = 0;
+
+        for (i, (item, text, item_rect)) in self.item_rects(&rect, levels).into_iter().enumerate() {
+            if i > 0 {
+                let separator = display_width(&self.separator) as u16;
+                let pos = (item_rect.pos.0.saturating_sub(separator), rect.pos.1);
+
+                if pos.0 + separator > rect.right() {
+                    break;
+                }
+
+                self.buffer
+                    .write_str_styled(pos, &self.separator, Style::DEFAULT)?;
+            }
+
+            let style = match item {
+                CrumbItem::Level(level) if level + 1 == levels.len() => self.current_style,
+                CrumbItem::Level(_) => self.style,
+                CrumbItem::Gap => Style::DEFAULT,
+            };
+
+            let room = rect.right().saturating_sub(item_rect.pos.0) as usize;
+            let text = truncate_to_width(&text, room, TruncateAt::End);
+
+            self.buffer.write_str_styled(item_rect.pos, &text, style)?;
+            width = item_rect.pos.0 + display_width(&text) as u16 - rect.pos.0;
+        }
+
+        // done
+        Ok((
+            RectBoundary::new(rect.pos, (width, 1)),
+            self.buffer.get_changes(),
+        ))
+    }
+}
@@ -3,9 +3,18 @@
 //! Write are written to the buffer first and then only the needed area is updated.
 use crossterm::cursor;
 use crossterm::QueueableCommand;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::io::{Result as IOResult, Stdout, Write};
+use std::sync::{Arc, Mutex};
 
-use super::drawing::Vec2;
+use super::drawing::{DrawingResult, Line, RectBoundary, Vec2};
+use super::i18n::{English, Locale};
+use super::sprite::Sprite;
+use super::style::Style;
+use super::text::char_width;
+use super::theme::Theme;
 
 // extras
 pub enum BufState {
@@ -17,19 +26,29 @@ pub enum BufState {
 pub struct BufCell {
     pub char: char,
     pub empty: bool,
+    pub style: Style,
 }
 
 impl BufCell {
+    /// Nothing. Writing it erases the cell (see [`BufferWrite::erase`]).
     pub const EMPTY: BufCell = BufCell {
         char: ' ',
         empty: true,
+        style: Style::DEFAULT,
     };
 
     /// Create [`BufCell`] from a [`char`]
     pub fn from_char(char: char) -> BufCell {
+        BufCell::styled(char, Style::DEFAULT)
+    }
+
+    /// Create [`BufCell`] from a [`char`] and a [`Style`].
+    /// Styled spaces are not empty (they still show their background).
+    pub fn styled(char: char, style: Style) -> BufCell {
         BufCell {
             char,
-            empty: char == ' ',
+            empty: (char == ' ') && (style == Style::DEFAULT),
+            style,
         }
     }
 
@@ -43,10 +62,102 @@ impl BufCell {
 
 pub type Row = Vec<BufCell>;
 
+/// Cells of a screen, stored row by row in one flat vector (cell `(x, y)` is at `y * width + x`)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Cells {
+    size: Vec2,
+    cells: Vec<BufCell>,
+}
+
+impl Cells {
+    /// Create new empty cells
+    pub fn new(size: Vec2) -> Cells {
+        Cells {
+            size,
+            cells: vec![BufCell::EMPTY; size.0 as usize * size.1 as usize],
+        }
+    }
+
+    /// Create cells from rows (rows are cut or padded to fit `size`)
+    pub fn from_rows(rows: Vec<Row>, size: Vec2) -> Cells {
+        let mut cells = Cells::new(size);
+
+        for (y, row) in rows.into_iter().take(size.1 as usize).enumerate() {
+            for (x, cell) in row.into_iter().take(size.0 as usize).enumerate() {
+                cells.cells[y * size.0 as usize + x] = cell;
+            }
+        }
+
+        cells
+    }
+
+    /// Get the size as (width, height)
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Get the index of a cell (`None` if it's outside)
+    fn index(&self, pos: Vec2) -> Option<usize> {
+        ((pos.0 < self.size.0) && (pos.1 < self.size.1))
+            .then_some(pos.1 as usize * self.size.0 as usize + pos.0 as usize)
+    }
+
+    /// Get a cell
+    pub fn get(&self, pos: Vec2) -> Option<&BufCell> {
+        self.cells.get(self.index(pos)?)
+    }
+
+    /// Get a mutable cell
+    pub fn get_mut(&mut self, pos: Vec2) -> Option<&mut BufCell> {
+        let i = self.index(pos)?;
+        self.cells.get_mut(i)
+    }
+
+    /// Get the cells of a row
+    pub fn get_row(&self, y: usize) -> Option<&[BufCell]> {
+        let width = self.size.0 as usize;
+        (y < self.size.1 as usize).then(|| &self.cells[(y * width)..((y + 1) * width)])
+    }
+
+    /// Get the mutable cells of a row
+    pub fn get_row_mut(&mut self, y: usize) -> Option<&mut [BufCell]> {
+        let width = self.size.0 as usize;
+        (y < self.size.1 as usize).then(|| &mut self.cells[(y * width)..((y + 1) * width)])
+    }
+
+    /// Iterate over the rows, top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[BufCell]> {
+        (0..self.size.1 as usize).filter_map(|y| self.get_row(y))
+    }
+
+    /// Copy the cells into separate rows
+    pub fn to_rows(&self) -> Vec<Row> {
+        self.rows().map(|row| row.to_vec()).collect()
+    }
+
+    /// Set every cell to `cell`
+    pub fn fill(&mut self, cell: BufCell) {
+        self.cells.fill(cell);
+    }
+}
+
+/// Get the cells of a row which are printed: the cell after a wide character
+/// is covered by it, so it's skipped
+pub fn visible_cells(row: &[BufCell]) -> impl Iterator<Item = &BufCell> {
+    let mut covered = false;
+
+    row.iter().filter(move |cell| {
+        let visible = !covered;
+        covered = visible && (char_width(cell.char) > 1);
+        visible
+    })
+}
+
 // traits
 pub trait BufferWrite {
     /// Write changes to the buffer.
-    /// If `pos` is greater than what the buffer supports, `Err` is returned.
+    /// If `pos` is outside of the buffer, `Err` is returned
+    /// (buffers which only collect changes, like [`PseudoBuffer`], drop the cell instead).
     ///
     /// ## Arguments
     /// * `pos` - [`Vec2`]
@@ -54,28 +165,140 @@ pub trait BufferWrite {
     fn write_cell(&mut self, pos: Vec2, buf: BufCell) -> IOResult<BufState>;
     /// Like [`write`], but with a str
     fn write_str(&mut self, pos: Vec2, buf: &str) -> IOResult<BufState> {
-        let chars = buf.chars().collect::<Vec<char>>();
-
-        for i in 0..chars.len() {
-            // get pos
-            let pos = (pos.0 + (i as u16), pos.1);
+        self.write_str_styled(pos, buf, Style::DEFAULT)
+    }
+    /// Like [`write_str`], but every cell gets `style`
+    fn write_str_styled(&mut self, pos: Vec2, buf: &str, style: Style) -> IOResult<BufState> {
+        let mut x = pos.0;
 
+        for char in buf.chars() {
             // write char
-            self.write_cell(pos, BufCell::from_char(chars.get(i).unwrap().to_owned()))?;
+            self.write_cell((x, pos.1), BufCell::styled(char, style))?;
+
+            // wide characters cover the next cell too
+            x += char_width(char).max(1) as u16;
         }
 
         Ok(BufState::Ok)
     }
+    /// Like [`write_str`], but with a [`Line`] of styled spans
+    fn write_line(&mut self, pos: Vec2, line: &Line) -> IOResult<BufState> {
+        let mut x = pos.0;
+
+        for span in &line.spans {
+            self.write_str_styled((x, pos.1), &span.text, span.style)?;
+            x += span
+                .text
+                .chars()
+                .map(|c| char_width(c).max(1) as u16)
+                .sum::<u16>();
+        }
+
+        Ok(BufState::Ok)
+    }
+    /// Fill every cell of a [`RectBoundary`] with `buf` (to paint a background, for example)
+    fn fill_rect(&mut self, rect: &RectBoundary, buf: BufCell) -> IOResult<BufState> {
+        for y in rect.pos.1..rect.bottom() {
+            for x in rect.pos.0..rect.right() {
+                self.write_cell((x, y), buf.clone())?;
+            }
+        }
+
+        Ok(BufState::Ok)
+    }
+    /// Erase a cell, so whatever was drawn there before is removed from the screen
+    fn erase(&mut self, pos: Vec2) -> IOResult<BufState> {
+        self.write_cell(pos, BufCell::EMPTY)
+    }
+    /// Erase every cell of a [`RectBoundary`]
+    fn clear_rect(&mut self, rect: &RectBoundary) -> IOResult<BufState> {
+        self.fill_rect(rect, BufCell::EMPTY)
+    }
+}
+
+/// What happens to the content of a [`Buffer`] when it's resized
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ResizePolicy {
+    /// Keep the top left, cut off the bottom and the right
+    #[default]
+    AnchorTopLeft,
+    /// Keep the bottom (console style apps), cut off the top and the right
+    AnchorBottom,
+    /// Wrap cut off cells onto the next rows, then keep the bottom
+    /// (rows pushed off the top are like scrollback)
+    Reflow,
+}
+
+/// Content which didn't fit after a resize
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResizeLoss {
+    /// Rows which were cut off (or scrolled away), top to bottom
+    pub rows: Vec<Row>,
+    /// Number of cells cut off the right edge of rows which were kept
+    pub cells: usize,
+}
+
+impl ResizeLoss {
+    /// Check if nothing was lost
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty() && (self.cells == 0)
+    }
+}
+
+/// What a commit (or [`crate::Frame::step`]) did
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderStats {
+    /// Number of cells which changed on screen
+    pub cells_changed: usize,
+    /// Number of rows which were rewritten
+    pub rows_touched: usize,
+    /// Number of bytes written to the terminal
+    pub bytes_written: usize,
+    /// How long it took
+    pub duration: std::time::Duration,
+}
+
+impl RenderStats {
+    /// Check if nothing changed on screen
+    pub fn is_empty(&self) -> bool {
+        self.cells_changed == 0
+    }
+}
+
+/// Output shared by several buffers (see [`Buffer::share_output`])
+#[derive(Clone)]
+pub struct SharedOutput(Arc<Mutex<Box<dyn Write + Send>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> IOResult<usize> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).write(buf)
+    }
+
+    fn flush(&mut self) -> IOResult<()> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).flush()
+    }
 }
 
 // main buffer
 pub struct Buffer {
-    stdout: Stdout,
+    /// Where everything is written (the terminal, usually)
+    stdout: Box<dyn Write + Send>,
     pub size: Vec2,
-    /// Vector of [`Row`]s, pre commit
-    pub vec: Vec<Row>,
-    /// Vector of [`Row`]s, what's on screen
-    pub screen_vec: Vec<Row>,
+    /// Cells, pre commit
+    pub vec: Cells,
+    /// Which cells of `vec` were written since the last commit (cells which
+    /// weren't are left alone, written empty cells erase what's on screen)
+    written: Vec<bool>,
+    /// Cells, what's on screen
+    pub screen_vec: Cells,
+    /// If extended underline styles/colors are written (plain underlines are used if not)
+    pub extended_underline: bool,
+    /// What happens to the content when the buffer is resized
+    pub resize_policy: ResizePolicy,
+    /// Regions nothing is written to (see [`Buffer::lock_region`])
+    locked: Vec<RectBoundary>,
+    /// Line being written by [`Buffer::commit`] (kept so it isn't allocated for every row)
+    line: String,
 }
 
 impl Buffer {
@@ -86,27 +309,50 @@ impl Buffer {
     /// * `stdout`: [`Stdout`]
     /// * `size`: [`Vec2`]
     pub fn new(stdout: Stdout, size: Vec2) -> Buffer {
-        let mut vec = Vec::new();
-        vec.resize(size.1 as usize, BufCell::as_row(size.0));
+        Buffer::with_output(stdout, size)
+    }
+
+    /// Create a new buffer which writes to anything (like a [`crate::testing::TestBackend`])
+    ///
+    /// ## Arguments
+    /// * `output`
+    /// * `size`: [`Vec2`]
+    pub fn with_output(output: impl Write + Send + 'static, size: Vec2) -> Buffer {
+        let vec = Cells::new(size);
 
         // ...
         Buffer {
-            stdout,
+            stdout: Box::new(output),
             size,
+            written: vec![false; size.0 as usize * size.1 as usize],
             vec: vec.clone(),
             screen_vec: vec.clone(),
+            extended_underline: super::style::supports_extended_underline(),
+            resize_policy: ResizePolicy::default(),
+            locked: Vec::new(),
+            line: String::new(),
         }
     }
 
+    /// Share the output with another buffer (like the one of a
+    /// [`crate::render::RenderThread`]), so both write to the same place
+    pub fn share_output(&mut self) -> SharedOutput {
+        let output = std::mem::replace(&mut self.stdout, Box::new(std::io::sink()));
+        let shared = SharedOutput(Arc::new(Mutex::new(output)));
+
+        self.stdout = Box::new(shared.clone());
+        shared
+    }
+
     /// Stdout thing
-    pub fn queue(&mut self, cmd: impl crossterm::Command) -> IOResult<&mut Stdout> {
-        self.stdout.queue(cmd)
+    pub fn queue(&mut self, cmd: impl crossterm::Command) -> IOResult<&mut (dyn Write + Send)> {
+        Ok(self.stdout.queue(cmd)?.as_mut())
     }
 
     /// Get a cell in the `screen_vec` using its [`Vec2`] position
     pub fn get_cell(&mut self, pos: Vec2) -> IOResult<BufCell> {
         // get row
-        let row = self.screen_vec.get_mut(pos.1 as usize);
+        let row = self.screen_vec.get_row(pos.1 as usize);
 
         if row.is_none() {
             return Err(std::io::Error::new(
@@ -131,39 +377,229 @@ impl Buffer {
         Ok(col.unwrap().to_owned())
     }
 
-    /// Resize a single vector to match screen size
-    fn resize_vec(&mut self, mut vec: Vec<Row>, size: Vec2) -> IOResult<Vec<Row>> {
+    /// Resize cells to match screen size (following `resize_policy`)
+    fn resize_vec(&self, cells: &Cells, size: Vec2) -> (Cells, ResizeLoss) {
+        let mut loss = ResizeLoss::default();
+        let mut vec = cells.to_rows(); // moving rows around is easier with separate rows
+
         // resize x
-        let rows_to_edit = 0..vec.len();
+        if self.resize_policy == ResizePolicy::Reflow {
+            // wrap cut off cells onto new rows
+            let mut wrapped = Vec::new();
+
+            for row in vec {
+                let len = row.iter().rposition(|c| !c.empty).map_or(0, |i| i + 1);
 
-        for i in rows_to_edit {
-            let r = vec.get_mut(i).unwrap();
-            r.resize(size.0 as usize, BufCell::EMPTY);
+                if (len <= size.0 as usize) || (size.0 == 0) {
+                    wrapped.push(row);
+                    continue;
+                }
+
+                for chunk in row[..len].chunks(size.0 as usize) {
+                    wrapped.push(chunk.to_vec());
+                }
+            }
+
+            vec = wrapped;
+        }
+
+        for row in vec.iter_mut() {
+            loss.cells += row
+                .iter()
+                .skip(size.0 as usize)
+                .filter(|c| !c.empty)
+                .count();
+            row.resize(size.0 as usize, BufCell::EMPTY);
         }
 
         // resize y
-        vec.resize(size.1 as usize, BufCell::as_row(size.0));
+        let height = size.1 as usize;
+
+        match self.resize_policy {
+            ResizePolicy::AnchorTopLeft => {
+                loss.rows = vec.split_off(height.min(vec.len()));
+            }
+            ResizePolicy::AnchorBottom | ResizePolicy::Reflow => {
+                if vec.len() > height {
+                    // scroll the top rows away
+                    let rest = vec.split_off(vec.len() - height);
+                    loss.rows = std::mem::replace(&mut vec, rest);
+                } else {
+                    // new rows go on top
+                    let mut rows = vec![BufCell::as_row(size.0); height - vec.len()];
+                    rows.append(&mut vec);
+                    vec = rows;
+                }
+            }
+        }
+
+        vec.resize(height, BufCell::as_row(size.0));
+
+        // forget rows which were empty anyway
+        loss.rows.retain(|row| row.iter().any(|c| !c.empty));
 
         // return
-        Ok(vec)
+        (Cells::from_rows(vec, size), loss)
     }
 
-    /// Resize buffer with a [`Vec2`].
+    /// Resize buffer with a [`Vec2`], following `resize_policy`.
     /// If larger than current buffer, new characters are added as whitespace.
+    /// The content which is kept is drawn again by the next commit.
+    /// Returns the on screen content which didn't fit.
     ///
     /// ## Arguments
     /// * `size`: [`Vec2`]
-    pub fn resize(&mut self, size: Vec2) -> IOResult<BufState> {
-        self.vec = self.resize_vec(self.vec.clone(), size)?;
-        self.screen_vec = self.resize_vec(self.screen_vec.clone(), size)?;
+    pub fn resize(&mut self, size: Vec2) -> IOResult<ResizeLoss> {
+        self.vec = self.resize_vec(&self.vec, size).0;
+        self.written = vec![false; size.0 as usize * size.1 as usize];
+
+        // pending cells moved with the content (erases are lost)
+        for (i, cell) in self.vec.rows().flatten().enumerate() {
+            self.written[i] = !cell.empty;
+        }
+
+        let (kept, loss) = self.resize_vec(&self.screen_vec, size);
+        self.screen_vec = Cells::new(size);
 
         // ...
         self.size = size; // update size
+
+        // terminals move (or drop) their content on their own when resized,
+        // so what's on screen isn't known anymore
+        self.invalidate()?;
+
+        // draw what was kept again (under the pending cells)
+        for (i, cell) in kept.rows().flatten().enumerate() {
+            if !self.written[i] && !cell.empty {
+                self.written[i] = true;
+                self.vec.cells[i] = cell.clone();
+            }
+        }
+
+        Ok(loss)
+    }
+
+    /// Reserve the cells of `rect` for content drawn outside of the buffer (like an inline
+    /// image or an embedded terminal). Writes to them are ignored and commits don't touch
+    /// them, so nothing flickers over that content. Clearing the whole screen still erases it.
+    pub fn lock_region(&mut self, rect: RectBoundary) {
+        self.locked.push(rect);
+    }
+
+    /// Give back the cells of a region locked with [`Buffer::lock_region`].
+    /// They're erased, so the next commit draws whatever goes there now.
+    pub fn unlock_region(&mut self, rect: &RectBoundary) -> IOResult<BufState> {
+        let Some(i) = self.locked.iter().position(|r| r == rect) else {
+            return Ok(BufState::Ok);
+        };
+
+        self.locked.remove(i);
+
+        // erase what the owner drew
+        for y in rect.pos.1..rect.bottom().min(self.size.1) {
+            let width = rect.right().min(self.size.0).saturating_sub(rect.pos.0);
+
+            if width == 0 {
+                break;
+            }
+
+            self.stdout.queue(cursor::MoveTo(rect.pos.0, y))?;
+            self.stdout
+                .write_all(" ".repeat(width as usize).as_bytes())?;
+
+            for x in rect.pos.0..(rect.pos.0 + width) {
+                if let Some(cell) = self.screen_vec.get_mut((x, y)) {
+                    *cell = BufCell::EMPTY;
+                }
+            }
+        }
+
+        Ok(BufState::Ok)
+    }
+
+    /// Check if a cell is in a locked region
+    pub fn is_locked(&self, pos: Vec2) -> bool {
+        self.locked.iter().any(|r| r.contains(pos))
+    }
+
+    /// Clear the screen and forget what's on it, so the next commit draws everything again
+    pub fn invalidate(&mut self) -> IOResult<BufState> {
+        self.queue(crossterm::terminal::Clear(
+            crossterm::terminal::ClearType::All,
+        ))?;
+        self.screen_vec.fill(BufCell::EMPTY);
         Ok(BufState::Ok)
     }
 
+    /// Take the cells written since the last commit, without committing them
+    pub fn take_pending(&mut self) -> Vec<BufferChange> {
+        let mut changes = Vec::new();
+
+        for (y, row) in self.vec.rows().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if self.written[y * self.size.0 as usize + x] {
+                    changes.push(BufferChange {
+                        loc: (x as u16, y as u16),
+                        cell: cell.clone(),
+                    });
+                }
+            }
+        }
+
+        self.vec.fill(BufCell::EMPTY);
+        self.written.fill(false);
+        changes
+    }
+
+    /// Clear the screen, and drop everything which was written but not committed yet
+    pub fn clear(&mut self) -> IOResult<BufState> {
+        self.vec.fill(BufCell::EMPTY);
+        self.written.fill(false);
+        self.invalidate()
+    }
+
+    /// Get what's on screen as plain text (trailing spaces are trimmed)
+    pub fn screen_text(rows: &[Row]) -> String {
+        let mut text = String::new();
+
+        for row in rows {
+            let line: String = visible_cells(row).map(|c| c.char).collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+
+        text
+    }
+
+    /// Get what's on screen as text with ANSI style escapes (`cat` it to see it)
+    pub fn screen_ansi(rows: &[Row]) -> String {
+        let mut text = String::new();
+
+        for row in rows {
+            let mut style = Style::DEFAULT;
+
+            for cell in visible_cells(row) {
+                if cell.style != style {
+                    text.push_str(&cell.style.to_ansi());
+                    style = cell.style;
+                }
+
+                text.push(cell.char);
+            }
+
+            if style != Style::DEFAULT {
+                text.push_str(&Style::DEFAULT.to_ansi());
+            }
+
+            text.push('\n');
+        }
+
+        text
+    }
+
     // writing
-    /// Like [`write`], but with a range of columns
+    /// Like [`write`], but with a range of columns (`end` is exclusive).
+    /// Nothing is written if the range doesn't fit.
     pub fn fill_range(
         &mut self,
         start: u16,
@@ -171,23 +607,23 @@ impl Buffer {
         row_y: u16,
         buf: BufCell,
     ) -> IOResult<BufState> {
-        // get row
-        let row = self.vec.get_mut(row_y as usize);
-
-        if row.is_none() {
+        if row_y >= self.size.1 {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "Row is invalid.",
             ));
         }
 
-        let row: &mut Row = row.unwrap();
+        if (start > end) || (end > self.size.0) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Column range is invalid.",
+            ));
+        }
 
         // update every column in this range
-        let r = start..end;
-
-        for col in r {
-            row[col as usize] = buf.clone();
+        for col in start..end {
+            self.write_cell((col, row_y), buf.clone())?;
         }
 
         // return
@@ -195,103 +631,152 @@ impl Buffer {
     }
 
     /// Consume changes from a [`PseudoBuffer`]
-    pub fn consume_changes(&mut self, changes: Vec<BufferChange>) -> IOResult<BufState> {
-        for change in changes {
-            // make sure change is ACTUALLY a change
-            let cell = self.get_cell(change.loc)?;
-            let is_changed: bool = cell != change.cell;
-
-            if is_changed == false {
-                continue;
-            }
+    pub fn consume_changes(&mut self, mut changes: Vec<BufferChange>) -> IOResult<BufState> {
+        self.drain_changes(&mut changes)
+    }
 
-            // ...
+    /// Like [`Buffer::consume_changes`], but the (emptied) list can be used again
+    pub(crate) fn drain_changes(&mut self, changes: &mut Vec<BufferChange>) -> IOResult<BufState> {
+        // cells which didn't change are skipped by the commit
+        for change in changes.drain(..) {
             self.write_cell(change.loc, change.cell)?;
         }
 
         Ok(BufState::Ok)
     }
 
-    /// Commit changes to buffer.
-    pub fn commit(&mut self) -> IOResult<BufState> {
-        // self.queue(crossterm::terminal::BeginSynchronizedUpdate)?; // commit all changes at once
-
-        // loop through rows to find changed rows
-        // the buffer does NOT represent what is on screen, instead it is just
-        // what SHOULD go on screen (we're allowed to lose some data since it'll likely redraw later)
-        let empty_row = BufCell::as_row(self.size.0);
-
-        for (y, row) in self.vec.clone().iter().enumerate() {
-            let is_empty = row != &empty_row;
-
-            if !is_empty {
+    /// Build the text of a row of the screen (styles as escapes, locked cells jumped over)
+    fn build_line(
+        line: &mut String,
+        row: &[BufCell],
+        y: u16,
+        locked: &[RectBoundary],
+        extended_underline: bool,
+    ) {
+        line.clear();
+
+        let width = row.len() as u16;
+        let mut style = Style::DEFAULT;
+        let mut x: u16 = 0;
+        let mut skipped = false; // jump over locked cells instead of writing them
+
+        for cell in visible_cells(row) {
+            let pos = (x, y);
+            let cell_width = char_width(cell.char);
+            x += cell_width.max(1) as u16;
+
+            if locked.iter().any(|r| r.contains(pos)) {
+                skipped = true;
                 continue;
             }
 
-            // get screen_vec version of this row
-            // if the row doesn't exist, the buf was likely resized ...
-            // we're going to skip this row if it doesn't exist on screen
-            let screen_vec_row = self.screen_vec.get_mut(y);
-
-            if screen_vec_row.is_none() {
-                continue;
+            if skipped {
+                line.push_str(&format!("\x1b[{};{}H", pos.1 + 1, pos.0 + 1));
+                skipped = false;
             }
 
-            let screen_vec_row = screen_vec_row.unwrap();
+            if cell.style != style {
+                line.push_str(&cell.style.to_ansi_with(extended_underline));
+                style = cell.style;
+            }
 
-            // make sure something in the row ACTUALLY changed so we don't
-            // pointlessly move the cursor (which stops mouse events)
-            if screen_vec_row == row {
-                continue;
+            // the terminal would move the cursor differently than we do for control characters,
+            // zero width characters (they join the previous one) and wide characters
+            // which don't fit (they wrap), so those are spaces
+            if cell.char.is_control() || (cell_width == 0) || (x > width) {
+                line.push(' ');
+            } else {
+                line.push(cell.char);
             }
+        }
 
-            // move cursor
-            self.stdout.queue(cursor::MoveTo(0, y as u16))?;
+        if style != Style::DEFAULT {
+            line.push_str(&Style::DEFAULT.to_ansi()); // don't leak style into the next line
+        }
+    }
 
-            // build full line
-            for (x, col) in row.iter().enumerate() {
-                // get screen_vec_char (same deal as screen_vec_row)
-                let screen_vec_char = screen_vec_row.get_mut(x);
+    /// Commit changes to buffer.
+    ///
+    /// Written cells which differ from what's on screen are moved to `screen_vec` first,
+    /// then every row with a changed cell is written again (as a whole, so the terminal
+    /// always matches `screen_vec`, even where wide characters overlapped).
+    pub fn commit(&mut self) -> IOResult<RenderStats> {
+        let start = std::time::Instant::now();
+        let mut stats = RenderStats::default();
 
-                if screen_vec_char.is_none() {
-                    continue;
-                }
+        // self.queue(crossterm::terminal::BeginSynchronizedUpdate)?; // commit all changes at once
 
-                let screen_vec_char = screen_vec_char.unwrap();
+        // find changed cells
+        // the buffer does NOT represent what is on screen, instead it is just
+        // what SHOULD go on screen (cells which weren't written are left alone)
+        let width = self.size.0 as usize;
+        let mut changed_rows = vec![false; self.size.1 as usize];
+
+        for (y, changed) in changed_rows.iter_mut().enumerate() {
+            let (Some(row), Some(screen_row)) =
+                (self.vec.get_row(y), self.screen_vec.get_row_mut(y))
+            else {
+                continue;
+            };
 
-                // if screen_vec_char is not empty but this one is, skip
-                // we should directly write to the screen vec if we want to clear things
-                if (col.empty == true) && (screen_vec_char.empty == false) {
+            for x in 0..width {
+                if !self.written[y * width + x]
+                    || self.locked.iter().any(|r| r.contains((x as u16, y as u16)))
+                {
                     continue;
                 }
 
-                // only update if char is different OR state changed
-                if screen_vec_char.char == col.char {
+                // only update if char is different OR style changed
+                if (screen_row[x].char == row[x].char) && (screen_row[x].style == row[x].style) {
                     continue;
                 }
 
-                // move vec row changes to screen_vec_row
-                screen_vec_row[x] = col.to_owned();
+                screen_row[x] = row[x].clone();
+                stats.cells_changed += 1;
+                *changed = true;
             }
+        }
 
-            // build text line from screen_vec_row
-            let mut line: String = String::new();
-
-            for cell in screen_vec_row {
-                line.push(cell.char);
+        // write changed rows
+        // rows without changes aren't touched, so we don't pointlessly move the cursor
+        // (which stops mouse events)
+        for (y, changed) in changed_rows.into_iter().enumerate() {
+            if !changed {
+                continue;
             }
 
+            let Some(row) = self.screen_vec.get_row(y) else {
+                continue;
+            };
+
+            Buffer::build_line(
+                &mut self.line,
+                row,
+                y as u16,
+                &self.locked,
+                self.extended_underline,
+            );
+
+            // move cursor
+            self.stdout.queue(cursor::MoveTo(0, y as u16))?;
+            stats.rows_touched += 1;
+            stats.bytes_written += format!("\x1b[{};1H", y + 1).len();
+
             // write line
-            self.stdout.write(line.as_bytes())?;
+            self.stdout.write_all(self.line.as_bytes())?;
+            stats.bytes_written += self.line.len();
         }
 
         // flush stdout
         self.stdout.flush()?;
 
         // return
-        self.vec.fill(BufCell::as_row(self.size.0));
+        self.vec.fill(BufCell::EMPTY);
+        self.written.fill(false);
+
         // self.queue(crossterm::terminal::EndSynchronizedUpdate)?; // commit to screen
-        Ok(BufState::Ok)
+        stats.duration = start.elapsed();
+        Ok(stats)
     }
 }
 
@@ -308,16 +793,12 @@ impl Write for Buffer {
 
 impl BufferWrite for Buffer {
     fn write_cell(&mut self, pos: Vec2, buf: BufCell) -> IOResult<BufState> {
-        // if we're writing an empty character, skip vec and write straight to screen
-        // this fixes issues with keyboard mode backspace and some random crashes (???)
-        let vec = if buf.empty == true {
-            &mut self.screen_vec
-        } else {
-            &mut self.vec
-        };
+        if self.is_locked(pos) {
+            return Ok(BufState::Ok);
+        }
 
         // get row
-        let row = vec.get_mut(pos.1 as usize);
+        let row = self.vec.get_row_mut(pos.1 as usize);
 
         if row.is_none() {
             return Err(std::io::Error::new(
@@ -326,17 +807,18 @@ impl BufferWrite for Buffer {
             ));
         }
 
-        let row: &mut Row = row.unwrap();
+        let row: &mut [BufCell] = row.unwrap();
 
         // update col
-        if pos.0 > row.len() as u16 {
+        if pos.0 as usize >= row.len() {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
                 "Column index is too large.",
             ));
         }
 
-        row[pos.0 as usize] = buf;
+        row[pos.0 as usize] = buf; // empty cells too, they erase
+        self.written[pos.1 as usize * self.size.0 as usize + pos.0 as usize] = true;
 
         // return
         Ok(BufState::Ok)
@@ -344,12 +826,32 @@ impl BufferWrite for Buffer {
 }
 
 // pseudobuffer
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct BufferChange {
     pub loc: Vec2,
     pub cell: BufCell,
 }
 
+/// Change list kept between frames, so drawing a frame doesn't allocate a new one
+/// (and grow it cell by cell) every time
+#[derive(Default)]
+pub(crate) struct ChangePool {
+    list: Vec<BufferChange>,
+}
+
+impl ChangePool {
+    /// Get an empty list (with the capacity of the last one given back)
+    pub(crate) fn take(&mut self) -> Vec<BufferChange> {
+        std::mem::take(&mut self.list)
+    }
+
+    /// Give the list back once it's not needed anymore
+    pub(crate) fn give(&mut self, mut list: Vec<BufferChange>) {
+        list.clear();
+        self.list = list;
+    }
+}
+
 /// This buffer receives changes like a normal buffer, but just stores them in a
 /// vector which can be pulled with `.get_changes()`.
 ///
@@ -359,6 +861,12 @@ pub struct PseudoBuffer {
     pub window_size: Vec2,
     /// Changes is append ONLY. If you must undo a change, just overwrite it.
     changes: Vec<BufferChange>,
+    /// Where a component wants the hardware cursor (shared between clones)
+    cursor: Arc<Mutex<Option<Vec2>>>,
+    /// Theme of the frame (components take their default styles from it)
+    theme: Option<Arc<Theme>>,
+    /// Locale of the frame (components take their strings from it)
+    locale: Option<Arc<dyn Locale>>,
 }
 
 impl PseudoBuffer {
@@ -366,6 +874,64 @@ impl PseudoBuffer {
         PseudoBuffer {
             window_size,
             changes: Vec::new(),
+            cursor: Arc::default(),
+            theme: None,
+            locale: None,
+        }
+    }
+
+    /// Get the last cell written at `pos` (if any)
+    pub fn get_cell(&self, pos: Vec2) -> Option<BufCell> {
+        self.changes
+            .iter()
+            .rev()
+            .find(|c| c.loc == pos)
+            .map(|c| c.cell.clone())
+    }
+
+    /// Erase the cells of `rect` which nothing was written to yet, so an overlay drawn
+    /// there last frame (which the screen keeps) is removed
+    pub fn erase_undrawn(&mut self, rect: &RectBoundary) -> IOResult<()> {
+        let drawn: HashSet<Vec2> = self
+            .changes
+            .iter()
+            .filter(|c| rect.contains(c.loc))
+            .map(|c| c.loc)
+            .collect();
+
+        for y in rect.pos.1..rect.bottom() {
+            for x in rect.pos.0..rect.right() {
+                if !drawn.contains(&(x, y)) {
+                    self.erase((x, y))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get a copy of this buffer without its changes (it keeps the theme, the locale
+    /// and the shared cursor), for rendering a component on its own
+    pub fn cleared(&self) -> PseudoBuffer {
+        PseudoBuffer {
+            window_size: self.window_size,
+            changes: Vec::new(),
+            cursor: self.cursor.clone(),
+            theme: self.theme.clone(),
+            locale: self.locale.clone(),
+        }
+    }
+
+    /// Create a [`PseudoBuffer`] which writes into an existing (cleared) list
+    pub(crate) fn with_changes(window_size: Vec2, mut changes: Vec<BufferChange>) -> PseudoBuffer {
+        changes.clear();
+
+        PseudoBuffer {
+            window_size,
+            changes,
+            cursor: Arc::default(),
+            theme: None,
+            locale: None,
         }
     }
 
@@ -374,14 +940,111 @@ impl PseudoBuffer {
         self.changes.clone()
     }
 
+    /// Take all changes to the buffer (without copying them like [`PseudoBuffer::get_changes`])
+    pub fn into_changes(self) -> Vec<BufferChange> {
+        self.changes
+    }
+
     /// We can only append or overwrite the whole thing
-    pub fn set_changes(&mut self, changes: Vec<BufferChange>) -> () {
+    pub fn set_changes(&mut self, changes: Vec<BufferChange>) {
         self.changes = changes;
     }
+
+    /// Ask for the hardware cursor to be placed at `pos` after the frame is committed
+    /// (`None` leaves it at [`crate::State::cursor_pos`]). Clones share the position,
+    /// so a component drawing into a clone declares it for the whole frame.
+    pub fn set_cursor(&self, pos: Option<Vec2>) {
+        *self.cursor.lock().unwrap_or_else(|e| e.into_inner()) = pos;
+    }
+
+    /// Get the position set with [`PseudoBuffer::set_cursor`]
+    pub fn cursor(&self) -> Option<Vec2> {
+        *self.cursor.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Set the theme components created from this buffer (and its clones) take their
+    /// default styles from. The buffer the frame passes to the draw function has
+    /// [`crate::State::theme`].
+    pub fn set_theme(&mut self, theme: Option<Arc<Theme>>) {
+        self.theme = theme;
+    }
+
+    /// Get the theme set with [`PseudoBuffer::set_theme`]
+    pub fn theme(&self) -> Option<&Theme> {
+        self.theme.as_deref()
+    }
+
+    /// Get a default style of a component: `pick` from the theme, or `fallback`
+    /// if the buffer doesn't have one
+    pub fn theme_style(&self, pick: fn(&Theme) -> Style, fallback: Style) -> Style {
+        self.theme().map_or(fallback, pick)
+    }
+
+    /// Set the locale components created from this buffer (and its clones) take their
+    /// strings and number formatting from. The buffer the frame passes to the draw
+    /// function has [`crate::State::locale`].
+    pub fn set_locale(&mut self, locale: Option<Arc<dyn Locale>>) {
+        self.locale = locale;
+    }
+
+    /// Get the locale set with [`PseudoBuffer::set_locale`] ([`English`] if there is none)
+    pub fn locale(&self) -> &dyn Locale {
+        self.locale.as_deref().unwrap_or(&English)
+    }
+
+    /// Draw a [`Sprite`] with its top left corner at `pos`. Transparent cells are skipped,
+    /// and cells outside of the window are clipped (so `pos` can be off screen).
+    ///
+    /// ## Arguments:
+    /// * `sprite`
+    /// * `pos` - (x, y), can be negative
+    pub fn blit(&mut self, sprite: &Sprite, pos: (i32, i32)) {
+        for ((x, y), cell) in sprite.cells() {
+            let x = pos.0 + x as i32;
+            let y = pos.1 + y as i32;
+
+            if (x < 0)
+                || (y < 0)
+                || (x >= self.window_size.0 as i32)
+                || (y >= self.window_size.1 as i32)
+            {
+                continue;
+            }
+
+            self.changes.push(BufferChange {
+                loc: (x as u16, y as u16),
+                cell: cell.clone(),
+            });
+        }
+    }
+
+    /// Draw a rendered [`CellGrid`] with its top left corner at `pos` (see [`PseudoBuffer::blit`]).
+    /// The cursor is placed where the component placed it, if that's inside of the window.
+    pub fn blit_grid(&mut self, grid: &CellGrid, pos: (i32, i32)) {
+        if let Some(sprite) = grid.sprite() {
+            self.blit(sprite, pos);
+        }
+
+        if let Some(cursor) = grid.cursor() {
+            let x = pos.0 + cursor.0 as i32;
+            let y = pos.1 + cursor.1 as i32;
+
+            if (0..self.window_size.0 as i32).contains(&x)
+                && (0..self.window_size.1 as i32).contains(&y)
+            {
+                self.set_cursor(Some((x as u16, y as u16)));
+            }
+        }
+    }
 }
 
 impl BufferWrite for PseudoBuffer {
     fn write_cell(&mut self, pos: Vec2, buf: BufCell) -> IOResult<BufState> {
+        // clip to the window
+        if (pos.0 >= self.window_size.0) || (pos.1 >= self.window_size.1) {
+            return Ok(BufState::Ok);
+        }
+
         self.changes.push(BufferChange {
             loc: pos,
             cell: buf,
@@ -390,3 +1053,158 @@ impl BufferWrite for PseudoBuffer {
         Ok(BufState::Ok)
     }
 }
+
+// off-screen grid
+/// An off-screen grid a component is rendered into once (at its own `(0, 0)`), then
+/// blitted each frame with [`PseudoBuffer::blit_grid`]. Moving the component around
+/// (or sliding it in and out) only costs a blit. Keep it between frames in
+/// [`crate::State::store`], for example.
+#[derive(Clone, Debug, Default)]
+pub struct CellGrid {
+    sprite: Option<Sprite>,
+    /// Hash of the key the grid was rendered with
+    key: u64,
+    /// Theme the grid was rendered with
+    theme: Option<Arc<Theme>>,
+    /// Where the component placed the cursor (relative to the grid)
+    cursor: Option<Vec2>,
+}
+
+impl CellGrid {
+    pub fn new() -> CellGrid {
+        CellGrid::default()
+    }
+
+    /// Render a component into the grid, if it wasn't rendered with the same `size` and `key` already.
+    /// Returns `true` if it was rendered.
+    ///
+    /// ## Arguments:
+    /// * `buffer` - buffer of the frame (the component gets its theme and locale)
+    /// * `size` - size of the grid
+    /// * `key` - everything the component's output depends on (its content)
+    /// * `render` - renders the component into the given (empty) buffer at the given rect
+    pub fn render(
+        &mut self,
+        buffer: &PseudoBuffer,
+        size: Vec2,
+        key: impl Hash,
+        render: impl FnOnce(PseudoBuffer, RectBoundary) -> DrawingResult,
+    ) -> IOResult<bool> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let key = hasher.finish();
+
+        if let Some(sprite) = &self.sprite {
+            if (sprite.size() == size)
+                && (self.key == key)
+                && (self.theme.as_deref() == buffer.theme())
+            {
+                return Ok(false);
+            }
+        }
+
+        // the grid is its own window, with its own cursor
+        let mut grid = buffer.cleared();
+        grid.window_size = size;
+        grid.cursor = Arc::default();
+
+        let (_, changes) = render(grid.clone(), RectBoundary::new((0, 0), size))?;
+        let mut sprite = Sprite::new(size);
+
+        for change in changes {
+            sprite.set(change.loc, Some(change.cell)); // outside of the grid is ignored
+        }
+
+        self.sprite = Some(sprite);
+        self.key = key;
+        self.theme = buffer.theme.clone();
+        self.cursor = grid.cursor();
+        Ok(true)
+    }
+
+    /// Forget the content, so the next [`CellGrid::render`] renders again
+    pub fn invalidate(&mut self) {
+        self.sprite = None;
+    }
+
+    /// Get the rendered cells (`None` if nothing was rendered yet)
+    pub fn sprite(&self) -> Option<&Sprite> {
+        self.sprite.as_ref()
+    }
+
+    /// Get where the component placed the cursor (relative to the grid)
+    pub fn cursor(&self) -> Option<Vec2> {
+        self.cursor
+    }
+}
+
+// shared pseudobuffer
+#[derive(Default)]
+struct SharedCells {
+    cells: HashMap<Vec2, BufCell>,
+    /// Cells removed by [`SharedPseudoBuffer::clear`] which weren't erased from the screen yet
+    cleared: HashSet<Vec2>,
+    /// If something was written since the last [`SharedPseudoBuffer::take_dirty`]
+    dirty: bool,
+}
+
+/// A [`PseudoBuffer`] which can be written to from other threads (clones share the same cells).
+///
+/// Its cells are drawn on top of every frame until they're cleared, so background
+/// work can show status lines or log output without going through the draw function.
+/// Get the one of a frame with [`crate::Frame::shared_buffer`].
+#[derive(Clone, Default)]
+pub struct SharedPseudoBuffer {
+    inner: Arc<Mutex<SharedCells>>,
+}
+
+impl SharedPseudoBuffer {
+    pub fn new() -> SharedPseudoBuffer {
+        SharedPseudoBuffer::default()
+    }
+
+    /// Lock the cells (a thread which panicked while writing can't break the buffer)
+    fn lock(&self) -> std::sync::MutexGuard<'_, SharedCells> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Remove every cell (they're erased from the screen with the next frame)
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        let SharedCells { cells, cleared, .. } = &mut *inner;
+        cleared.extend(cells.drain().map(|(loc, _)| loc));
+        inner.dirty = true;
+    }
+
+    /// Get the positions of the cells removed since the last call, so they can be erased
+    pub fn take_cleared(&self) -> Vec<Vec2> {
+        self.lock().cleared.drain().collect()
+    }
+
+    /// Get all cells as changes
+    pub fn get_changes(&self) -> Vec<BufferChange> {
+        self.lock()
+            .cells
+            .iter()
+            .map(|(loc, cell)| BufferChange {
+                loc: *loc,
+                cell: cell.clone(),
+            })
+            .collect()
+    }
+
+    /// Check if something was written since the last call (so the frame should be redrawn)
+    pub fn take_dirty(&self) -> bool {
+        std::mem::take(&mut self.lock().dirty)
+    }
+}
+
+impl BufferWrite for SharedPseudoBuffer {
+    fn write_cell(&mut self, pos: Vec2, buf: BufCell) -> IOResult<BufState> {
+        let mut inner = self.lock();
+        inner.cells.insert(pos, buf);
+        inner.dirty = true;
+
+        Ok(BufState::Ok)
+    }
+}
@@ -0,0 +1,198 @@
+//! Async buttons
+//!
+//! An [`AsyncButton`] starts a background task (see [`crate::State::spawn_task`]) when
+//! it's clicked, disables itself with a spinner while the task runs, and then shows if
+//! the task succeeded or failed. The result of the task still comes back as a
+//! [`crate::event::UserEvent`] for the application.
+use std::sync::{Arc, Mutex};
+
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{
+    Creatable, DrawingResult, RectBoundary, TextAttribute, TextBackgroundColor, TextColor, Vec2,
+};
+use crate::style::Style;
+use crate::task::{TaskContext, TaskHandle};
+use crate::text::display_width;
+use crate::State;
+
+/// What an [`AsyncButton`] shows
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum AsyncStatus {
+    /// Waiting to be clicked
+    #[default]
+    Idle,
+    /// The task is running (the button is disabled)
+    Running,
+    /// The task returned `Ok`
+    Succeeded,
+    /// The task returned `Err` (with the error), or was cancelled
+    Failed(String),
+}
+
+/// State of an [`AsyncButton`], kept by the application between frames
+#[derive(Default)]
+pub struct AsyncButtonState {
+    pub status: AsyncStatus,
+    /// Running task
+    task: Option<TaskHandle>,
+    /// Set by the task when it's done
+    outcome: Arc<Mutex<Option<Result<(), String>>>>,
+    /// Frame of the spinner
+    tick: usize,
+}
+
+impl AsyncButtonState {
+    /// Run `work` in the background (its result is a [`crate::event::UserEvent`] named `name`).
+    /// Does nothing while a task is running.
+    pub fn start<T, E>(
+        &mut self,
+        state: &State,
+        name: &str,
+        work: impl FnOnce(&TaskContext) -> Result<T, E> + Send + 'static,
+    ) where
+        T: Send + 'static,
+        E: std::fmt::Display + Send + 'static,
+    {
+        if self.is_running() {
+            return;
+        }
+
+        let outcome = Arc::new(Mutex::new(None));
+        self.outcome = outcome.clone();
+        self.status = AsyncStatus::Running;
+        self.tick = 0;
+
+        self.task = Some(state.spawn_task(name, move |context| {
+            let result = work(context);
+
+            *outcome.lock().unwrap_or_else(|e| e.into_inner()) =
+                Some(result.as_ref().map(|_| ()).map_err(|e| e.to_string()));
+
+            result
+        }));
+    }
+
+    /// Check if the task is still running
+    pub fn is_running(&self) -> bool {
+        self.status == AsyncStatus::Running
+    }
+
+    /// Cancel the task (the button shows it failed)
+    pub fn cancel(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.cancel();
+            self.status = AsyncStatus::Failed("cancelled".to_string());
+        }
+    }
+
+    /// Go back to [`AsyncStatus::Idle`] (after showing the outcome)
+    pub fn reset(&mut self) {
+        if !self.is_running() {
+            self.status = AsyncStatus::Idle;
+        }
+    }
+
+    /// Pick up the outcome of a finished task
+    fn poll(&mut self) {
+        let Some(task) = &self.task else {
+            return;
+        };
+
+        let outcome = self
+            .outcome
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+
+        self.status = match outcome {
+            Some(Ok(())) => AsyncStatus::Succeeded,
+            Some(Err(e)) => AsyncStatus::Failed(e),
+            // the task panicked
+            None if task.is_finished() => AsyncStatus::Failed("panicked".to_string()),
+            None => return,
+        };
+
+        self.task = None;
+    }
+}
+
+/// Button which runs a task: `➚ Save`, then `⠹ Save` while it runs and `✓ Save` or `✗ Save`
+pub struct AsyncButton {
+    pub buffer: PseudoBuffer,
+    pub style: Style,
+    /// Style while the task runs
+    pub running_style: Style,
+    pub success_style: Style,
+    pub failure_style: Style,
+    /// Frames of the spinner (one per draw)
+    pub spinner: Vec<char>,
+}
+
+impl Creatable for AsyncButton {
+    fn new(buffer: PseudoBuffer) -> Self {
+        AsyncButton {
+            buffer,
+            style: Style::DEFAULT
+                .fg(TextColor::Black)
+                .bg(TextBackgroundColor::BrightWhite),
+            running_style: Style::DEFAULT.attr(TextAttribute::Dim),
+            success_style: Style::DEFAULT
+                .fg(TextColor::Black)
+                .bg(TextBackgroundColor::Green),
+            failure_style: Style::DEFAULT
+                .fg(TextColor::Black)
+                .bg(TextBackgroundColor::Red),
+            spinner: "⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏".chars().collect(),
+        }
+    }
+}
+
+impl AsyncButton {
+    /// Get the rect of a button with `label` at `pos`
+    pub fn rect(pos: Vec2, label: &str) -> RectBoundary {
+        RectBoundary::new(pos, (display_width(label) as u16 + 2, 1))
+    }
+
+    /// Check if the button was clicked while it isn't running
+    /// (start the task with [`AsyncButtonState::start`] then)
+    pub fn handle_click(state: &State, rect: &RectBoundary, button: &AsyncButtonState) -> bool {
+        !button.is_running() && state.click.as_ref().is_some_and(|c| rect.contains(c.pos))
+    }
+
+    /// Draw the button. Keep stepping the frame while
+    /// [`AsyncButtonState::is_running`] so the spinner turns.
+    ///
+    /// ## Arguments:
+    /// * `pos` - x, y
+    /// * `label`
+    /// * `button` - [`AsyncButtonState`] (picks up the outcome of the task)
+    pub fn render(
+        &mut self,
+        pos: Vec2,
+        label: &str,
+        button: &mut AsyncButtonState,
+    ) -> DrawingResult {
+        button.poll();
+
+        let (icon, style) = match &button.status {
+            AsyncStatus::Idle => ('➚', self.style),
+            AsyncStatus::Running => {
+                let icon = self
+                    .spinner
+                    .get(button.tick % self.spinner.len().max(1))
+                    .copied()
+                    .unwrap_or(' ');
+                button.tick = button.tick.wrapping_add(1);
+                (icon, self.running_style)
+            }
+            AsyncStatus::Succeeded => ('✓', self.success_style),
+            AsyncStatus::Failed(_) => ('✗', self.failure_style),
+        };
+
+        self.buffer
+            .write_str_styled(pos, &format!("{icon} {label}"), style)?;
+
+        // done
+        Ok((AsyncButton::rect(pos, label), self.buffer.get_changes()))
+    }
+}
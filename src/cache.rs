@@ -0,0 +1,102 @@
+//! Memoized rendering
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::io::Result as IOResult;
+
+use crate::buffer::{BufferChange, BufferWrite, PseudoBuffer};
+use crate::drawing::{DrawingResult, RectBoundary, Vec2};
+
+/// Changes of a component, and where it placed the cursor
+struct Entry {
+    hash: u64,
+    rect: RectBoundary,
+    changes: Vec<BufferChange>,
+    cursor: Option<Vec2>,
+}
+
+/// Keeps the changes of components between frames, so components whose
+/// content and rect didn't change are replayed instead of rendered again
+/// (for static panels like headers and help text).
+///
+/// Entries which aren't used during a frame are dropped after it.
+#[derive(Default)]
+pub struct RenderCache {
+    entries: HashMap<String, Entry>,
+    used: HashSet<String>,
+}
+
+impl RenderCache {
+    /// Render a component, or replay its changes from the last frame if `key` and `rect` are the same.
+    ///
+    /// ## Arguments:
+    /// * `buffer` - buffer the changes are written to
+    /// * `id` - id of the component
+    /// * `key` - everything the component's output depends on (its content)
+    /// * `rect` - rect the component is drawn at
+    /// * `render` - renders the component into the given buffer (a copy of `buffer` without its changes)
+    pub fn render(
+        &mut self,
+        buffer: &mut PseudoBuffer,
+        id: &str,
+        key: impl Hash,
+        rect: RectBoundary,
+        render: impl FnOnce(PseudoBuffer, RectBoundary) -> DrawingResult,
+    ) -> IOResult<RectBoundary> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        self.used.insert(id.to_string());
+
+        let (own_rect, entry) = match self.entries.get(id) {
+            Some(entry) if (entry.hash == hash) && (entry.rect == rect) => (rect, entry),
+            _ => {
+                // render with no cursor, so a cursor placed by the component can be replayed
+                let before = buffer.cursor();
+                buffer.set_cursor(None);
+                let rendered = render(buffer.cleared(), rect.clone());
+                let cursor = buffer.cursor();
+                buffer.set_cursor(before);
+                let (own_rect, changes) = rendered?;
+
+                self.entries.insert(
+                    id.to_string(),
+                    Entry {
+                        hash,
+                        rect,
+                        changes,
+                        cursor,
+                    },
+                );
+                (own_rect, &self.entries[id])
+            }
+        };
+
+        for change in &entry.changes {
+            buffer.write_cell(change.loc, change.cell.clone())?;
+        }
+
+        if entry.cursor.is_some() {
+            buffer.set_cursor(entry.cursor);
+        }
+
+        Ok(own_rect)
+    }
+
+    /// Forget the changes of a component, so it's rendered again next time
+    pub fn invalidate(&mut self, id: &str) {
+        self.entries.remove(id);
+    }
+
+    /// Forget everything
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Drop entries which weren't used this frame
+    pub(crate) fn end_frame(&mut self) {
+        let used = std::mem::take(&mut self.used);
+        self.entries.retain(|id, _| used.contains(id));
+    }
+}
@@ -0,0 +1,101 @@
+//! Braille canvas
+//!
+//! Every cell of a [`Canvas`] is a braille pattern of 2x4 dots, so charts and overviews
+//! can draw at a finer resolution than whole cells. Dots are set in dot coordinates,
+//! styles apply to whole cells.
+use std::io::Result as IOResult;
+
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::Vec2;
+use crate::style::Style;
+
+/// Braille dot bits, indexed by `[y][x]` within a cell (2 dots wide, 4 dots tall)
+const DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Grid of braille dots
+#[derive(Clone, Debug, PartialEq)]
+pub struct Canvas {
+    /// Size in cells
+    size: Vec2,
+    /// Dot bits of every cell
+    cells: Vec<u8>,
+    /// Style of every cell
+    styles: Vec<Style>,
+}
+
+impl Canvas {
+    /// Create an empty canvas
+    ///
+    /// ## Arguments:
+    /// * `size` - size(x, y) in cells
+    pub fn new(size: Vec2) -> Self {
+        let len = size.0 as usize * size.1 as usize;
+
+        Canvas {
+            size,
+            cells: vec![0; len],
+            styles: vec![Style::DEFAULT; len],
+        }
+    }
+
+    /// Get the size in dots (two per cell across, four per cell down)
+    pub fn dot_size(&self) -> (usize, usize) {
+        (self.size.0 as usize * 2, self.size.1 as usize * 4)
+    }
+
+    /// Set a dot, and the style of its cell (dots outside of the canvas are ignored)
+    ///
+    /// ## Arguments:
+    /// * `x`, `y` - position in dots
+    /// * `style`
+    pub fn set(&mut self, x: usize, y: usize, style: Style) {
+        let (width, height) = self.dot_size();
+
+        if (x >= width) || (y >= height) {
+            return;
+        }
+
+        let i = (y / 4) * self.size.0 as usize + x / 2;
+        self.cells[i] |= DOTS[y % 4][x % 2];
+        self.styles[i] = style;
+    }
+
+    /// Set a vertical line of dots from `y0` to `y1` (inclusive, in either order)
+    pub fn line_v(&mut self, x: usize, y0: usize, y1: usize, style: Style) {
+        for y in y0.min(y1)..=y0.max(y1) {
+            self.set(x, y, style);
+        }
+    }
+
+    /// Get the characters of a row of cells (empty cells are blank braille patterns)
+    pub fn row(&self, row: u16) -> String {
+        let start = row as usize * self.size.0 as usize;
+
+        self.cells[start..start + self.size.0 as usize]
+            .iter()
+            .map(|bits| char::from_u32(0x2800 + *bits as u32).unwrap_or(' '))
+            .collect()
+    }
+
+    /// Draw the canvas (cells without dots are erased, so it can be drawn over last frame)
+    ///
+    /// ## Arguments:
+    /// * `buffer`
+    /// * `pos` - pos(x, y) of the top left cell
+    pub fn draw(&self, buffer: &mut PseudoBuffer, pos: Vec2) -> IOResult<()> {
+        for y in 0..self.size.1 {
+            for (x, char) in self.row(y).chars().enumerate() {
+                let i = y as usize * self.size.0 as usize + x;
+                let pos = (pos.0 + x as u16, pos.1 + y);
+
+                if self.cells[i] == 0 {
+                    buffer.erase(pos)?;
+                } else {
+                    buffer.write_str_styled(pos, &char.to_string(), self.styles[i])?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
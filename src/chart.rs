@@ -0,0 +1,895 @@
+//! Charts
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::canvas::Canvas;
+use crate::drawing::{
+    Align, Creatable, DrawingResult, RectBoundary, TextAttribute, TextBackgroundColor, TextColor,
+};
+use crate::grid::GridCursor;
+use crate::style::Style;
+
+// axis
+/// Value range of a chart, with tick generation and label formatting
+/// (shared by the charts so they place and format labels the same way)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Axis {
+    pub min: f64,
+    pub max: f64,
+    /// Digits after the decimal point in labels (`None` picks them from the tick step)
+    pub precision: Option<usize>,
+    /// Text after every label (like `s` or `%`)
+    pub unit: String,
+}
+
+impl Axis {
+    /// Create a new [`Axis`] from `min` to `max`
+    pub fn new(min: f64, max: f64) -> Self {
+        Axis {
+            min,
+            max,
+            precision: None,
+            unit: String::new(),
+        }
+    }
+
+    /// Create an axis covering every value
+    pub fn fit(values: impl IntoIterator<Item = f64>) -> Self {
+        let (min, max) = values
+            .into_iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+                (min.min(v), max.max(v))
+            });
+
+        if min.is_finite() {
+            Axis::new(min, max)
+        } else {
+            Axis::new(0.0, 1.0)
+        }
+    }
+
+    /// Set the digits after the decimal point in labels
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Set the text after every label
+    pub fn unit(mut self, unit: &str) -> Self {
+        self.unit = unit.to_string();
+        self
+    }
+
+    /// Get the smallest round step (1, 2 or 5 times a power of ten) which splits the
+    /// range into at most `count - 1` parts
+    fn step(&self, count: usize) -> f64 {
+        let raw = (self.max - self.min) / count.saturating_sub(1).max(1) as f64;
+        let magnitude = 10f64.powf(raw.log10().floor());
+
+        let nice = [1.0, 2.0, 5.0]
+            .into_iter()
+            .find(|n| n * magnitude >= raw * (1.0 - 1e-9))
+            .unwrap_or(10.0);
+
+        nice * magnitude
+    }
+
+    /// Get tick values from `min` to `max` (at most `count` of them): `min` and `max`
+    /// themselves, and round values in between which aren't too close to them
+    pub fn ticks(&self, count: usize) -> Vec<f64> {
+        if (self.max <= self.min) || self.max.is_nan() {
+            return vec![self.min];
+        }
+
+        let step = self.step(count);
+        let mut ticks = vec![self.min];
+        let mut i = (self.min / step).floor() + 1.0;
+
+        while i * step < self.max - step / 2.0 {
+            if i * step > self.min + step / 2.0 {
+                ticks.push(i * step);
+            }
+
+            i += 1.0;
+        }
+
+        ticks.push(self.max);
+        ticks
+    }
+
+    /// Format a value as a label
+    pub fn label(&self, value: f64, ticks: usize) -> String {
+        let precision = self.precision.unwrap_or_else(|| {
+            let step = self.step(ticks);
+
+            if (step > 0.0) && step.is_finite() {
+                (-step.log10().floor()).max(0.0) as usize
+            } else {
+                0
+            }
+        });
+
+        format!("{value:.precision$}{}", self.unit)
+    }
+
+    /// Get the width of the widest label
+    pub fn label_width(&self, ticks: usize) -> u16 {
+        [self.min, self.max]
+            .into_iter()
+            .chain(self.ticks(ticks))
+            .map(|v| self.label(v, ticks).chars().count() as u16)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Get where `value` is along `cells` cells (0 is `min`, `cells - 1` is `max`)
+    pub fn position(&self, value: f64, cells: u16) -> f64 {
+        if self.max <= self.min {
+            return (cells.saturating_sub(1)) as f64 / 2.0;
+        }
+
+        (value - self.min) / (self.max - self.min) * cells.saturating_sub(1) as f64
+    }
+
+    /// Draw tick labels top to bottom (highest value at the top), right aligned in `rect`
+    ///
+    /// ## Arguments:
+    /// * `buffer`
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `ticks` - at most how many ticks to draw
+    /// * `style`
+    pub fn draw_vertical(
+        &self,
+        buffer: &mut PseudoBuffer,
+        rect: &RectBoundary,
+        ticks: usize,
+        style: Style,
+    ) -> std::io::Result<()> {
+        for value in self.ticks(ticks) {
+            let y = rect.pos.1 + rect.size.1.saturating_sub(1)
+                - self.position(value, rect.size.1).round() as u16;
+            let label = self.label(value, ticks);
+            let x = Align::Right.offset(rect.size.0, label.chars().count() as u16);
+
+            buffer.write_str_styled((rect.pos.0 + x, y), &label, style)?;
+        }
+
+        Ok(())
+    }
+
+    /// Draw an axis line with tick labels under it, left to right
+    /// (labels which would overlap are skipped)
+    ///
+    /// ## Arguments:
+    /// * `buffer`
+    /// * `rect` - pos(x, y), size(x, _); the line is on the first row, labels on the second
+    /// * `ticks` - at most how many ticks to draw
+    /// * `style`
+    pub fn draw_horizontal(
+        &self,
+        buffer: &mut PseudoBuffer,
+        rect: &RectBoundary,
+        ticks: usize,
+        style: Style,
+    ) -> std::io::Result<()> {
+        let width = rect.size.0;
+        buffer.write_str_styled(rect.pos, &"─".repeat(width as usize), style)?;
+
+        if rect.size.1 < 2 {
+            return Ok(());
+        }
+
+        let mut free_x = 0;
+
+        for value in self.ticks(ticks) {
+            let label = self.label(value, ticks);
+            let len = label.chars().count() as u16;
+            let center = self.position(value, width).round() as u16;
+
+            if len > width {
+                continue;
+            }
+
+            // centered under the tick, but kept inside of the rect
+            let x = center.saturating_sub(len / 2).min(width - len);
+
+            if x < free_x {
+                continue;
+            }
+
+            buffer.write_str_styled((rect.pos.0 + center, rect.pos.1), "┬", style)?;
+            buffer.write_str_styled((rect.pos.0 + x, rect.pos.1 + 1), &label, style)?;
+            free_x = x + len + 1;
+        }
+
+        Ok(())
+    }
+}
+
+// legend
+/// Colored swatches naming the series of a chart
+pub struct Legend {
+    pub buffer: PseudoBuffer,
+    /// Style of the names
+    pub label_style: Style,
+    /// Character of the swatches
+    pub swatch: char,
+    /// Stack entries instead of putting them in a row
+    pub vertical: bool,
+}
+
+impl Creatable for Legend {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Legend {
+            buffer,
+            label_style: Style::DEFAULT,
+            swatch: '■',
+            vertical: false,
+        }
+    }
+}
+
+impl Legend {
+    /// Draw the legend
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `entries` - (name, style of the series)
+    pub fn render<S: AsRef<str>>(
+        &mut self,
+        rect: RectBoundary,
+        entries: &[(S, Style)],
+    ) -> DrawingResult {
+        let (mut x, mut y) = rect.pos;
+
+        for (name, style) in entries {
+            let name = name.as_ref();
+            let len = name.chars().count() as u16 + 2;
+
+            if self.vertical {
+                if y >= rect.bottom() {
+                    break;
+                }
+            } else if x + len > rect.right() {
+                break;
+            }
+
+            self.buffer
+                .write_str_styled((x, y), &self.swatch.to_string(), *style)?;
+            self.buffer
+                .write_str_styled((x + 2, y), name, self.label_style)?;
+
+            if self.vertical {
+                y += 1;
+            } else {
+                x += len + 2;
+            }
+        }
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
+
+// heatmap
+/// Grid of values shown as background color intensity (like a contribution calendar)
+pub struct Heatmap {
+    pub buffer: PseudoBuffer,
+    /// Colors from the lowest to the highest value
+    pub levels: Vec<TextBackgroundColor>,
+    /// Width of a cell
+    pub cell_width: u16,
+    /// Style of the axis labels and the readout
+    pub label_style: Style,
+    /// Show the value of the selected cell under the grid
+    pub readout: bool,
+    /// Show the value of every level in the bottom row (see [`Legend`])
+    pub legend: bool,
+}
+
+impl Creatable for Heatmap {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Heatmap {
+            levels: vec![
+                TextBackgroundColor::BrightBlack,
+                TextBackgroundColor::Green,
+                TextBackgroundColor::BrightGreen,
+            ],
+            cell_width: 2,
+            label_style: buffer.theme_style(|t| t.muted, Style::DEFAULT.fg(TextColor::BrightBlack)),
+            readout: true,
+            legend: false,
+            buffer,
+        }
+    }
+}
+
+impl Heatmap {
+    /// Get the level (index into `levels`) of `value` between `min` and `max`
+    pub fn level(&self, value: f64, min: f64, max: f64) -> usize {
+        let top = self.levels.len().saturating_sub(1);
+
+        if max <= min {
+            return if value > min { top } else { 0 };
+        }
+
+        (((value - min) / (max - min)).clamp(0.0, 1.0) * top as f64).round() as usize
+    }
+
+    /// Get the rect of the cells (without the labels)
+    ///
+    /// ## Arguments:
+    /// * `rect` - rect the heatmap is drawn at
+    /// * `values` - rows of values
+    /// * `row_labels`
+    /// * `col_labels`
+    pub fn grid_rect<S: AsRef<str>>(
+        &self,
+        rect: &RectBoundary,
+        values: &[Vec<f64>],
+        row_labels: &[S],
+        col_labels: &[S],
+    ) -> RectBoundary {
+        let label_width = row_labels
+            .iter()
+            .map(|l| l.as_ref().chars().count() as u16 + 1)
+            .max()
+            .unwrap_or(0);
+        let top = if col_labels.is_empty() { 0 } else { 1 };
+        let bottom = if self.legend { 1 } else { 0 };
+        let cols = values.iter().map(|r| r.len()).max().unwrap_or(0) as u16;
+
+        RectBoundary {
+            pos: (rect.pos.0 + label_width, rect.pos.1 + top),
+            size: (
+                (cols * self.cell_width).min(rect.size.0.saturating_sub(label_width)),
+                (values.len() as u16).min(rect.size.1.saturating_sub(top + bottom)),
+            ),
+        }
+    }
+
+    /// Draw the heatmap
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `values` - rows of values
+    /// * `row_labels` - shown left of the rows (can be empty)
+    /// * `col_labels` - shown above the columns where they fit (can be empty)
+    /// * `cursor` - selected cell (see [`GridCursor::handle_click`] with [`Heatmap::grid_rect`];
+    ///   for hovering, select the cell at [`crate::State::cursor_pos`] with [`GridCursor::cell_at`])
+    pub fn render<S: AsRef<str>>(
+        &mut self,
+        rect: RectBoundary,
+        values: &[Vec<f64>],
+        row_labels: &[S],
+        col_labels: &[S],
+        cursor: Option<&GridCursor>,
+    ) -> DrawingResult {
+        let grid = self.grid_rect(&rect, values, row_labels, col_labels);
+        let cell_width = self.cell_width.max(1);
+        let visible_cols = (grid.size.0 / cell_width) as usize;
+
+        let axis = Axis::fit(values.iter().flatten().copied());
+        let (min, max) = (axis.min, axis.max);
+
+        // column labels (skipped where they'd run into the last one)
+        let mut free_x = grid.pos.0;
+
+        for (col, label) in col_labels.iter().take(visible_cols).enumerate() {
+            let x = grid.pos.0 + col as u16 * cell_width;
+            let label = label.as_ref();
+
+            if (x < free_x) || ((x + label.chars().count() as u16) > (grid.pos.0 + grid.size.0)) {
+                continue;
+            }
+
+            self.buffer
+                .write_str_styled((x, rect.pos.1), label, self.label_style)?;
+            free_x = x + label.chars().count() as u16 + 1;
+        }
+
+        // rows
+        let selected = cursor.and_then(|c| c.selected());
+
+        for (row, row_values) in values.iter().take(grid.size.1 as usize).enumerate() {
+            let y = grid.pos.1 + row as u16;
+
+            if let Some(label) = row_labels.get(row) {
+                self.buffer
+                    .write_str_styled((rect.pos.0, y), label.as_ref(), self.label_style)?;
+            }
+
+            for (col, value) in row_values.iter().take(visible_cols).enumerate() {
+                // (without levels there are no colors to pick from)
+                let mut style = match self.levels.get(self.level(*value, min, max)) {
+                    Some(bg) => Style::DEFAULT.bg(*bg),
+                    None => Style::DEFAULT,
+                };
+
+                // the selected cell is marked with brackets
+                let text = if selected == Some((row, col)) {
+                    style = style.fg(TextColor::BrightWhite).attr(TextAttribute::Bold);
+                    let mut text = "[".to_string() + &" ".repeat(cell_width as usize);
+                    text.truncate(cell_width as usize - 1);
+                    text.push(']');
+                    text
+                } else {
+                    " ".repeat(cell_width as usize)
+                };
+
+                self.buffer.write_str_styled(
+                    (grid.pos.0 + col as u16 * cell_width, y),
+                    &text,
+                    style,
+                )?;
+            }
+        }
+
+        // readout
+        let readout_y = grid.pos.1 + grid.size.1;
+
+        if self.readout && (readout_y < rect.pos.1 + rect.size.1) {
+            if let Some((row, col)) = selected {
+                if let Some(value) = values.get(row).and_then(|r| r.get(col)) {
+                    let mut text = String::new();
+
+                    if let Some(label) = row_labels.get(row) {
+                        text += label.as_ref();
+                        text += " ";
+                    }
+
+                    if let Some(label) = col_labels.get(col) {
+                        text += label.as_ref();
+                    }
+
+                    let text = format!("{}: {value}", text.trim_end());
+                    let text: String = text.chars().take(rect.size.0 as usize).collect();
+
+                    self.buffer.write_str_styled(
+                        (rect.pos.0, readout_y),
+                        &text,
+                        self.label_style,
+                    )?;
+                }
+            }
+        }
+
+        // legend (the value every level starts at)
+        if self.legend && !rect.is_empty() {
+            let top = self.levels.len().saturating_sub(1).max(1) as f64;
+            let entries: Vec<(String, Style)> = self
+                .levels
+                .iter()
+                .enumerate()
+                .map(|(i, bg)| {
+                    let value = min + (max - min) * i as f64 / top;
+                    (axis.label(value, self.levels.len()), Style::DEFAULT.bg(*bg))
+                })
+                .collect();
+
+            let mut legend = Legend::new(self.buffer.clone());
+            legend.label_style = self.label_style;
+            legend.swatch = ' ';
+
+            let (_, changes) = legend.render(
+                RectBoundary::new((rect.pos.0, rect.bottom() - 1), (rect.size.0, 1)),
+                &entries,
+            )?;
+            self.buffer.set_changes(changes);
+        }
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
+
+// candlestick
+/// Prices of one period of a [`Candlestick`] chart
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Candle {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+impl Candle {
+    pub fn new(open: f64, high: f64, low: f64, close: f64) -> Self {
+        Candle {
+            open,
+            high,
+            low,
+            close,
+        }
+    }
+
+    /// If the price went up (or stayed the same)
+    pub fn is_up(&self) -> bool {
+        self.close >= self.open
+    }
+}
+
+/// Open/high/low/close chart, with a price axis on the left
+pub struct Candlestick {
+    pub buffer: PseudoBuffer,
+    /// Style of candles which closed higher than they opened
+    pub up_style: Style,
+    /// Style of candles which closed lower than they opened
+    pub down_style: Style,
+    /// Style of the price labels
+    pub label_style: Style,
+    /// Digits after the decimal point in price labels
+    pub precision: usize,
+    /// Cells between candles
+    pub gap: u16,
+}
+
+impl Creatable for Candlestick {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Candlestick {
+            up_style: Style::DEFAULT.fg(TextColor::Green),
+            down_style: Style::DEFAULT.fg(TextColor::Red),
+            label_style: buffer.theme_style(|t| t.muted, Style::DEFAULT.fg(TextColor::BrightBlack)),
+            precision: 2,
+            gap: 1,
+            buffer,
+        }
+    }
+}
+
+impl Candlestick {
+    /// Get the price axis of candles
+    fn axis(&self, candles: &[Candle]) -> Axis {
+        Axis::fit(candles.iter().flat_map(|c| [c.low, c.high])).precision(self.precision)
+    }
+
+    /// Get the width of the price labels (plus a space)
+    fn label_width(&self, candles: &[Candle]) -> u16 {
+        self.axis(candles).label_width(2) + 1
+    }
+
+    /// Get the number of candles which fit in `rect`
+    pub fn visible_count(&self, rect: &RectBoundary, candles: &[Candle]) -> usize {
+        let width = rect.size.0.saturating_sub(self.label_width(candles));
+        (width as usize).div_ceil(1 + self.gap as usize)
+    }
+
+    /// Handle a key event (Left/Right pan by one candle, Home/End go to the
+    /// first/last candles). Returns the new offset if it changed.
+    ///
+    /// ## Arguments:
+    /// * `event`
+    /// * `offset` - index of the first visible candle
+    /// * `total` - number of candles
+    /// * `visible` - number of visible candles (see [`Candlestick::visible_count`])
+    pub fn handle_key(
+        event: KeyEvent,
+        offset: usize,
+        total: usize,
+        visible: usize,
+    ) -> Option<usize> {
+        let last = total.saturating_sub(visible);
+
+        let new = match event.code {
+            KeyCode::Left => offset.saturating_sub(1),
+            KeyCode::Right => (offset + 1).min(last),
+            KeyCode::Home => 0,
+            KeyCode::End => last,
+            _ => return None,
+        };
+
+        (new != offset).then_some(new)
+    }
+
+    /// Like [`Candlestick::handle_key`], with the key of this frame translated by the
+    /// navigation preset (see [`crate::State::nav_key`])
+    pub fn handle_nav_key(
+        state: &mut crate::State,
+        offset: usize,
+        total: usize,
+        visible: usize,
+    ) -> Option<usize> {
+        Candlestick::handle_key(state.nav_key()?, offset, total, visible)
+    }
+
+    /// Draw the chart
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `candles` - every candle (oldest first)
+    /// * `offset` - index of the first visible candle (for panning)
+    pub fn render(
+        &mut self,
+        rect: RectBoundary,
+        candles: &[Candle],
+        offset: usize,
+    ) -> DrawingResult {
+        let label_width = self.label_width(candles);
+        let visible = &candles[offset.min(candles.len())..];
+        let visible = &visible[..self.visible_count(&rect, candles).min(visible.len())];
+
+        if visible.is_empty() || (rect.size.1 == 0) {
+            self.buffer.clear_rect(&rect)?;
+            return Ok((rect, self.buffer.get_changes()));
+        }
+
+        // the price range is fit to the visible candles (four dot rows per cell)
+        let axis = self.axis(visible);
+        let mut canvas = Canvas::new((rect.size.0 - label_width, rect.size.1));
+        let dot_rows = canvas.dot_size().1;
+        let dot_row = |price: f64| -> usize {
+            dot_rows - 1 - axis.position(price, dot_rows as u16).round() as usize
+        };
+
+        // price labels (about one every three rows, the labels of last frame are erased)
+        let labels = RectBoundary::new(rect.pos, (label_width, rect.size.1));
+        self.buffer.clear_rect(&labels)?;
+        axis.draw_vertical(
+            &mut self.buffer,
+            &RectBoundary::new(rect.pos, (label_width - 1, rect.size.1)),
+            (rect.size.1 as usize / 3).max(2),
+            self.label_style,
+        )?;
+
+        // candles (one cell each: the wick is one dot column, the body both)
+        for (i, candle) in visible.iter().enumerate() {
+            let x = i * (1 + self.gap as usize) * 2;
+            let style = if candle.is_up() {
+                self.up_style
+            } else {
+                self.down_style
+            };
+
+            canvas.line_v(x, dot_row(candle.high), dot_row(candle.low), style);
+
+            let (body_top, body_bottom) = (
+                dot_row(candle.open.max(candle.close)),
+                dot_row(candle.open.min(candle.close)),
+            );
+
+            canvas.line_v(x, body_top, body_bottom, style);
+            canvas.line_v(x + 1, body_top, body_bottom, style);
+        }
+
+        canvas.draw(&mut self.buffer, (rect.pos.0 + label_width, rect.pos.1))?;
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
+
+// timeline
+/// A labeled span of time in a [`Timeline`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimelineBar {
+    pub label: String,
+    pub start: f64,
+    pub end: f64,
+    pub style: Style,
+}
+
+impl TimelineBar {
+    pub fn new(label: &str, start: f64, end: f64) -> Self {
+        TimelineBar {
+            label: label.to_string(),
+            start,
+            end,
+            style: Style::DEFAULT.fg(TextColor::Blue),
+        }
+    }
+
+    /// Set the style of the bar
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+/// Visible part of a [`Timeline`], kept by the application between frames
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimelineView {
+    /// Time at the left edge
+    pub start: f64,
+    /// Time between the left and right edge
+    pub span: f64,
+    /// First visible bar
+    pub scroll: usize,
+}
+
+impl Default for TimelineView {
+    fn default() -> Self {
+        TimelineView {
+            start: 0.0,
+            span: 1.0,
+            scroll: 0,
+        }
+    }
+}
+
+impl TimelineView {
+    /// Create a view showing every bar
+    pub fn fit(bars: &[TimelineBar]) -> Self {
+        let (start, end) = bars
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(s, e), b| {
+                (s.min(b.start), e.max(b.end))
+            });
+
+        if !start.is_finite() || (end <= start) {
+            return TimelineView::default();
+        }
+
+        TimelineView {
+            start,
+            span: end - start,
+            scroll: 0,
+        }
+    }
+
+    /// Get the time at the right edge
+    pub fn end(&self) -> f64 {
+        self.start + self.span
+    }
+
+    /// Zoom around the middle (`factor` < 1 zooms in)
+    pub fn zoom(&mut self, factor: f64) {
+        let middle = self.start + self.span / 2.0;
+        self.span = (self.span * factor).max(f64::EPSILON);
+        self.start = middle - self.span / 2.0;
+    }
+
+    /// Move by a fraction of the visible span (negative goes back in time)
+    pub fn pan(&mut self, by: f64) {
+        self.start += self.span * by;
+    }
+
+    /// Handle a key event (`+`/`-` zoom, Left/Right pan, Up/Down scroll the bars).
+    /// Returns `true` if the key was used.
+    pub fn handle_key(&mut self, event: KeyEvent) -> bool {
+        match event.code {
+            KeyCode::Char('+') | KeyCode::Char('=') => self.zoom(0.5),
+            KeyCode::Char('-') => self.zoom(2.0),
+            KeyCode::Left => self.pan(-0.1),
+            KeyCode::Right => self.pan(0.1),
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll += 1,
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Like [`TimelineView::handle_key`], with the key of this frame translated by the
+    /// navigation preset (see [`crate::State::nav_key`]). Returns `true` if the key was used.
+    pub fn handle_nav_key(&mut self, state: &mut crate::State) -> bool {
+        match state.nav_key() {
+            Some(event) => self.handle_key(event),
+            None => false,
+        }
+    }
+}
+
+/// Labeled horizontal bars against a time axis (for task runners and profilers)
+pub struct Timeline {
+    pub buffer: PseudoBuffer,
+    /// Style of the labels and the time axis
+    pub label_style: Style,
+    /// Style of the "now" marker
+    pub now_style: Style,
+    /// Digits after the decimal point on the time axis
+    pub precision: usize,
+}
+
+impl Creatable for Timeline {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Timeline {
+            label_style: buffer.theme_style(|t| t.muted, Style::DEFAULT.fg(TextColor::BrightBlack)),
+            now_style: Style::DEFAULT.fg(TextColor::Yellow),
+            precision: 1,
+            buffer,
+        }
+    }
+}
+
+impl Timeline {
+    /// Draw the timeline
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y); the bottom row is the time axis
+    /// * `bars`
+    /// * `view` - [`TimelineView`]
+    /// * `now` - time of the "now" marker
+    pub fn render(
+        &mut self,
+        rect: RectBoundary,
+        bars: &[TimelineBar],
+        view: &mut TimelineView,
+        now: Option<f64>,
+    ) -> DrawingResult {
+        if (rect.size.1 < 2) || (view.span <= 0.0) {
+            return Ok((rect, self.buffer.get_changes()));
+        }
+
+        // labels take up to a third of the width
+        let label_width = bars
+            .iter()
+            .map(|b| b.label.chars().count() as u16 + 1)
+            .max()
+            .unwrap_or(0)
+            .min(rect.size.0 / 3);
+        let x = rect.pos.0 + label_width;
+        let width = rect.size.0 - label_width;
+        let height = (rect.size.1 - 1) as usize;
+
+        view.scroll = view.scroll.min(bars.len().saturating_sub(height));
+
+        // screen column of a time (can be off screen)
+        let col =
+            |time: f64| -> i64 { ((time - view.start) / view.span * width as f64).floor() as i64 };
+        let now_col = now.map(col).filter(|c| (0..width as i64).contains(c));
+
+        // bars
+        for (i, bar) in bars.iter().skip(view.scroll).take(height).enumerate() {
+            let y = rect.pos.1 + i as u16;
+            let label: String = bar
+                .label
+                .chars()
+                .take(label_width.saturating_sub(1) as usize)
+                .collect();
+
+            self.buffer
+                .write_str_styled((rect.pos.0, y), &label, self.label_style)?;
+
+            if let Some(c) = now_col {
+                self.buffer
+                    .write_str_styled((x + c as u16, y), "│", self.now_style)?;
+            }
+
+            // bars are at least one cell wide
+            let start = col(bar.start).max(0);
+            let end = col(bar.end).max(start + 1).min(width as i64);
+
+            if start < end {
+                self.buffer.write_str_styled(
+                    (x + start as u16, y),
+                    &"█".repeat((end - start) as usize),
+                    bar.style,
+                )?;
+            }
+        }
+
+        // time axis (start, middle, end)
+        let axis_y = rect.bottom() - 1;
+        let axis = Axis::new(view.start, view.end()).precision(self.precision);
+
+        self.buffer
+            .write_str_styled((x, axis_y), &"─".repeat(width as usize), self.label_style)?;
+
+        for (time, align) in [
+            (view.start, Align::Left),
+            (view.start + view.span / 2.0, Align::Center),
+            (view.end(), Align::Right),
+        ] {
+            let label = axis.label(time, 3);
+            let label_len = label.chars().count() as u16;
+
+            if label_len > width {
+                continue;
+            }
+
+            self.buffer.write_str_styled(
+                (x + align.offset(width, label_len), axis_y),
+                &label,
+                self.label_style,
+            )?;
+        }
+
+        if let Some(c) = now_col {
+            self.buffer
+                .write_str_styled((x + c as u16, axis_y), "▲", self.now_style)?;
+        }
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
@@ -0,0 +1,232 @@
+//! Collapsible sections
+//!
+//! A [`Collapsible`] is a header which opens and closes the content below it. The content
+//! grows and shrinks over a few frames; keep stepping the frame while
+//! [`CollapsibleState::is_animating`]. An [`Accordion`] keeps several sections, optionally
+//! with only one open at a time.
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{Creatable, DrawingResult, RectBoundary, TextAttribute};
+use crate::style::Style;
+use crate::text::{truncate_to_width, TruncateAt};
+use crate::State;
+
+/// State of a [`Collapsible`], kept by the application between frames
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct CollapsibleState {
+    pub open: bool,
+    /// Rows of the content which are shown (moves towards the content height while open)
+    pub shown: u16,
+    /// Height of the content when it was last drawn
+    height: u16,
+}
+
+impl CollapsibleState {
+    /// Create a new [`CollapsibleState`] (open sections start fully open)
+    pub fn new(open: bool) -> Self {
+        CollapsibleState {
+            open,
+            shown: if open { u16::MAX } else { 0 },
+            height: 0,
+        }
+    }
+
+    /// Open or close the section
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Check if the content is still growing or shrinking
+    pub fn is_animating(&self) -> bool {
+        let target = if self.open { self.height } else { 0 };
+        self.shown.min(self.height) != target
+    }
+
+    /// Move `shown` one frame towards its target
+    fn tick(&mut self, height: u16, ticks: u16) {
+        self.height = height;
+        self.shown = self.shown.min(height);
+
+        let step = height.div_ceil(ticks.max(1)).max(1);
+
+        self.shown = if self.open {
+            self.shown.saturating_add(step).min(height)
+        } else {
+            self.shown.saturating_sub(step)
+        };
+    }
+}
+
+/// Header which opens and closes the content below it: `▾ Title`
+pub struct Collapsible {
+    pub buffer: PseudoBuffer,
+    pub header_style: Style,
+    /// Number of frames opening or closing takes (`1` for no animation)
+    pub ticks: u16,
+    /// Marker of an open section
+    pub open_icon: char,
+    /// Marker of a closed section
+    pub closed_icon: char,
+}
+
+impl Creatable for Collapsible {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Collapsible {
+            header_style: buffer
+                .theme_style(|t| t.header, Style::DEFAULT.attr(TextAttribute::Bold)),
+            ticks: 4,
+            open_icon: '▾',
+            closed_icon: '▸',
+            buffer,
+        }
+    }
+}
+
+impl Collapsible {
+    /// Get the rect of the header (the first row)
+    pub fn header_rect(rect: &RectBoundary) -> RectBoundary {
+        RectBoundary::new(rect.pos, (rect.size.0, rect.size.1.min(1)))
+    }
+
+    /// Toggle the section if its header was clicked (or activated with the keyboard).
+    /// Returns `true` if it was.
+    pub fn handle_click(
+        state: &State,
+        rect: &RectBoundary,
+        section: &mut CollapsibleState,
+    ) -> bool {
+        match &state.click {
+            Some(click) if Collapsible::header_rect(rect).contains(click.pos) => {
+                section.toggle();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Draw the header and the shown part of the content. Returns the rect which was
+    /// used (the header and the shown rows), so sections can be stacked.
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `title`
+    /// * `section` - [`CollapsibleState`] (moves one frame further in its animation)
+    /// * `height` - rows of the content when it's fully open
+    /// * `content` - draws the content into the buffer and rect it's given
+    pub fn render(
+        &mut self,
+        rect: RectBoundary,
+        title: &str,
+        section: &mut CollapsibleState,
+        height: u16,
+        content: impl FnOnce(PseudoBuffer, RectBoundary) -> DrawingResult,
+    ) -> DrawingResult {
+        section.tick(height.min(rect.size.1.saturating_sub(1)), self.ticks);
+
+        // header
+        let icon = if section.open {
+            self.open_icon
+        } else {
+            self.closed_icon
+        };
+
+        self.buffer.write_str_styled(
+            rect.pos,
+            &truncate_to_width(
+                &format!("{icon} {title}"),
+                rect.size.0 as usize,
+                TruncateAt::End,
+            ),
+            self.header_style,
+        )?;
+
+        // content (cut off below the shown rows)
+        if section.shown > 0 {
+            let content_rect =
+                RectBoundary::new((rect.pos.0, rect.pos.1 + 1), (rect.size.0, section.height));
+            let shown = RectBoundary::new(content_rect.pos, (rect.size.0, section.shown));
+
+            let before = self.buffer.get_changes().len();
+            let (_, mut changes) = content(self.buffer.clone(), content_rect)?;
+            let mut drawn = changes.split_off(before.min(changes.len()));
+
+            drawn.retain(|c| shown.contains(c.loc));
+            changes.extend(drawn);
+            self.buffer.set_changes(changes);
+        }
+
+        // done
+        Ok((
+            RectBoundary::new(rect.pos, (rect.size.0, 1 + section.shown)),
+            self.buffer.get_changes(),
+        ))
+    }
+}
+
+/// Sections of which only one may be open at a time
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Accordion {
+    pub sections: Vec<CollapsibleState>,
+    /// Opening a section closes the others
+    pub exclusive: bool,
+}
+
+impl Accordion {
+    /// Create a new [`Accordion`] with `count` closed sections
+    pub fn new(count: usize, exclusive: bool) -> Self {
+        Accordion {
+            sections: vec![CollapsibleState::default(); count],
+            exclusive,
+        }
+    }
+
+    /// Open or close a section (closing the others if it opens in exclusive mode)
+    pub fn toggle(&mut self, index: usize) {
+        let Some(section) = self.sections.get_mut(index) else {
+            return;
+        };
+
+        section.toggle();
+
+        if self.exclusive && section.open {
+            for (i, other) in self.sections.iter_mut().enumerate() {
+                if i != index {
+                    other.open = false;
+                }
+            }
+        }
+    }
+
+    /// Get the index of the open section (the first one, if more are open)
+    pub fn open(&self) -> Option<usize> {
+        self.sections.iter().position(|s| s.open)
+    }
+
+    /// Check if any section is still growing or shrinking
+    pub fn is_animating(&self) -> bool {
+        self.sections.iter().any(|s| s.is_animating())
+    }
+
+    /// Toggle the section whose header was clicked
+    ///
+    /// ## Arguments:
+    /// * `state`
+    /// * `rects` - rects the sections were drawn at (see [`Collapsible::render`])
+    pub fn handle_click(&mut self, state: &State, rects: &[RectBoundary]) -> bool {
+        let Some(click) = &state.click else {
+            return false;
+        };
+
+        match rects
+            .iter()
+            .position(|r| Collapsible::header_rect(r).contains(click.pos))
+        {
+            Some(i) => {
+                self.toggle(i);
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Platform compatibility
+//!
+//! Differences between terminals the rest of hamui shouldn't have to care about,
+//! mostly the Windows console (conhost) and Windows Terminal.
+use crate::drawing::Vec2;
+
+/// Make sure the terminal understands the escape sequences we write.
+/// On Windows, virtual terminal processing has to be turned on for the console
+/// (Windows Terminal has it on already). Returns `false` if it couldn't be.
+pub fn enable_virtual_terminal() -> bool {
+    #[cfg(windows)]
+    {
+        crossterm::ansi_support::supports_ansi()
+    }
+
+    #[cfg(not(windows))]
+    {
+        true
+    }
+}
+
+/// Check if we're running in the legacy Windows console (conhost) instead of
+/// Windows Terminal (which sets `WT_SESSION`) or another terminal emulator
+pub fn is_legacy_console() -> bool {
+    #[cfg(windows)]
+    {
+        std::env::var_os("WT_SESSION").is_none() && std::env::var_os("TERM_PROGRAM").is_none()
+    }
+
+    #[cfg(not(windows))]
+    {
+        false
+    }
+}
+
+/// Get the window size after a resize event of `size`. The legacy Windows console
+/// reports the size of its screen buffer (with all of its scrollback) in resize events,
+/// so the size of the visible window is asked for again there.
+pub fn resize_size(size: Vec2) -> Vec2 {
+    if is_legacy_console() {
+        return crossterm::terminal::size().unwrap_or(size);
+    }
+
+    size
+}
@@ -0,0 +1,91 @@
+//! TOML configuration (requires the `config` feature)
+//!
+//! ```toml
+//! [theme]
+//! accent = { fg = "BrightMagenta" }
+//! selected = { fg = "Black", bg = "Magenta", attributes = ["Bold"] }
+//!
+//! [keys]
+//! quit = ["ctrl+c", "q"]
+//! save = ["ctrl+s"]
+//! ```
+//!
+//! Everything is optional: missing styles come from [`Theme::default`], and
+//! actions which aren't listed keep their [`Keymap::default`] bindings.
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::path::Path;
+
+use crate::keymap::Keymap;
+use crate::theme::Theme;
+
+/// Error while loading a [`Config`]
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file couldn't be read
+    Io(std::io::Error),
+    /// The file isn't valid TOML, or has invalid values (the message says where)
+    Invalid(toml::de::Error),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "couldn't read config: {e}"),
+            ConfigError::Invalid(e) => write!(f, "invalid config: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(e) => Some(e),
+            ConfigError::Invalid(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(value: std::io::Error) -> Self {
+        ConfigError::Io(value)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(value: toml::de::Error) -> Self {
+        ConfigError::Invalid(value)
+    }
+}
+
+/// User configuration of a hamui application
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub theme: Theme,
+    #[serde(rename = "keys")]
+    pub keymap: Keymap,
+}
+
+impl Config {
+    /// Parse a config from TOML
+    pub fn parse(toml: &str) -> Result<Config, ConfigError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// Load a config file
+    pub fn load(path: impl AsRef<Path>) -> Result<Config, ConfigError> {
+        Config::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Load a config file, or get the default config if the file doesn't exist.
+    /// Other errors (including invalid configs) are still returned.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Config, ConfigError> {
+        match Config::load(path) {
+            Err(ConfigError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Config::default())
+            }
+            res => res,
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Crash screen
+//!
+//! With [`crate::Frame::enable_crash_screen`], a panic in the draw function shows
+//! an error screen inside of the UI (instead of a backtrace over the raw mode screen).
+use crossterm::{cursor, event, terminal, QueueableCommand};
+use std::cell::Cell;
+use std::io::Write;
+use std::panic::{PanicHookInfo, UnwindSafe};
+use std::sync::{Mutex, Once};
+
+use crate::buffer::{BufCell, BufferWrite, PseudoBuffer};
+use crate::drawing::{RectBoundary, TextAttribute, TextBackgroundColor, TextColor, Vec2};
+use crate::i18n::{self, Locale};
+use crate::style::Style;
+
+/// Message of the last panic (set by the hook, taken by the frame)
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+/// The hook is only installed once (installing it again would chain it to itself)
+static INSTALL: Once = Once::new();
+
+thread_local! {
+    /// If this thread is running the draw function (see [`catch_draw`])
+    static IN_DRAW: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Install a panic hook which only remembers the message of panics in the draw function
+/// (printing it would mess up the screen while we're in raw mode). Every other panic goes
+/// to the previous hook, after leaving the UI if it happened on the main thread.
+pub fn install_panic_hook() {
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+
+        std::panic::set_hook(Box::new(move |info: &PanicHookInfo| {
+            if IN_DRAW.with(Cell::get) {
+                *LAST_PANIC.lock().unwrap_or_else(|e| e.into_inner()) = Some(message(info));
+                return;
+            }
+
+            if std::thread::current().name() == Some("main") {
+                restore_terminal();
+            }
+
+            previous(info);
+        }));
+    });
+}
+
+/// Run the draw function, catching its panics (their message is kept for
+/// [`take_panic_message`] instead of being printed)
+pub(crate) fn catch_draw<R>(f: impl FnOnce() -> R + UnwindSafe) -> std::thread::Result<R> {
+    /// Resets the flag, even when `f` panics
+    struct Reset(bool);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            IN_DRAW.with(|d| d.set(self.0));
+        }
+    }
+
+    let _reset = Reset(IN_DRAW.with(|d| d.replace(true)));
+    std::panic::catch_unwind(f)
+}
+
+/// Get the message of a panic (with where it happened)
+fn message(info: &PanicHookInfo) -> String {
+    let message = match info.payload().downcast_ref::<&str>() {
+        Some(s) => s.to_string(),
+        None => match info.payload().downcast_ref::<String>() {
+            Some(s) => s.clone(),
+            None => "Box<dyn Any>".to_string(),
+        },
+    };
+
+    match info.location() {
+        Some(l) => format!("{message}\n\nat {}:{}:{}", l.file(), l.line(), l.column()),
+        None => message,
+    }
+}
+
+/// Leave the UI (like [`crate::Frame::exit`] does), so the panic is readable
+fn restore_terminal() {
+    if !terminal::is_raw_mode_enabled().unwrap_or(false) {
+        return;
+    }
+
+    let mut stdout = std::io::stdout();
+    let _ = stdout.queue(event::DisableFocusChange);
+    let _ = stdout.queue(event::DisableMouseCapture);
+    let _ = stdout.queue(terminal::LeaveAlternateScreen);
+    let _ = stdout.queue(cursor::Show);
+    let _ = stdout.flush();
+    let _ = terminal::disable_raw_mode();
+}
+
+/// Take the message of the last panic
+pub fn take_panic_message() -> Option<String> {
+    LAST_PANIC.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// Draw the crash screen: `message` on red, with a "press any key to exit" prompt
+///
+/// ## Arguments:
+/// * `buffer`
+/// * `window_size`
+/// * `message`
+/// * `locale` - strings of the screen
+pub fn render_crash_screen(
+    buffer: &mut PseudoBuffer,
+    window_size: Vec2,
+    message: &str,
+    locale: &dyn Locale,
+) {
+    let style = Style::DEFAULT
+        .fg(TextColor::BrightWhite)
+        .bg(TextBackgroundColor::Red);
+    let width = window_size.0.saturating_sub(4) as usize;
+
+    let _ = buffer.fill_rect(
+        &RectBoundary::window(window_size),
+        BufCell::styled(' ', style),
+    );
+    let _ = buffer.write_str_styled(
+        (2, 1),
+        &i18n::text(locale, i18n::CRASH_TITLE),
+        style.attr(TextAttribute::Bold),
+    );
+
+    // message (wrapped)
+    let mut y = 3;
+
+    for line in message.lines() {
+        let chars: Vec<char> = line.chars().collect();
+
+        for chunk in chars.chunks(width.max(1)) {
+            if y + 2 >= window_size.1 {
+                break;
+            }
+
+            let _ = buffer.write_str_styled((2, y), &chunk.iter().collect::<String>(), style);
+            y += 1;
+        }
+
+        if line.is_empty() {
+            y += 1;
+        }
+    }
+
+    let _ = buffer.write_str_styled(
+        (2, window_size.1.saturating_sub(2)),
+        &i18n::text(locale, i18n::CRASH_EXIT),
+        style.attr(TextAttribute::Dim),
+    );
+}
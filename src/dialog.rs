@@ -0,0 +1,270 @@
+//! Dialogs
+//!
+//! Modal confirmation and input dialogs. [`crate::Frame::confirm`] and
+//! [`crate::Frame::prompt`] show one on top of the application and block until it's
+//! answered, so simple apps don't need any state for them. Applications with their own
+//! event loop can keep a [`DialogState`] and draw it with [`Dialog`] instead.
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::buffer::PseudoBuffer;
+use crate::drawing::{
+    Component, Creatable, DrawingResult, QuickBox, RectBoundary, Text, TextBackgroundColor,
+    TextColor, TextLeaf, Vec2,
+};
+use crate::i18n::{self, Locale};
+use crate::input::{TextInput, TextInputState};
+use crate::style::{Blend, Style};
+use crate::text::{display_width, truncate_to_width, TruncateAt};
+
+/// How a dialog was closed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DialogAnswer {
+    /// Yes/OK
+    Accept,
+    /// No/Cancel (or Escape)
+    Cancel,
+}
+
+/// Question of a [`Dialog`], kept by the application between frames
+#[derive(Clone, Debug, Default)]
+pub struct DialogState {
+    pub message: String,
+    /// Value of a prompt (`None` for a confirmation)
+    pub input: Option<TextInputState>,
+    /// If the cancel button is selected (instead of the accept button)
+    pub cancel_selected: bool,
+}
+
+impl DialogState {
+    /// Create a yes/no question
+    pub fn confirm(message: &str) -> Self {
+        DialogState {
+            message: message.to_string(),
+            input: None,
+            cancel_selected: false,
+        }
+    }
+
+    /// Create a question with a text field (starting with `text`)
+    pub fn prompt(label: &str, text: &str) -> Self {
+        DialogState {
+            message: label.to_string(),
+            input: Some(TextInputState::new(text)),
+            cancel_selected: false,
+        }
+    }
+
+    /// Get the labels of the accept and cancel buttons (Yes/No, or OK/Cancel for prompts)
+    pub fn labels(&self, locale: &dyn Locale) -> [String; 2] {
+        if self.input.is_some() {
+            [
+                i18n::text(locale, i18n::OK),
+                i18n::text(locale, i18n::CANCEL),
+            ]
+        } else {
+            [i18n::text(locale, i18n::YES), i18n::text(locale, i18n::NO)]
+        }
+    }
+
+    /// Handle a key event. Enter presses the selected button, Escape cancels, Tab switches
+    /// the button (and so do Left/Right, `y` and `n` answer confirmations). Other keys
+    /// edit the value of a prompt.
+    pub fn handle_key(&mut self, event: KeyEvent) -> Option<DialogAnswer> {
+        let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+
+        match event.code {
+            KeyCode::Enter => {
+                return Some(if self.cancel_selected {
+                    DialogAnswer::Cancel
+                } else {
+                    DialogAnswer::Accept
+                })
+            }
+            KeyCode::Esc => return Some(DialogAnswer::Cancel),
+            KeyCode::Char('c') if ctrl => return Some(DialogAnswer::Cancel),
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.cancel_selected = !self.cancel_selected;
+                return None;
+            }
+            _ => {}
+        }
+
+        match &mut self.input {
+            Some(input) => {
+                input.handle_key(event);
+                None
+            }
+            None => match event.code {
+                KeyCode::Left | KeyCode::Right => {
+                    self.cancel_selected = !self.cancel_selected;
+                    None
+                }
+                KeyCode::Char('y' | 'Y') => Some(DialogAnswer::Accept),
+                KeyCode::Char('n' | 'N') => Some(DialogAnswer::Cancel),
+                _ => None,
+            },
+        }
+    }
+
+    /// Get the value of the field (empty for confirmations)
+    pub fn value(&self) -> String {
+        self.input
+            .as_ref()
+            .map(|i| i.text.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Box in the middle of the window with the message, a field (for prompts) and buttons
+pub struct Dialog {
+    pub buffer: PseudoBuffer,
+    /// Style of the selected button
+    pub selected_style: Style,
+    /// Recolor the application behind the dialog
+    pub backdrop: Option<Blend>,
+}
+
+impl Creatable for Dialog {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Dialog {
+            selected_style: buffer.theme_style(
+                |t| t.selected,
+                Style::DEFAULT
+                    .fg(TextColor::Black)
+                    .bg(TextBackgroundColor::Cyan),
+            ),
+            backdrop: Some(Blend::Darken),
+            buffer,
+        }
+    }
+}
+
+impl Dialog {
+    /// Get the rect of the dialog (centered, as wide as the message but at least 30 cells)
+    pub fn rect(window_size: Vec2, dialog: &DialogState) -> RectBoundary {
+        let width = (display_width(&dialog.message) as u16 + 4)
+            .max(30)
+            .min(window_size.0.saturating_sub(2));
+        // message, (field,) empty row, buttons, bottom border
+        let height = if dialog.input.is_some() { 5 } else { 4 };
+
+        RectBoundary::new(
+            (
+                window_size.0.saturating_sub(width) / 2,
+                window_size.1.saturating_sub(height + 1) / 2,
+            ),
+            (width, height),
+        )
+    }
+
+    /// Get the rects of the accept and cancel buttons (right aligned on the last row)
+    pub fn button_rects(rect: &RectBoundary, labels: &[String; 2]) -> [RectBoundary; 2] {
+        let y = rect.pos.1 + rect.size.1 - 1;
+        let widths = labels.clone().map(|l| display_width(&l) as u16 + 2);
+
+        let cancel = RectBoundary::new(
+            (rect.right().saturating_sub(widths[1] + 2), y),
+            (widths[1], 1),
+        );
+        let accept = RectBoundary::new(
+            (cancel.pos.0.saturating_sub(widths[0] + 2), y),
+            (widths[0], 1),
+        );
+
+        [accept, cancel]
+    }
+
+    /// Handle a click on the buttons
+    ///
+    /// ## Arguments:
+    /// * `pos` - where the click landed
+    /// * `window_size`
+    /// * `dialog` - [`DialogState`]
+    /// * `locale` - for the labels of the buttons
+    pub fn handle_click(
+        pos: Vec2,
+        window_size: Vec2,
+        dialog: &DialogState,
+        locale: &dyn Locale,
+    ) -> Option<DialogAnswer> {
+        let rect = Dialog::rect(window_size, dialog);
+        let [accept, cancel] = Dialog::button_rects(&rect, &dialog.labels(locale));
+
+        if accept.contains(pos) {
+            Some(DialogAnswer::Accept)
+        } else if cancel.contains(pos) {
+            Some(DialogAnswer::Cancel)
+        } else {
+            None
+        }
+    }
+
+    /// Draw the dialog on top of everything drawn before it
+    ///
+    /// ## Arguments:
+    /// * `window_size`
+    /// * `dialog` - [`DialogState`]
+    /// * `locale` - for the labels of the buttons
+    pub fn render(
+        &mut self,
+        window_size: Vec2,
+        dialog: &mut DialogState,
+        locale: &dyn Locale,
+    ) -> DrawingResult {
+        let rect = Dialog::rect(window_size, dialog);
+        let inner_width = rect.size.0.saturating_sub(4);
+
+        // box
+        let mut frame = QuickBox::new(self.buffer.clone());
+        frame.shadow = true;
+        frame.fill = Some(Style::DEFAULT);
+        frame.backdrop = self.backdrop;
+
+        let (_, changes) = frame.render(window_size, rect.clone())?;
+        self.buffer.set_changes(changes);
+
+        // message
+        let (_, changes) = Text::new(self.buffer.clone()).render(
+            TextLeaf::styled(
+                &truncate_to_width(&dialog.message, inner_width as usize, TruncateAt::End),
+                Style::DEFAULT,
+            ),
+            (rect.pos.0 + 2, rect.pos.1 + 1),
+        )?;
+        self.buffer.set_changes(changes);
+
+        // field
+        if let Some(input) = &mut dialog.input {
+            let mut field = TextInput::new(self.buffer.clone());
+            field.cursor = true;
+
+            let (_, changes) = field.render(
+                RectBoundary::new((rect.pos.0 + 2, rect.pos.1 + 2), (inner_width, 1)),
+                input,
+            )?;
+            self.buffer.set_changes(changes);
+        }
+
+        // buttons
+        let labels = dialog.labels(locale);
+
+        for (i, (label, button)) in labels
+            .iter()
+            .zip(Dialog::button_rects(&rect, &labels))
+            .enumerate()
+        {
+            let style = if (i == 1) == dialog.cancel_selected {
+                self.selected_style
+            } else {
+                Style::DEFAULT
+            };
+
+            let (_, changes) = Text::new(self.buffer.clone())
+                .render_button(TextLeaf::styled(label, style), button.pos)?;
+            self.buffer.set_changes(changes);
+        }
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
@@ -1,5 +1,13 @@
 //! Components
-use crate::buffer::{BufferChange, BufferWrite, PseudoBuffer};
+use crossterm::event::KeyEvent;
+
+use crate::buffer::{BufCell, BufferChange, BufferWrite, PseudoBuffer};
+use crate::event::{ClickEvent, ScrollEvent};
+use crate::style::{Blend, Style};
+use crate::task::Progress;
+use crate::text::{
+    display_width, truncate_cells, truncate_to_width, visual_map, visual_order, TruncateAt,
+};
 use crate::State;
 
 // traits
@@ -14,53 +22,201 @@ pub trait Creatable {
 
 /// Component can be clicked
 pub trait Clickable {
-    /// Handle a click event on the element
+    /// Handle a click event on the element.
+    /// `run` is called if the last click was inside of `res`, and `true` is returned.
     fn on_click(
         &mut self,
         res: RectBoundary,
-        state: State,
-        run: &mut dyn FnMut(State) -> State,
-    ) -> State {
-        if check_click(&state, res) {
-            return run(state);
+        state: &mut State,
+        run: &mut dyn FnMut(&mut State, &ClickEvent),
+    ) -> bool {
+        match state.click.clone() {
+            Some(event) if res.contains(event.pos) => {
+                run(state, &event);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Handle a scroll event on the element.
+    /// `run` is called if the mouse was inside of `res` while scrolling, and `true` is returned.
+    fn on_scroll(
+        &mut self,
+        res: RectBoundary,
+        state: &mut State,
+        run: &mut dyn FnMut(&mut State, &ScrollEvent),
+    ) -> bool {
+        match state.scroll.clone() {
+            Some(event) if res.contains(event.pos) => {
+                run(state, &event);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Handle the mouse hovering over the element.
+    /// `run` is called if the cursor is inside of `res`, and `true` is returned.
+    fn on_hover(
+        &mut self,
+        res: RectBoundary,
+        state: &mut State,
+        run: &mut dyn FnMut(&mut State),
+    ) -> bool {
+        if state.keyboard_input_mode | !res.contains(state.cursor_pos) {
+            return false;
         }
 
-        state
+        run(state);
+        true
     }
 }
 
+/// If a key was used by a [`KeyHandler`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Handled {
+    Yes,
+    No,
+}
+
+impl From<bool> for Handled {
+    fn from(value: bool) -> Self {
+        if value {
+            Handled::Yes
+        } else {
+            Handled::No
+        }
+    }
+}
+
+/// Component (or component state) which handles its own keys while it's focused.
+/// Pass it to [`State::dispatch_key`] during the draw; keys it doesn't use fall
+/// through to the global keybindings (focus movement, etc.).
+pub trait KeyHandler {
+    fn handle_key(&mut self, event: KeyEvent, state: &mut State) -> Handled;
+}
+
 // types
 pub type Vec2 = (u16, u16);
 pub type DrawingResult = Result<DrawingNode, std::io::Error>;
 pub type DrawingNode = (RectBoundary, Vec<BufferChange>);
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct RectBoundary {
     pub pos: Vec2,
     pub size: Vec2,
 }
 
+/// Where a rect is attached to its parent with [`RectBoundary::anchor`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl RectBoundary {
+    /// Create a rect covering the whole window
+    pub fn window(window_size: Vec2) -> RectBoundary {
+        RectBoundary {
+            pos: (0, 0),
+            size: window_size,
+        }
+    }
+
+    /// Create a rect (at `(0, 0)`) which is a percentage of the window size.
+    /// Percentages over 100 are treated as 100.
+    ///
+    /// ## Arguments:
+    /// * `window_size`
+    /// * `width` - percent of the window width
+    /// * `height` - percent of the window height
+    pub fn percent_of(window_size: Vec2, width: u16, height: u16) -> RectBoundary {
+        let percent = |total: u16, p: u16| ((total as u32 * p.min(100) as u32) / 100) as u16;
+
+        RectBoundary {
+            pos: (0, 0),
+            size: (
+                percent(window_size.0, width),
+                percent(window_size.1, height),
+            ),
+        }
+    }
+
+    /// Move this rect so it is attached to `anchor` in `parent`.
+    /// `offset` moves the rect inwards, away from the anchored edges.
+    pub fn anchor(&self, parent: &RectBoundary, anchor: Anchor, offset: Vec2) -> RectBoundary {
+        let free = (
+            parent.size.0.saturating_sub(self.size.0),
+            parent.size.1.saturating_sub(self.size.1),
+        );
+
+        // x
+        let x = match anchor {
+            Anchor::TopLeft | Anchor::Left | Anchor::BottomLeft => offset.0,
+            Anchor::Top | Anchor::Center | Anchor::Bottom => free.0 / 2,
+            Anchor::TopRight | Anchor::Right | Anchor::BottomRight => {
+                free.0.saturating_sub(offset.0)
+            }
+        };
+
+        // y
+        let y = match anchor {
+            Anchor::TopLeft | Anchor::Top | Anchor::TopRight => offset.1,
+            Anchor::Left | Anchor::Center | Anchor::Right => free.1 / 2,
+            Anchor::BottomLeft | Anchor::Bottom | Anchor::BottomRight => {
+                free.1.saturating_sub(offset.1)
+            }
+        };
+
+        RectBoundary {
+            pos: (parent.pos.0 + x, parent.pos.1 + y),
+            size: self.size,
+        }
+    }
+
+    /// Move this rect to the center of `parent`
+    pub fn centered_in(&self, parent: &RectBoundary) -> RectBoundary {
+        self.anchor(parent, Anchor::Center, (0, 0))
+    }
+}
+
 // utility
-/// Get the center of the screen based on the size of a box
+/// Get the center of the screen based on the size of a box.
+/// Boxes larger than the window are placed at `0` instead of underflowing.
 pub fn get_center(window_size: (u16, u16), size: (u16, u16)) -> (u16, u16) {
-    let (w, h) = window_size;
-    let (x, y) = size;
-
-    ((w / 2) - (x / 2), (h / 2) - (y / 2))
+    (
+        center_h(window_size.0, size.0),
+        center_v(window_size.1, size.1),
+    )
 }
 
-/// Check if a click was inside of a target position and size
-pub fn check_click(state: &State, res: RectBoundary) -> bool {
-    let (x, y) = state.clicked;
+/// Get the x position which centers `width` within `parent_width` (saturating at `0`)
+pub fn center_h(parent_width: u16, width: u16) -> u16 {
+    (parent_width / 2).saturating_sub(width / 2)
+}
 
-    let range_x = res.pos.0..(res.pos.0 + res.size.0);
-    let range_y = res.pos.1..(res.pos.1 + res.size.1);
+/// Get the y position which centers `height` within `parent_height` (saturating at `0`)
+pub fn center_v(parent_height: u16, height: u16) -> u16 {
+    (parent_height / 2).saturating_sub(height / 2)
+}
 
-    if !range_x.contains(&x) | !range_y.contains(&y) {
-        return false;
-    }
+/// Get the position which centers a box of `size` within `parent`
+pub fn center_in(parent: &RectBoundary, size: Vec2) -> Vec2 {
+    let (x, y) = get_center(parent.size, size);
+    (parent.pos.0 + x, parent.pos.1 + y)
+}
 
-    return true;
+/// Check if a click was inside of a target position and size
+pub fn check_click(state: &State, res: RectBoundary) -> bool {
+    res.contains(state.clicked)
 }
 
 // line
@@ -77,6 +233,7 @@ impl DownwardsLine {
     /// * `start` - x, y
     /// * `char` - line character
     /// * `end_char` - line character at the end of the line (for corners)
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(
         buffer: &mut PseudoBuffer,
         height: u16,
@@ -101,14 +258,394 @@ impl DownwardsLine {
     }
 }
 
+pub struct HorizontalLine {
+    pub rect: RectBoundary,
+}
+
+impl HorizontalLine {
+    /// Draw a line going right
+    ///
+    /// ## Arguments:
+    /// * `width`
+    /// * `start` - x, y
+    /// * `char` - line character
+    /// * `end_char` - line character at the end of the line (for corners)
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        buffer: &mut PseudoBuffer,
+        width: u16,
+        start: Vec2,
+        char: &str,
+        end_char: &str,
+    ) -> RectBoundary {
+        for i in 0..width {
+            if i == width - 1 {
+                buffer.write_str((start.0 + i, start.1), end_char).unwrap();
+                break;
+            }
+
+            buffer.write_str((start.0 + i, start.1), char).unwrap();
+        }
+
+        // return
+        RectBoundary {
+            pos: start,
+            size: (width, 1),
+        }
+    }
+}
+
+/// Directions a line character connects to (see [`LineCharset::char_for`])
+pub mod connects {
+    pub const UP: u8 = 1;
+    pub const DOWN: u8 = 2;
+    pub const LEFT: u8 = 4;
+    pub const RIGHT: u8 = 8;
+}
+
+/// Set of box-drawing characters used to draw lines
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineCharset {
+    pub horizontal: char,
+    pub vertical: char,
+    pub top_left: char,
+    pub top_right: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+    /// `┬`
+    pub t_down: char,
+    /// `┴`
+    pub t_up: char,
+    /// `├`
+    pub t_right: char,
+    /// `┤`
+    pub t_left: char,
+    pub cross: char,
+}
+
+impl LineCharset {
+    pub const LIGHT: LineCharset = LineCharset {
+        horizontal: '─',
+        vertical: '│',
+        top_left: '┌',
+        top_right: '┐',
+        bottom_left: '└',
+        bottom_right: '┘',
+        t_down: '┬',
+        t_up: '┴',
+        t_right: '├',
+        t_left: '┤',
+        cross: '┼',
+    };
+
+    pub const ROUNDED: LineCharset = LineCharset {
+        top_left: '╭',
+        top_right: '╮',
+        bottom_left: '╰',
+        bottom_right: '╯',
+        ..LineCharset::LIGHT
+    };
+
+    pub const HEAVY: LineCharset = LineCharset {
+        horizontal: '━',
+        vertical: '┃',
+        top_left: '┏',
+        top_right: '┓',
+        bottom_left: '┗',
+        bottom_right: '┛',
+        t_down: '┳',
+        t_up: '┻',
+        t_right: '┣',
+        t_left: '┫',
+        cross: '╋',
+    };
+
+    pub const DOUBLE: LineCharset = LineCharset {
+        horizontal: '═',
+        vertical: '║',
+        top_left: '╔',
+        top_right: '╗',
+        bottom_left: '╚',
+        bottom_right: '╝',
+        t_down: '╦',
+        t_up: '╩',
+        t_right: '╠',
+        t_left: '╣',
+        cross: '╬',
+    };
+
+    /// Get the character which connects to `directions` (a mix of [`connects`] flags)
+    pub fn char_for(&self, directions: u8) -> char {
+        use connects::*;
+
+        match directions {
+            d if d == (UP | DOWN | LEFT | RIGHT) => self.cross,
+            d if d == (DOWN | LEFT | RIGHT) => self.t_down,
+            d if d == (UP | LEFT | RIGHT) => self.t_up,
+            d if d == (UP | DOWN | RIGHT) => self.t_right,
+            d if d == (UP | DOWN | LEFT) => self.t_left,
+            d if d == (DOWN | RIGHT) => self.top_left,
+            d if d == (DOWN | LEFT) => self.top_right,
+            d if d == (UP | RIGHT) => self.bottom_left,
+            d if d == (UP | LEFT) => self.bottom_right,
+            d if (d & (UP | DOWN)) != 0 => self.vertical,
+            _ => self.horizontal,
+        }
+    }
+
+    /// Get the directions a character of this charset connects to
+    pub fn connections(&self, char: char) -> Option<u8> {
+        use connects::*;
+
+        let all = [
+            UP | DOWN | LEFT | RIGHT,
+            DOWN | LEFT | RIGHT,
+            UP | LEFT | RIGHT,
+            UP | DOWN | RIGHT,
+            UP | DOWN | LEFT,
+            DOWN | RIGHT,
+            DOWN | LEFT,
+            UP | RIGHT,
+            UP | LEFT,
+            UP | DOWN,
+            LEFT | RIGHT,
+        ];
+
+        all.into_iter().find(|d| self.char_for(*d) == char)
+    }
+}
+
+/// Draw a line from `from` to `to`. Lines which aren't straight go horizontally
+/// first and then vertically (with a corner between). Where the line crosses or
+/// touches another line of the same charset, the correct junction is drawn.
+pub fn draw_line(
+    buffer: &mut PseudoBuffer,
+    from: Vec2,
+    to: Vec2,
+    charset: &LineCharset,
+) -> RectBoundary {
+    use connects::*;
+
+    // get every cell of the path in order
+    let mut path: Vec<Vec2> = Vec::new();
+    let step = |a: u16, b: u16| if b >= a { 1i32 } else { -1i32 };
+
+    let mut x = from.0 as i32;
+    let sx = step(from.0, to.0);
+
+    while x != to.0 as i32 {
+        path.push((x as u16, from.1));
+        x += sx;
+    }
+
+    let mut y = from.1 as i32;
+    let sy = step(from.1, to.1);
+
+    while y != to.1 as i32 {
+        path.push((to.0, y as u16));
+        y += sy;
+    }
+
+    path.push(to);
+
+    // direction from one cell to a neighbor
+    let direction = |a: Vec2, b: Vec2| -> u8 {
+        if b.0 > a.0 {
+            RIGHT
+        } else if b.0 < a.0 {
+            LEFT
+        } else if b.1 > a.1 {
+            DOWN
+        } else {
+            UP
+        }
+    };
+
+    // write
+    for (i, pos) in path.iter().enumerate() {
+        let prev = if i > 0 { path.get(i - 1) } else { None };
+        let next = path.get(i + 1);
+
+        let mut directions = match (prev, next) {
+            (Some(p), Some(n)) => direction(*pos, *p) | direction(*pos, *n),
+            // line ends only connect inwards (and are drawn straight unless they join a line)
+            (Some(p), None) => direction(*pos, *p),
+            (None, Some(n)) => direction(*pos, *n),
+            (None, None) => LEFT | RIGHT,
+        };
+
+        // connect to lines which are already there
+        if let Some(existing) = buffer.get_cell(*pos) {
+            directions |= charset.connections(existing.char).unwrap_or(0);
+        }
+
+        buffer
+            .write_cell(*pos, BufCell::from_char(charset.char_for(directions)))
+            .unwrap();
+    }
+
+    // return
+    RectBoundary {
+        pos: (from.0.min(to.0), from.1.min(to.1)),
+        size: (from.0.abs_diff(to.0) + 1, from.1.abs_diff(to.1) + 1),
+    }
+}
+
+impl LineCharset {
+    /// Every built-in charset (rounded first so its corners are found before light ones)
+    pub const ALL: [LineCharset; 4] = [
+        LineCharset::ROUNDED,
+        LineCharset::LIGHT,
+        LineCharset::HEAVY,
+        LineCharset::DOUBLE,
+    ];
+
+    /// Find the built-in charset a character belongs to
+    pub fn find(char: char) -> Option<LineCharset> {
+        LineCharset::ALL
+            .into_iter()
+            .find(|c| c.connections(char).is_some())
+    }
+}
+
+/// Merge box-drawing characters which touch into proper junctions.
+///
+/// Borders of adjacent boxes overwrite each other (a `╭` drawn over the end of a
+/// `─` line); this goes over every box-drawing cell written to `buffer` and connects
+/// it to neighboring lines which point at it, turning it into `├ ┬ ┼` (and so on).
+/// Call this at the end of a draw.
+pub fn merge_junctions(buffer: &mut PseudoBuffer) {
+    use connects::*;
+    use std::collections::HashMap;
+
+    // get what every cell will end up as
+    let mut cells: HashMap<Vec2, BufCell> = HashMap::new();
+
+    for change in buffer.get_changes() {
+        cells.insert(change.loc, change.cell);
+    }
+
+    let connections_at = |pos: Option<Vec2>| -> u8 {
+        pos.and_then(|pos| cells.get(&pos))
+            .and_then(|cell| LineCharset::find(cell.char).and_then(|c| c.connections(cell.char)))
+            .unwrap_or(0)
+    };
+
+    let mut merged: Vec<(Vec2, BufCell)> = Vec::new();
+
+    for (pos, cell) in &cells {
+        let charset = match LineCharset::find(cell.char) {
+            Some(c) => c,
+            None => continue,
+        };
+
+        let own = charset.connections(cell.char).unwrap_or(0);
+        let mut directions = own;
+
+        // connect to every neighbor which points at this cell
+        let (x, y) = *pos;
+        let neighbors = [
+            (y.checked_sub(1).map(|y| (x, y)), UP, DOWN),
+            (Some((x, y + 1)), DOWN, UP),
+            (x.checked_sub(1).map(|x| (x, y)), LEFT, RIGHT),
+            (Some((x + 1, y)), RIGHT, LEFT),
+        ];
+
+        for (neighbor, towards, back) in neighbors {
+            if (connections_at(neighbor) & back) != 0 {
+                directions |= towards;
+            }
+        }
+
+        if directions != own {
+            merged.push((
+                *pos,
+                BufCell::styled(charset.char_for(directions), cell.style),
+            ));
+        }
+    }
+
+    // write
+    for (pos, cell) in merged {
+        buffer.write_cell(pos, cell).unwrap();
+    }
+}
+
+// shadow
+/// Draw a drop shadow for `rect` (one cell to the right and below it).
+/// Whatever is already under the shadow is kept, but dimmed.
+pub fn draw_shadow(buffer: &mut PseudoBuffer, window_size: Vec2, rect: &RectBoundary) {
+    let style = Style::DEFAULT
+        .fg(TextColor::BrightBlack)
+        .bg(TextBackgroundColor::Black)
+        .attr(TextAttribute::Dim);
+
+    let right = (rect.right(), (rect.pos.1 + 1)..=rect.bottom());
+    let bottom = ((rect.pos.0 + 1)..=rect.right(), rect.bottom());
+
+    let cells = right
+        .1
+        .map(|y| (right.0, y))
+        .chain(bottom.0.map(|x| (x, bottom.1)));
+
+    for pos in cells {
+        if (pos.0 >= window_size.0) | (pos.1 >= window_size.1) {
+            continue;
+        }
+
+        let char = buffer.get_cell(pos).map(|c| c.char).unwrap_or(' ');
+        buffer
+            .write_cell(pos, BufCell::styled(char, style))
+            .unwrap();
+    }
+}
+
+// blending
+/// Recolor every cell drawn so far inside of `rect` (like an overlay would), see [`Style::blend`].
+/// Cells nothing was drawn to are left alone.
+pub fn blend_rect(
+    buffer: &mut PseudoBuffer,
+    rect: &RectBoundary,
+    blend: Blend,
+) -> std::io::Result<()> {
+    // last write wins
+    let mut cells = std::collections::HashMap::new();
+
+    for change in buffer.get_changes() {
+        if rect.contains(change.loc) {
+            cells.insert(change.loc, change.cell);
+        }
+    }
+
+    for (pos, cell) in cells {
+        buffer.write_cell(pos, BufCell::styled(cell.char, cell.style.blend(blend)))?;
+    }
+
+    Ok(())
+}
+
 // box
 pub struct QuickBox {
     pub buffer: PseudoBuffer,
+    /// Draw a drop shadow (for popups and modals)
+    pub shadow: bool,
+    /// Title drawn in the top border
+    pub title: Option<String>,
+    /// Fill the inside of the box with this style (its background)
+    pub fill: Option<Style>,
+    /// Recolor everything drawn before the box (to dim the background of a modal)
+    pub backdrop: Option<Blend>,
 }
 
 impl Creatable for QuickBox {
     fn new(buffer: PseudoBuffer) -> Self {
-        QuickBox { buffer }
+        QuickBox {
+            buffer,
+            shadow: false,
+            title: None,
+            fill: None,
+            backdrop: None,
+        }
     }
 }
 
@@ -123,9 +660,36 @@ impl Component for QuickBox {
         let pos = rect.pos;
         let mut size = rect.size;
 
-        // auto resize (y)
-        if size.1 >= window_size.1 {
-            size.1 -= size.1 - window_size.1;
+        // auto resize (y, the bottom line is drawn at `pos.1 + size.1`)
+        size.1 = size
+            .1
+            .min(window_size.1.saturating_sub(pos.1.saturating_add(1)));
+
+        // auto resize (x)
+        size.0 = size.0.min(window_size.0.saturating_sub(pos.0));
+
+        if (size.0 < 2) | (size.1 == 0) {
+            // too small to have borders, draw nothing
+            return Ok((
+                RectBoundary { pos, size: (0, 0) },
+                self.buffer.get_changes(),
+            ));
+        }
+
+        // backdrop
+        if let Some(blend) = self.backdrop {
+            blend_rect(
+                &mut self.buffer,
+                &RectBoundary::new((0, 0), window_size),
+                blend,
+            )?;
+        }
+
+        // fill
+        if let Some(style) = self.fill {
+            let inside = RectBoundary::new((pos.0 + 1, pos.1 + 1), (size.0 - 2, size.1 - 1));
+            self.buffer
+                .fill_rect(&inside, BufCell::styled(' ', style))?;
         }
 
         // draw line
@@ -135,6 +699,17 @@ impl Component for QuickBox {
         // write
         self.buffer.write_str(pos, &line_top)?; // top
 
+        if let Some(title) = &self.title {
+            // " title " after the corner and one line, keeping one line before the other corner
+            let max = (size.0 as usize).saturating_sub(6);
+
+            if max > 0 {
+                let title: String = title.chars().take(max).collect();
+                self.buffer
+                    .write_str((pos.0 + 2, pos.1), &format!(" {title} "))?;
+            }
+        }
+
         DownwardsLine::new(&mut self.buffer, size.1, (pos.0, pos.1 + 1), "│", "╰"); // left
         DownwardsLine::new(
             // right
@@ -148,6 +723,12 @@ impl Component for QuickBox {
         self.buffer
             .write_str((pos.0 + 1, pos.1 + size.1), &line_bottom)?; // bottom
 
+        if self.shadow {
+            // the bottom line is drawn at `pos.1 + size.1`, so the box is one row taller than `size`
+            let outer = RectBoundary::new(pos, (size.0, size.1 + 1));
+            draw_shadow(&mut self.buffer, window_size, &outer);
+        }
+
         // done
         Ok((RectBoundary { pos, size }, self.buffer.get_changes()))
     }
@@ -166,21 +747,37 @@ impl Creatable for Text {
 
 impl Text {
     /// Draw text at the center of a given [`Vec2`]
+    #[deprecated(note = "use `Text::render_aligned` with `Align::Center`")]
     pub fn render_center(&mut self, leaf: TextLeaf, pos: Vec2, parent_width: u16) -> DrawingResult {
-        let text = &leaf.text;
+        self.render_aligned(&Line::from(leaf), pos, parent_width, Align::Center)
+    }
 
-        // get center
-        let center = get_center((parent_width, 1), (text.len() as u16, 1));
+    /// Draw a [`Line`] aligned within `width` cells starting at `pos`.
+    /// Lines wider than `width` are cut (with a `…`). Wide characters take two cells.
+    ///
+    /// ## Arguments:
+    /// * `line`
+    /// * `pos` - left end of the area
+    /// * `width` - width of the area
+    /// * `align` - [`Align`]
+    pub fn render_aligned(
+        &mut self,
+        line: &Line,
+        pos: Vec2,
+        width: u16,
+        align: Align,
+    ) -> DrawingResult {
+        let line = line.truncate_to_width(width, TruncateAt::End).visual();
+        let line_width = line.display_width();
+        let pos = (pos.0 + align.offset(width, line_width), pos.1);
 
-        // draw
-        // center.0 + pos.0 so it's offset by the position of what we're centering around
-        self.buffer.write_str((center.0 + pos.0, pos.1), text)?;
+        self.buffer.write_line(pos, &line)?;
 
         // done
         Ok((
             RectBoundary {
                 pos,
-                size: (text.len() as u16, 1),
+                size: (line_width, 1),
             },
             self.buffer.get_changes(),
         ))
@@ -188,17 +785,42 @@ impl Text {
 
     /// Draw text at a given [`Vec2`]
     pub fn render(&mut self, leaf: TextLeaf, pos: Vec2) -> DrawingResult {
-        let text = &leaf.text;
+        let text = &visual_order(&leaf.text);
 
         // draw
-        // center.0 + pos.0 so it's offset by the position of what we're centering around
-        self.buffer.write_str(pos, text)?;
+        self.buffer.write_str_styled(pos, text, leaf.style)?;
 
         // done
         Ok((
             RectBoundary {
                 pos: (pos.0, pos.1),
-                size: (text.len() as u16, 1),
+                size: (display_width(text) as u16, 1),
+            },
+            self.buffer.get_changes(),
+        ))
+    }
+
+    /// Draw text at a given [`Vec2`], cut down to `width` cells (with a `…` where it was cut)
+    pub fn render_truncated(
+        &mut self,
+        leaf: TextLeaf,
+        pos: Vec2,
+        width: u16,
+        at: TruncateAt,
+    ) -> DrawingResult {
+        let text = truncate_to_width(&leaf.text, width as usize, at);
+        self.render(TextLeaf::styled(&text, leaf.style), pos)
+    }
+
+    /// Draw a [`Line`] of spans at a given [`Vec2`]
+    pub fn render_line(&mut self, line: &Line, pos: Vec2) -> DrawingResult {
+        self.buffer.write_line(pos, &line.visual())?;
+
+        // done
+        Ok((
+            RectBoundary {
+                pos,
+                size: (line.display_width(), 1),
             },
             self.buffer.get_changes(),
         ))
@@ -208,16 +830,20 @@ impl Text {
     pub fn render_button(&mut self, leaf: TextLeaf, pos: Vec2) -> DrawingResult {
         let text = &leaf.text;
 
+        // buttons are black on white unless the leaf has its own colors
+        let mut style = leaf.style;
+        style.fg = style.fg.or(Some(TextColor::Black));
+        style.bg = style.bg.or(Some(TextBackgroundColor::BrightWhite));
+
         // draw
-        // center.0 + pos.0 so it's offset by the position of what we're centering around
         self.buffer
-            .write_str(pos, &format!("\x1b[107;30m➚ {text}\x1b[0m"))?;
+            .write_str_styled(pos, &format!("➚ {text}"), style)?;
 
         // done
         Ok((
             RectBoundary {
                 pos: (pos.0, pos.1),
-                size: (text.len() as u16, 1),
+                size: (display_width(text) as u16 + 2, 1),
             },
             self.buffer.get_changes(),
         ))
@@ -229,95 +855,322 @@ impl Clickable for Text {}
 // status line
 pub struct StatusLine {
     pub buffer: PseudoBuffer,
+    /// Style of the bar (spans without colors inherit its colors)
+    pub style: Style,
 }
 
 impl Creatable for StatusLine {
     fn new(buffer: PseudoBuffer) -> Self {
-        StatusLine { buffer }
+        StatusLine {
+            // white backgroud, black text
+            style: buffer.theme_style(
+                |t| t.status,
+                Style::DEFAULT
+                    .fg(TextColor::Black)
+                    .bg(TextBackgroundColor::BrightWhite),
+            ),
+            buffer,
+        }
     }
 }
 
-impl Component for StatusLine {
-    /// Draw a status line (full width line)
-    ///
-    /// ## Arguments:
-    /// * `stdout`
-    /// * `rect` - size(x, y), pos(x, y)
-    fn render(&mut self, window_size: (u16, u16), rect: RectBoundary) -> DrawingResult {
-        // draw chars
-        self.buffer.write_str(rect.pos, "\x1b[107;30m")?; // white backgroud, black text
-        self.buffer
-            .write_str(rect.pos, &" ".repeat(rect.size.0 as usize))?;
+/// Built-in status line segment (see [`StatusLine::render_segments`])
+pub enum StatusSegment {
+    /// Current time in UTC (`HH:MM:SS UTC`, there's no time zone database to get
+    /// the local time from; use [`StatusSegment::Custom`] for that).
+    /// It asks for a redraw at the next second (see [`State::redraw_in`]).
+    Clock,
+    /// Current input mode (`MOUSE` or `KEYBOARD`)
+    Mode,
+    /// Window size (`80x24`)
+    WindowSize,
+    /// Fixed text
+    Text(Line),
+    /// Text computed from the state every time the status line is drawn
+    Custom(Box<dyn Fn(&State) -> Line>),
+}
+
+impl StatusSegment {
+    /// Get the contents of the segment
+    pub fn line(&self, state: &State) -> Line {
+        match self {
+            StatusSegment::Clock => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default();
+                let secs = now.as_secs();
+
+                // tick
+                state.redraw_in(std::time::Duration::from_nanos(
+                    1_000_000_000 - now.subsec_nanos() as u64,
+                ));
+
+                Line::from(
+                    format!(
+                        "{:02}:{:02}:{:02} UTC",
+                        (secs / 3600) % 24,
+                        (secs / 60) % 60,
+                        secs % 60
+                    )
+                    .as_str(),
+                )
+            }
+            StatusSegment::Mode => Line::from(if state.keyboard_input_mode {
+                "KEYBOARD"
+            } else {
+                "MOUSE"
+            }),
+            StatusSegment::WindowSize => {
+                Line::from(format!("{}x{}", state.window_size.0, state.window_size.1).as_str())
+            }
+            StatusSegment::Text(line) => line.clone(),
+            StatusSegment::Custom(f) => f(state),
+        }
+    }
+}
+
+impl StatusLine {
+    /// Draw a status line made of segments, separated by `│`.
+    /// `left` segments are drawn from the left edge and `right` segments end at the right edge.
+    pub fn render_segments(
+        &mut self,
+        rect: RectBoundary,
+        state: &State,
+        left: &[StatusSegment],
+        right: &[StatusSegment],
+    ) -> DrawingResult {
+        let join = |segments: &[StatusSegment]| {
+            let mut line = Line::default();
+
+            for (i, segment) in segments.iter().enumerate() {
+                if i > 0 {
+                    line = line.push(" │ ");
+                }
+
+                line.spans.extend(segment.line(state).spans);
+            }
+
+            line
+        };
+
+        let mut line = Line::from(" ");
+        line.spans.extend(join(left).spans);
+
+        let right = join(right).push(" ");
+        let gap = rect
+            .size
+            .0
+            .saturating_sub(line.display_width() + right.display_width());
+
+        // right segments only fit if there is space left
+        if gap > 0 {
+            line = line.push(" ".repeat(gap as usize).as_str());
+            line.spans.extend(right.spans);
+        }
+
+        self.render_line(rect, &line)
+    }
+
+    /// Draw a status line with a [`Line`] of spans in it.
+    /// Spans without colors are drawn with the colors of the bar.
+    pub fn render_line(&mut self, rect: RectBoundary, line: &Line) -> DrawingResult {
+        let line = line
+            .truncate_to_width(rect.size.0, TruncateAt::End)
+            .with_base(self.style);
+        let width = line.display_width();
+        let padding = " ".repeat(rect.size.0.saturating_sub(width) as usize);
+
+        self.buffer.write_line(rect.pos, &line)?;
         self.buffer
-            .write_str((rect.pos.0 + rect.size.0, rect.pos.1), "\x1b[0m")?;
+            .write_str_styled((rect.pos.0 + width, rect.pos.1), &padding, self.style)?;
 
         // done
         Ok((
             RectBoundary {
                 pos: rect.pos,
-                size: (window_size.0, 1),
+                size: (rect.size.0, 1),
             },
             self.buffer.get_changes(),
         ))
     }
 }
 
+impl Component for StatusLine {
+    /// Draw a status line (full width line)
+    ///
+    /// ## Arguments:
+    /// * `stdout`
+    /// * `rect` - size(x, y), pos(x, y)
+    fn render(&mut self, window_size: (u16, u16), rect: RectBoundary) -> DrawingResult {
+        // draw chars
+        let width = rect.size.0.min(window_size.0.saturating_sub(rect.pos.0));
+        self.render_line(RectBoundary::new(rect.pos, (width, 1)), &Line::default())
+    }
+}
+
 // row
+/// Horizontal alignment of content within a rect
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    /// Get the x offset of content `width` cells wide inside of `parent_width` cells
+    pub fn offset(&self, parent_width: u16, width: u16) -> u16 {
+        match self {
+            Align::Left => 0,
+            Align::Center => parent_width.saturating_sub(width) / 2,
+            Align::Right => parent_width.saturating_sub(width),
+        }
+    }
+}
+
 pub struct QuickRow {
     pub buffer: PseudoBuffer,
+    /// Number of empty cells between components
+    pub gap: u16,
+    /// Alignment of the components within the row
+    pub align: Align,
 }
 
 impl Creatable for QuickRow {
     fn new(buffer: PseudoBuffer) -> Self {
-        QuickRow { buffer }
+        QuickRow {
+            buffer,
+            gap: 0,
+            align: Align::Left,
+        }
     }
 }
 
 impl QuickRow {
-    /// Get the correct position of the next component.
-    fn get_component_position(
-        &self,
-        prev_component_rect: Option<RectBoundary>,
-        mut component_pos: Vec2,
-    ) -> Vec2 {
-        if prev_component_rect.is_none() {
-            // leave component as is if it's the first
-            return component_pos;
+    /// Get the rect of every component in the row.
+    ///
+    /// ## Arguments:
+    /// * `rect` - the row
+    /// * `sizes` - size of every component
+    pub fn layout(&self, rect: &RectBoundary, sizes: &[Vec2]) -> Vec<RectBoundary> {
+        // total width of components + gaps
+        let gaps = self
+            .gap
+            .saturating_mul(sizes.len().saturating_sub(1).min(u16::MAX as usize) as u16);
+        let width = sizes
+            .iter()
+            .fold(gaps, |width, size| width.saturating_add(size.0));
+
+        let mut x = rect
+            .pos
+            .0
+            .saturating_add(self.align.offset(rect.size.0, width));
+        let mut rects = Vec::new();
+
+        for size in sizes {
+            rects.push(RectBoundary {
+                pos: (x, rect.pos.1),
+                size: *size,
+            });
+
+            // next component starts after this one and the gap
+            x = x.saturating_add(size.0).saturating_add(self.gap);
         }
 
-        let prev_component_rect = prev_component_rect.unwrap();
-        component_pos.0 += prev_component_rect.pos.0 + prev_component_rect.size.0; // new position is x + prev x + prev width
-                                                                                   // height (size.1) and y (pos.1) is ignored, we don't need that
-        component_pos
+        rects
     }
 
-    /// Render [`QuickRow`]. Components can only be simple text components.
-    /// Starts at `rect.pos.0` and fills `components` with no gap.
+    /// Render [`QuickRow`] with simple text components.
     /// `components` contains `(content, size)` (`(TextLeaf, Vec2)`)
     pub fn render(
         &mut self,
         rect: RectBoundary,
         components: Vec<(TextLeaf, Vec2)>,
     ) -> DrawingResult {
-        let mut prev_rect: Option<RectBoundary> = Option::None; // store previous row item
-        let mut global_buffer = self.buffer.clone();
+        let sizes: Vec<Vec2> = components.iter().map(|c| c.1).collect();
+        let rects = self.layout(&rect, &sizes);
 
-        for component in components {
+        for (component, child_rect) in components.into_iter().zip(rects) {
             // create text component
-            let mut text = Text::new(self.buffer.clone());
-
-            // get correct component
-            let pos = self.get_component_position(prev_rect.clone(), component.1);
+            let mut text = Text::new(PseudoBuffer::new(self.buffer.window_size));
 
             // render
-            let res = text.render(component.0, pos)?;
-            global_buffer.set_changes([global_buffer.get_changes(), res.1].concat());
-            prev_rect = Option::Some(res.0);
-            // concat global_buffer with component changes
+            let res = text.render(component.0, child_rect.pos)?;
+            self.buffer
+                .set_changes([self.buffer.get_changes(), res.1].concat());
         }
 
         // ...
-        Ok((rect, global_buffer.get_changes()))
+        Ok((rect, self.buffer.get_changes()))
+    }
+
+    /// Render [`QuickRow`] with any [`Component`]s.
+    /// `components` contains `(component, size)`; each component is rendered
+    /// with its rect in the row.
+    pub fn render_components(
+        &mut self,
+        window_size: Vec2,
+        rect: RectBoundary,
+        components: Vec<(&mut dyn Component, Vec2)>,
+    ) -> DrawingResult {
+        let sizes: Vec<Vec2> = components.iter().map(|c| c.1).collect();
+        let rects = self.layout(&rect, &sizes);
+
+        for (component, child_rect) in components.into_iter().zip(rects) {
+            let res = component.0.render(window_size, child_rect)?;
+            self.buffer
+                .set_changes([self.buffer.get_changes(), res.1].concat());
+        }
+
+        // ...
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
+
+// stack
+/// Container which draws its children on top of each other (in order).
+/// Empty cells are transparent, so lower children show through them.
+pub struct Stack {
+    pub buffer: PseudoBuffer,
+}
+
+impl Creatable for Stack {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Stack { buffer }
+    }
+}
+
+impl Stack {
+    /// Render [`Stack`]. Every child is rendered with `rect`, and anything
+    /// they draw outside of `rect` is dropped.
+    ///
+    /// ## Arguments:
+    /// * `window_size`
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `children` - bottom child first
+    pub fn render(
+        &mut self,
+        window_size: Vec2,
+        rect: RectBoundary,
+        children: Vec<&mut dyn Component>,
+    ) -> DrawingResult {
+        for child in children {
+            let res = child.render(window_size, rect.clone())?;
+
+            // keep only opaque cells inside of the stack
+            let changes: Vec<BufferChange> = res
+                .1
+                .into_iter()
+                .filter(|c| !c.cell.empty && rect.contains(c.loc))
+                .collect();
+
+            self.buffer
+                .set_changes([self.buffer.get_changes(), changes].concat());
+        }
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
     }
 }
 
@@ -327,15 +1180,20 @@ pub enum TextCommand {
     Reset = 0,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum TextAttribute {
     Bold = 1,
+    Dim = 2,
     Italic = 3,
     Underline = 4,
+    Blink = 5,
+    /// Reverse video (swap text and background colors)
     Swap = 7,
+    Hidden = 8,
+    Strikethrough = 9,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum TextColor {
     Black = 30,
     Red = 31,
@@ -355,7 +1213,7 @@ pub enum TextColor {
     BrightWhite = 97,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum TextBackgroundColor {
     Black = 40,
     Red = 41,
@@ -375,33 +1233,319 @@ pub enum TextBackgroundColor {
     BrightWhite = 107,
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct TextLeaf {
     pub text: String,
+    pub style: Style,
 }
 
 impl TextLeaf {
+    /// Create a [`TextLeaf`] with any [`Style`]
+    pub fn styled(text: &str, style: Style) -> Self {
+        TextLeaf {
+            text: text.to_string(),
+            style,
+        }
+    }
+
     pub fn new(text: String, fg: TextColor, bg: TextBackgroundColor) -> Self {
         TextLeaf {
-            text: format!(
-                "\x1b[{};{}m{text}\x1b[{}m",
-                fg as u8,
-                bg as u8,
-                TextCommand::Reset as u8
-            ),
+            text,
+            style: Style::DEFAULT.fg(fg).bg(bg),
         }
     }
+
+    /// Add a [`TextAttribute`] (can be chained to combine attributes)
+    pub fn attr(mut self, attr: TextAttribute) -> Self {
+        self.style = self.style.attr(attr);
+        self
+    }
 }
 
 impl From<&str> for TextLeaf {
     fn from(value: &str) -> Self {
         TextLeaf {
             text: value.to_string(),
+            style: Style::DEFAULT,
         }
     }
 }
 
 impl std::fmt::Display for TextLeaf {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.text)
+        if self.style == Style::DEFAULT {
+            return f.write_str(&self.text);
+        }
+
+        write!(
+            f,
+            "{}{}{}",
+            self.style.to_ansi(),
+            self.text,
+            Style::DEFAULT.to_ansi()
+        )
+    }
+}
+
+// line
+/// A piece of styled text in a [`Line`]
+pub type Span = TextLeaf;
+
+/// A single row of text made of [`Span`]s which can each have their own style
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Line {
+    pub spans: Vec<Span>,
+}
+
+impl Line {
+    pub fn new(spans: Vec<Span>) -> Self {
+        Line { spans }
+    }
+
+    /// Add a span to the end of the line
+    pub fn push(mut self, span: impl Into<Span>) -> Self {
+        self.spans.push(span.into());
+        self
+    }
+
+    /// Width of the line in cells (styling is not counted)
+    pub fn width(&self) -> u16 {
+        self.spans
+            .iter()
+            .map(|s| s.text.chars().count() as u16)
+            .sum()
+    }
+
+    /// Width of the line in terminal cells (wide characters take two)
+    pub fn display_width(&self) -> u16 {
+        self.spans
+            .iter()
+            .map(|s| display_width(&s.text) as u16)
+            .sum()
+    }
+
+    /// Get the line cut down to `width` cells
+    pub fn truncate(&self, width: u16) -> Line {
+        let mut left = width as usize;
+        let mut spans = Vec::new();
+
+        for span in &self.spans {
+            if left == 0 {
+                break;
+            }
+
+            let text: String = span.text.chars().take(left).collect();
+            left -= text.chars().count();
+            spans.push(TextLeaf::styled(&text, span.style));
+        }
+
+        Line { spans }
+    }
+
+    /// Get every character of the line with its style
+    /// (collect them back into a [`Line`] after changing them)
+    pub fn cells(&self) -> Vec<(char, Style)> {
+        self.spans
+            .iter()
+            .flat_map(|s| s.text.chars().map(move |c| (c, s.style)))
+            .collect()
+    }
+
+    /// Get the line cut down to `width` terminal cells, with a `…` where it was cut
+    pub fn truncate_to_width(&self, width: u16, at: TruncateAt) -> Line {
+        truncate_cells(&self.cells(), width as usize, at)
+            .into_iter()
+            .collect()
+    }
+
+    /// Get the line with its characters in visual order, so right-to-left
+    /// text is drawn correctly (see [`crate::text::visual_map`])
+    pub fn visual(&self) -> Line {
+        let text: String = self.spans.iter().map(|s| s.text.as_str()).collect();
+        let cells = self.cells();
+
+        visual_map(&text).into_iter().map(|i| cells[i]).collect()
+    }
+
+    /// Fill in missing colors of every span from `base`
+    pub fn with_base(&self, base: Style) -> Line {
+        Line {
+            spans: self
+                .spans
+                .iter()
+                .map(|span| {
+                    let mut style = span.style;
+                    style.fg = style.fg.or(base.fg);
+                    style.bg = style.bg.or(base.bg);
+                    TextLeaf::styled(&span.text, style)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FromIterator<(char, Style)> for Line {
+    /// Group styled characters into spans (neighbours with the same style share one)
+    fn from_iter<T: IntoIterator<Item = (char, Style)>>(iter: T) -> Self {
+        let mut spans: Vec<Span> = Vec::new();
+
+        for (char, style) in iter {
+            match spans.last_mut() {
+                Some(span) if span.style == style => span.text.push(char),
+                _ => spans.push(TextLeaf::styled(&char.to_string(), style)),
+            }
+        }
+
+        Line { spans }
+    }
+}
+
+impl From<&str> for Line {
+    fn from(value: &str) -> Self {
+        Line {
+            spans: vec![TextLeaf::from(value)],
+        }
+    }
+}
+
+impl From<TextLeaf> for Line {
+    fn from(value: TextLeaf) -> Self {
+        Line { spans: vec![value] }
+    }
+}
+
+impl From<Vec<Span>> for Line {
+    fn from(value: Vec<Span>) -> Self {
+        Line { spans: value }
+    }
+}
+
+// paragraph
+/// Multiple [`Line`]s of text in a rect
+pub struct Paragraph {
+    pub buffer: PseudoBuffer,
+    /// Number of lines scrolled past
+    pub scroll: u16,
+}
+
+impl Creatable for Paragraph {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Paragraph { buffer, scroll: 0 }
+    }
+}
+
+impl Paragraph {
+    /// Draw lines in a rect. Lines which don't fit are cut off.
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `lines`
+    pub fn render(&mut self, rect: RectBoundary, lines: &[Line]) -> DrawingResult {
+        let visible = lines
+            .iter()
+            .skip(self.scroll as usize)
+            .take(rect.size.1 as usize);
+
+        for (i, line) in visible.enumerate() {
+            self.buffer.write_line(
+                (rect.pos.0, rect.pos.1 + i as u16),
+                &line.truncate(rect.size.0).visual(),
+            )?;
+        }
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
+
+// progress bar
+/// Horizontal bar showing how far along something is
+pub struct ProgressBar {
+    pub buffer: PseudoBuffer,
+    /// Style of the done part
+    pub style: Style,
+    /// Style of the rest
+    pub track_style: Style,
+    /// Show the percentage and status over the bar
+    pub label: bool,
+}
+
+impl Creatable for ProgressBar {
+    fn new(buffer: PseudoBuffer) -> Self {
+        ProgressBar {
+            buffer,
+            style: Style::DEFAULT
+                .fg(TextColor::Black)
+                .bg(TextBackgroundColor::Green),
+            track_style: Style::DEFAULT.bg(TextBackgroundColor::BrightBlack),
+            label: true,
+        }
+    }
+}
+
+impl ProgressBar {
+    /// Draw the bar
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, _)
+    /// * `progress` - [`Progress`]
+    pub fn render(&mut self, rect: RectBoundary, progress: &Progress) -> DrawingResult {
+        let width = rect.size.0 as usize;
+        let done = (progress.fraction.clamp(0.0, 1.0) * width as f64).round() as usize;
+
+        // label (padded to fill the bar)
+        let mut label = String::new();
+
+        if self.label {
+            label = format!(" {:.0}%", progress.fraction * 100.0);
+
+            if !progress.status.is_empty() {
+                label = format!("{label} {}", progress.status);
+            }
+        }
+
+        let text: Vec<char> = label
+            .chars()
+            .chain(std::iter::repeat(' '))
+            .take(width)
+            .collect();
+
+        self.buffer.write_str_styled(
+            rect.pos,
+            &text[..done].iter().collect::<String>(),
+            self.style,
+        )?;
+        self.buffer.write_str_styled(
+            (rect.pos.0 + done as u16, rect.pos.1),
+            &text[done..].iter().collect::<String>(),
+            self.track_style,
+        )?;
+
+        // done
+        Ok((
+            RectBoundary {
+                pos: rect.pos,
+                size: (rect.size.0, 1),
+            },
+            self.buffer.get_changes(),
+        ))
+    }
+
+    /// Draw the progress of the task `name` (see [`crate::Frame::spawn_task`]).
+    /// Nothing is drawn if the task isn't running.
+    pub fn render_task(&mut self, rect: RectBoundary, state: &State, name: &str) -> DrawingResult {
+        match state.progress.get(name) {
+            Some(progress) => self.render(rect, &progress),
+            None => Ok((rect, self.buffer.get_changes())),
+        }
+    }
+
+    /// Draw the combined progress of every running task.
+    /// Nothing is drawn if no task is running.
+    pub fn render_total(&mut self, rect: RectBoundary, state: &State) -> DrawingResult {
+        match state.progress.total() {
+            Some(progress) => self.render(rect, &progress),
+            None => Ok((rect, self.buffer.get_changes())),
+        }
     }
 }
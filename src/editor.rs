@@ -0,0 +1,792 @@
+//! Code editor
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{
+    Creatable, DrawingResult, Line, RectBoundary, TextBackgroundColor, TextColor, TextLeaf, Vec2,
+};
+use crate::input::{EditKind, TextInputState, UndoStack};
+use crate::scroll::Gutter;
+use crate::style::Style;
+
+/// Editing mode of an [`Editor`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum EditorMode {
+    /// Keys move the cursor and run commands (`i` to insert, `/` to search, ...)
+    #[default]
+    Normal,
+    /// Keys insert text (`Esc` to go back to normal mode)
+    Insert,
+}
+
+/// A change to the text of an [`EditorState`], passed to the `on_change` callback
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditorChange {
+    /// A line was edited
+    Edited(usize),
+    /// A line was inserted at the index
+    Inserted(usize),
+    /// The line at the index was removed
+    Removed(usize),
+    /// The whole text was replaced (undo, redo, [`EditorState::set_text`])
+    Replaced,
+}
+
+/// Turns a line of source into styled spans
+pub trait Highlighter {
+    fn highlight(&self, line: &str) -> Line;
+}
+
+/// Simple [`Highlighter`] for keywords, strings, numbers and line comments
+#[derive(Clone, Debug)]
+pub struct KeywordHighlighter {
+    pub keywords: Vec<String>,
+    /// Start of a line comment (like `//` or `#`)
+    pub comment: String,
+    pub keyword_style: Style,
+    pub string_style: Style,
+    pub number_style: Style,
+    pub comment_style: Style,
+}
+
+impl KeywordHighlighter {
+    /// Create a new [`KeywordHighlighter`]
+    pub fn new(keywords: &[&str], comment: &str) -> Self {
+        KeywordHighlighter {
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            comment: comment.to_string(),
+            keyword_style: Style::DEFAULT.fg(TextColor::Magenta),
+            string_style: Style::DEFAULT.fg(TextColor::Green),
+            number_style: Style::DEFAULT.fg(TextColor::Yellow),
+            comment_style: Style::DEFAULT.fg(TextColor::BrightBlack),
+        }
+    }
+}
+
+impl Highlighter for KeywordHighlighter {
+    fn highlight(&self, line: &str) -> Line {
+        let chars: Vec<char> = line.chars().collect();
+        let comment: Vec<char> = self.comment.chars().collect();
+        let mut spans = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let start = i;
+
+            let style = if !comment.is_empty() && chars[i..].starts_with(&comment) {
+                // comment (the rest of the line)
+                i = chars.len();
+                self.comment_style
+            } else if (chars[i] == '"') || (chars[i] == '\'') {
+                // string (until the closing quote)
+                let quote = chars[i];
+                i += 1;
+
+                while (i < chars.len()) && (chars[i] != quote) {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+
+                i = (i + 1).min(chars.len());
+                self.string_style
+            } else if chars[i].is_alphanumeric() || (chars[i] == '_') {
+                // word
+                while (i < chars.len()) && (chars[i].is_alphanumeric() || (chars[i] == '_')) {
+                    i += 1;
+                }
+
+                let word: String = chars[start..i].iter().collect();
+
+                if chars[start].is_ascii_digit() {
+                    self.number_style
+                } else if self.keywords.contains(&word) {
+                    self.keyword_style
+                } else {
+                    Style::DEFAULT
+                }
+            } else {
+                i += 1;
+                Style::DEFAULT
+            };
+
+            spans.push(TextLeaf::styled(
+                &chars[start..i].iter().collect::<String>(),
+                style,
+            ));
+        }
+
+        Line::new(spans)
+    }
+}
+
+/// Text of an [`Editor`], kept by the application between frames
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct EditorState {
+    /// Lines of text (never empty)
+    pub lines: Vec<String>,
+    /// Cursor position as (line, column in characters)
+    pub cursor: (usize, usize),
+    pub mode: EditorMode,
+    /// First visible line
+    pub scroll: usize,
+    /// First visible column (when `wrap` is off)
+    pub hscroll: usize,
+    /// Wrap long lines instead of scrolling horizontally
+    pub wrap: bool,
+    /// Undo/redo history (not saved in sessions)
+    #[serde(skip)]
+    pub history: UndoStack,
+    /// Search being typed (after `/`)
+    #[serde(skip)]
+    pub search_input: Option<TextInputState>,
+    /// Last search (highlighted, `n`/`N` jump between matches)
+    pub search: Option<String>,
+    /// Incremented on every change to the text
+    pub version: u64,
+}
+
+impl Default for EditorState {
+    fn default() -> Self {
+        EditorState::new("")
+    }
+}
+
+impl EditorState {
+    /// Create a new [`EditorState`] with the cursor at the start of `text`
+    pub fn new(text: &str) -> Self {
+        EditorState {
+            lines: EditorState::split(text),
+            cursor: (0, 0),
+            mode: EditorMode::Normal,
+            scroll: 0,
+            hscroll: 0,
+            wrap: false,
+            history: UndoStack::default(),
+            search_input: None,
+            search: None,
+            version: 0,
+        }
+    }
+
+    /// Split text into lines (always at least one)
+    fn split(text: &str) -> Vec<String> {
+        text.split('\n').map(|l| l.to_string()).collect()
+    }
+
+    /// Get the whole text
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Replace the whole text (can be undone)
+    pub fn set_text(&mut self, text: &str, on_change: &mut dyn FnMut(EditorChange)) {
+        self.record(EditKind::Other);
+        self.lines = EditorState::split(text);
+        self.clamp_cursor();
+        self.changed(EditorChange::Replaced, on_change);
+    }
+
+    /// Toggle soft wrapping of long lines
+    pub fn toggle_wrap(&mut self) {
+        self.wrap = !self.wrap;
+        self.hscroll = 0;
+    }
+
+    // cursor
+    /// Number of characters in a line
+    fn line_len(&self, line: usize) -> usize {
+        self.lines[line].chars().count()
+    }
+
+    /// Get the byte index of a column in a line
+    fn byte_index(&self, line: usize, col: usize) -> usize {
+        self.lines[line]
+            .char_indices()
+            .nth(col)
+            .map(|(i, _)| i)
+            .unwrap_or(self.lines[line].len())
+    }
+
+    /// Get the cursor as a character index into [`EditorState::text`]
+    fn cursor_index(&self) -> usize {
+        self.lines[..self.cursor.0]
+            .iter()
+            .map(|l| l.chars().count() + 1)
+            .sum::<usize>()
+            + self.cursor.1
+    }
+
+    /// Set the cursor from a character index into [`EditorState::text`]
+    fn set_cursor_index(&mut self, mut index: usize) {
+        for (i, line) in self.lines.iter().enumerate() {
+            let len = line.chars().count();
+
+            if index <= len {
+                self.cursor = (i, index);
+                return;
+            }
+
+            index -= len + 1;
+        }
+
+        self.cursor = (self.lines.len() - 1, self.line_len(self.lines.len() - 1));
+    }
+
+    /// Keep the cursor inside of the text
+    fn clamp_cursor(&mut self) {
+        let line = self.cursor.0.min(self.lines.len() - 1);
+        let mut max = self.line_len(line);
+
+        // normal mode puts the cursor on a character, not after the last one
+        if self.mode == EditorMode::Normal {
+            max = max.saturating_sub(1);
+        }
+
+        self.cursor = (line, self.cursor.1.min(max));
+    }
+
+    /// Move the cursor by lines (keeping the column where possible)
+    pub fn move_lines(&mut self, delta: isize) {
+        self.cursor.0 = self
+            .cursor
+            .0
+            .saturating_add_signed(delta)
+            .min(self.lines.len() - 1);
+        self.clamp_cursor();
+    }
+
+    /// Move the cursor by characters, wrapping to the previous/next line
+    pub fn move_chars(&mut self, delta: isize) {
+        let index = self.cursor_index().saturating_add_signed(delta);
+        self.set_cursor_index(index);
+        self.clamp_cursor();
+    }
+
+    // editing
+    /// Record the text before an edit
+    fn record(&mut self, kind: EditKind) {
+        let (text, cursor) = (self.text(), self.cursor_index());
+        self.history.record(&text, cursor, kind);
+    }
+
+    /// Count a change and tell the application about it
+    fn changed(&mut self, change: EditorChange, on_change: &mut dyn FnMut(EditorChange)) {
+        self.version += 1;
+        on_change(change);
+    }
+
+    /// Insert a character at the cursor
+    pub fn insert(&mut self, c: char, on_change: &mut dyn FnMut(EditorChange)) {
+        self.record(EditKind::Insert(c));
+
+        let (line, col) = self.cursor;
+        let i = self.byte_index(line, col);
+        self.lines[line].insert(i, c);
+        self.cursor.1 += 1;
+
+        self.changed(EditorChange::Edited(line), on_change);
+    }
+
+    /// Split the line at the cursor
+    pub fn newline(&mut self, on_change: &mut dyn FnMut(EditorChange)) {
+        self.record(EditKind::Other);
+
+        let (line, col) = self.cursor;
+        let i = self.byte_index(line, col);
+        let rest = self.lines[line].split_off(i);
+        self.lines.insert(line + 1, rest);
+        self.cursor = (line + 1, 0);
+
+        self.changed(EditorChange::Edited(line), on_change);
+        self.changed(EditorChange::Inserted(line + 1), on_change);
+    }
+
+    /// Remove the character before the cursor (joining lines at the start of a line)
+    pub fn backspace(&mut self, on_change: &mut dyn FnMut(EditorChange)) {
+        let (line, col) = self.cursor;
+
+        if (line == 0) && (col == 0) {
+            return;
+        }
+
+        self.record(EditKind::Delete);
+
+        if col == 0 {
+            let removed = self.lines.remove(line);
+            self.cursor = (line - 1, self.line_len(line - 1));
+            self.lines[line - 1].push_str(&removed);
+
+            self.changed(EditorChange::Removed(line), on_change);
+            self.changed(EditorChange::Edited(line - 1), on_change);
+        } else {
+            let i = self.byte_index(line, col - 1);
+            self.lines[line].remove(i);
+            self.cursor.1 -= 1;
+
+            self.changed(EditorChange::Edited(line), on_change);
+        }
+    }
+
+    /// Remove the character under the cursor (joining lines at the end of a line)
+    pub fn delete(&mut self, on_change: &mut dyn FnMut(EditorChange)) {
+        let (line, col) = self.cursor;
+
+        if col < self.line_len(line) {
+            self.record(EditKind::Delete);
+
+            let i = self.byte_index(line, col);
+            self.lines[line].remove(i);
+
+            self.changed(EditorChange::Edited(line), on_change);
+        } else if (line + 1) < self.lines.len() {
+            self.record(EditKind::Delete);
+
+            let next = self.lines.remove(line + 1);
+            self.lines[line].push_str(&next);
+
+            self.changed(EditorChange::Removed(line + 1), on_change);
+            self.changed(EditorChange::Edited(line), on_change);
+        }
+    }
+
+    /// Remove the line under the cursor
+    pub fn delete_line(&mut self, on_change: &mut dyn FnMut(EditorChange)) {
+        self.record(EditKind::Other);
+
+        let line = self.cursor.0;
+
+        if self.lines.len() == 1 {
+            self.lines[0].clear();
+            self.changed(EditorChange::Edited(0), on_change);
+        } else {
+            self.lines.remove(line);
+            self.changed(EditorChange::Removed(line), on_change);
+        }
+
+        self.clamp_cursor();
+    }
+
+    /// Undo the last group of edits
+    pub fn undo(&mut self, on_change: &mut dyn FnMut(EditorChange)) {
+        if let Some((text, cursor)) = self.history.undo(&self.text(), self.cursor_index()) {
+            self.lines = EditorState::split(&text);
+            self.set_cursor_index(cursor);
+            self.clamp_cursor();
+            self.changed(EditorChange::Replaced, on_change);
+        }
+    }
+
+    /// Redo the last undone group of edits
+    pub fn redo(&mut self, on_change: &mut dyn FnMut(EditorChange)) {
+        if let Some((text, cursor)) = self.history.redo(&self.text(), self.cursor_index()) {
+            self.lines = EditorState::split(&text);
+            self.set_cursor_index(cursor);
+            self.clamp_cursor();
+            self.changed(EditorChange::Replaced, on_change);
+        }
+    }
+
+    // search
+    /// Get every match of the last search as (line, start column)
+    pub fn matches(&self) -> Vec<(usize, usize)> {
+        let query = match &self.search {
+            Some(q) if !q.is_empty() => q,
+            _ => return Vec::new(),
+        };
+
+        let mut matches = Vec::new();
+
+        for (i, line) in self.lines.iter().enumerate() {
+            for (byte, _) in line.match_indices(query.as_str()) {
+                matches.push((i, line[..byte].chars().count()));
+            }
+        }
+
+        matches
+    }
+
+    /// Move the cursor to the next (or previous) match of the last search,
+    /// wrapping around the ends. Returns `false` if there are no matches.
+    pub fn find_next(&mut self, forward: bool) -> bool {
+        let matches = self.matches();
+
+        let next = if forward {
+            matches
+                .iter()
+                .find(|m| **m > self.cursor)
+                .or(matches.first())
+        } else {
+            matches
+                .iter()
+                .rev()
+                .find(|m| **m < self.cursor)
+                .or(matches.last())
+        };
+
+        match next {
+            Some(pos) => {
+                self.cursor = *pos;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // keys
+    /// Handle a key event. Returns `true` if the key was used.
+    ///
+    /// ## Arguments:
+    /// * `event`
+    /// * `on_change` - called for every change to the text
+    pub fn handle_key(&mut self, event: KeyEvent, on_change: &mut dyn FnMut(EditorChange)) -> bool {
+        if UndoStack::is_undo(&event) {
+            self.undo(on_change);
+            return true;
+        } else if UndoStack::is_redo(&event) {
+            self.redo(on_change);
+            return true;
+        }
+
+        // typing a search
+        if let Some(input) = &mut self.search_input {
+            match event.code {
+                KeyCode::Enter => {
+                    self.search = Some(input.text.clone());
+                    self.search_input = None;
+                    self.find_next(true);
+                }
+                KeyCode::Esc => self.search_input = None,
+                _ => return input.handle_key(event),
+            }
+
+            return true;
+        }
+
+        // movement (both modes)
+        match event.code {
+            KeyCode::Up => self.move_lines(-1),
+            KeyCode::Down => self.move_lines(1),
+            KeyCode::Left => self.move_chars(-1),
+            KeyCode::Right => self.move_chars(1),
+            KeyCode::Home => self.cursor.1 = 0,
+            KeyCode::End => {
+                self.cursor.1 = usize::MAX;
+                self.clamp_cursor();
+            }
+            KeyCode::PageUp => self.move_lines(-20),
+            KeyCode::PageDown => self.move_lines(20),
+            _ => {
+                return match self.mode {
+                    EditorMode::Normal => self.handle_normal_key(event, on_change),
+                    EditorMode::Insert => self.handle_insert_key(event, on_change),
+                };
+            }
+        }
+
+        true
+    }
+
+    /// Handle a key in [`EditorMode::Normal`]
+    fn handle_normal_key(
+        &mut self,
+        event: KeyEvent,
+        on_change: &mut dyn FnMut(EditorChange),
+    ) -> bool {
+        let KeyCode::Char(c) = event.code else {
+            return false;
+        };
+
+        if event.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+
+        match c {
+            'h' => self.move_chars(-1),
+            'j' => self.move_lines(1),
+            'k' => self.move_lines(-1),
+            'l' => self.move_chars(1),
+            '0' => self.cursor.1 = 0,
+            '$' => {
+                self.cursor.1 = usize::MAX;
+                self.clamp_cursor();
+            }
+            'g' => self.cursor = (0, 0),
+            'G' => {
+                self.cursor = (self.lines.len() - 1, 0);
+            }
+            'i' => self.mode = EditorMode::Insert,
+            'a' => {
+                self.mode = EditorMode::Insert;
+                self.cursor.1 = (self.cursor.1 + 1).min(self.line_len(self.cursor.0));
+            }
+            'A' => {
+                self.mode = EditorMode::Insert;
+                self.cursor.1 = self.line_len(self.cursor.0);
+            }
+            'o' => {
+                self.mode = EditorMode::Insert;
+                self.cursor.1 = self.line_len(self.cursor.0);
+                self.newline(on_change);
+            }
+            'x' => {
+                self.delete(on_change);
+                self.clamp_cursor();
+            }
+            'D' => self.delete_line(on_change),
+            'u' => self.undo(on_change),
+            '/' => self.search_input = Some(TextInputState::default()),
+            'n' => {
+                self.find_next(true);
+            }
+            'N' => {
+                self.find_next(false);
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Handle a key in [`EditorMode::Insert`]
+    fn handle_insert_key(
+        &mut self,
+        event: KeyEvent,
+        on_change: &mut dyn FnMut(EditorChange),
+    ) -> bool {
+        match event.code {
+            KeyCode::Esc => {
+                self.mode = EditorMode::Normal;
+                self.clamp_cursor();
+            }
+            KeyCode::Char(c) => {
+                if event.modifiers.contains(KeyModifiers::CONTROL) {
+                    return false;
+                }
+
+                self.insert(c, on_change);
+            }
+            KeyCode::Tab => {
+                for _ in 0..4 {
+                    self.insert(' ', on_change);
+                }
+            }
+            KeyCode::Enter => self.newline(on_change),
+            KeyCode::Backspace => self.backspace(on_change),
+            KeyCode::Delete => self.delete(on_change),
+            _ => return false,
+        }
+
+        true
+    }
+
+    // layout
+    /// Get the screen rows of the visible text as (line, first column, number of columns)
+    fn rows(&self, width: usize, height: usize) -> Vec<(usize, usize, usize)> {
+        let width = width.max(1);
+        let mut rows = Vec::new();
+
+        for line in self.scroll..self.lines.len() {
+            let len = self.line_len(line);
+
+            if self.wrap {
+                let mut start = 0;
+
+                loop {
+                    rows.push((line, start, (len - start).min(width)));
+                    start += width;
+
+                    if start >= len {
+                        break;
+                    }
+                }
+            } else {
+                rows.push((line, self.hscroll, width));
+            }
+
+            if rows.len() >= height {
+                rows.truncate(height);
+                break;
+            }
+        }
+
+        rows
+    }
+
+    /// Update `scroll` and `hscroll` so the cursor is visible in a `width` by `height` area
+    pub fn scroll_to_cursor(&mut self, width: usize, height: usize) {
+        let (line, col) = self.cursor;
+
+        if line < self.scroll {
+            self.scroll = line;
+        }
+
+        if self.wrap {
+            // scroll down until the row of the cursor is on screen
+            while !self
+                .rows(width, height)
+                .iter()
+                .any(|(l, start, _)| (*l == line) && (col / width.max(1) == start / width.max(1)))
+                && (self.scroll < line)
+            {
+                self.scroll += 1;
+            }
+        } else {
+            if line >= self.scroll + height {
+                self.scroll = line + 1 - height;
+            }
+
+            if col < self.hscroll {
+                self.hscroll = col;
+            } else if col >= self.hscroll + width {
+                self.hscroll = col + 1 - width;
+            }
+        }
+    }
+}
+
+/// Multi-line code editor with line numbers, highlighting and search
+pub struct Editor {
+    pub buffer: PseudoBuffer,
+    pub gutter: bool,
+    pub highlighter: Option<Box<dyn Highlighter>>,
+    /// Style of search matches
+    pub match_style: Style,
+    /// Style of the search line at the bottom
+    pub search_style: Style,
+}
+
+impl Creatable for Editor {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Editor {
+            buffer,
+            gutter: true,
+            highlighter: None,
+            match_style: Style::DEFAULT
+                .fg(TextColor::Black)
+                .bg(TextBackgroundColor::Yellow),
+            search_style: Style::DEFAULT,
+        }
+    }
+}
+
+impl Editor {
+    /// Get the rect of the text (without the gutter and search line)
+    fn text_rect(&self, rect: &RectBoundary, editor: &EditorState) -> RectBoundary {
+        let gutter = if self.gutter {
+            Gutter::width(editor.lines.len()).min(rect.size.0)
+        } else {
+            0
+        };
+        let search = if editor.search_input.is_some() { 1 } else { 0 };
+
+        RectBoundary {
+            pos: (rect.pos.0 + gutter, rect.pos.1),
+            size: (rect.size.0 - gutter, rect.size.1.saturating_sub(search)),
+        }
+    }
+
+    /// Get the screen position of the cursor of an editor at `rect`
+    /// (after it was drawn, so the scroll offset is up to date)
+    pub fn cursor_pos(&self, rect: &RectBoundary, editor: &EditorState) -> Vec2 {
+        // the cursor is on the search line while searching
+        if let Some(input) = &editor.search_input {
+            return (
+                rect.pos.0 + 1 + input.cursor as u16,
+                rect.pos.1 + rect.size.1.saturating_sub(1),
+            );
+        }
+
+        let text = self.text_rect(rect, editor);
+        let width = text.size.0 as usize;
+        let (line, col) = editor.cursor;
+
+        for (y, (l, start, len)) in editor
+            .rows(width, text.size.1 as usize)
+            .into_iter()
+            .enumerate()
+        {
+            let last = (start + len) >= editor.line_len(l);
+
+            // the cursor can be after the last character of a line
+            if (l == line) && (col >= start) && ((col < start + len) || last) {
+                return (
+                    text.pos.0 + (col - start).min(width.saturating_sub(1)) as u16,
+                    text.pos.1 + y as u16,
+                );
+            }
+        }
+
+        text.pos
+    }
+
+    /// Draw an editor
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `editor` - [`EditorState`]
+    pub fn render(&mut self, rect: RectBoundary, editor: &mut EditorState) -> DrawingResult {
+        let text = self.text_rect(&rect, editor);
+        let (width, height) = (text.size.0 as usize, text.size.1 as usize);
+
+        editor.scroll_to_cursor(width, height);
+
+        let matches = editor.matches();
+        let match_len = editor.search.as_ref().map_or(0, |s| s.chars().count());
+
+        // erase what's left of last frame (shorter lines, deleted lines, the gutter of
+        // rows which are a wrapped line now and the search line)
+        self.buffer.clear_rect(&rect)?;
+
+        let mut last_line = None;
+
+        for (y, (line, start, len)) in editor.rows(width, height).into_iter().enumerate() {
+            let y = text.pos.1 + y as u16;
+
+            // line number (only on the first row of a wrapped line)
+            if self.gutter && (last_line != Some(line)) {
+                let mut gutter = Gutter::new(self.buffer.clone());
+                let (_, changes) = gutter.render(
+                    RectBoundary {
+                        pos: (rect.pos.0, y),
+                        size: (text.pos.0 - rect.pos.0, 1),
+                    },
+                    editor.lines.len(),
+                    line,
+                    Some(editor.cursor.0),
+                )?;
+                self.buffer.set_changes(changes);
+            }
+
+            last_line = Some(line);
+
+            // style every character, then mark search matches
+            let styled = match &self.highlighter {
+                Some(h) => h.highlight(&editor.lines[line]),
+                None => Line::from(editor.lines[line].as_str()),
+            };
+            let mut cells = styled.cells();
+
+            for (_, col) in matches.iter().filter(|(l, _)| *l == line) {
+                for cell in cells.iter_mut().skip(*col).take(match_len) {
+                    cell.1 = self.match_style;
+                }
+            }
+
+            // draw the part of the line on this row
+            let part: Line = cells.into_iter().skip(start).take(len).collect();
+            self.buffer.write_line((text.pos.0, y), &part)?;
+        }
+
+        // search line
+        if let Some(input) = &editor.search_input {
+            self.buffer.write_str_styled(
+                (rect.pos.0, rect.pos.1 + rect.size.1.saturating_sub(1)),
+                &format!("/{}", input.text),
+                self.search_style,
+            )?;
+        }
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
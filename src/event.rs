@@ -0,0 +1,135 @@
+//! Events
+use crossterm::event::KeyModifiers;
+pub use crossterm::event::MouseButton;
+
+use crate::drawing::Vec2;
+
+/// How long two clicks can be apart to count as a double (or triple, ...) click
+pub const MULTI_CLICK_TIME: std::time::Duration = std::time::Duration::from_millis(400);
+
+/// Check if the terminal (probably) reports mouse events. The Linux console and dumb
+/// terminals don't, and `HAMUI_NO_MOUSE` can be set to say so for any other terminal.
+pub fn mouse_supported() -> bool {
+    if std::env::var_os("HAMUI_NO_MOUSE").is_some() {
+        return false;
+    }
+
+    let term = std::env::var("TERM").unwrap_or_default();
+    !["dumb", "linux", "vt100", "vt102", "vt220", "cons25"].contains(&term.as_str())
+}
+
+/// A mouse click
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClickEvent {
+    /// Where the click happened
+    pub pos: Vec2,
+    /// Where the click happened inside of the component it was given to
+    /// (see [`crate::State::click_in`], the same as `pos` until then)
+    pub local: Vec2,
+    /// Which button was clicked
+    pub button: MouseButton,
+    /// Number of clicks in a row at this position (`2` is a double click)
+    pub count: u8,
+    /// If this is a keyboard activation (Enter/Space on the focused widget)
+    pub keyboard: bool,
+    /// Modifiers held while clicking (Ctrl+click, Shift+click)
+    pub modifiers: KeyModifiers,
+}
+
+/// Direction of a [`ScrollEvent`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// A mouse wheel scroll
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScrollEvent {
+    /// Where the mouse was while scrolling
+    pub pos: Vec2,
+    /// Where the mouse was inside of the component it was given to
+    /// (see [`crate::State::scroll_in`], the same as `pos` until then)
+    pub local: Vec2,
+    pub direction: ScrollDirection,
+}
+
+/// An active drag (started with [`crate::State::start_drag`])
+#[derive(Clone, Debug, PartialEq)]
+pub struct DragState {
+    /// Id of what is being dragged
+    pub payload: String,
+    /// Text drawn next to the mouse while dragging
+    pub label: String,
+    /// Where the drag started
+    pub origin: Vec2,
+    /// Where the mouse currently is
+    pub pos: Vec2,
+}
+
+/// A drag which was released
+#[derive(Clone, Debug, PartialEq)]
+pub struct DropEvent {
+    /// Id of what was dragged
+    pub payload: String,
+    /// Where the drag started
+    pub origin: Vec2,
+    /// Where the mouse was released
+    pub pos: Vec2,
+}
+
+/// The terminal window gained or lost focus
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TerminalFocusEvent {
+    Gained,
+    Lost,
+}
+
+/// Event sent from other threads through [`crate::Frame::event_sender`]
+/// (like the result of a [`crate::task`])
+pub struct UserEvent {
+    /// What the event is about (like the name of the task which sent it)
+    pub name: String,
+    payload: Box<dyn std::any::Any + Send>,
+}
+
+impl UserEvent {
+    /// Create a new [`UserEvent`]
+    pub fn new(name: &str, payload: impl std::any::Any + Send) -> Self {
+        UserEvent {
+            name: name.to_string(),
+            payload: Box::new(payload),
+        }
+    }
+
+    /// Check if the payload is a `T`
+    pub fn is<T: 'static>(&self) -> bool {
+        self.payload.is::<T>()
+    }
+
+    /// Get the payload if it's a `T`
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.payload.downcast_ref()
+    }
+
+    /// Take the payload out if it's a `T`
+    pub fn take<T: 'static>(self) -> Result<T, UserEvent> {
+        match self.payload.downcast() {
+            Ok(payload) => Ok(*payload),
+            Err(payload) => Err(UserEvent {
+                name: self.name,
+                payload,
+            }),
+        }
+    }
+}
+
+impl std::fmt::Debug for UserEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserEvent")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,252 @@
+//! Filtering of list-like widgets
+//!
+//! [`Filterable`] puts a filter input above any [`Searchable`] widget and only passes it
+//! the items which match, with the matches highlighted, so widgets don't need their own search.
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{
+    Creatable, DrawingResult, Line, RectBoundary, TextAttribute, TextBackgroundColor, TextColor,
+    TextLeaf,
+};
+use crate::input::{TextInput, TextInputState};
+use crate::style::Style;
+
+/// Matches of a filter in the text of an item (case insensitive)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Highlight {
+    pub query: String,
+    /// Style of the matched characters
+    pub style: Style,
+}
+
+impl Highlight {
+    pub fn new(query: &str, style: Style) -> Self {
+        Highlight {
+            query: query.to_string(),
+            style,
+        }
+    }
+
+    /// Get the matches in `text` as (first character, number of characters)
+    pub fn matches(&self, text: &str) -> Vec<(usize, usize)> {
+        let query: Vec<char> = self.query.chars().map(Highlight::fold).collect();
+        let text: Vec<char> = text.chars().map(Highlight::fold).collect();
+
+        let mut matches = Vec::new();
+        let mut i = 0;
+
+        if query.is_empty() {
+            return matches;
+        }
+
+        while i + query.len() <= text.len() {
+            if text[i..i + query.len()] == query[..] {
+                matches.push((i, query.len()));
+                i += query.len();
+            } else {
+                i += 1;
+            }
+        }
+
+        matches
+    }
+
+    /// Check if `text` matches (everything matches an empty query)
+    pub fn is_match(&self, text: &str) -> bool {
+        self.query.is_empty() || !self.matches(text).is_empty()
+    }
+
+    /// Get `text` in `style` with the matches in the highlight style
+    pub fn line(&self, text: &str, style: Style) -> Line {
+        let mut cells: Vec<(char, Style)> = text.chars().map(|c| (c, style)).collect();
+
+        for (start, len) in self.matches(text) {
+            for cell in cells.iter_mut().skip(start).take(len) {
+                cell.1 = self.style;
+            }
+        }
+
+        let mut spans: Vec<TextLeaf> = Vec::new();
+
+        for (char, style) in cells {
+            match spans.last_mut() {
+                Some(span) if span.style == style => span.text.push(char),
+                _ => spans.push(TextLeaf::styled(&char.to_string(), style)),
+            }
+        }
+
+        Line::new(spans)
+    }
+
+    /// Compare characters without case
+    fn fold(c: char) -> char {
+        c.to_lowercase().next().unwrap_or(c)
+    }
+}
+
+/// Widget showing items which can be narrowed down by a [`Filterable`]
+pub trait Searchable {
+    /// Number of items
+    fn item_count(&self) -> usize;
+
+    /// Text the filter is matched against
+    fn item_text(&self, index: usize) -> String;
+
+    /// Draw only some of the items
+    ///
+    /// ## Arguments:
+    /// * `buffer` - buffer to draw into
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `visible` - indices of the items which match the filter
+    /// * `highlight` - matches to highlight
+    fn render_items(
+        &mut self,
+        buffer: PseudoBuffer,
+        rect: RectBoundary,
+        visible: &[usize],
+        highlight: &Highlight,
+    ) -> DrawingResult;
+}
+
+/// Filter of a [`Filterable`], kept by the application between frames
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct FilterState {
+    pub input: TextInputState,
+    /// If the filter input has the keys
+    pub editing: bool,
+}
+
+impl FilterState {
+    /// Get the current filter
+    pub fn query(&self) -> &str {
+        &self.input.text
+    }
+
+    /// Check if the filter input is shown (while editing, or while something is filtered)
+    pub fn is_open(&self) -> bool {
+        self.editing || !self.input.text.is_empty()
+    }
+
+    /// Handle a key event. Returns `true` if the key was used (the widget shouldn't get it).
+    ///
+    /// * `/` (or Ctrl+F) starts editing the filter
+    /// * while editing, keys edit the filter, Enter stops editing (keeping the filter)
+    /// * Esc stops editing and clears the filter
+    pub fn handle_key(&mut self, event: KeyEvent) -> bool {
+        if !self.editing {
+            return match event.code {
+                KeyCode::Char('/')
+                    if event.modifiers - KeyModifiers::SHIFT == KeyModifiers::NONE =>
+                {
+                    self.editing = true;
+                    true
+                }
+                KeyCode::Char('f') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.editing = true;
+                    true
+                }
+                KeyCode::Esc if !self.input.text.is_empty() => {
+                    self.input.set_text("");
+                    true
+                }
+                _ => false,
+            };
+        }
+
+        match event.code {
+            KeyCode::Enter => self.editing = false,
+            KeyCode::Esc => {
+                self.editing = false;
+                self.input.set_text("");
+            }
+            _ => return self.input.handle_key(event),
+        }
+
+        true
+    }
+}
+
+/// Filter input above a [`Searchable`] widget
+pub struct Filterable<W: Searchable> {
+    pub buffer: PseudoBuffer,
+    pub inner: W,
+    /// Text in front of the filter input
+    pub prompt: String,
+    pub prompt_style: Style,
+    /// Style of the matches in the items
+    pub match_style: Style,
+    /// Place the hardware cursor in the filter input while editing (see [`TextInput::cursor`])
+    pub cursor: bool,
+}
+
+impl<W: Searchable> Filterable<W> {
+    /// Create a new [`Filterable`] around `inner`
+    pub fn new(buffer: PseudoBuffer, inner: W) -> Self {
+        Filterable {
+            buffer,
+            inner,
+            prompt: "/".to_string(),
+            prompt_style: Style::DEFAULT.attr(TextAttribute::Bold),
+            match_style: Style::DEFAULT
+                .fg(TextColor::Black)
+                .bg(TextBackgroundColor::Yellow),
+            cursor: false,
+        }
+    }
+
+    /// Get the indices of the items which match the filter
+    pub fn visible(&self, filter: &FilterState) -> Vec<usize> {
+        let highlight = Highlight::new(filter.query(), self.match_style);
+
+        (0..self.inner.item_count())
+            .filter(|i| highlight.is_match(&self.inner.item_text(*i)))
+            .collect()
+    }
+
+    /// Get the rect of the filter input (the first row, `None` while it's hidden)
+    pub fn input_rect(rect: &RectBoundary, filter: &FilterState) -> Option<RectBoundary> {
+        (filter.is_open() && rect.size.1 > 0).then(|| RectBoundary::new(rect.pos, (rect.size.0, 1)))
+    }
+
+    /// Draw the filter input (while it's open) and the matching items below it
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `filter` - [`FilterState`]
+    pub fn render(&mut self, rect: RectBoundary, filter: &mut FilterState) -> DrawingResult {
+        let mut items = rect.clone();
+
+        if let Some(input_rect) = Filterable::<W>::input_rect(&rect, filter) {
+            let prompt = Line::from(TextLeaf::styled(&self.prompt, self.prompt_style));
+            self.buffer
+                .write_line(input_rect.pos, &prompt.truncate(rect.size.0))?;
+
+            let prompt_width = prompt.width().min(rect.size.0);
+            let mut input = TextInput::new(self.buffer.clone());
+            input.cursor = self.cursor && filter.editing;
+            let (_, changes) = input.render(
+                RectBoundary::new(
+                    (input_rect.pos.0 + prompt_width, input_rect.pos.1),
+                    (input_rect.size.0 - prompt_width, 1),
+                ),
+                &mut filter.input,
+            )?;
+            self.buffer.set_changes(changes);
+
+            items = RectBoundary::new((rect.pos.0, rect.pos.1 + 1), (rect.size.0, rect.size.1 - 1));
+        }
+
+        // items
+        let visible = self.visible(filter);
+        let highlight = Highlight::new(filter.query(), self.match_style);
+        let (_, changes) =
+            self.inner
+                .render_items(self.buffer.clone(), items, &visible, &highlight)?;
+        self.buffer.set_changes(changes);
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
@@ -0,0 +1,88 @@
+//! Focus management
+use crate::drawing::RectBoundary;
+
+/// Keeps track of which widget has keyboard focus.
+///
+/// Widgets register themselves (with an id and their rect) every time they are drawn,
+/// and Tab/Shift+Tab move the focus through them in the order they were registered.
+#[derive(Clone, Debug, Default)]
+pub struct FocusRing {
+    /// Widgets registered during the last draw
+    order: Vec<(String, RectBoundary)>,
+    /// Widgets registered during the current draw
+    pending: Vec<(String, RectBoundary)>,
+    /// Id of the focused widget
+    pub focused: Option<String>,
+}
+
+impl FocusRing {
+    /// Register a focusable widget for this draw. Returns `true` if the widget is focused.
+    pub fn register(&mut self, id: &str, rect: RectBoundary) -> bool {
+        self.pending.push((id.to_string(), rect));
+        self.is_focused(id)
+    }
+
+    /// Check if the widget with `id` is focused
+    pub fn is_focused(&self, id: &str) -> bool {
+        self.focused.as_deref() == Some(id)
+    }
+
+    /// Focus the widget with `id`
+    pub fn focus(&mut self, id: &str) {
+        self.focused = Some(id.to_string());
+    }
+
+    /// Remove focus from every widget
+    pub fn blur(&mut self) {
+        self.focused = None;
+    }
+
+    /// Get the rect of the focused widget (as of the last draw)
+    pub fn focused_rect(&self) -> Option<RectBoundary> {
+        let focused = self.focused.as_deref()?;
+
+        self.order
+            .iter()
+            .find(|(id, _)| id == focused)
+            .map(|(_, rect)| rect.clone())
+    }
+
+    /// Index of the focused widget in the last draw
+    fn focused_index(&self) -> Option<usize> {
+        let focused = self.focused.as_deref()?;
+        self.order.iter().position(|(id, _)| id == focused)
+    }
+
+    /// Move focus to the next widget (wrapping around)
+    pub fn next(&mut self) {
+        if self.order.is_empty() {
+            return;
+        }
+
+        let i = match self.focused_index() {
+            Some(i) => (i + 1) % self.order.len(),
+            None => 0,
+        };
+
+        self.focused = Some(self.order[i].0.clone());
+    }
+
+    /// Move focus to the previous widget (wrapping around)
+    pub fn prev(&mut self) {
+        if self.order.is_empty() {
+            return;
+        }
+
+        let i = match self.focused_index() {
+            Some(0) | None => self.order.len() - 1,
+            Some(i) => i - 1,
+        };
+
+        self.focused = Some(self.order[i].0.clone());
+    }
+
+    /// Finish a draw, making the widgets registered during it the current focus order
+    pub(crate) fn end_frame(&mut self) {
+        self.order = std::mem::take(&mut self.pending);
+    }
+}
@@ -0,0 +1,207 @@
+//! Grid navigation
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::drawing::{RectBoundary, Vec2};
+
+/// What happens when a [`GridCursor`] moves past the edge of the grid
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum GridWrap {
+    /// Stop at the edge
+    #[default]
+    Clamp,
+    /// Left/Right continue on the previous/next row (like reading text, or a calendar)
+    NextRow,
+    /// Come back in on the other side of the same row/column
+    Around,
+}
+
+/// A (row, column) selection over a grid of cells, with keyboard navigation.
+/// Used by widgets which select cells (like [`crate::table::TableState`]).
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct GridCursor {
+    pub row: usize,
+    pub col: usize,
+    /// Number of rows in the grid
+    pub rows: usize,
+    /// Number of columns in the grid
+    pub cols: usize,
+    pub wrap: GridWrap,
+    /// Number of rows moved by Page Up/Page Down
+    pub page: usize,
+}
+
+impl GridCursor {
+    /// Create a new [`GridCursor`] at the first cell
+    pub fn new(rows: usize, cols: usize) -> Self {
+        GridCursor {
+            row: 0,
+            col: 0,
+            rows,
+            cols,
+            wrap: GridWrap::Clamp,
+            page: 10,
+        }
+    }
+
+    /// Set the wrapping rule
+    pub fn wrap(mut self, wrap: GridWrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Change the size of the grid, keeping the cursor inside of it
+    pub fn resize(&mut self, rows: usize, cols: usize) {
+        self.rows = rows;
+        self.cols = cols;
+        self.row = self.row.min(rows.saturating_sub(1));
+        self.col = self.col.min(cols.saturating_sub(1));
+    }
+
+    /// Get the selected cell as (row, column)
+    pub fn selected(&self) -> Option<(usize, usize)> {
+        if (self.rows == 0) || (self.cols == 0) {
+            return None;
+        }
+
+        Some((self.row, self.col))
+    }
+
+    /// Move by `rows` rows (Up/Down never wrap to another column)
+    pub fn move_rows(&mut self, rows: isize) {
+        if self.rows == 0 {
+            return;
+        }
+
+        self.row = match self.wrap {
+            GridWrap::Around => (self.row as isize + rows).rem_euclid(self.rows as isize) as usize,
+            _ => self.row.saturating_add_signed(rows).min(self.rows - 1),
+        };
+    }
+
+    /// Move by `cols` columns
+    pub fn move_cols(&mut self, cols: isize) {
+        if (self.rows == 0) || (self.cols == 0) {
+            return;
+        }
+
+        match self.wrap {
+            GridWrap::Clamp => {
+                self.col = self.col.saturating_add_signed(cols).min(self.cols - 1);
+            }
+            GridWrap::Around => {
+                self.col = (self.col as isize + cols).rem_euclid(self.cols as isize) as usize;
+            }
+            GridWrap::NextRow => {
+                // walk through the cells in reading order
+                let last = (self.rows * self.cols) as isize - 1;
+                let i = ((self.row * self.cols + self.col) as isize + cols).clamp(0, last) as usize;
+
+                self.row = i / self.cols;
+                self.col = i % self.cols;
+            }
+        }
+    }
+
+    /// Handle a key event. Returns `true` if the key was used.
+    ///
+    /// * Arrows move by one cell
+    /// * Home/End go to the first/last column (with Ctrl: the first/last cell)
+    /// * Page Up/Page Down move by [`GridCursor::page`] rows
+    pub fn handle_key(&mut self, event: KeyEvent) -> bool {
+        let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+
+        match event.code {
+            KeyCode::Up => self.move_rows(-1),
+            KeyCode::Down => self.move_rows(1),
+            KeyCode::Left => self.move_cols(-1),
+            KeyCode::Right => self.move_cols(1),
+            KeyCode::Home => {
+                if ctrl {
+                    self.row = 0;
+                }
+
+                self.col = 0;
+            }
+            KeyCode::End => {
+                if ctrl {
+                    self.row = self.rows.saturating_sub(1);
+                }
+
+                self.col = self.cols.saturating_sub(1);
+            }
+            KeyCode::PageUp => self.row = self.row.saturating_sub(self.page.max(1)),
+            KeyCode::PageDown => {
+                self.row = (self.row + self.page.max(1)).min(self.rows.saturating_sub(1));
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Like [`GridCursor::handle_key`], with the key of this frame translated by the
+    /// navigation preset (see [`crate::State::nav_key`]). Returns `true` if the key was used.
+    pub fn handle_nav_key(&mut self, state: &mut crate::State) -> bool {
+        match state.nav_key() {
+            Some(event) => self.handle_key(event),
+            None => false,
+        }
+    }
+
+    /// Get the rect of a cell of a grid drawn at `rect` with every cell `cell_size` big
+    pub fn cell_rect(rect: &RectBoundary, cell_size: Vec2, cell: (usize, usize)) -> RectBoundary {
+        RectBoundary {
+            pos: (
+                rect.pos.0 + (cell.1 as u16 * cell_size.0),
+                rect.pos.1 + (cell.0 as u16 * cell_size.1),
+            ),
+            size: cell_size,
+        }
+    }
+
+    /// Get the rect of the selected cell (see [`GridCursor::cell_rect`])
+    pub fn selected_rect(&self, rect: &RectBoundary, cell_size: Vec2) -> Option<RectBoundary> {
+        Some(GridCursor::cell_rect(rect, cell_size, self.selected()?))
+    }
+
+    /// Get the cell at a screen position inside of a grid drawn at `rect`
+    pub fn cell_at(
+        &self,
+        rect: &RectBoundary,
+        cell_size: Vec2,
+        pos: Vec2,
+    ) -> Option<(usize, usize)> {
+        if (cell_size.0 == 0) || (cell_size.1 == 0) {
+            return None;
+        }
+
+        let local = rect.to_local(pos)?;
+        let row = (local.1 / cell_size.1) as usize;
+        let col = (local.0 / cell_size.0) as usize;
+
+        ((row < self.rows) && (col < self.cols)).then_some((row, col))
+    }
+
+    /// Handle a click on a grid drawn at `rect`, selecting the clicked cell.
+    /// Returns `true` if the click was used.
+    pub fn handle_click(
+        &mut self,
+        state: &crate::State,
+        rect: &RectBoundary,
+        cell_size: Vec2,
+    ) -> bool {
+        let Some(click) = &state.click else {
+            return false;
+        };
+
+        match self.cell_at(rect, cell_size, click.pos) {
+            Some((row, col)) => {
+                self.row = row;
+                self.col = col;
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Keybinding hints
+//!
+//! A [`HintBar`] shows the shortcuts which work right now: `q quit  / filter  ? help`.
+//! The application passes its global hints (see [`Hint::from_keymap`]), and the focused
+//! widget adds its own to [`crate::State::hints`] while it's drawn.
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{Creatable, DrawingResult, RectBoundary, TextAttribute, TextColor};
+use crate::keymap::Keymap;
+use crate::style::Style;
+use crate::text::display_width;
+use crate::State;
+
+/// A key and what it does
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hint {
+    pub key: String,
+    pub description: String,
+}
+
+impl Hint {
+    pub fn new(key: &str, description: &str) -> Self {
+        Hint {
+            key: key.to_string(),
+            description: description.to_string(),
+        }
+    }
+
+    /// Get a hint for an action of a keymap, with the first key bound to it
+    /// (`None` if the action isn't bound)
+    pub fn from_keymap(keymap: &Keymap, action: &str, description: &str) -> Option<Self> {
+        let binding = keymap.bindings.get(action)?.first()?;
+        Some(Hint::new(&binding.to_string(), description))
+    }
+
+    /// Get the width of the hint (`key description`)
+    pub fn width(&self) -> u16 {
+        (display_width(&self.key) + 1 + display_width(&self.description)) as u16
+    }
+}
+
+/// Bar of hints. Hints which don't fit are left out (the first ones are kept).
+pub struct HintBar {
+    pub buffer: PseudoBuffer,
+    pub key_style: Style,
+    pub description_style: Style,
+    /// Cells between hints
+    pub gap: u16,
+}
+
+impl Creatable for HintBar {
+    fn new(buffer: PseudoBuffer) -> Self {
+        HintBar {
+            key_style: buffer.theme_style(
+                |t| t.accent.attr(TextAttribute::Bold),
+                Style::DEFAULT.fg(TextColor::Cyan).attr(TextAttribute::Bold),
+            ),
+            description_style: buffer.theme_style(|t| t.text, Style::DEFAULT),
+            gap: 2,
+            buffer,
+        }
+    }
+}
+
+impl HintBar {
+    /// Get the hints of the focused widget (see [`State::hints`]) followed by `global`.
+    /// Keys the widget uses hide the global hints for them.
+    pub fn hints(state: &State, global: &[Hint]) -> Vec<Hint> {
+        let mut hints = state.hints.clone();
+
+        for hint in global {
+            if !hints.iter().any(|h| h.key == hint.key) {
+                hints.push(hint.clone());
+            }
+        }
+
+        hints
+    }
+
+    /// Draw as many hints as fit
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, _)
+    /// * `hints`
+    pub fn render(&mut self, rect: RectBoundary, hints: &[Hint]) -> DrawingResult {
+        let mut x = rect.pos.0;
+
+        for hint in hints {
+            let gap = if x > rect.pos.0 { self.gap } else { 0 };
+
+            if x + gap + hint.width() > rect.right() {
+                continue;
+            }
+
+            x += gap;
+            self.buffer
+                .write_str_styled((x, rect.pos.1), &hint.key, self.key_style)?;
+            x += display_width(&hint.key) as u16 + 1;
+            self.buffer.write_str_styled(
+                (x, rect.pos.1),
+                &hint.description,
+                self.description_style,
+            )?;
+            x += display_width(&hint.description) as u16;
+        }
+
+        // done
+        Ok((
+            RectBoundary::new(rect.pos, (x - rect.pos.0, 1)),
+            self.buffer.get_changes(),
+        ))
+    }
+}
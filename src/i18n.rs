@@ -0,0 +1,148 @@
+//! Localization
+//!
+//! Built-in widgets don't hardcode their strings ("OK", "Cancel", "Press any key to exit"):
+//! they look them up by key in the [`Locale`] of the [`crate::State`] (see
+//! [`crate::State::text`]), which the frame also hands to components through the
+//! [`crate::buffer::PseudoBuffer`] (see [`crate::buffer::PseudoBuffer::locale`]).
+//! The locale formats the values of number inputs too, and dates for the application
+//! (no built-in widget shows one). Set one with [`crate::Frame::set_locale`], the
+//! default is [`English`].
+use std::collections::HashMap;
+
+// keys
+pub const OK: &str = "ok";
+pub const CANCEL: &str = "cancel";
+pub const YES: &str = "yes";
+pub const NO: &str = "no";
+pub const BACK: &str = "back";
+pub const NEXT: &str = "next";
+pub const FINISH: &str = "finish";
+pub const SCROLL_ALL: &str = "scroll.all";
+pub const SCROLL_TOP: &str = "scroll.top";
+pub const SCROLL_BOTTOM: &str = "scroll.bottom";
+pub const CRASH_TITLE: &str = "crash.title";
+pub const CRASH_EXIT: &str = "crash.exit";
+
+/// Get the English string of a built-in key (`None` for unknown keys)
+pub fn english(key: &str) -> Option<&'static str> {
+    Some(match key {
+        OK => "OK",
+        CANCEL => "Cancel",
+        YES => "Yes",
+        NO => "No",
+        BACK => "Back",
+        NEXT => "Next",
+        FINISH => "Finish",
+        SCROLL_ALL => "All",
+        SCROLL_TOP => "Top",
+        SCROLL_BOTTOM => "Bot",
+        CRASH_TITLE => "The application crashed",
+        CRASH_EXIT => "Press any key to exit",
+        _ => return None,
+    })
+}
+
+/// Supplies translations and formatting to the built-in widgets
+pub trait Locale: Send + Sync {
+    /// Translate the string with `key` (`None` falls back to English)
+    fn text(&self, key: &str) -> Option<String>;
+
+    /// Format a number with `precision` decimals
+    fn number(&self, value: f64, precision: usize) -> String {
+        format!("{value:.precision$}")
+    }
+
+    /// Format a date (`month` and `day` start at 1) for the application
+    fn date(&self, year: i32, month: u8, day: u8) -> String {
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+}
+
+/// Look up `key` in `locale`, then in English, then use the key itself
+pub fn text(locale: &dyn Locale, key: &str) -> String {
+    locale
+        .text(key)
+        .or_else(|| english(key).map(str::to_string))
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// Built-in English strings and plain formatting
+#[derive(Clone, Copy, Debug, Default)]
+pub struct English;
+
+impl Locale for English {
+    fn text(&self, key: &str) -> Option<String> {
+        english(key).map(str::to_string)
+    }
+}
+
+/// Locale from a table of strings (like one loaded from a file of the application)
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Translations {
+    /// Translated strings by key
+    pub strings: HashMap<String, String>,
+    /// Separator between the integer part and the decimals (`.` if not set)
+    pub decimal_separator: Option<char>,
+    /// Separator between groups of thousands (none if not set)
+    pub thousands_separator: Option<char>,
+    /// Date format with `{year}`, `{month}` and `{day}` (`{year}-{month}-{day}` if not set)
+    pub date_format: Option<String>,
+}
+
+impl Translations {
+    pub fn new() -> Translations {
+        Translations::default()
+    }
+
+    /// Add a translated string
+    pub fn with(mut self, key: &str, text: &str) -> Self {
+        self.strings.insert(key.to_string(), text.to_string());
+        self
+    }
+}
+
+impl Locale for Translations {
+    fn text(&self, key: &str) -> Option<String> {
+        self.strings.get(key).cloned()
+    }
+
+    fn number(&self, value: f64, precision: usize) -> String {
+        let text = format!("{:.precision$}", value.abs());
+        let (int, decimals) = text.split_once('.').unwrap_or((&text, ""));
+
+        // group the integer part
+        let mut out = String::new();
+
+        if value.is_sign_negative() && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            out.push('-');
+        }
+
+        for (i, c) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {
+                if let Some(separator) = self.thousands_separator {
+                    out.push(separator);
+                }
+            }
+
+            out.push(c);
+        }
+
+        if !decimals.is_empty() {
+            out.push(self.decimal_separator.unwrap_or('.'));
+            out.push_str(decimals);
+        }
+
+        out
+    }
+
+    fn date(&self, year: i32, month: u8, day: u8) -> String {
+        match &self.date_format {
+            Some(format) => format
+                .replace("{year}", &format!("{year:04}"))
+                .replace("{month}", &format!("{month:02}"))
+                .replace("{day}", &format!("{day:02}")),
+            None => English.date(year, month, day),
+        }
+    }
+}
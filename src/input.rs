@@ -0,0 +1,748 @@
+//! Input widgets
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::time::{Duration, Instant};
+
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{
+    Creatable, DrawingResult, Handled, KeyHandler, Line, RectBoundary, TextAttribute, Vec2,
+};
+use crate::style::Style;
+use crate::text::{logical_to_visual, visual_map};
+use crate::State;
+
+// undo
+/// What kind of edit is being recorded in an [`UndoStack`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EditKind {
+    /// A character was typed
+    Insert(char),
+    /// A character was removed
+    Delete,
+    /// Anything else (pasting, replacing the whole value, ...), always its own group
+    Other,
+}
+
+/// Undo/redo history for a text value (of a [`TextInputState`], a [`NumberInputState`],
+/// or the multi-line [`crate::editor::EditorState`]).
+///
+/// Edits are grouped so that undo removes a whole word (or a burst of typing)
+/// at once instead of a single character.
+#[derive(Clone, Debug)]
+pub struct UndoStack {
+    /// Snapshots of `(text, cursor)` before each group of edits
+    undo: Vec<(String, usize)>,
+    /// Snapshots of `(text, cursor)` which were undone
+    redo: Vec<(String, usize)>,
+    /// When the last edit was recorded
+    last_edit: Option<Instant>,
+    /// Kind of the last recorded edit
+    last_kind: Option<EditKind>,
+    /// Edits further apart than this start a new group
+    pub group_timeout: Duration,
+    /// Maximum number of undo steps kept
+    pub limit: usize,
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        UndoStack {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_edit: None,
+            last_kind: None,
+            group_timeout: Duration::from_millis(1000),
+            limit: 100,
+        }
+    }
+}
+
+impl UndoStack {
+    /// Check if an edit of `kind` belongs to the same group as the last edit
+    fn continues_group(&self, kind: EditKind) -> bool {
+        let recent = match self.last_edit {
+            Some(time) => time.elapsed() < self.group_timeout,
+            None => false,
+        };
+
+        if !recent {
+            return false;
+        }
+
+        match (self.last_kind, kind) {
+            // typing continues until a word ends
+            (Some(EditKind::Insert(prev)), EditKind::Insert(c)) => {
+                !c.is_whitespace() || prev.is_whitespace()
+            }
+            (Some(EditKind::Delete), EditKind::Delete) => true,
+            _ => false,
+        }
+    }
+
+    /// Record the value *before* an edit of `kind` is applied
+    pub fn record(&mut self, text: &str, cursor: usize, kind: EditKind) {
+        if !self.continues_group(kind) {
+            self.undo.push((text.to_string(), cursor));
+
+            if self.undo.len() > self.limit {
+                self.undo.remove(0);
+            }
+        }
+
+        // any new edit makes the redo history invalid
+        self.redo.clear();
+        self.last_edit = Some(Instant::now());
+        self.last_kind = Some(kind);
+    }
+
+    /// Undo the last group of edits. Returns the `(text, cursor)` to restore.
+    pub fn undo(&mut self, text: &str, cursor: usize) -> Option<(String, usize)> {
+        let snapshot = self.undo.pop()?;
+        self.redo.push((text.to_string(), cursor));
+        self.last_kind = None; // next edit starts a new group
+        Some(snapshot)
+    }
+
+    /// Redo the last undone group of edits. Returns the `(text, cursor)` to restore.
+    pub fn redo(&mut self, text: &str, cursor: usize) -> Option<(String, usize)> {
+        let snapshot = self.redo.pop()?;
+        self.undo.push((text.to_string(), cursor));
+        self.last_kind = None;
+        Some(snapshot)
+    }
+
+    /// Check if an event is the undo binding (Ctrl+Z)
+    pub fn is_undo(event: &KeyEvent) -> bool {
+        event.modifiers.contains(KeyModifiers::CONTROL) && (event.code == KeyCode::Char('z'))
+    }
+
+    /// Check if an event is the redo binding (Ctrl+Y)
+    pub fn is_redo(event: &KeyEvent) -> bool {
+        event.modifiers.contains(KeyModifiers::CONTROL) && (event.code == KeyCode::Char('y'))
+    }
+}
+
+// text input
+/// Value of a [`TextInput`], kept by the application between frames
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct TextInputState {
+    /// Current value
+    pub text: String,
+    /// Cursor position (in characters)
+    pub cursor: usize,
+    /// Undo/redo history (not saved in sessions)
+    #[serde(skip)]
+    pub history: UndoStack,
+    /// First visible character when the value is wider than the field
+    pub scroll: usize,
+    /// Text being composed by an input method (shown at the cursor, not part of `text` yet)
+    #[serde(skip)]
+    pub preedit: Option<String>,
+}
+
+impl TextInputState {
+    /// Create a new [`TextInputState`] with the cursor at the end of `text`
+    pub fn new(text: &str) -> Self {
+        TextInputState {
+            text: text.to_string(),
+            cursor: text.chars().count(),
+            history: UndoStack::default(),
+            scroll: 0,
+            preedit: None,
+        }
+    }
+
+    /// Get the text as it's shown (with the preedit at the cursor)
+    /// and the cursor position in it (after the preedit)
+    fn display(&self) -> (String, usize) {
+        match &self.preedit {
+            Some(preedit) => {
+                let i = self.byte_index(self.cursor);
+                let text = format!("{}{preedit}{}", &self.text[..i], &self.text[i..]);
+                (text, self.cursor + preedit.chars().count())
+            }
+            None => (self.text.clone(), self.cursor),
+        }
+    }
+
+    /// Get the position of the cursor in the text as it's drawn
+    /// (differs from `cursor` in right-to-left text, see [`crate::text::visual_map`],
+    /// and while composing, see [`TextInputState::set_preedit`])
+    pub fn visual_cursor(&self) -> usize {
+        let (text, cursor) = self.display();
+        logical_to_visual(&text, cursor)
+    }
+
+    /// Set the text being composed by an input method (CJK input, dead keys, ...).
+    /// It's drawn underlined at the cursor until it's committed or cancelled.
+    /// An empty `preedit` ends composition without inserting anything.
+    ///
+    /// Most terminals compose text themselves and only send the result as key
+    /// events, so this is for hosts which report composition (see [`TextInputState::handle_preedit`]).
+    pub fn set_preedit(&mut self, preedit: &str) {
+        self.preedit = if preedit.is_empty() {
+            None
+        } else {
+            Some(preedit.to_string())
+        };
+    }
+
+    /// Apply the composition reported by the host during this frame (like the web
+    /// shim, see [`crate::web::preedit_event`]). Returns `true` if there was any.
+    pub fn handle_preedit(&mut self, state: &State) -> bool {
+        let mut handled = false;
+
+        for preedit in state
+            .user_events
+            .iter()
+            .filter_map(|e| e.get::<crate::web::Preedit>())
+        {
+            self.set_preedit(&preedit.0);
+            handled = true;
+        }
+
+        handled
+    }
+
+    /// Insert the text being composed at the cursor (as one undo step)
+    pub fn commit_preedit(&mut self) {
+        let Some(preedit) = self.preedit.take() else {
+            return;
+        };
+
+        self.history
+            .record(&self.text, self.cursor, EditKind::Other);
+
+        let i = self.byte_index(self.cursor);
+        self.text.insert_str(i, &preedit);
+        self.cursor += preedit.chars().count();
+    }
+
+    /// Drop the text being composed
+    pub fn cancel_preedit(&mut self) {
+        self.preedit = None;
+    }
+
+    /// Update `scroll` so the cursor stays visible in a field `width` cells wide.
+    ///
+    /// `margin` is how many cells to keep between the cursor and the edges of
+    /// the field while there is hidden text on that side (room for indicators).
+    pub fn scroll_to_cursor(&mut self, width: usize, margin: usize) {
+        if width == 0 {
+            return;
+        }
+
+        let len = self.display().0.chars().count();
+        let cursor = self.visual_cursor();
+        let margin = if width > (margin * 2) { margin } else { 0 };
+
+        // cursor went past the left edge
+        if cursor < self.scroll + margin {
+            self.scroll = cursor.saturating_sub(margin);
+        }
+
+        // cursor went past the right edge
+        if cursor + margin >= self.scroll + width {
+            self.scroll = cursor + margin + 1 - width;
+        }
+
+        // never scroll further than needed to show the end (and the cursor after it)
+        self.scroll = self.scroll.min((len + 1).saturating_sub(width));
+    }
+
+    /// Get the byte index of a character index
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.text.len())
+    }
+
+    /// Insert a character at the cursor
+    pub fn insert(&mut self, c: char) {
+        self.history
+            .record(&self.text, self.cursor, EditKind::Insert(c));
+
+        let i = self.byte_index(self.cursor);
+        self.text.insert(i, c);
+        self.cursor += 1;
+    }
+
+    /// Remove the character before the cursor
+    pub fn backspace(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+
+        self.history
+            .record(&self.text, self.cursor, EditKind::Delete);
+
+        let i = self.byte_index(self.cursor - 1);
+        self.text.remove(i);
+        self.cursor -= 1;
+    }
+
+    /// Remove the character under the cursor
+    pub fn delete(&mut self) {
+        if self.cursor >= self.text.chars().count() {
+            return;
+        }
+
+        self.history
+            .record(&self.text, self.cursor, EditKind::Delete);
+
+        let i = self.byte_index(self.cursor);
+        self.text.remove(i);
+    }
+
+    /// Replace the whole value
+    pub fn set_text(&mut self, text: &str) {
+        if text != self.text {
+            self.history
+                .record(&self.text, self.cursor, EditKind::Other);
+        }
+
+        self.text = text.to_string();
+        self.cursor = self.text.chars().count();
+    }
+
+    /// Undo the last group of edits
+    pub fn undo(&mut self) {
+        if let Some((text, cursor)) = self.history.undo(&self.text, self.cursor) {
+            self.text = text;
+            self.cursor = cursor;
+        }
+    }
+
+    /// Redo the last undone group of edits
+    pub fn redo(&mut self) {
+        if let Some((text, cursor)) = self.history.redo(&self.text, self.cursor) {
+            self.text = text;
+            self.cursor = cursor;
+        }
+    }
+
+    /// Handle a key event. Returns `true` if the key was used.
+    pub fn handle_key(&mut self, event: KeyEvent) -> bool {
+        // while composing, enter commits and escape cancels
+        if self.preedit.is_some() {
+            match event.code {
+                KeyCode::Enter => {
+                    self.commit_preedit();
+                    return true;
+                }
+                KeyCode::Esc => {
+                    self.cancel_preedit();
+                    return true;
+                }
+                _ => {}
+            }
+        }
+
+        if UndoStack::is_undo(&event) {
+            self.undo();
+            return true;
+        } else if UndoStack::is_redo(&event) {
+            self.redo();
+            return true;
+        }
+
+        match event.code {
+            KeyCode::Char(c) => {
+                if event.modifiers.contains(KeyModifiers::CONTROL) {
+                    return false;
+                }
+
+                self.insert(c);
+            }
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.text.chars().count()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.text.chars().count(),
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+impl KeyHandler for TextInputState {
+    fn handle_key(&mut self, event: KeyEvent, _: &mut State) -> Handled {
+        TextInputState::handle_key(self, event).into()
+    }
+}
+
+/// Single line text input
+pub struct TextInput {
+    pub buffer: PseudoBuffer,
+    /// Show `…` at the edges of the field when the value is scrolled
+    pub indicators: bool,
+    /// Style of the text being composed (see [`TextInputState::set_preedit`])
+    pub preedit_style: Style,
+    /// Place the hardware cursor at the cursor of the value (see [`PseudoBuffer::set_cursor`]).
+    /// Turn it on for the focused input only.
+    pub cursor: bool,
+}
+
+impl Creatable for TextInput {
+    fn new(buffer: PseudoBuffer) -> Self {
+        TextInput {
+            buffer,
+            indicators: true,
+            preedit_style: Style::DEFAULT.attr(TextAttribute::Underline),
+            cursor: false,
+        }
+    }
+}
+
+impl TextInput {
+    /// Get the screen position of the cursor of a text input at `rect`
+    pub fn cursor_pos(rect: &RectBoundary, input: &TextInputState) -> Vec2 {
+        let visible_x = input.visual_cursor().saturating_sub(input.scroll) as u16;
+        (
+            rect.pos.0 + visible_x.min(rect.size.0.saturating_sub(1)),
+            rect.pos.1,
+        )
+    }
+
+    /// Draw a text input. Values wider than `rect` are scrolled so the cursor stays visible.
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, _)
+    /// * `input` - [`TextInputState`]
+    pub fn render(&mut self, rect: RectBoundary, input: &mut TextInputState) -> DrawingResult {
+        let width = rect.size.0 as usize;
+        input.scroll_to_cursor(width, if self.indicators { 1 } else { 0 });
+
+        // style every character (the preedit is underlined)
+        let (text, cursor) = input.display();
+        let preedit_len = input.preedit.as_ref().map_or(0, |p| p.chars().count());
+        let chars: Vec<(char, Style)> = text
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if (i >= (cursor - preedit_len)) && (i < cursor) {
+                    (c, self.preedit_style)
+                } else {
+                    (c, Style::DEFAULT)
+                }
+            })
+            .collect();
+
+        // get visible part of the value (in visual order)
+        let mut visible: Vec<(char, Style)> = visual_map(&text)
+            .into_iter()
+            .map(|i| chars[i])
+            .skip(input.scroll)
+            .take(width)
+            .collect();
+        let hidden_right = chars.len() > (input.scroll + width);
+
+        if self.indicators && (width > 1) {
+            if input.scroll > 0 {
+                visible[0] = ('…', Style::DEFAULT);
+            }
+
+            if hidden_right {
+                visible[width - 1] = ('…', Style::DEFAULT);
+            }
+        }
+
+        self.buffer
+            .write_line(rect.pos, &visible.into_iter().collect::<Line>())?;
+
+        if self.cursor && (width > 0) {
+            self.buffer
+                .set_cursor(Some(TextInput::cursor_pos(&rect, input)));
+        }
+
+        // done
+        Ok((
+            RectBoundary {
+                pos: rect.pos,
+                size: (rect.size.0, 1),
+            },
+            self.buffer.get_changes(),
+        ))
+    }
+}
+
+// number input
+/// Value of a [`NumberInput`], kept by the application between frames
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct NumberInputState {
+    /// Current (clamped) value
+    pub value: f64,
+    /// Minimum value
+    pub min: f64,
+    /// Maximum value
+    pub max: f64,
+    /// How much Up/Down and the `-`/`+` buttons change the value by
+    pub step: f64,
+    /// What is currently typed in the field
+    pub text: String,
+    /// Undo/redo history of `text` (not saved in sessions)
+    #[serde(skip)]
+    pub history: UndoStack,
+}
+
+impl NumberInputState {
+    /// Create a new [`NumberInputState`]
+    ///
+    /// ## Arguments:
+    /// * `value` - initial value (clamped to `min..=max`)
+    /// * `min`
+    /// * `max`
+    /// * `step`
+    pub fn new(value: f64, min: f64, max: f64, step: f64) -> Self {
+        let mut state = NumberInputState {
+            value: value.clamp(min, max),
+            min,
+            max,
+            step,
+            text: String::new(),
+            history: UndoStack::default(),
+        };
+
+        // (not an edit, so there is nothing to undo yet)
+        state.text = state.format(state.value);
+        state
+    }
+
+    /// Number of decimal places in `step`, used when formatting the value
+    fn precision(&self) -> usize {
+        let step = self.step.to_string();
+
+        match step.split_once('.') {
+            Some((_, decimals)) => decimals.len(),
+            None => 0,
+        }
+    }
+
+    /// Format a value with the precision of `step`
+    fn format(&self, value: f64) -> String {
+        format!("{:.*}", self.precision(), value)
+    }
+
+    /// Set the value (clamped) and sync `text` to it
+    pub fn set_value(&mut self, value: f64) {
+        self.value = value.clamp(self.min, self.max);
+        let text = self.format(self.value);
+
+        // only changes of the text can be undone
+        if text != self.text {
+            let len = self.text.chars().count();
+            self.history.record(&self.text, len, EditKind::Other);
+            self.text = text;
+        }
+    }
+
+    /// Increase the value by `step`
+    pub fn increment(&mut self) {
+        self.set_value(self.value + self.step);
+    }
+
+    /// Decrease the value by `step`
+    pub fn decrement(&mut self) {
+        self.set_value(self.value - self.step);
+    }
+
+    /// Check if `text` is a number (or the start of one, like `-` or `1.`)
+    fn is_partial_number(&self, text: &str) -> bool {
+        let digits = text.strip_prefix('-').unwrap_or(text);
+
+        if (digits.len() != text.len()) && (self.min >= 0.0) {
+            // negative numbers can never be valid
+            return false;
+        }
+
+        if (self.precision() == 0) && digits.contains('.') {
+            return false;
+        }
+
+        let mut seen_dot = false;
+
+        for c in digits.chars() {
+            match c {
+                '0'..='9' => {}
+                '.' if !seen_dot => seen_dot = true,
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
+    /// Type a character into the field. Characters which would not leave a
+    /// valid number are rejected (and `false` is returned).
+    pub fn push_char(&mut self, c: char) -> bool {
+        let mut text = self.text.clone();
+        text.push(c);
+
+        if !self.is_partial_number(&text) {
+            return false;
+        }
+
+        let len = self.text.chars().count();
+        self.history.record(&self.text, len, EditKind::Insert(c));
+
+        self.text = text;
+        self.sync_value();
+        true
+    }
+
+    /// Remove the last typed character
+    pub fn pop_char(&mut self) {
+        let len = self.text.chars().count();
+        self.history.record(&self.text, len, EditKind::Delete);
+
+        self.text.pop();
+        self.sync_value();
+    }
+
+    /// Update the value from `text` as we type, but only clamp the text once committed
+    fn sync_value(&mut self) {
+        if let Ok(value) = self.text.parse::<f64>() {
+            self.value = value.clamp(self.min, self.max);
+        }
+    }
+
+    /// Undo the last group of edits
+    pub fn undo(&mut self) {
+        let len = self.text.chars().count();
+
+        if let Some((text, _)) = self.history.undo(&self.text, len) {
+            self.text = text;
+            self.sync_value();
+        }
+    }
+
+    /// Redo the last undone group of edits
+    pub fn redo(&mut self) {
+        let len = self.text.chars().count();
+
+        if let Some((text, _)) = self.history.redo(&self.text, len) {
+            self.text = text;
+            self.sync_value();
+        }
+    }
+
+    /// Sync `text` with the clamped `value` (done when Enter is pressed)
+    pub fn commit(&mut self) {
+        self.set_value(self.value);
+    }
+
+    /// Handle a key event. Returns `true` if the key was used.
+    pub fn handle_key(&mut self, event: KeyEvent) -> bool {
+        if UndoStack::is_undo(&event) {
+            self.undo();
+            return true;
+        } else if UndoStack::is_redo(&event) {
+            self.redo();
+            return true;
+        }
+
+        match event.code {
+            KeyCode::Up => self.increment(),
+            KeyCode::Down => self.decrement(),
+            KeyCode::Enter => self.commit(),
+            KeyCode::Backspace => self.pop_char(),
+            KeyCode::Char(c) => return self.push_char(c),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Handle a click on the `-`/`+` buttons of a [`NumberInput`] rendered at `rect`.
+    /// Returns `true` if a button was clicked.
+    pub fn handle_click(&mut self, state: &State, rect: RectBoundary) -> bool {
+        let (minus, plus) = NumberInput::button_rects(&rect);
+
+        let pos = match &state.click {
+            Some(click) => click.pos,
+            None => return false,
+        };
+
+        if minus.contains(pos) {
+            self.decrement();
+            return true;
+        } else if plus.contains(pos) {
+            self.increment();
+            return true;
+        }
+
+        false
+    }
+}
+
+impl KeyHandler for NumberInputState {
+    fn handle_key(&mut self, event: KeyEvent, _: &mut State) -> Handled {
+        NumberInputState::handle_key(self, event).into()
+    }
+}
+
+/// Numeric stepper (`- 12 +`)
+pub struct NumberInput {
+    pub buffer: PseudoBuffer,
+}
+
+impl Creatable for NumberInput {
+    fn new(buffer: PseudoBuffer) -> Self {
+        NumberInput { buffer }
+    }
+}
+
+impl NumberInput {
+    /// Get the rects of the `-` and `+` buttons for a number input at `rect`
+    pub fn button_rects(rect: &RectBoundary) -> (RectBoundary, RectBoundary) {
+        (
+            RectBoundary {
+                pos: rect.pos,
+                size: (1, 1),
+            },
+            RectBoundary {
+                pos: (rect.pos.0 + rect.size.0.saturating_sub(1), rect.pos.1),
+                size: (1, 1),
+            },
+        )
+    }
+
+    /// Draw a number input
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, _); the value is drawn between the buttons
+    /// * `input` - [`NumberInputState`] (a committed value is formatted by the locale of
+    ///   the buffer, a value being typed is shown as it is)
+    pub fn render(&mut self, rect: RectBoundary, input: &NumberInputState) -> DrawingResult {
+        let (minus, plus) = NumberInput::button_rects(&rect);
+        let width = rect.size.0.saturating_sub(4) as usize; // buttons + padding
+
+        // draw buttons
+        self.buffer.write_str(minus.pos, "-")?;
+        self.buffer.write_str(plus.pos, "+")?;
+
+        let text = if input.text == input.format(input.value) {
+            self.buffer.locale().number(input.value, input.precision())
+        } else {
+            input.text.clone()
+        };
+
+        // draw value (right aligned like most number fields)
+        let text: String = text.chars().take(width).collect();
+        self.buffer
+            .write_str((rect.pos.0 + 2, rect.pos.1), &format!("{text:>width$}"))?;
+
+        // done
+        Ok((
+            RectBoundary {
+                pos: rect.pos,
+                size: (rect.size.0, 1),
+            },
+            self.buffer.get_changes(),
+        ))
+    }
+}
@@ -0,0 +1,202 @@
+//! Input macros
+//!
+//! [`Macros`] is a [`Middleware`] recording key sequences into registers and playing them
+//! back, like the macros of vim. It's driven by the [`MACRO_RECORD`] and [`MACRO_PLAY`]
+//! actions of a [`Keymap`].
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use std::collections::HashMap;
+
+use crate::keymap::{Keymap, MACRO_PLAY, MACRO_RECORD};
+use crate::middleware::{FilterAction, Middleware};
+use crate::State;
+
+/// Most times a macro is played in a row (larger counts are cut down to it)
+pub const MAX_COUNT: usize = 1000;
+
+/// Get the register a key names (`a` to `z`)
+fn register_name(code: KeyCode) -> Option<char> {
+    match code {
+        KeyCode::Char(c) if c.is_ascii_lowercase() => Some(c),
+        _ => None,
+    }
+}
+
+/// What [`Macros`] is waiting for
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MacroMode {
+    Idle,
+    /// The register to record into
+    RecordRegister,
+    /// The count (`0` if none was typed yet) and the register to play
+    PlayRegister(usize),
+}
+
+/// Vim-style input macros, recording key sequences into registers (`a` to `z`) and
+/// playing them back. Install it with [`crate::Frame::add_middleware`].
+///
+/// With the default bindings, `alt+q a` records into `a` until `alt+q` is pressed again,
+/// and `alt+@ 3 a` plays `a` three times (`alt+@ @` plays the last played register).
+#[derive(Clone, Debug)]
+pub struct Macros {
+    /// Bindings of [`MACRO_RECORD`] and [`MACRO_PLAY`] (other actions are ignored)
+    pub keymap: Keymap,
+    registers: HashMap<char, Vec<KeyEvent>>,
+    mode: MacroMode,
+    /// Register being recorded into
+    recording: Option<char>,
+    /// Register played last
+    last_played: Option<char>,
+}
+
+impl Macros {
+    pub fn new(keymap: Keymap) -> Macros {
+        Macros {
+            keymap,
+            registers: HashMap::new(),
+            mode: MacroMode::Idle,
+            recording: None,
+            last_played: None,
+        }
+    }
+
+    /// Get the register being recorded into
+    pub fn recording(&self) -> Option<char> {
+        self.recording
+    }
+
+    /// Get the keys recorded into a register
+    pub fn register(&self, name: char) -> Option<&[KeyEvent]> {
+        self.registers.get(&name).map(Vec::as_slice)
+    }
+
+    /// Set the keys of a register (like ones saved from an earlier session)
+    pub fn set_register(&mut self, name: char, keys: Vec<KeyEvent>) {
+        self.registers.insert(name, keys);
+    }
+
+    /// Get the events of a register, `count` times in a row (at most [`MAX_COUNT`] times)
+    pub fn play(&mut self, name: char, count: usize) -> Vec<Event> {
+        self.last_played = Some(name);
+
+        let keys = self.registers.get(&name).map_or(&[][..], Vec::as_slice);
+        let keys: Vec<KeyEvent> = keys
+            .iter()
+            .cycle()
+            .take(keys.len().saturating_mul(count.min(MAX_COUNT)))
+            .copied()
+            .collect();
+
+        // played keys are part of the macro being recorded
+        if let Some(recording) = self.recording {
+            self.registers
+                .entry(recording)
+                .or_default()
+                .extend(keys.iter().copied());
+        }
+
+        keys.into_iter().map(Event::Key).collect()
+    }
+
+    /// Handle a key press
+    ///
+    /// ## Arguments:
+    /// * `key`
+    /// * `releases` - if the terminal reports key releases (played keys are released
+    ///   right away then, so they don't stay held in [`crate::State::keys_down`])
+    fn handle_key(&mut self, key: &KeyEvent, releases: bool) -> FilterAction {
+        match self.mode {
+            MacroMode::Idle if self.keymap.matches(MACRO_RECORD, key) => {
+                match self.recording {
+                    Some(_) => self.recording = None,
+                    None => self.mode = MacroMode::RecordRegister,
+                }
+
+                FilterAction::Drop
+            }
+            MacroMode::Idle if self.keymap.matches(MACRO_PLAY, key) => {
+                self.mode = MacroMode::PlayRegister(0);
+                FilterAction::Drop
+            }
+            MacroMode::Idle => {
+                if let Some(recording) = self.recording {
+                    self.registers.entry(recording).or_default().push(*key);
+                }
+
+                FilterAction::Keep
+            }
+            MacroMode::RecordRegister => {
+                self.mode = MacroMode::Idle;
+
+                // (anything else cancels)
+                if let Some(c) = register_name(key.code) {
+                    self.registers.insert(c, Vec::new());
+                    self.recording = Some(c);
+                }
+
+                FilterAction::Drop
+            }
+            MacroMode::PlayRegister(count) => {
+                self.mode = MacroMode::Idle;
+
+                let name = match key.code {
+                    KeyCode::Char(d @ '0'..='9') if (count > 0) || (d != '0') => {
+                        let digit = d.to_digit(10).unwrap_or(0) as usize;
+                        self.mode = MacroMode::PlayRegister(
+                            count
+                                .saturating_mul(10)
+                                .saturating_add(digit)
+                                .min(MAX_COUNT),
+                        );
+                        return FilterAction::Drop;
+                    }
+                    KeyCode::Char('@') => self.last_played,
+                    code => register_name(code),
+                };
+
+                let Some(name) = name else {
+                    return FilterAction::Drop;
+                };
+
+                let mut events = self.play(name, count.max(1));
+
+                if releases {
+                    events = events
+                        .into_iter()
+                        .flat_map(|e| match e {
+                            Event::Key(key) => vec![
+                                Event::Key(key),
+                                Event::Key(KeyEvent {
+                                    kind: KeyEventKind::Release,
+                                    ..key
+                                }),
+                            ],
+                            e => vec![e],
+                        })
+                        .collect();
+                }
+
+                FilterAction::Replace(events)
+            }
+        }
+    }
+}
+
+impl Default for Macros {
+    fn default() -> Self {
+        Macros::new(Keymap::default())
+    }
+}
+
+impl Middleware for Macros {
+    fn before_event(&mut self, event: &Event, state: &mut State) -> FilterAction {
+        let action = match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => {
+                self.handle_key(key, state.keys_down.is_exact())
+            }
+            _ => FilterAction::Keep,
+        };
+
+        state.macro_recording = self.recording;
+        action
+    }
+}
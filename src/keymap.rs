@@ -0,0 +1,230 @@
+//! Keybindings
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Action which starts (then a register) and stops recording a macro
+/// (see [`crate::keymacro::Macros`])
+pub const MACRO_RECORD: &str = "macro_record";
+/// Action which plays a macro (then an optional count and a register, `@` for the last one)
+pub const MACRO_PLAY: &str = "macro_play";
+
+/// A key with modifiers, written like `ctrl+z`, `shift+tab`, `f2`, or `q`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        KeyBinding { code, modifiers }
+    }
+
+    /// Check if a key event is this binding.
+    /// Shift is ignored for characters, since it's already part of the character.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        match (self.code, event.code) {
+            (KeyCode::Char(a), KeyCode::Char(b)) => {
+                (a == b)
+                    && (self.modifiers - KeyModifiers::SHIFT
+                        == event.modifiers - KeyModifiers::SHIFT)
+            }
+            // terminals send shift+tab as backtab, with or without shift
+            (KeyCode::BackTab, KeyCode::BackTab) => {
+                self.modifiers - KeyModifiers::SHIFT == event.modifiers - KeyModifiers::SHIFT
+            }
+            (a, b) => (a == b) && (self.modifiers == event.modifiers),
+        }
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('+').map(|p| p.trim()).collect();
+        let (key, modifiers) = match parts.split_last() {
+            // "+" alone, or a binding ending in "++"
+            Some((&"", rest)) if s.ends_with('+') => ("+", &rest[..rest.len().saturating_sub(1)]),
+            Some((key, rest)) => (*key, rest),
+            None => return Err(format!("empty key binding `{s}`")),
+        };
+
+        let mut binding = KeyBinding::new(KeyCode::Null, KeyModifiers::NONE);
+
+        for modifier in modifiers {
+            binding.modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                "super" => KeyModifiers::SUPER,
+                _ => return Err(format!("unknown modifier `{modifier}` in `{s}`")),
+            };
+        }
+
+        binding.code = match key.to_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" if binding.modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "space" => KeyCode::Char(' '),
+            "plus" => KeyCode::Char('+'),
+            f if f.starts_with('f') && (f.len() > 1) && f[1..].parse::<u8>().is_ok() => {
+                match f[1..].parse::<u8>() {
+                    Ok(n @ 1..=24) => KeyCode::F(n),
+                    _ => return Err(format!("unknown function key `{key}` in `{s}`")),
+                }
+            }
+            _ => {
+                let mut chars = key.chars();
+
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(format!("unknown key `{key}` in `{s}`")),
+                }
+            }
+        };
+
+        Ok(binding)
+    }
+}
+
+impl Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl"),
+            (KeyModifiers::ALT, "alt"),
+            (KeyModifiers::SHIFT, "shift"),
+            (KeyModifiers::SUPER, "super"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            code => write!(f, "{}", format!("{code:?}").to_lowercase()),
+        }
+    }
+}
+
+/// Actions mapped to key bindings. Applications look up their own action
+/// names (`"quit"`, `"save"`, ...), so users can rebind them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(
+    try_from = "BTreeMap<String, Vec<String>>",
+    into = "BTreeMap<String, Vec<String>>"
+)]
+pub struct Keymap {
+    /// Bindings by action (sorted, so a key bound to two actions always
+    /// triggers the same one, see [`Keymap::action`])
+    pub bindings: BTreeMap<String, Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Keymap {
+            bindings: BTreeMap::new(),
+        };
+
+        for (action, keys) in [
+            ("quit", "ctrl+c"),
+            ("undo", "ctrl+z"),
+            ("redo", "ctrl+y"),
+            ("focus_next", "tab"),
+            ("focus_prev", "shift+tab"),
+            ("activate", "enter"),
+            ("cancel", "esc"),
+            (MACRO_RECORD, "alt+q"),
+            (MACRO_PLAY, "alt+@"),
+        ] {
+            keymap.bind(action, keys.parse().expect("default key binding"));
+        }
+
+        keymap
+    }
+}
+
+impl Keymap {
+    /// Add a binding for an action (an action can have multiple bindings)
+    pub fn bind(&mut self, action: &str, binding: KeyBinding) {
+        self.bindings
+            .entry(action.to_string())
+            .or_default()
+            .push(binding);
+    }
+
+    /// Remove every binding of an action
+    pub fn unbind(&mut self, action: &str) {
+        self.bindings.remove(action);
+    }
+
+    /// Check if a key event triggers an action
+    pub fn matches(&self, action: &str, event: &KeyEvent) -> bool {
+        self.bindings
+            .get(action)
+            .is_some_and(|b| b.iter().any(|b| b.matches(event)))
+    }
+
+    /// Get the action a key event triggers (the first one by name, if the key is
+    /// bound to more than one)
+    pub fn action(&self, event: &KeyEvent) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|(_, b)| b.iter().any(|b| b.matches(event)))
+            .map(|(action, _)| action.as_str())
+    }
+}
+
+impl TryFrom<BTreeMap<String, Vec<String>>> for Keymap {
+    type Error = String;
+
+    /// Bindings replace the default bindings of their action, other actions keep their defaults
+    fn try_from(value: BTreeMap<String, Vec<String>>) -> Result<Self, Self::Error> {
+        let mut keymap = Keymap::default();
+
+        for (action, keys) in value {
+            keymap.unbind(&action);
+
+            for key in keys {
+                let binding = key
+                    .parse()
+                    .map_err(|e| format!("invalid binding for `{action}`: {e}"))?;
+                keymap.bind(&action, binding);
+            }
+        }
+
+        Ok(keymap)
+    }
+}
+
+impl From<Keymap> for BTreeMap<String, Vec<String>> {
+    fn from(value: Keymap) -> Self {
+        value
+            .bindings
+            .into_iter()
+            .map(|(action, b)| (action, b.iter().map(|b| b.to_string()).collect()))
+            .collect()
+    }
+}
@@ -0,0 +1,157 @@
+//! Held keys
+//!
+//! [`KeysDown`] (see [`crate::State::keys_down`]) keeps track of the keys which are held,
+//! for games and hold-to-scroll. Terminals which report key releases (the kitty keyboard
+//! protocol, see [`crate::Frame::enable_key_release_events`], and Windows) make it exact;
+//! elsewhere a key counts as held while its auto-repeat keeps coming.
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+
+/// A key which is down
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct HeldKey {
+    /// When it was pressed
+    since: Instant,
+    /// When the last press (or repeat) came in
+    last: Instant,
+    /// Number of repeats since it was pressed
+    repeats: u32,
+}
+
+/// Keys which are held down
+#[derive(Clone, Debug)]
+pub struct KeysDown {
+    held: HashMap<KeyCode, HeldKey>,
+    /// If the terminal reported a release (so releases can be trusted)
+    releases: bool,
+    /// Without releases: how long a pressed key counts as held before it repeats
+    /// (about the auto-repeat delay of the keyboard)
+    pub repeat_delay: Duration,
+    /// Without releases: how long a repeating key counts as held after its last repeat
+    pub repeat_gap: Duration,
+}
+
+impl Default for KeysDown {
+    fn default() -> Self {
+        KeysDown {
+            held: HashMap::new(),
+            releases: false,
+            repeat_delay: Duration::from_millis(550),
+            repeat_gap: Duration::from_millis(150),
+        }
+    }
+}
+
+impl KeysDown {
+    /// Record a key event
+    pub fn record(&mut self, event: &KeyEvent) {
+        let now = Instant::now();
+        let code = normalize(event.code);
+
+        match event.kind {
+            KeyEventKind::Release => {
+                self.releases = true;
+                self.held.remove(&code);
+            }
+            _ => {
+                // (pressed again after it was let go, going by the heuristics)
+                if self.get(code).is_none() {
+                    self.held.remove(&code);
+                }
+
+                // a press of a key which is still down is a repeat
+                match self.held.get_mut(&code) {
+                    Some(key) => {
+                        key.repeats += 1;
+                        key.last = now;
+                    }
+                    None => {
+                        self.held.insert(
+                            code,
+                            HeldKey {
+                                since: now,
+                                last: now,
+                                repeats: 0,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check if releases are reported (so [`KeysDown::is_down`] is exact)
+    pub fn is_exact(&self) -> bool {
+        self.releases
+    }
+
+    /// Get the key if it's (probably) still down
+    fn get(&self, code: KeyCode) -> Option<&HeldKey> {
+        let key = self.held.get(&normalize(code))?;
+
+        if self.releases {
+            return Some(key);
+        }
+
+        let timeout = if key.repeats == 0 {
+            self.repeat_delay
+        } else {
+            self.repeat_gap
+        };
+
+        (key.last.elapsed() < timeout).then_some(key)
+    }
+
+    /// Check if a key is held down
+    pub fn is_down(&self, code: KeyCode) -> bool {
+        self.get(code).is_some()
+    }
+
+    /// Get how long a key has been held down
+    pub fn held_for(&self, code: KeyCode) -> Option<Duration> {
+        self.get(code).map(|k| k.since.elapsed())
+    }
+
+    /// Get how often a held key repeated (`0` right after it was pressed)
+    pub fn repeats(&self, code: KeyCode) -> Option<u32> {
+        self.get(code).map(|k| k.repeats)
+    }
+
+    /// Get every key which is held down
+    pub fn keys(&self) -> Vec<KeyCode> {
+        self.held
+            .keys()
+            .copied()
+            .filter(|code| self.is_down(*code))
+            .collect()
+    }
+
+    /// Forget every held key (when the terminal loses focus, releases get lost)
+    pub fn clear(&mut self) {
+        self.held.clear();
+    }
+
+    /// Forget keys which were released (going by the heuristics)
+    pub(crate) fn prune(&mut self) {
+        if self.releases {
+            return;
+        }
+
+        let (delay, gap) = (self.repeat_delay, self.repeat_gap);
+
+        self.held.retain(|_, key| {
+            let timeout = if key.repeats == 0 { delay } else { gap };
+            key.last.elapsed() < timeout
+        });
+    }
+}
+
+/// Letters are the same key with and without Shift
+fn normalize(code: KeyCode) -> KeyCode {
+    match code {
+        KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+        code => code,
+    }
+}
@@ -1,10 +1,65 @@
+pub mod badge;
+pub mod breadcrumb;
 pub mod buffer;
+pub mod button;
+pub mod cache;
+pub mod canvas;
+pub mod chart;
+pub mod collapsible;
+pub mod compat;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod crash;
+pub mod dialog;
 pub mod drawing;
+pub mod editor;
+pub mod event;
+pub mod filter;
+pub mod focus;
+pub mod grid;
+pub mod hint;
+pub mod i18n;
+pub mod input;
+pub mod keymacro;
+pub mod keymap;
+pub mod keys;
+#[cfg(feature = "hamui-macros")]
+pub mod macros;
+pub mod middleware;
+pub mod nav;
+pub mod notification;
+pub mod pagination;
+pub mod plugin;
+pub mod rect;
+pub mod remote;
+pub mod render;
+pub mod repl;
+pub mod scroll;
+pub mod segment;
+pub mod selection;
+pub mod sprite;
+pub mod ssh;
+pub mod stdin;
+pub mod store;
+pub mod style;
+pub mod table;
+pub mod task;
+pub mod testing;
+pub mod text;
+pub mod theme;
+pub mod tooltip;
+#[cfg(feature = "watch")]
+pub mod watch;
+pub mod web;
+pub mod wizard;
 
-use crossterm::event::{poll, read, Event, KeyCode, KeyModifiers, MouseEventKind};
+use crossterm::event::{
+    poll, read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEventKind,
+};
 use crossterm::QueueableCommand;
 use crossterm::{cursor, terminal};
 use std::io::{Result as IOResult, Stdout, Write};
+use std::time::Instant;
 
 use crate::buffer::BufferWrite;
 
@@ -17,304 +72,1650 @@ pub struct State {
     /// Where the user has clicked on the screen (in cursor mode)
     /// Where we are typing (in keyboard mode)
     pub clicked: drawing::Vec2,
-    /// Current text input (in keyboard mode)
-    pub input: String,
     /// Where the cursor currently is (in cursor mode)
     pub cursor_pos: drawing::Vec2,
     /// Minimum cursor X value
     pub min_x: u16,
+    /// Last key event (cleared after every draw)
+    pub key: Option<KeyEvent>,
+    /// Last click (cleared after every draw)
+    pub click: Option<event::ClickEvent>,
+    /// Last scroll (cleared after every draw)
+    pub scroll: Option<event::ScrollEvent>,
+    /// Which widget has keyboard focus
+    pub focus: focus::FocusRing,
+    /// Per-widget state kept between frames
+    pub store: store::StateStore,
+    /// Changes of memoized components (see [`cache::RenderCache::render`])
+    pub cache: cache::RenderCache,
+    /// Where the left mouse button was pressed (while it is held)
+    pub mouse_down: Option<drawing::Vec2>,
+    /// Active drag
+    pub drag: Option<event::DragState>,
+    /// Last released drag (cleared after every draw)
+    pub drop: Option<event::DropEvent>,
+    /// Current theme (change it with [`State::set_theme`] or [`Frame::set_theme`]).
+    /// Built-in components take their default styles from it.
+    pub theme: theme::Theme,
+    /// If the theme changed since the last draw (cleared after every draw)
+    pub theme_changed: bool,
+    /// Strings and formatting of built-in widgets (change it with [`Frame::set_locale`])
+    pub locale: std::sync::Arc<dyn i18n::Locale>,
+    /// Regions being redrawn this frame (`None` means the whole screen, see [`Frame::mark_dirty`])
+    pub dirty: Option<Vec<drawing::RectBoundary>>,
+    /// If the terminal window has focus (apps can pause animations while it doesn't)
+    pub terminal_focused: bool,
+    /// Last terminal focus change (cleared after every draw)
+    pub terminal_focus: Option<event::TerminalFocusEvent>,
+    /// Content which didn't fit after the last resize (cleared after every draw)
+    pub resize_loss: Option<buffer::ResizeLoss>,
+    /// Events sent through [`Frame::event_sender`] since the last draw (cleared after every draw)
+    pub user_events: Vec<event::UserEvent>,
+    /// Progress of tasks started with [`Frame::spawn_task`]
+    pub progress: task::ProgressTracker,
+    /// Navigation convention of list-like widgets (change it with [`Frame::set_navigation`])
+    pub navigation: nav::Navigation,
+    /// Name of the screen the application shows, for applications with several
+    /// (saved in sessions, see [`Frame::save_session`])
+    pub screen: Option<String>,
+    /// Shortcuts of the focused widget, for a [`hint::HintBar`] (cleared after every draw)
+    pub hints: Vec<hint::Hint>,
+    /// Register a macro is being recorded into (see [`keymacro::Macros`])
+    pub macro_recording: Option<char>,
+    /// Keys which are held down (see [`Frame::enable_key_release_events`])
+    pub keys_down: keys::KeysDown,
+    /// How long the mouse has to rest over a component before its tooltip is shown
+    pub tooltip_delay: std::time::Duration,
+    /// Tooltips registered with [`State::tooltip`] this frame
+    tooltips: Vec<(drawing::RectBoundary, String)>,
+    /// When a component wants to be drawn again (see [`State::redraw_in`])
+    redraw_at: std::sync::Mutex<Option<Instant>>,
+    /// Sending side of the user event channel (for [`State::spawn_task`])
+    event_sender: std::sync::mpsc::Sender<event::UserEvent>,
+    /// If a [`drawing::KeyHandler`] used the key this frame
+    key_handled: bool,
+    /// `key` translated by `navigation` (once per frame, see [`State::nav_key`])
+    nav_key: Option<Option<KeyEvent>>,
+    /// Theme requested with [`State::set_theme`], applied after the draw
+    pending_theme: Option<theme::Theme>,
+    /// Draw a pointer cell at `cursor_pos` on top of everything (in mouse mode),
+    /// so the mouse stays visible while the hardware cursor is somewhere else
+    pub software_cursor: bool,
+    /// Style of the software cursor
+    pub pointer_style: style::Style,
+    /// Move `cursor_pos` with the arrow keys and click with Enter (in mouse mode),
+    /// for terminals without mouse reporting (see [`Frame::set_virtual_cursor`])
+    pub virtual_cursor: bool,
+}
+
+impl State {
+    /// Check if `rect` is being redrawn this frame. Components outside of the
+    /// dirty regions can skip rendering (their cells are dropped anyway).
+    pub fn is_dirty(&self, rect: &drawing::RectBoundary) -> bool {
+        match &self.dirty {
+            Some(regions) => regions.iter().any(|r| r.intersects(rect)),
+            None => true,
+        }
+    }
+
+    /// Give the key of this frame to `handler` if the component `id` is focused.
+    /// If the handler uses it, the key is taken out of `key` (so nothing else
+    /// handles it too) and the frame skips its global keybindings. If Enter or Space
+    /// also clicked the component (see [`event::ClickEvent::keyboard`]), the click is dropped.
+    pub fn dispatch_key(
+        &mut self,
+        id: &str,
+        handler: &mut dyn drawing::KeyHandler,
+    ) -> drawing::Handled {
+        let key = match self.key {
+            Some(key) if self.focus.is_focused(id) => key,
+            _ => return drawing::Handled::No,
+        };
+
+        self.key = None;
+        let handled = handler.handle_key(key, self);
+
+        match handled {
+            drawing::Handled::Yes => {
+                self.key_handled = true;
+                self.click.take_if(|c| c.keyboard);
+            }
+            drawing::Handled::No => self.key = Some(key),
+        }
+
+        handled
+    }
+
+    /// Get the key of this frame translated by the navigation preset (see [`nav::Navigation`]):
+    /// `j` is Down with vi navigation, so list-like widgets handle this instead of `key`.
+    /// `None` if there is no key, or it started a sequence (like the first `g` of `gg`).
+    pub fn nav_key(&mut self) -> Option<KeyEvent> {
+        let key = self.key?;
+
+        *self
+            .nav_key
+            .get_or_insert_with(|| self.navigation.translate(key))
+    }
+
+    /// Switch to another theme. The screen is repainted with it right after this draw.
+    pub fn set_theme(&mut self, theme: theme::Theme) {
+        self.pending_theme = Some(theme);
+    }
+
+    /// Get the string with `key` in the current locale (see [`i18n::text`])
+    pub fn text(&self, key: &str) -> String {
+        i18n::text(self.locale.as_ref(), key)
+    }
+
+    /// Run `work` on a background thread from the draw function
+    /// (like [`Frame::spawn_task`], its result comes back as a [`event::UserEvent`] named `name`)
+    pub fn spawn_task<T: Send + 'static>(
+        &self,
+        name: &str,
+        work: impl FnOnce(&task::TaskContext) -> T + Send + 'static,
+    ) -> task::TaskHandle {
+        task::spawn(self.event_sender.clone(), &self.progress, name, work)
+    }
+
+    /// Start dragging `payload` if the mouse was pressed inside of `rect` and is now being dragged.
+    /// Returns `true` if the drag was started (or is already active for this payload).
+    ///
+    /// ## Arguments:
+    /// * `rect` - rect of the dragged component
+    /// * `payload` - id of what is dragged, given back in the [`event::DropEvent`]
+    /// * `label` - text drawn next to the mouse while dragging
+    pub fn start_drag(&mut self, rect: &drawing::RectBoundary, payload: &str, label: &str) -> bool {
+        if let Some(drag) = &self.drag {
+            return drag.payload == payload;
+        }
+
+        let origin = match self.mouse_down {
+            Some(pos) if rect.contains(pos) => pos,
+            _ => return false,
+        };
+
+        // only start once the mouse has actually moved
+        if self.cursor_pos == origin {
+            return false;
+        }
+
+        self.drag = Some(event::DragState {
+            payload: payload.to_string(),
+            label: label.to_string(),
+            origin,
+            pos: self.cursor_pos,
+        });
+
+        true
+    }
+
+    /// Show `text` when the mouse rests over `rect` (call it every frame while the
+    /// component is drawn, tooltips registered later win where rects overlap)
+    pub fn tooltip(&mut self, rect: &drawing::RectBoundary, text: &str) {
+        self.tooltips.push((rect.clone(), text.to_string()));
+    }
+
+    /// Draw again after `delay` even if nothing happens (for clocks and other content
+    /// which changes by itself). The earliest request wins, and it's forgotten once it's due.
+    /// Frames only notice it while waiting for events, up to [`Frame::WAIT_SLICE`] late.
+    pub fn redraw_in(&self, delay: std::time::Duration) {
+        let at = Instant::now() + delay;
+        let mut redraw_at = self.redraw_at.lock().unwrap_or_else(|e| e.into_inner());
+
+        if redraw_at.is_none_or(|current| at < current) {
+            *redraw_at = Some(at);
+        }
+    }
+
+    /// Get the click of this frame if it landed inside of `rect`,
+    /// with [`event::ClickEvent::local`] relative to `rect`
+    pub fn click_in(&self, rect: &drawing::RectBoundary) -> Option<event::ClickEvent> {
+        let click = self.click.as_ref()?;
+
+        Some(event::ClickEvent {
+            local: rect.to_local(click.pos)?,
+            ..click.clone()
+        })
+    }
+
+    /// Get the scroll of this frame if the mouse was inside of `rect`,
+    /// with [`event::ScrollEvent::local`] relative to `rect`
+    pub fn scroll_in(&self, rect: &drawing::RectBoundary) -> Option<event::ScrollEvent> {
+        let scroll = self.scroll.as_ref()?;
+
+        Some(event::ScrollEvent {
+            local: rect.to_local(scroll.pos)?,
+            ..scroll.clone()
+        })
+    }
+
+    /// Get where the mouse is inside of `rect` (relative to it, `None` in keyboard mode)
+    pub fn pointer_in(&self, rect: &drawing::RectBoundary) -> Option<drawing::Vec2> {
+        if self.keyboard_input_mode {
+            return None;
+        }
+
+        rect.to_local(self.cursor_pos)
+    }
+
+    /// Get the last drop if it was released inside of `rect`
+    pub fn drop_in(&self, rect: &drawing::RectBoundary) -> Option<&event::DropEvent> {
+        self.drop.as_ref().filter(|d| rect.contains(d.pos))
+    }
 }
 
 pub type Drawfn = dyn FnMut(&mut State, buffer::PseudoBuffer) -> buffer::PseudoBuffer;
 
 /// UI Frame
 pub struct Frame<'a> {
-    stdout: Stdout,
+    stdout: Box<dyn Write + Send>,
     draw_fn: &'a mut Drawfn,
     buffer: buffer::Buffer,
     state: State,
+    /// Last click and when it happened (for counting double clicks)
+    last_click: Option<(Instant, event::ClickEvent)>,
+    /// Where the software cursor was drawn last
+    last_pointer: Option<drawing::Vec2>,
+    /// Hardware cursor position declared by a component last frame (see [`buffer::PseudoBuffer::set_cursor`])
+    widget_cursor: Option<drawing::Vec2>,
+    /// Hardware cursor position placed with [`Frame::set_cursor`] (until the mouse moves)
+    hardware_cursor: Option<drawing::Vec2>,
+    /// Remote viewers (see [`Frame::set_remote`])
+    remote: Option<remote::RemoteServer>,
+    /// Thread which writes to the terminal (see [`Frame::spawn_render_thread`])
+    render_thread: Option<render::RenderThread>,
+    /// Cells written by other threads (see [`Frame::shared_buffer`])
+    shared: buffer::SharedPseudoBuffer,
+    /// Key which saves a screenshot (see [`Frame::set_screenshot_key`])
+    screenshot_key: Option<keymap::KeyBinding>,
+    /// If panics in the draw function show the crash screen
+    crash_screen: bool,
+    /// Regions marked with [`Frame::mark_dirty`] for the next step
+    dirty: Vec<drawing::RectBoundary>,
+    /// Sending side of the user event channel (see [`Frame::event_sender`])
+    event_sender: std::sync::mpsc::Sender<event::UserEvent>,
+    /// Receiving side of the user event channel
+    event_receiver: std::sync::mpsc::Receiver<event::UserEvent>,
+    /// Change list reused between steps
+    pool: buffer::ChangePool,
+    /// [`State::theme`] as handed to components (only copied again when it changes)
+    theme: std::sync::Arc<theme::Theme>,
+    /// Hooks around events and steps (see [`Frame::add_middleware`])
+    middleware: Vec<Box<dyn middleware::Middleware>>,
+    /// Dialog shown on top of everything (see [`Frame::confirm`])
+    dialog: Option<dialog::DialogState>,
+    /// How the dialog was answered (set by [`Frame::handle_dialog_event`])
+    dialog_answer: Option<dialog::DialogAnswer>,
+    /// When the mouse last moved, until its tooltip is due
+    hover: Option<Instant>,
+    /// If the mouse rested long enough to show tooltips
+    rested: bool,
+    /// Where the tooltip was drawn last frame (to erase it)
+    last_tooltip: Option<drawing::RectBoundary>,
+    /// Where the drag ghost was drawn last frame (to erase it)
+    last_ghost: Option<drawing::RectBoundary>,
+    /// Where the dialog was drawn last frame, with its shadow (to erase it once it's closed)
+    last_dialog: Option<drawing::RectBoundary>,
+    /// If a tooltip was drawn last frame
+    tooltip_shown: bool,
+    /// If the terminal was asked to report key releases
+    key_releases: bool,
 }
 
 impl Frame<'_> {
+    /// How long [`Frame::wait_for_event`] waits for terminal input at a time
+    pub const WAIT_SLICE: std::time::Duration = std::time::Duration::from_millis(50);
+
     /// Create a new [`UIFrame`]
-    pub fn new(stdout: Stdout, draw_fn: &'_ mut Drawfn) -> Frame {
+    pub fn new(stdout: Stdout, draw_fn: &'_ mut Drawfn) -> Frame<'_> {
         let window_size = terminal::size().unwrap();
+        Frame::create(Box::new(stdout), std::io::stdout(), window_size, draw_fn)
+    }
+
+    /// Create a new [`Frame`] which writes to `output` instead of the terminal
+    /// (like [`web::WebOutput`]). Events are passed in with [`Frame::handle_event`].
+    ///
+    /// ## Arguments:
+    /// * `output` - where escape sequences go (clones must write to the same place)
+    /// * `size` - screen size
+    /// * `draw_fn`
+    pub fn with_output(
+        output: impl Write + Clone + Send + 'static,
+        size: drawing::Vec2,
+        draw_fn: &'_ mut Drawfn,
+    ) -> Frame<'_> {
+        Frame::create(Box::new(output.clone()), output, size, draw_fn)
+    }
+
+    fn create(
+        stdout: Box<dyn Write + Send>,
+        output: impl Write + Send + 'static,
+        window_size: drawing::Vec2,
+        draw_fn: &'_ mut Drawfn,
+    ) -> Frame<'_> {
+        let (event_sender, event_receiver) = std::sync::mpsc::channel();
 
         // ...
         Frame {
             stdout,
             draw_fn,
-            buffer: buffer::Buffer::new(std::io::stdout(), window_size),
+            buffer: buffer::Buffer::with_output(output, window_size),
             state: State {
                 window_size,
                 keyboard_input_mode: false, // mouse by default
                 clicked: (0, 0),
-                input: String::new(),
                 cursor_pos: (0, 0),
                 min_x: 0,
+                key: None,
+                click: None,
+                scroll: None,
+                focus: focus::FocusRing::default(),
+                store: store::StateStore::default(),
+                cache: cache::RenderCache::default(),
+                mouse_down: None,
+                drag: None,
+                drop: None,
+                theme: theme::Theme::default(),
+                theme_changed: false,
+                locale: std::sync::Arc::new(i18n::English),
+                dirty: None,
+                terminal_focused: true,
+                terminal_focus: None,
+                resize_loss: None,
+                user_events: Vec::new(),
+                progress: task::ProgressTracker::default(),
+                navigation: nav::Navigation::default(),
+                screen: None,
+                hints: Vec::new(),
+                macro_recording: None,
+                keys_down: keys::KeysDown::default(),
+                tooltip_delay: std::time::Duration::from_millis(600),
+                tooltips: Vec::new(),
+                redraw_at: std::sync::Mutex::new(None),
+                event_sender: event_sender.clone(),
+                key_handled: false,
+                nav_key: None,
+                pending_theme: None,
+                software_cursor: false,
+                pointer_style: style::Style::DEFAULT.attr(drawing::TextAttribute::Swap),
+                virtual_cursor: false,
             },
+            last_click: None,
+            last_pointer: None,
+            widget_cursor: None,
+            hardware_cursor: None,
+            remote: None,
+            render_thread: None,
+            shared: buffer::SharedPseudoBuffer::new(),
+            screenshot_key: None,
+            crash_screen: false,
+            dirty: Vec::new(),
+            event_sender,
+            event_receiver,
+            pool: buffer::ChangePool::default(),
+            theme: std::sync::Arc::default(),
+            middleware: Vec::new(),
+            dialog: None,
+            dialog_answer: None,
+            hover: None,
+            rested: false,
+            last_tooltip: None,
+            last_ghost: None,
+            last_dialog: None,
+            tooltip_shown: false,
+            key_releases: false,
         }
     }
 
-    /// Step rendering without redrawing components
-    pub fn step_no_draw(&mut self) -> IOResult<buffer::BufState> {
+    /// Step rendering without redrawing components.
+    /// With a render thread, nothing is committed here, so the stats are empty.
+    pub fn step_no_draw(&mut self) -> IOResult<buffer::RenderStats> {
+        // a component asked for the cursor (or it was placed), otherwise it follows cursor_pos
+        let cursor = self
+            .widget_cursor
+            .or(self.hardware_cursor)
+            .unwrap_or(self.state.cursor_pos);
+
+        // let the render thread commit
+        if let Some(thread) = &mut self.render_thread {
+            thread.send(render::RenderCommand::Changes(self.buffer.take_pending()))?;
+            thread.send(render::RenderCommand::MoveCursor(cursor))?;
+
+            let stats = buffer::RenderStats::default();
+
+            for middleware in &mut self.middleware {
+                middleware.after_commit(&stats, &mut self.state);
+            }
+
+            return Ok(stats);
+        }
+
         // commit changes
-        self.buffer.commit()?; // push buffer to screen
-        self.move_cursor(self.state.cursor_pos)?; // sync actual cursor and cusor_pos
-        Ok(buffer::BufState::Ok)
+        let stats = self.buffer.commit()?; // push buffer to screen
+        self.move_cursor(cursor)?; // sync actual cursor
+
+        for middleware in &mut self.middleware {
+            middleware.after_commit(&stats, &mut self.state);
+        }
+
+        Ok(stats)
     }
 
-    /// Step rendering
-    pub fn step(&mut self) -> IOResult<buffer::BufState> {
+    /// Step rendering. Returns what changed on screen (`duration` includes drawing).
+    pub fn step(&mut self) -> IOResult<buffer::RenderStats> {
+        let start = Instant::now();
+
+        // only redraw the marked regions (if any)
+        if !self.dirty.is_empty() {
+            self.state.dirty = Some(std::mem::take(&mut self.dirty));
+        }
+
+        for middleware in &mut self.middleware {
+            middleware.before_draw(&mut self.state);
+        }
+
         // call function and consume changes
-        let pseudo = (self.draw_fn)(&mut self.state, buffer::PseudoBuffer::new(self.buffer.size));
-        self.buffer.consume_changes(pseudo.get_changes())?; // move changes to buffer
+        if *self.theme != self.state.theme {
+            self.theme = std::sync::Arc::new(self.state.theme.clone());
+        }
+
+        let mut pseudo = buffer::PseudoBuffer::with_changes(self.buffer.size, self.pool.take());
+        pseudo.set_theme(Some(self.theme.clone()));
+        pseudo.set_locale(Some(self.state.locale.clone()));
+        let mut pseudo = if self.crash_screen {
+            let (draw_fn, state) = (&mut self.draw_fn, &mut self.state);
+
+            match crash::catch_draw(std::panic::AssertUnwindSafe(|| draw_fn(state, pseudo))) {
+                Ok(pseudo) => pseudo,
+                Err(_) => {
+                    let message = crash::take_panic_message();
+                    self.crash(message.as_deref().unwrap_or("The draw function panicked."))
+                }
+            }
+        } else {
+            (self.draw_fn)(&mut self.state, pseudo)
+        };
+
+        // merge cells written by other threads (skip ones which don't fit anymore),
+        // and erase the ones they cleared unless the draw function wrote there
+        self.shared.take_dirty();
+
+        for loc in self.shared.take_cleared() {
+            if (loc.0 < self.buffer.size.0) && (loc.1 < self.buffer.size.1) {
+                pseudo.erase_undrawn(&drawing::RectBoundary::new(loc, (1, 1)))?;
+            }
+        }
+
+        for change in self.shared.get_changes() {
+            if (change.loc.0 < self.buffer.size.0) && (change.loc.1 < self.buffer.size.1) {
+                pseudo.write_cell(change.loc, change.cell)?;
+            }
+        }
+
+        // draw the dialog on top of the application (and erase it once it's closed)
+        if let Some(last) = self.last_dialog.take() {
+            pseudo.erase_undrawn(&last)?;
+        }
+
+        if let Some(state) = &mut self.dialog {
+            let (rect, changes) = <dialog::Dialog as drawing::Creatable>::new(pseudo.clone())
+                .render(self.buffer.size, state, &*self.state.locale)?;
+            pseudo.set_changes(changes);
+
+            // the bottom border and the shadow are outside of the rect
+            self.last_dialog = Some(drawing::RectBoundary::new(
+                rect.pos,
+                (rect.size.0.saturating_add(1), rect.size.1.saturating_add(2)),
+            ));
+        }
+
+        self.draw_tooltip(&mut pseudo)?;
+
+        // draw drag ghost on top of everything (and erase it where it was)
+        if let Some(last) = self.last_ghost.take() {
+            pseudo.erase_undrawn(&last)?;
+        }
+
+        if let Some(drag) = &self.state.drag {
+            let pos = (drag.pos.0.saturating_add(1), drag.pos.1);
+            pseudo.write_str(pos, &drag.label)?;
+            self.last_ghost = Some(drawing::RectBoundary::new(
+                pos,
+                (text::display_width(&drag.label) as u16, 1),
+            ));
+        }
+
+        self.draw_pointer(&mut pseudo)?;
+
+        // the draw function has seen these events now
+        self.state.key = None;
+        self.state.nav_key = None;
+        self.state.click = None;
+        self.state.scroll = None;
+        self.state.drop = None;
+        self.state.theme_changed = false;
+        self.state.resize_loss = None;
+        self.state.terminal_focus = None;
+        self.state.user_events.clear();
+        self.state.hints.clear();
+        self.state.tooltips.clear();
+        self.state.keys_down.prune();
+        self.state.focus.end_frame();
+        self.state.cache.end_frame();
+        self.widget_cursor = pseudo.cursor();
+        let mut changes = pseudo.into_changes();
+
+        // skip everything outside of the dirty regions
+        if let Some(regions) = self.state.dirty.take() {
+            changes.retain(|c| regions.iter().any(|r| r.contains(c.loc)));
+        }
+
+        // mirror the frame to remote viewers
+        if let Some(remote) = &mut self.remote {
+            remote.push(self.buffer.size, &changes)?;
+        }
+
+        match &mut self.render_thread {
+            Some(thread) => thread.send(render::RenderCommand::Changes(changes))?,
+            None => {
+                self.buffer.drain_changes(&mut changes)?; // move changes to buffer
+                self.pool.give(changes);
+            }
+        }
 
         // commit changes
-        self.step_no_draw()
+        let mut stats = self.step_no_draw()?;
+
+        // theme was switched during the draw, repaint with it
+        if let Some(theme) = self.state.pending_theme.take() {
+            self.set_theme(theme)?;
+            stats = self.step()?;
+        }
+
+        stats.duration = start.elapsed();
+        Ok(stats)
     }
 
-    /// Move cursor
+    /// Draw the tooltip under the mouse (if it rested long enough)
+    fn draw_tooltip(&mut self, pseudo: &mut buffer::PseudoBuffer) -> IOResult<()> {
+        let pos = self.state.cursor_pos;
+        let visible = self.rested
+            && !self.state.keyboard_input_mode
+            && self.state.drag.is_none()
+            && self.dialog.is_none();
+
+        let text = self
+            .state
+            .tooltips
+            .iter()
+            .rev()
+            .find(|(rect, _)| visible && rect.contains(pos))
+            .map(|(_, text)| text.clone());
+
+        self.tooltip_shown = text.is_some();
+
+        // nothing is drawn where the tooltip was, so erase it ourselves
+        if let Some(last) = self.last_tooltip.take() {
+            pseudo.erase_undrawn(&last)?;
+        }
+
+        if let Some(text) = text {
+            let (rect, changes) = <tooltip::Tooltip as drawing::Creatable>::new(pseudo.clone())
+                .render(self.buffer.size, pos, &text)?;
+            pseudo.set_changes(changes);
+            self.last_tooltip = Some(rect);
+        }
+
+        Ok(())
+    }
+
+    /// Check (once) if a redraw requested with [`State::redraw_in`] is due
+    fn redraw_due(&mut self) -> bool {
+        let redraw_at = self
+            .state
+            .redraw_at
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner());
+
+        match *redraw_at {
+            Some(at) if Instant::now() >= at => {
+                *redraw_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Check (once) if the mouse rested long enough for its tooltip
+    fn tooltip_due(&mut self) -> bool {
+        match self.hover {
+            Some(moved) if moved.elapsed() >= self.state.tooltip_delay => {
+                self.hover = None;
+                self.rested = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Draw the software cursor (if enabled) on top of a drawn frame,
+    /// and erase it from where it was last frame
+    fn draw_pointer(&mut self, pseudo: &mut buffer::PseudoBuffer) -> IOResult<()> {
+        let pos = self.state.cursor_pos;
+        let visible = self.state.software_cursor && !self.state.keyboard_input_mode;
+
+        // nothing was drawn where the pointer was, so erase it ourselves
+        if let Some(last) = self.last_pointer.take() {
+            if (!visible || (last != pos)) && pseudo.get_cell(last).is_none() {
+                pseudo.erase(last)?;
+            }
+        }
+
+        if !visible || (pos.0 >= self.buffer.size.0) || (pos.1 >= self.buffer.size.1) {
+            return Ok(());
+        }
+
+        // keep the character under the pointer, with the pointer style
+        let char = pseudo.get_cell(pos).map(|c| c.char).unwrap_or(' ');
+        pseudo.write_cell(
+            pos,
+            buffer::BufCell {
+                char,
+                empty: false,
+                style: self.state.pointer_style,
+            },
+        )?;
+
+        self.last_pointer = Some(pos);
+        Ok(())
+    }
+
+    /// Choose what happens to the content when the terminal is resized
+    /// (what didn't fit is in `state.resize_loss` for the next draw)
+    pub fn set_resize_policy(&mut self, policy: buffer::ResizePolicy) {
+        self.buffer.resize_policy = policy;
+    }
+
+    /// Get a handle to the [`buffer::SharedPseudoBuffer`] which is drawn on top of every
+    /// frame. Clone it into background threads; writing to it redraws on the next poll.
+    pub fn shared_buffer(&self) -> buffer::SharedPseudoBuffer {
+        self.shared.clone()
+    }
+
+    /// Save the values of the state store which are marked persistent (see
+    /// [`store::StateStore::get_persistent`]), which widget is focused and the
+    /// [`State::screen`] to a JSON file, so the application can resume where the user
+    /// left off with [`Frame::load_session`]
+    pub fn save_session(&self, path: impl AsRef<std::path::Path>) -> IOResult<()> {
+        let mut session = self.state.store.to_session();
+        session.focused = self.state.focus.focused.clone();
+        session.screen = self.state.screen.clone();
+
+        std::fs::write(path, serde_json::to_vec_pretty(&session)?)
+    }
+
+    /// Load a session saved with [`Frame::save_session`] (before the first step)
+    pub fn load_session(&mut self, path: impl AsRef<std::path::Path>) -> IOResult<()> {
+        let session: store::Session = serde_json::from_slice(&std::fs::read(path)?)?;
+
+        if let Some(id) = &session.focused {
+            self.state.focus.focus(id);
+        }
+
+        if session.screen.is_some() {
+            self.state.screen = session.screen.clone();
+        }
+
+        self.state.store.load_session(session);
+        Ok(())
+    }
+
+    /// Send every drawn frame to the clients of a [`remote::RemoteServer`]
+    pub fn set_remote(&mut self, server: remote::RemoteServer) {
+        self.remote = Some(server);
+    }
+
+    /// Get the [`remote::RemoteServer`] (to give a client input control, for example)
+    pub fn remote_mut(&mut self) -> Option<&mut remote::RemoteServer> {
+        self.remote.as_mut()
+    }
+
+    /// Handle input from the remote client with input control.
+    /// Returns `true` if there was any.
+    fn handle_remote_input(&mut self) -> IOResult<bool> {
+        let input = match &mut self.remote {
+            Some(remote) => remote.take_input()?,
+            None => return Ok(false),
+        };
+
+        for event in &input {
+            self.handle_event(event.clone())?;
+        }
+
+        Ok(!input.is_empty())
+    }
+
+    /// Switch to another theme. The whole screen is invalidated, so the next
+    /// step repaints everything (the draw function sees `state.theme_changed`).
+    pub fn set_theme(&mut self, theme: theme::Theme) -> IOResult<buffer::BufState> {
+        self.state.theme = theme;
+        self.state.theme_changed = true;
+        // memoized components were rendered with the old theme
+        self.state.cache.clear();
+        self.invalidate()
+    }
+
+    /// Switch the navigation keys of every list-like widget (see [`nav::NavPreset`])
+    pub fn set_navigation(&mut self, navigation: nav::Navigation) {
+        self.state.navigation = navigation;
+    }
+
+    /// Use another locale for the strings of built-in widgets. The whole screen is
+    /// invalidated, so the next step redraws everything with it.
+    pub fn set_locale(
+        &mut self,
+        locale: impl i18n::Locale + 'static,
+    ) -> IOResult<buffer::BufState> {
+        self.state.locale = std::sync::Arc::new(locale);
+        self.invalidate()
+    }
+
+    /// Only redraw `rect` on the next step (call it multiple times to mark more regions).
+    /// The draw function can check [`State::is_dirty`] to skip components outside of them,
+    /// and only cells inside of them are compared with the screen.
+    /// Input events, resizes, and full repaints always redraw everything.
+    pub fn mark_dirty(&mut self, rect: drawing::RectBoundary) {
+        self.dirty.push(rect);
+    }
+
+    /// Reserve a region for content drawn outside of the buffer, like an inline image
+    /// or an embedded terminal (see [`buffer::Buffer::lock_region`])
+    pub fn lock_region(&mut self, rect: drawing::RectBoundary) -> IOResult<()> {
+        match &mut self.render_thread {
+            Some(thread) => thread.send(render::RenderCommand::LockRegion(rect)),
+            None => {
+                self.buffer.lock_region(rect);
+                Ok(())
+            }
+        }
+    }
+
+    /// Give back a region locked with [`Frame::lock_region`] (it's erased)
+    pub fn unlock_region(&mut self, rect: &drawing::RectBoundary) -> IOResult<()> {
+        match &mut self.render_thread {
+            Some(thread) => thread.send(render::RenderCommand::UnlockRegion(rect.clone())),
+            None => {
+                self.buffer.unlock_region(rect)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Clear the screen and forget what's on it (in the render thread too)
+    fn invalidate(&mut self) -> IOResult<buffer::BufState> {
+        self.dirty.clear();
+
+        if let Some(thread) = &mut self.render_thread {
+            thread.send(render::RenderCommand::Invalidate)?;
+        }
+
+        self.buffer.invalidate()
+    }
+
+    /// Clear the screen. What the buffer thinks is on screen is cleared with it,
+    /// so the next step draws everything again.
+    pub fn clear(&mut self) -> IOResult<buffer::BufState> {
+        if let Some(thread) = &mut self.render_thread {
+            thread.send(render::RenderCommand::Invalidate)?;
+        }
+
+        self.buffer.clear()
+    }
+
+    /// Save what's currently on screen to a file. Files ending in `.ans` or `.ansi`
+    /// keep the styles (as ANSI escapes), anything else is saved as plain text.
+    pub fn screenshot(&mut self, path: impl AsRef<std::path::Path>) -> IOResult<()> {
+        let rows = match &mut self.render_thread {
+            Some(thread) => {
+                let (sender, receiver) = std::sync::mpsc::channel();
+                thread.send(render::RenderCommand::Snapshot(sender))?;
+                receiver.recv().map_err(std::io::Error::other)?
+            }
+            None => self.buffer.screen_vec.to_rows(),
+        };
+
+        let path = path.as_ref();
+        let text = match path.extension().and_then(|e| e.to_str()) {
+            Some("ans") | Some("ansi") => buffer::Buffer::screen_ansi(&rows),
+            _ => buffer::Buffer::screen_text(&rows),
+        };
+
+        std::fs::write(path, text)
+    }
+
+    /// Save a screenshot (`screenshot-<unix time>.txt` in the working directory)
+    /// whenever `key` is pressed. `None` turns it off (the default).
+    pub fn set_screenshot_key(&mut self, key: Option<keymap::KeyBinding>) {
+        self.screenshot_key = key;
+    }
+
+    /// Repaint the whole screen now, even the cells which didn't change.
+    /// Use it when something else wrote to the terminal (like a program we shelled out to).
+    pub fn force_redraw(&mut self) -> IOResult<buffer::RenderStats> {
+        self.invalidate()?;
+        self.step()
+    }
+
+    /// Move writing to the terminal to its own thread, so a slow draw function
+    /// doesn't hold up repainting. Steps block once `capacity` commands are queued.
+    pub fn spawn_render_thread(&mut self, capacity: usize) {
+        // (the thread writes where the frame would, like a test backend or a remote client)
+        let output = self.buffer.share_output();
+        self.render_thread = Some(render::RenderThread::with_output(
+            output,
+            self.buffer.size,
+            capacity,
+        ));
+    }
+
+    /// Move cursor (after the frames queued for the render thread, if there is one)
     pub fn move_cursor(&mut self, pos: drawing::Vec2) -> IOResult<buffer::BufState> {
+        if let Some(thread) = &mut self.render_thread {
+            thread.send(render::RenderCommand::MoveCursor(pos))?;
+            return Ok(buffer::BufState::Ok);
+        }
+
         self.stdout.queue(cursor::MoveTo(pos.0, pos.1))?;
         Ok(buffer::BufState::Ok)
     }
 
-    /// Open frame environment
+    /// Place the hardware cursor and show or hide it. It stays there after every step
+    /// (until the mouse moves it or a component places it, see [`buffer::PseudoBuffer::set_cursor`]).
+    /// The pointer ([`State::cursor_pos`]) doesn't move.
+    ///
+    /// ## Arguments:
+    /// * `pos` - position on screen
+    /// * `visible` - show the cursor
+    pub fn set_cursor(&mut self, pos: drawing::Vec2, visible: bool) -> IOResult<()> {
+        self.hardware_cursor = Some(pos);
+
+        // the render thread writes to the terminal, in order with the frames
+        if let Some(thread) = &mut self.render_thread {
+            thread.send(render::RenderCommand::ShowCursor(visible))?;
+            return thread.send(render::RenderCommand::MoveCursor(pos));
+        }
+
+        if visible {
+            self.stdout.queue(cursor::Show)?;
+        } else {
+            self.stdout.queue(cursor::Hide)?;
+        }
+
+        self.move_cursor(pos)?;
+        self.stdout.flush()
+    }
+
+    /// Save the position of the hardware cursor (see [`Frame::restore_cursor`])
+    pub fn save_cursor(&mut self) -> IOResult<()> {
+        if let Some(thread) = &mut self.render_thread {
+            return thread.send(render::RenderCommand::SaveCursor);
+        }
+
+        self.stdout.queue(cursor::SavePosition)?;
+        self.stdout.flush()
+    }
+
+    /// Move the hardware cursor back to where [`Frame::save_cursor`] saved it
+    pub fn restore_cursor(&mut self) -> IOResult<()> {
+        if let Some(thread) = &mut self.render_thread {
+            return thread.send(render::RenderCommand::RestoreCursor);
+        }
+
+        self.stdout.queue(cursor::RestorePosition)?;
+        self.stdout.flush()
+    }
+
+    /// Open frame environment.
+    /// Terminals without mouse reporting get the virtual cursor instead (see [`event::mouse_supported`]).
     pub fn open_env(&mut self) -> IOResult<()> {
+        // raw mode first: on Windows, console modes are set right away (not queued),
+        // and raw mode must not undo what mouse capture sets
+        compat::enable_virtual_terminal();
+        terminal::enable_raw_mode().unwrap();
         self.stdout.queue(terminal::EnterAlternateScreen)?;
         self.stdout.queue(cursor::MoveTo(0, 0))?;
-        terminal::enable_raw_mode().unwrap();
         self.stdout.queue(terminal::EnableLineWrap).unwrap();
-        self.stdout
-            .queue(crossterm::event::EnableMouseCapture)
-            .unwrap();
-        Ok(())
+
+        if event::mouse_supported() {
+            self.stdout
+                .queue(crossterm::event::EnableMouseCapture)
+                .unwrap();
+        } else {
+            self.set_virtual_cursor(true);
+        }
+
+        self.stdout.queue(crossterm::event::EnableFocusChange)?;
+        self.stdout.flush()
     }
 
-    /// Exit frame
-    pub fn exit(&mut self) -> () {
-        terminal::disable_raw_mode().unwrap();
-        self.stdout.queue(terminal::LeaveAlternateScreen).unwrap();
-        // self.stdout.queue(terminal::DisableLineWrap).unwrap();
+    /// Turn mouse capture on or off. Without it, the terminal handles the mouse
+    /// (so text can be selected), and the virtual cursor is used instead.
+    pub fn set_mouse_capture(&mut self, enabled: bool) -> IOResult<()> {
+        if enabled {
+            self.stdout.queue(crossterm::event::EnableMouseCapture)?;
+        } else {
+            self.stdout.queue(crossterm::event::DisableMouseCapture)?;
+        }
+
+        self.set_virtual_cursor(!enabled);
+        self.stdout.flush()
+    }
+
+    /// Turn the virtual cursor on or off: in mouse mode, the arrow keys move `cursor_pos`
+    /// (with Shift: 5 cells at a time) and Enter clicks where it is, so clickable
+    /// components work without a mouse. The software cursor shows where it is.
+    pub fn set_virtual_cursor(&mut self, enabled: bool) {
+        self.state.virtual_cursor = enabled;
+        self.state.software_cursor = enabled;
+    }
+
+    /// Move the virtual cursor or click with it (see [`Frame::set_virtual_cursor`]).
+    /// Returns `true` if the key was used.
+    fn handle_virtual_cursor_key(&mut self, event: KeyEvent) -> IOResult<bool> {
+        if !self.state.virtual_cursor || self.state.keyboard_input_mode {
+            return Ok(false);
+        }
+
+        let step = if event.modifiers.contains(KeyModifiers::SHIFT) {
+            5
+        } else {
+            1
+        };
+        let (x, y) = self.state.cursor_pos;
+        let max = (
+            self.buffer.size.0.saturating_sub(1),
+            self.buffer.size.1.saturating_sub(1),
+        );
+
+        self.state.cursor_pos = match event.code {
+            KeyCode::Up => (x, y.saturating_sub(step)),
+            KeyCode::Down => (x, (y + step).min(max.1)),
+            KeyCode::Left => (x.saturating_sub(step), y),
+            KeyCode::Right => ((x + step).min(max.0), y),
+            KeyCode::Enter => {
+                // click where the cursor is
+                self.state.clicked = self.state.cursor_pos;
+                self.state.click = Some(event::ClickEvent {
+                    pos: self.state.cursor_pos,
+                    local: self.state.cursor_pos,
+                    button: event::MouseButton::Left,
+                    count: 1,
+                    keyboard: false,
+                    modifiers: event.modifiers,
+                });
+                (x, y)
+            }
+            _ => return Ok(false),
+        };
+
+        self.state.key = None; // it was a click, not a key
+        self.step()?;
+        Ok(true)
+    }
+
+    /// Close frame environment
+    fn close_env(&mut self) {
+        // the reverse of `open_env`
+        if self.key_releases {
+            self.stdout
+                .queue(crossterm::event::PopKeyboardEnhancementFlags)
+                .unwrap();
+        }
+
+        self.stdout
+            .queue(crossterm::event::DisableFocusChange)
+            .unwrap();
         self.stdout
             .queue(crossterm::event::DisableMouseCapture)
             .unwrap();
+        // self.stdout.queue(terminal::DisableLineWrap).unwrap();
+        self.stdout.queue(terminal::LeaveAlternateScreen).unwrap();
+        self.stdout.queue(cursor::Show).unwrap(); // in case it was hidden with `set_cursor`
         self.stdout.flush().unwrap();
+        terminal::disable_raw_mode().unwrap();
+    }
+
+    /// Ask the terminal to report key releases (the kitty keyboard protocol), so
+    /// [`State::keys_down`] knows exactly which keys are held. Returns `false` if the
+    /// terminal doesn't support it (held keys are guessed from the auto-repeat then).
+    pub fn enable_key_release_events(&mut self) -> IOResult<bool> {
+        if !terminal::supports_keyboard_enhancement().unwrap_or(false) {
+            return Ok(false);
+        }
+
+        self.stdout
+            .queue(crossterm::event::PushKeyboardEnhancementFlags(
+                crossterm::event::KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | crossterm::event::KeyboardEnhancementFlags::REPORT_EVENT_TYPES,
+            ))?;
+        self.stdout.flush()?;
+
+        self.key_releases = true;
+        Ok(true)
+    }
+
+    /// Show a desktop notification through the terminal (for when a long job finishes).
+    /// Returns `false` if the terminal (probably) can't show them, nothing is sent then.
+    pub fn notify(&mut self, title: &str, body: &str) -> IOResult<bool> {
+        let Some(protocol) = notification::NotifyProtocol::detect() else {
+            return Ok(false);
+        };
+
+        let sequence = protocol.sequence(title, body);
+
+        match &mut self.render_thread {
+            Some(thread) => thread.send(render::RenderCommand::Write(sequence))?,
+            None => {
+                self.stdout.write_all(sequence.as_bytes())?;
+                self.stdout.flush()?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Exit frame
+    pub fn exit(&mut self) {
+        self.close_env();
         std::process::exit(0);
     }
 
+    /// Show panics in the draw function on a crash screen (see [`Frame::crash`]).
+    /// This wraps the panic hook, so panics in the draw function aren't printed over the UI
+    /// (other panics still reach the previous hook, see [`crash::install_panic_hook`]).
+    pub fn enable_crash_screen(&mut self) {
+        crash::install_panic_hook();
+        self.crash_screen = true;
+    }
+
+    /// Show `message` on a red crash screen, wait for a key, and exit.
+    /// The message is printed again after leaving the UI, so it isn't lost.
+    pub fn crash(&mut self, message: &str) -> ! {
+        // draw it ourselves, whatever state the render thread is in
+        drop(self.render_thread.take());
+
+        let mut pseudo = buffer::PseudoBuffer::new(self.buffer.size);
+        crash::render_crash_screen(
+            &mut pseudo,
+            self.buffer.size,
+            message,
+            self.state.locale.as_ref(),
+        );
+
+        let _ = self.buffer.clear();
+        let _ = self.buffer.consume_changes(pseudo.get_changes());
+        let _ = self.buffer.commit();
+
+        // press any key to exit
+        loop {
+            match read() {
+                Ok(Event::Key(_)) | Err(_) => break,
+                _ => (),
+            }
+        }
+
+        self.close_env();
+        eprintln!("{message}");
+        std::process::exit(101);
+    }
+
+    /// Handle focus keys (in mouse mode): Tab/Shift+Tab move focus (Enter/Space
+    /// activate the focused widget while it gets the key, see [`Frame::activation_click`]).
+    /// Returns `true` if the key was used.
+    fn handle_focus_key(&mut self, event: KeyEvent) -> IOResult<bool> {
+        if self.state.keyboard_input_mode {
+            return Ok(false);
+        }
+
+        match event.code {
+            KeyCode::Tab => self.state.focus.next(),
+            KeyCode::BackTab => self.state.focus.prev(),
+            _ => return Ok(false),
+        }
+
+        // redraw
+        self.step()?;
+        Ok(true)
+    }
+
+    /// Get the click Enter or Space activate the focused widget with
+    /// (`None` for other keys, or if the keys go elsewhere)
+    fn activation_click(&self, event: KeyEvent) -> Option<event::ClickEvent> {
+        if self.state.keyboard_input_mode
+            || self.state.virtual_cursor
+            || !matches!(event.code, KeyCode::Enter | KeyCode::Char(' '))
+        {
+            return None;
+        }
+
+        let rect = self.state.focus.focused_rect()?;
+
+        Some(event::ClickEvent {
+            pos: rect.pos,
+            local: rect.pos,
+            button: event::MouseButton::Left,
+            count: 1,
+            keyboard: true,
+            modifiers: event.modifiers,
+        })
+    }
+
+    /// Get a sender for [`event::UserEvent`]s. Other threads can use it to wake
+    /// the frame up; the events are in [`State::user_events`] during the next draw.
+    pub fn event_sender(&self) -> std::sync::mpsc::Sender<event::UserEvent> {
+        self.event_sender.clone()
+    }
+
+    /// Run `work` on a background thread. Its result is sent back as an
+    /// [`event::UserEvent`] named `name` (see [`State::user_events`]).
+    ///
+    /// ```ignore
+    /// frame.spawn_task("load", |_| std::fs::read_to_string("big.log"));
+    ///
+    /// // in the draw function
+    /// for event in state.user_events.drain(..) {
+    ///     if let Ok(Ok(text)) = event.take::<std::io::Result<String>>() { ... }
+    /// }
+    /// ```
+    pub fn spawn_task<T: Send + 'static>(
+        &self,
+        name: &str,
+        work: impl FnOnce(&task::TaskContext) -> T + Send + 'static,
+    ) -> task::TaskHandle {
+        task::spawn(self.event_sender(), &self.state.progress, name, work)
+    }
+
+    /// Like [`Frame::spawn_task`], but runs a future on the current tokio runtime
+    #[cfg(feature = "async")]
+    pub fn spawn_async_task<T, F>(
+        &self,
+        name: &str,
+        work: impl FnOnce(task::TaskContext) -> F,
+    ) -> task::TaskHandle
+    where
+        T: Send + 'static,
+        F: std::future::Future<Output = T> + Send + 'static,
+    {
+        task::spawn_async(self.event_sender(), &self.state.progress, name, work)
+    }
+
+    /// Watch files, sending their changes as [`event::UserEvent`]s named `name`
+    /// (with a [`watch::FileChange`] payload). Keep the watcher around while watching.
+    ///
+    /// ```ignore
+    /// let mut watcher = frame.watch_files("config")?;
+    /// watcher.watch("config.toml", false)?;
+    /// ```
+    #[cfg(feature = "watch")]
+    pub fn watch_files(&self, name: &str) -> notify::Result<watch::FileWatcher> {
+        watch::FileWatcher::new(self.event_sender(), name)
+    }
+
+    /// Read lines piped into stdin (like `tail -f app.log | myapp`), sending them as
+    /// [`event::UserEvent`]s named `name` (with a [`stdin::StdinEvent`] payload).
+    /// Keys are read from the controlling terminal meanwhile.
+    ///
+    /// Returns `None` if nothing is piped in.
+    pub fn stdin_source(&self, name: &str) -> IOResult<Option<stdin::StdinSource>> {
+        stdin::StdinSource::spawn(self.event_sender(), name)
+    }
+
+    /// Move received user events into the state. Returns `true` if there were
+    /// any (or if the progress of a task changed).
+    fn take_user_events(&mut self) -> bool {
+        self.state
+            .user_events
+            .extend(self.event_receiver.try_iter());
+        self.state.progress.take_dirty() | !self.state.user_events.is_empty()
+    }
+
     /// Handle all events
     pub fn poll_events(&mut self) -> IOResult<buffer::BufState> {
-        let window_size = self.buffer.size;
+        // other threads drew something (or sent events), or a tooltip or redraw is due
+        if self.shared.take_dirty()
+            | self.take_user_events()
+            | self.tooltip_due()
+            | self.redraw_due()
+        {
+            self.step()?;
+        }
+
+        self.handle_remote_input()?;
+
         if poll(std::time::Duration::from_millis(0)).expect("Failed to poll events!") {
-            match read().expect("Failed to read event!") {
-                // handle window resize
-                Event::Resize(width, height) => {
-                    // sync buffer and window
-                    self.buffer.resize((width, height))?;
-
-                    // clear
-                    self.stdout
-                        .queue(terminal::Clear(terminal::ClearType::All))
-                        .unwrap();
-
-                    // redraw
-                    // we're not drawing every frame, instead we only draw when needed
-                    self.step()?;
+            self.handle_event(read().expect("Failed to read event!"))?;
+        }
+
+        Ok(buffer::BufState::Ok)
+    }
+
+    /// Block until an event was handled or `timeout` passed (forever if `None`).
+    /// Returns `true` if something was handled, `false` on timeout.
+    ///
+    /// Unlike [`Frame::poll_events`] in a loop, this sleeps in the terminal poll while
+    /// idle. The terminal poll can't be woken up by other threads though, so it wakes
+    /// up every [`Frame::WAIT_SLICE`] to check for writes to the [`Frame::shared_buffer`],
+    /// user events, remote input, due tooltips and redraws (they're handled up to that late).
+    pub fn wait_for_event(&mut self, timeout: Option<std::time::Duration>) -> IOResult<bool> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+
+        loop {
+            // other threads drew something (or sent events), or a tooltip or redraw is due
+            if self.shared.take_dirty()
+                | self.take_user_events()
+                | self.tooltip_due()
+                | self.redraw_due()
+            {
+                self.step()?;
+                return Ok(true);
+            }
+
+            if self.handle_remote_input()? {
+                return Ok(true);
+            }
+
+            let mut slice = Frame::WAIT_SLICE;
+
+            if let Some(deadline) = deadline {
+                slice = slice.min(deadline.saturating_duration_since(Instant::now()));
+            }
+
+            if poll(slice)? {
+                self.handle_event(read()?)?;
+                return Ok(true);
+            }
+
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Ask a yes/no question in a dialog on top of the application and block until it's
+    /// answered. The draw function keeps drawing behind the dialog, but gets no input.
+    pub fn confirm(&mut self, message: &str) -> IOResult<bool> {
+        let answer = self.run_dialog(dialog::DialogState::confirm(message))?;
+        Ok(answer.is_some())
+    }
+
+    /// Ask for a line of text in a dialog on top of the application and block until it's
+    /// answered. Returns `None` if it was cancelled.
+    pub fn prompt(&mut self, label: &str) -> IOResult<Option<String>> {
+        self.run_dialog(dialog::DialogState::prompt(label, ""))
+    }
+
+    /// Show a dialog and handle events until it's answered (see [`Frame::wait_for_event`],
+    /// so they go through the middleware, and remote clients can answer it).
+    /// Returns its value if it was accepted.
+    fn run_dialog(&mut self, state: dialog::DialogState) -> IOResult<Option<String>> {
+        self.dialog = Some(state);
+        self.dialog_answer = None;
+        self.step()?;
+
+        let answer = loop {
+            if self.dialog.is_none() {
+                break dialog::DialogAnswer::Cancel;
+            }
+
+            if let Some(answer) = self.dialog_answer.take() {
+                break answer;
+            }
+
+            self.wait_for_event(None)?;
+        };
+
+        let state = self.dialog.take();
+        self.step()?;
+
+        Ok(match answer {
+            dialog::DialogAnswer::Accept => Some(state.map(|s| s.value()).unwrap_or_default()),
+            dialog::DialogAnswer::Cancel => None,
+        })
+    }
+
+    /// Send an event to the open dialog, which gets all input. Returns `false` if there is
+    /// no dialog (or for resizes, which are handled as usual and redraw the dialog too).
+    fn handle_dialog_event(&mut self, event: &Event) -> IOResult<bool> {
+        let Some(state) = &mut self.dialog else {
+            return Ok(false);
+        };
+
+        let answer = match event {
+            Event::Resize(..) => return Ok(false),
+            Event::Key(key) if key.kind != KeyEventKind::Release => state.handle_key(*key),
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(event::MouseButton::Left) => {
+                dialog::Dialog::handle_click(
+                    (mouse.column, mouse.row),
+                    self.buffer.size,
+                    state,
+                    &*self.state.locale,
+                )
+            }
+            _ => None,
+        };
+
+        if answer.is_some() {
+            self.dialog_answer = answer;
+        }
+
+        self.step()?;
+        Ok(true)
+    }
+
+    /// Add hooks around events and steps (run in the order they were added)
+    pub fn add_middleware(&mut self, middleware: impl middleware::Middleware + 'static) {
+        self.middleware.push(Box::new(middleware));
+    }
+
+    /// Draw a frame with `event` in [`State::user_events`] (along with any received
+    /// through [`Frame::event_sender`]). For backends which don't poll, like the web
+    /// shim (see [`web::preedit_event`]).
+    pub fn handle_user_event(&mut self, event: event::UserEvent) -> IOResult<buffer::BufState> {
+        self.take_user_events();
+        self.state.user_events.push(event);
+        self.step()?;
+
+        Ok(buffer::BufState::Ok)
+    }
+
+    /// Handle an event (after running it through the middleware, which can consume or
+    /// replace it). [`Frame::poll_events`] reads them from the terminal, other backends
+    /// pass them in here.
+    pub fn handle_event(&mut self, event: Event) -> IOResult<buffer::BufState> {
+        let mut events = vec![event];
+
+        for middleware in &mut self.middleware {
+            let mut kept = Vec::with_capacity(events.len());
+
+            for event in events {
+                match middleware.before_event(&event, &mut self.state) {
+                    middleware::FilterAction::Keep => kept.push(event),
+                    middleware::FilterAction::Drop => (),
+                    middleware::FilterAction::Replace(replaced) => kept.extend(replaced),
                 }
-                // handle keyboard events
-                Event::Key(event) => {
-                    match event.code {
-                        KeyCode::Char(c) => {
-                            if event.modifiers.contains(KeyModifiers::CONTROL) {
-                                match c {
-                                    'c' => {
-                                        // Ctrl+C
-                                        // handle smooth exit
-                                        self.exit();
-                                    }
-                                    _ => {}
-                                }
-                            } else {
-                                if self.state.keyboard_input_mode == false {
-                                    return Ok(buffer::BufState::Ok);
-                                }
-
-                                // add to prompt
-                                let write_at = self.state.clicked.0;
-                                let real_pos = self.state.cursor_pos.0 - write_at; // where we are in the prompt
-
-                                if real_pos > self.state.input.len() as u16 {
-                                    return Ok(buffer::BufState::Ok);
-                                }
-
-                                // write char to input
-                                self.state.input.insert(real_pos as usize, c);
-
-                                // update screen
-                                let old_loc = self.state.cursor_pos.0;
-
-                                self.state.cursor_pos = (write_at, self.state.cursor_pos.1); // move to line start
-                                self.move_cursor(self.state.cursor_pos)?;
-
-                                // actual write
-                                self.buffer.write_str(
-                                    (write_at, self.state.cursor_pos.1),
-                                    &self.state.input,
-                                )?;
-
-                                // move cursor back
-                                self.state.cursor_pos = (old_loc, self.state.cursor_pos.1); // restore position
-                                self.move_cursor(self.state.cursor_pos)?;
-
-                                // move cursor
-                                self.state.cursor_pos.0 += 1;
-
-                                // redraw
-                                self.step()?;
-
-                                // ...
-                                return Ok(buffer::BufState::Ok);
-                            }
-                        }
-                        // Toggle Mouse Mode
-                        KeyCode::Esc => {
-                            self.state.keyboard_input_mode = !self.state.keyboard_input_mode;
-
-                            if self.state.keyboard_input_mode == true {
-                                // we use the x of clicked to tell where we're typing,
-                                // setting this to the current cursor position will make
-                                // us type in the correct location
-                                self.state.clicked.0 = self.state.cursor_pos.0;
-                            } else {
-                                // TODO: do something to expose the input
-                                self.state.input = String::new(); // clear input
-                            }
-                        }
-                        // Submit
-                        KeyCode::Enter => {
-                            // let res = inter_stdin(prompt.clone(), global_state);
-                            // global_state = res.0; // update global state
-
-                            // map_result(&res.1);
-
-                            // clear prompt
-                            self.state.input = String::new();
-
-                            // if we're at the end of the frame, clear
-                            if (self.state.cursor_pos.1 + 1) == window_size.1 {
-                                // TODO: clear buffer here
-                                self.stdout
-                                    .queue(terminal::Clear(terminal::ClearType::All))
-                                    .unwrap();
-
-                                self.state.cursor_pos = (0, 0);
-                                self.move_cursor(self.state.cursor_pos)?;
-                            } else {
-                                // line down from clicked.1 at clicked.0 (write_at)
-                                self.state.clicked.1 += 1;
-                                self.state.cursor_pos = self.state.clicked.clone();
-                            }
+            }
 
-                            // redraw
-                            self.step()?;
-                        }
-                        // Move Left
-                        KeyCode::Left => {
-                            if self.state.cursor_pos.0 == self.state.min_x {
-                                // cannot go through prompt
-                                return Ok(buffer::BufState::Ok);
-                            }
+            events = kept;
+        }
 
-                            self.state.cursor_pos.0 -= 1;
-                        }
-                        // Move Right
-                        KeyCode::Right => {
-                            if self.state.cursor_pos.0 == (window_size.0 - 51) {
-                                // cannot go through side windows (50 cells wide)
-                                return Ok(buffer::BufState::Ok);
-                            }
+        let mut res = buffer::BufState::Ok;
 
-                            self.state.cursor_pos.0 += 1;
-                        }
-                        // Backspace
-                        KeyCode::Backspace => {
-                            if self.state.cursor_pos.0 == self.state.min_x {
-                                // cannot go through prompt
-                                return Ok(buffer::BufState::Ok);
-                            }
+        for event in events {
+            res = self.dispatch_event(event.clone())?;
 
-                            // make sure we are within the prompt
-                            let write_at = self.state.clicked.0;
-                            let real_pos = self.state.cursor_pos.0 - write_at; // where we are in the prompt
+            for middleware in &mut self.middleware {
+                middleware.after_event(&event, &mut self.state);
+            }
+        }
 
-                            if (real_pos > self.state.input.len() as u16) | (real_pos == 0) {
-                                return Ok(buffer::BufState::Ok);
-                            }
+        Ok(res)
+    }
 
-                            self.state.input.remove((real_pos - 1) as usize); // remove character
+    /// Handle an event
+    fn dispatch_event(&mut self, event: Event) -> IOResult<buffer::BufState> {
+        let window_size = self.buffer.size;
 
-                            // move cursor back
-                            self.state.cursor_pos.0 -= 1;
+        // anything can change after input, redraw everything
+        self.dirty.clear();
 
-                            // update screen
-                            let old_loc = self.state.cursor_pos.0.clone();
+        if self.handle_dialog_event(&event)? {
+            return Ok(buffer::BufState::Ok);
+        }
 
-                            // write the whole input + a space so the character gets erased
-                            self.buffer.fill_range(
-                                write_at,
-                                (self.state.input.len() + 1) as u16,
-                                self.state.cursor_pos.1,
-                                buffer::BufCell::EMPTY,
-                            )?;
+        match event {
+            // handle window resize
+            Event::Resize(width, height) => {
+                let (width, height) = compat::resize_size((width, height));
 
-                            self.buffer.write_str(
-                                (write_at, self.state.cursor_pos.1),
-                                &" ".repeat(self.state.input.len() + 1),
-                            )?;
+                // Windows sends a few of these for one resize
+                if (width, height) == window_size {
+                    return Ok(buffer::BufState::Ok);
+                }
 
-                            self.buffer.write_str(
-                                (write_at, self.state.cursor_pos.1),
-                                &self.state.input,
-                            )?;
+                // sync buffer and window
+                let loss = self.buffer.resize((width, height))?;
+                self.state.resize_loss = Some(loss).filter(|l| !l.is_empty());
 
-                            // ...
-                            self.state.cursor_pos = (old_loc, self.state.cursor_pos.1); // restore position
-                            self.move_cursor(self.state.cursor_pos)?;
+                if let Some(thread) = &mut self.render_thread {
+                    thread.send(render::RenderCommand::Resize((width, height)))?;
+                }
 
-                            // redraw
-                            self.step()?;
-                        }
-                        // ...
-                        _ => {}
-                    }
+                self.state.window_size = (width, height);
+
+                // redraw (the buffer was cleared, what it kept is drawn again)
+                // we're not drawing every frame, instead we only draw when needed
+                self.step()?;
+            }
+            // handle keyboard events
+            Event::Key(event) => {
+                self.state.keys_down.record(&event);
+
+                // releases only change the held keys
+                if event.kind == KeyEventKind::Release {
+                    self.step()?;
+                    return Ok(buffer::BufState::Ok);
+                }
+
+                // built-in screenshot key
+                if self.screenshot_key.is_some_and(|k| k.matches(&event)) {
+                    let time = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs();
+
+                    self.screenshot(format!("screenshot-{time}.txt"))?;
+                    return Ok(buffer::BufState::Ok);
                 }
-                // handle mouse events
-                Event::Mouse(event) => {
-                    if self.state.keyboard_input_mode == true {
+
+                // expose key to the draw function
+                self.state.key = Some(event);
+
+                // the focused component gets the key first. Enter and Space click it in
+                // the same draw (so it's activated once, by the key or by the click)
+                if self.state.focus.focused.is_some() {
+                    self.state.key_handled = false;
+                    self.state.click = self.activation_click(event);
+                    let activated = self.state.click.is_some();
+                    self.step()?;
+
+                    if self.state.key_handled || activated {
                         return Ok(buffer::BufState::Ok);
                     }
+                }
+
+                if self.handle_virtual_cursor_key(event)? || self.handle_focus_key(event)? {
+                    return Ok(buffer::BufState::Ok);
+                }
 
+                match event.code {
+                    // Ctrl+C
+                    KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                        // handle smooth exit
+                        self.exit();
+                    }
+                    // Toggle Mouse Mode
+                    KeyCode::Esc => {
+                        self.state.keyboard_input_mode = !self.state.keyboard_input_mode;
+
+                        if self.state.keyboard_input_mode {
+                            // we use the x of clicked to tell where we're typing,
+                            // setting this to the current cursor position will make
+                            // us type in the correct location
+                            self.state.clicked.0 = self.state.cursor_pos.0;
+                        }
+                    }
+                    // Move Left
+                    KeyCode::Left if !self.state.keyboard_input_mode => {
+                        if self.state.cursor_pos.0 == self.state.min_x {
+                            // cannot go through prompt
+                            return Ok(buffer::BufState::Ok);
+                        }
+
+                        self.state.cursor_pos.0 -= 1;
+                    }
+                    // Move Right
+                    KeyCode::Right if !self.state.keyboard_input_mode => {
+                        if self.state.cursor_pos.0 == (window_size.0 - 51) {
+                            // cannot go through side windows (50 cells wide)
+                            return Ok(buffer::BufState::Ok);
+                        }
+
+                        self.state.cursor_pos.0 += 1;
+                    }
+                    // typing goes to the draw function (see `repl::Repl`)
+                    _ if self.state.keyboard_input_mode => {
+                        self.step()?;
+                    }
                     // ...
-                    if event.kind == MouseEventKind::Up(crossterm::event::MouseButton::Left) {
+                    _ => {}
+                }
+            }
+            // handle mouse events
+            Event::Mouse(event) => {
+                if self.state.keyboard_input_mode {
+                    return Ok(buffer::BufState::Ok);
+                }
+
+                // ...
+                let pos = (event.column, event.row);
+
+                match event.kind {
+                    MouseEventKind::Down(event::MouseButton::Left) => {
+                        self.state.mouse_down = Some(pos);
+                    }
+                    MouseEventKind::Drag(event::MouseButton::Left) => {
+                        self.state.cursor_pos = pos;
+
+                        if let Some(drag) = &mut self.state.drag {
+                            drag.pos = pos;
+                        }
+
+                        // redraw (so components can start a drag and the ghost moves)
+                        self.step()?;
+                    }
+                    MouseEventKind::Up(event::MouseButton::Left) if self.state.drag.is_some() => {
+                        // drop instead of clicking
+                        let drag = self.state.drag.take().unwrap();
+                        self.state.mouse_down = None;
+                        self.state.drop = Some(event::DropEvent {
+                            payload: drag.payload,
+                            origin: drag.origin,
+                            pos,
+                        });
+
+                        // redraw
+                        self.step()?;
+                    }
+                    MouseEventKind::Up(button) => {
+                        if button == event::MouseButton::Left {
+                            self.state.mouse_down = None;
+                        }
+
                         // handle click
-                        self.state.clicked = (event.column, event.row);
+                        if button == event::MouseButton::Left {
+                            self.state.clicked = pos;
+                        }
+
+                        // count clicks in a row on the same cell
+                        let count = match &self.last_click {
+                            Some((time, last))
+                                if (last.pos == pos)
+                                    && (last.button == button)
+                                    && (time.elapsed() < event::MULTI_CLICK_TIME) =>
+                            {
+                                last.count.saturating_add(1)
+                            }
+                            _ => 1,
+                        };
+
+                        let click = event::ClickEvent {
+                            pos,
+                            local: pos,
+                            button,
+                            count,
+                            keyboard: false,
+                            modifiers: event.modifiers,
+                        };
+                        self.last_click = Some((Instant::now(), click.clone()));
+                        self.state.click = Some(click);
 
                         // redraw
-                        self.stdout.queue(cursor::SavePosition).unwrap();
+                        self.save_cursor()?;
                         self.step()?;
-                        self.stdout.queue(cursor::RestorePosition).unwrap();
-                    } else if event.kind == MouseEventKind::Moved {
+
+                        // unless a component placed it somewhere
+                        if self.widget_cursor.is_none() {
+                            self.restore_cursor()?;
+                        }
+                    }
+                    MouseEventKind::ScrollUp
+                    | MouseEventKind::ScrollDown
+                    | MouseEventKind::ScrollLeft
+                    | MouseEventKind::ScrollRight => {
+                        let direction = match event.kind {
+                            MouseEventKind::ScrollUp => event::ScrollDirection::Up,
+                            MouseEventKind::ScrollDown => event::ScrollDirection::Down,
+                            MouseEventKind::ScrollLeft => event::ScrollDirection::Left,
+                            _ => event::ScrollDirection::Right,
+                        };
+
+                        self.state.scroll = Some(event::ScrollEvent {
+                            pos,
+                            local: pos,
+                            direction,
+                        });
+                        self.step()?;
+                    }
+                    MouseEventKind::Moved => {
                         // move cursor to position (like a cursor)
-                        self.state.cursor_pos = (event.column, event.row);
-                        self.move_cursor(self.state.cursor_pos)?;
+                        self.state.cursor_pos = pos;
+                        self.hardware_cursor = None;
+
+                        // start waiting for the tooltip again
+                        self.hover = Some(Instant::now());
+                        self.rested = false;
+
+                        // a component placed the cursor, leave it there
+                        if self.widget_cursor.is_none() {
+                            self.move_cursor(self.state.cursor_pos)?;
+                        }
+
+                        // redraw so the software cursor follows the mouse (and tooltips hide)
+                        if self.state.software_cursor || self.tooltip_shown {
+                            self.step()?;
+                        }
                     }
+                    _ => {}
                 }
-                // drop everything else
-                _ => (),
-            };
-        }
+            }
+            // terminal focus
+            Event::FocusGained => {
+                self.state.terminal_focused = true;
+                self.state.terminal_focus = Some(event::TerminalFocusEvent::Gained);
+
+                // something might have drawn over us while we were away
+                self.force_redraw()?;
+            }
+            Event::FocusLost => {
+                // the keys might be released somewhere else
+                self.state.keys_down.clear();
+                self.state.terminal_focused = false;
+                self.state.terminal_focus = Some(event::TerminalFocusEvent::Lost);
+                self.step()?;
+            }
+            // drop everything else
+            _ => (),
+        };
 
         Ok(buffer::BufState::Ok)
     }
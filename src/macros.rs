@@ -0,0 +1,129 @@
+//! Declarative layouts
+//!
+//! With the `hamui-macros` feature, [`crate::ui!`] builds simple screens out of the
+//! built-in components without computing every rect by hand:
+//!
+//! ```ignore
+//! ui!(buffer, RectBoundary::window(state.window_size), column {
+//!     box("Files") {
+//!         row { text("a.txt"), button("Open") }
+//!     },
+//!     text("2 files"),
+//! })?;
+//! ```
+//!
+//! Nodes:
+//! * `box { .. }`, `box(title) { .. }` - [`crate::drawing::QuickBox`] with its children stacked inside
+//! * `row { .. }` - children side by side in columns of equal width
+//! * `column { .. }` - children on top of each other in rows of equal height
+//! * `text(leaf)` - [`crate::drawing::Text`] cut down to the width of its rect
+//! * `button(leaf)` - [`crate::drawing::Text::render_button`]
+//! * `component(expr)` - any [`crate::drawing::Component`], rendered with its rect
+//!   (create it with its own `PseudoBuffer::new`, its cells are added to the buffer)
+//!
+//! `leaf` is anything which turns into a [`crate::drawing::TextLeaf`] (like `&str`).
+//!
+//! [`Component`] can be derived for structs whose fields are components, laid out with
+//! `#[row]` / `#[column]` on the struct and `#[fixed(n)]` / `#[skip]` on the fields.
+
+/// Draw a tree of components into a [`crate::buffer::PseudoBuffer`] (see [`crate::macros`]).
+/// Evaluates to a `std::io::Result<()>`.
+///
+/// ## Arguments:
+/// * `buffer` - name of the [`crate::buffer::PseudoBuffer`] to draw into
+/// * `rect` - [`crate::drawing::RectBoundary`] of the root node
+/// * the root node
+#[macro_export]
+macro_rules! ui {
+    // entry
+    ($buffer:ident, $rect:expr, $($node:tt)+) => {
+        (|| -> ::std::io::Result<()> {
+            let rect: $crate::drawing::RectBoundary = $rect;
+            $crate::ui!(@node $buffer, rect, $($node)+);
+            Ok(())
+        })()
+    };
+
+    // containers
+    (@node $buffer:ident, $rect:ident, box { $($children:tt)* }) => {
+        $crate::ui!(@box $buffer, $rect, None::<&str>, $($children)*);
+    };
+    (@node $buffer:ident, $rect:ident, box ($title:expr) { $($children:tt)* }) => {
+        $crate::ui!(@box $buffer, $rect, Some($title), $($children)*);
+    };
+    (@node $buffer:ident, $rect:ident, row { $($children:tt)* }) => {
+        $crate::ui!(@split $buffer, $rect.columns, $($children)*);
+    };
+    (@node $buffer:ident, $rect:ident, column { $($children:tt)* }) => {
+        $crate::ui!(@split $buffer, $rect.rows, $($children)*);
+    };
+
+    // leaves
+    (@node $buffer:ident, $rect:ident, text ($leaf:expr)) => {
+        $buffer.set_changes(
+            <$crate::drawing::Text as $crate::drawing::Creatable>::new($buffer.clone())
+                .render_truncated(
+                    ::std::convert::Into::into($leaf),
+                    $rect.pos,
+                    $rect.size.0,
+                    $crate::text::TruncateAt::End,
+                )?
+                .1,
+        );
+    };
+    (@node $buffer:ident, $rect:ident, button ($leaf:expr)) => {
+        $buffer.set_changes(
+            <$crate::drawing::Text as $crate::drawing::Creatable>::new($buffer.clone())
+                .render_button(::std::convert::Into::into($leaf), $rect.pos)?
+                .1,
+        );
+    };
+    (@node $buffer:ident, $rect:ident, component ($component:expr)) => {
+        let (_, changes) = $crate::drawing::Component::render(
+            &mut $component,
+            $buffer.window_size,
+            $rect.clone(),
+        )?;
+        $buffer.set_changes([$buffer.get_changes(), changes].concat());
+    };
+
+    // a box with its children in a column inside of the borders
+    (@box $buffer:ident, $rect:ident, $title:expr, $($children:tt)*) => {
+        let mut quick_box =
+            <$crate::drawing::QuickBox as $crate::drawing::Creatable>::new($buffer.clone());
+        quick_box.title = $title.map(|t| t.to_string());
+
+        // the bottom border is drawn at `pos.1 + size.1`, keep it inside of the rect
+        let outer = $crate::drawing::RectBoundary::new(
+            $rect.pos,
+            ($rect.size.0, $rect.size.1.saturating_sub(1)),
+        );
+        let (outer, changes) =
+            $crate::drawing::Component::render(&mut quick_box, $buffer.window_size, outer)?;
+        $buffer.set_changes(changes);
+
+        let inside = $crate::drawing::RectBoundary::new(
+            (outer.pos.0 + 1, outer.pos.1 + 1),
+            (outer.size.0.saturating_sub(2), outer.size.1.saturating_sub(1)),
+        );
+        $crate::ui!(@split $buffer, inside.rows, $($children)*);
+    };
+
+    // lay children out in the rects of `$split(count)`
+    (@split $buffer:ident, $rect:ident . $split:ident, $($kind:ident $(($($arg:tt)*))? $({ $($body:tt)* })?),* $(,)?) => {
+        #[allow(unused_mut, unused_variables)]
+        let mut rects = $rect.$split($crate::ui!(@count $($kind)*)).into_iter();
+        $(
+            if let Some(rect) = rects.next() {
+                $crate::ui!(@node $buffer, rect, $kind $(($($arg)*))? $({ $($body)* })?);
+            }
+        )*
+    };
+
+    // number of children
+    (@count) => { 0u16 };
+    (@count $head:ident $($tail:ident)*) => { 1u16 + $crate::ui!(@count $($tail)*) };
+}
+
+/// Implement [`crate::drawing::Component`] for a struct of components (see [`hamui_macros::Component`])
+pub use hamui_macros::Component;
@@ -0,0 +1,78 @@
+//! Middleware
+//!
+//! Hooks around the event and render pipeline of a [`crate::Frame`], for things
+//! which touch every frame (metrics, recording, input remapping) without
+//! changing the draw function. Register them with [`crate::Frame::add_middleware`].
+//! Before an event is handled, middleware can consume it or turn it into other events
+//! (see [`FilterAction`]).
+use std::collections::HashMap;
+
+use crossterm::event::{Event, KeyCode, KeyEvent};
+
+use crate::buffer::RenderStats;
+use crate::State;
+
+/// Hooks called by the frame (every one does nothing by default).
+/// Middleware runs in the order it was added.
+pub trait Middleware {
+    /// Called before a terminal event is handled, to keep, consume or replace it.
+    /// Events a middleware returns only go through the middleware after it.
+    fn before_event(&mut self, _event: &Event, _state: &mut State) -> FilterAction {
+        FilterAction::Keep
+    }
+
+    /// Called after a terminal event was handled (including the redraw it caused)
+    fn after_event(&mut self, _event: &Event, _state: &mut State) {}
+
+    /// Called right before the draw function
+    fn before_draw(&mut self, _state: &mut State) {}
+
+    /// Called after a frame was committed to the terminal
+    /// (with a render thread, the stats are empty)
+    fn after_commit(&mut self, _stats: &RenderStats, _state: &mut State) {}
+}
+
+/// What a [`Middleware`] does with an event (see [`Middleware::before_event`])
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterAction {
+    /// Pass the event on unchanged
+    Keep,
+    /// Consume the event
+    Drop,
+    /// Handle these events instead (none, one to transform it, or more to synthesize events)
+    Replace(Vec<Event>),
+}
+
+/// Middleware which turns keys into other keys (like WASD into the arrow keys)
+#[derive(Clone, Debug, Default)]
+pub struct KeyRemap {
+    /// Keys and what they turn into
+    pub keys: HashMap<KeyCode, KeyCode>,
+}
+
+impl KeyRemap {
+    pub fn new() -> KeyRemap {
+        KeyRemap::default()
+    }
+
+    /// Turn `from` into `to` (the modifiers are kept)
+    pub fn map(mut self, from: KeyCode, to: KeyCode) -> Self {
+        self.keys.insert(from, to);
+        self
+    }
+}
+
+impl Middleware for KeyRemap {
+    fn before_event(&mut self, event: &Event, _state: &mut State) -> FilterAction {
+        match event {
+            Event::Key(key) => match self.keys.get(&key.code) {
+                Some(code) => FilterAction::Replace(vec![Event::Key(KeyEvent {
+                    code: *code,
+                    ..*key
+                })]),
+                None => FilterAction::Keep,
+            },
+            _ => FilterAction::Keep,
+        }
+    }
+}
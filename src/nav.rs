@@ -0,0 +1,246 @@
+//! Navigation presets
+//!
+//! List-like widgets (tables, grids, charts, paginators) are driven with the arrow keys,
+//! Home/End and Page Up/Page Down. A [`Navigation`] translates the keys of a convention
+//! (vi, emacs) into those, so switching every widget of the application is a single
+//! [`crate::Frame::set_navigation`]. Widgets consult it through [`crate::State::nav_key`]:
+//! their `handle_nav_key` (like [`crate::table::TableState::handle_nav_key`] or
+//! [`crate::pagination::Paginator::handle_nav_key`]) takes the translated key, while
+//! `handle_key` takes the standard keys as they are.
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::keymap::KeyBinding;
+
+/// Movement a navigation key stands for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NavAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    /// Start of the line (Home)
+    LineStart,
+    /// End of the line (End)
+    LineEnd,
+    /// First item (Ctrl+Home)
+    First,
+    /// Last item (Ctrl+End)
+    Last,
+}
+
+impl NavAction {
+    /// Get the standard key of the action (what widgets handle)
+    pub fn key_event(&self) -> KeyEvent {
+        let (code, modifiers) = match self {
+            NavAction::Up => (KeyCode::Up, KeyModifiers::NONE),
+            NavAction::Down => (KeyCode::Down, KeyModifiers::NONE),
+            NavAction::Left => (KeyCode::Left, KeyModifiers::NONE),
+            NavAction::Right => (KeyCode::Right, KeyModifiers::NONE),
+            NavAction::PageUp => (KeyCode::PageUp, KeyModifiers::NONE),
+            NavAction::PageDown => (KeyCode::PageDown, KeyModifiers::NONE),
+            NavAction::LineStart => (KeyCode::Home, KeyModifiers::NONE),
+            NavAction::LineEnd => (KeyCode::End, KeyModifiers::NONE),
+            NavAction::First => (KeyCode::Home, KeyModifiers::CONTROL),
+            NavAction::Last => (KeyCode::End, KeyModifiers::CONTROL),
+        };
+
+        KeyEvent::new(code, modifiers)
+    }
+}
+
+/// Built-in conventions of a [`Navigation`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum NavPreset {
+    /// Arrows, Home/End, Page Up/Page Down
+    #[default]
+    Standard,
+    /// `hjkl`, `0`/`$`, `gg`/`G`, `ctrl+f`/`ctrl+b`, `ctrl+d`/`ctrl+u` (and the standard keys)
+    Vi,
+    /// `ctrl+n`/`ctrl+p`/`ctrl+f`/`ctrl+b`, `ctrl+a`/`ctrl+e`, `alt+<`/`alt+>`,
+    /// `ctrl+v`/`alt+v` (and the standard keys)
+    Emacs,
+}
+
+/// Key sequences mapped to [`NavAction`]s
+#[derive(Clone, Debug, PartialEq)]
+pub struct Navigation {
+    pub preset: NavPreset,
+    bindings: Vec<(Vec<KeyBinding>, NavAction)>,
+    /// Keys of a sequence typed so far (like the first `g` of `gg`)
+    pending: Vec<KeyBinding>,
+}
+
+impl Default for Navigation {
+    fn default() -> Self {
+        Navigation::new(NavPreset::Standard)
+    }
+}
+
+impl Navigation {
+    /// Create a new [`Navigation`] with the bindings of a preset
+    pub fn new(preset: NavPreset) -> Self {
+        let mut nav = Navigation {
+            preset,
+            bindings: Vec::new(),
+            pending: Vec::new(),
+        };
+
+        let standard = [
+            ("up", NavAction::Up),
+            ("down", NavAction::Down),
+            ("left", NavAction::Left),
+            ("right", NavAction::Right),
+            ("pageup", NavAction::PageUp),
+            ("pagedown", NavAction::PageDown),
+            ("home", NavAction::LineStart),
+            ("end", NavAction::LineEnd),
+            ("ctrl+home", NavAction::First),
+            ("ctrl+end", NavAction::Last),
+        ];
+
+        let extra: &[(&str, NavAction)] = match preset {
+            NavPreset::Standard => &[],
+            NavPreset::Vi => &[
+                ("k", NavAction::Up),
+                ("j", NavAction::Down),
+                ("h", NavAction::Left),
+                ("l", NavAction::Right),
+                ("ctrl+b", NavAction::PageUp),
+                ("ctrl+f", NavAction::PageDown),
+                ("ctrl+u", NavAction::PageUp),
+                ("ctrl+d", NavAction::PageDown),
+                ("0", NavAction::LineStart),
+                ("$", NavAction::LineEnd),
+                ("g g", NavAction::First),
+                ("G", NavAction::Last),
+            ],
+            NavPreset::Emacs => &[
+                ("ctrl+p", NavAction::Up),
+                ("ctrl+n", NavAction::Down),
+                ("ctrl+b", NavAction::Left),
+                ("ctrl+f", NavAction::Right),
+                ("alt+v", NavAction::PageUp),
+                ("ctrl+v", NavAction::PageDown),
+                ("ctrl+a", NavAction::LineStart),
+                ("ctrl+e", NavAction::LineEnd),
+                ("alt+<", NavAction::First),
+                ("alt+>", NavAction::Last),
+            ],
+        };
+
+        for (keys, action) in standard.iter().chain(extra) {
+            nav.bind(keys, *action).expect("preset key binding");
+        }
+
+        nav
+    }
+
+    /// Add a binding (on top of the preset)
+    ///
+    /// ## Arguments:
+    /// * `keys` - key bindings separated by spaces (like `ctrl+n` or `g g`)
+    /// * `action`
+    pub fn bind(&mut self, keys: &str, action: NavAction) -> Result<(), String> {
+        let sequence = keys
+            .split_whitespace()
+            .map(|k| k.parse())
+            .collect::<Result<Vec<KeyBinding>, String>>()?;
+
+        if sequence.is_empty() {
+            return Err(format!("empty key sequence `{keys}`"));
+        }
+
+        self.bindings.push((sequence, action));
+        Ok(())
+    }
+
+    /// Remove every binding of an action (to rebind it)
+    pub fn unbind(&mut self, action: NavAction) {
+        self.bindings.retain(|(_, a)| *a != action);
+    }
+
+    /// Get the key sequences bound to an action
+    pub fn keys(&self, action: NavAction) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(keys, _)| {
+                keys.iter()
+                    .map(|k| k.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    /// Drop the keys of an unfinished sequence
+    pub fn reset(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Get the action of a key event. Keys which start a longer sequence
+    /// are remembered (see [`Navigation::translate`]).
+    pub fn action(&mut self, event: &KeyEvent) -> Option<NavAction> {
+        self.step(event).flatten()
+    }
+
+    /// Translate a key event into the standard key of its action (other keys
+    /// are returned as they are). Returns `None` for a key which starts a sequence
+    /// (like the first `g` of `gg`), widgets should ignore it.
+    pub fn translate(&mut self, event: KeyEvent) -> Option<KeyEvent> {
+        match self.step(&event) {
+            None => None,
+            Some(Some(action)) => Some(action.key_event()),
+            Some(None) => Some(event),
+        }
+    }
+
+    /// Add `event` to the pending keys. `None` if a sequence continues,
+    /// `Some(None)` if the key isn't bound.
+    fn step(&mut self, event: &KeyEvent) -> Option<Option<NavAction>> {
+        for pending in [true, false] {
+            if !pending {
+                // the key doesn't continue the sequence, try it on its own
+                if self.pending.is_empty() {
+                    break;
+                }
+
+                self.pending.clear();
+            }
+
+            let n = self.pending.len();
+            let mut continues = false;
+
+            for (keys, action) in &self.bindings {
+                if (keys.len() <= n)
+                    || !keys.iter().zip(&self.pending).all(|(a, b)| a == b)
+                    || !keys[n].matches(event)
+                {
+                    continue;
+                }
+
+                if keys.len() == n + 1 {
+                    self.pending.clear();
+                    return Some(Some(*action));
+                }
+
+                continues = true;
+            }
+
+            if continues {
+                let binding = self
+                    .bindings
+                    .iter()
+                    .find_map(|(keys, _)| keys.get(n).filter(|k| k.matches(event)).copied());
+
+                self.pending.extend(binding);
+                return None;
+            }
+        }
+
+        self.pending.clear();
+        Some(None)
+    }
+}
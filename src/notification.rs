@@ -0,0 +1,71 @@
+//! Desktop notifications
+//!
+//! Terminals can show a desktop notification when they get an escape sequence, so tools
+//! can tell the user a long job finished while they're in another window (see
+//! [`crate::Frame::notify`]). There are two common sequences, and no way to ask the
+//! terminal which one it understands, so [`NotifyProtocol::detect`] goes by the
+//! environment. Terminals which understand neither get nothing.
+
+/// Escape sequence for desktop notifications
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotifyProtocol {
+    /// `OSC 777 ; notify ; title ; body` (foot, WezTerm, Ghostty, rxvt-unicode, some VTE builds)
+    Osc777,
+    /// `OSC 9 ; message` (iTerm2, kitty, Ghostty), without a separate title
+    Osc9,
+}
+
+impl NotifyProtocol {
+    /// Guess the protocol the terminal understands (`None` if it (probably) doesn't
+    /// show notifications, or `HAMUI_NO_NOTIFY` is set)
+    pub fn detect() -> Option<NotifyProtocol> {
+        if std::env::var_os("HAMUI_NO_NOTIFY").is_some() {
+            return None;
+        }
+
+        let term = std::env::var("TERM").unwrap_or_default().to_lowercase();
+        let term_program = std::env::var("TERM_PROGRAM")
+            .unwrap_or_default()
+            .to_lowercase();
+
+        if ["wezterm", "ghostty"].contains(&term_program.as_str())
+            || term.starts_with("foot")
+            || term.starts_with("rxvt-unicode")
+            || std::env::var_os("VTE_VERSION").is_some()
+        {
+            Some(NotifyProtocol::Osc777)
+        } else if (term_program == "iterm.app") || term.contains("kitty") {
+            Some(NotifyProtocol::Osc9)
+        } else {
+            None
+        }
+    }
+
+    /// Get the escape sequence showing a notification
+    pub fn sequence(&self, title: &str, body: &str) -> String {
+        let (title, body) = (clean(title), clean(body));
+
+        let sequence = match self {
+            // (the title can't contain the separator)
+            NotifyProtocol::Osc777 => {
+                format!("\x1b]777;notify;{};{body}\x07", title.replace(';', ","))
+            }
+            NotifyProtocol::Osc9 if title.is_empty() => format!("\x1b]9;{body}\x07"),
+            NotifyProtocol::Osc9 => format!("\x1b]9;{title}: {body}\x07"),
+        };
+
+        // tmux only passes sequences it doesn't know through when they're wrapped
+        if std::env::var_os("TMUX").is_some() {
+            format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+        } else {
+            sequence
+        }
+    }
+}
+
+/// Remove control characters (which would end the sequence early)
+fn clean(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
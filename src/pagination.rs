@@ -0,0 +1,195 @@
+//! Pagination widget
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{Creatable, DrawingResult, RectBoundary, TextAttribute};
+use crate::style::Style;
+use crate::State;
+
+/// Item of a [`Paginator`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PageItem {
+    /// `«`
+    Previous,
+    /// Page number (0 based, drawn 1 based)
+    Page(usize),
+    /// `…` (skipped pages)
+    Gap,
+    /// `»`
+    Next,
+}
+
+impl PageItem {
+    /// Get the text of the item
+    pub fn text(&self, current: usize) -> String {
+        match self {
+            PageItem::Previous => "«".to_string(),
+            PageItem::Page(page) if *page == current => format!("[{}]", page + 1),
+            PageItem::Page(page) => (page + 1).to_string(),
+            PageItem::Gap => "…".to_string(),
+            PageItem::Next => "»".to_string(),
+        }
+    }
+}
+
+/// Page navigation: `« 1 2 [3] 4 … 20 »`
+pub struct Paginator {
+    pub buffer: PseudoBuffer,
+    pub style: Style,
+    /// Style of the current page
+    pub current_style: Style,
+    /// Number of pages shown on each side of the current page
+    pub siblings: usize,
+}
+
+impl Creatable for Paginator {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Paginator {
+            buffer,
+            style: Style::DEFAULT,
+            current_style: Style::DEFAULT.attr(TextAttribute::Bold),
+            siblings: 1,
+        }
+    }
+}
+
+impl Paginator {
+    /// Get the items to draw. The first page, the last page, and the pages
+    /// around the current page are always shown; the rest collapse into gaps.
+    ///
+    /// ## Arguments:
+    /// * `page` - current page (0 based)
+    /// * `pages` - number of pages
+    pub fn items(&self, page: usize, pages: usize) -> Vec<PageItem> {
+        let mut items = vec![PageItem::Previous];
+        let mut last_shown: Option<usize> = None;
+
+        for p in 0..pages {
+            let near = (p + self.siblings >= page) && (p <= page + self.siblings);
+
+            if (p != 0) && (p != pages - 1) && !near {
+                continue;
+            }
+
+            match last_shown {
+                // a gap of one page just shows the page
+                Some(last) if p == last + 2 => items.push(PageItem::Page(last + 1)),
+                Some(last) if p > last + 2 => items.push(PageItem::Gap),
+                _ => (),
+            }
+
+            items.push(PageItem::Page(p));
+            last_shown = Some(p);
+        }
+
+        items.push(PageItem::Next);
+        items
+    }
+
+    /// Get every item with its rect (items are separated by one cell)
+    pub fn item_rects(
+        &self,
+        rect: &RectBoundary,
+        page: usize,
+        pages: usize,
+    ) -> Vec<(PageItem, RectBoundary)> {
+        let mut x = rect.pos.0;
+        let mut rects = Vec::new();
+
+        for item in self.items(page, pages) {
+            let width = item.text(page).chars().count() as u16;
+            rects.push((item, RectBoundary::new((x, rect.pos.1), (width, 1))));
+            x = x.saturating_add(width + 1);
+        }
+
+        rects
+    }
+
+    /// Get the page an item leads to (`None` if it leads nowhere)
+    fn target(item: PageItem, page: usize, pages: usize) -> Option<usize> {
+        let target = match item {
+            PageItem::Previous => page.checked_sub(1)?,
+            PageItem::Page(p) => p,
+            PageItem::Gap => return None,
+            PageItem::Next => page + 1,
+        };
+
+        if (target < pages) && (target != page) {
+            Some(target)
+        } else {
+            None
+        }
+    }
+
+    /// Handle a click on the paginator. Returns the selected page if it changed.
+    pub fn handle_click(
+        &self,
+        state: &State,
+        rect: &RectBoundary,
+        page: usize,
+        pages: usize,
+    ) -> Option<usize> {
+        let click = state.click.as_ref()?;
+
+        let (item, _) = self
+            .item_rects(rect, page, pages)
+            .into_iter()
+            .find(|(_, r)| r.contains(click.pos))?;
+
+        Paginator::target(item, page, pages)
+    }
+
+    /// Handle a key event (Left/Right go to the previous/next page, Home/End to the
+    /// first/last page). Returns the selected page if it changed.
+    pub fn handle_key(event: KeyEvent, page: usize, pages: usize) -> Option<usize> {
+        match event.code {
+            KeyCode::Left => Paginator::target(PageItem::Previous, page, pages),
+            KeyCode::Right => Paginator::target(PageItem::Next, page, pages),
+            KeyCode::Home => Paginator::target(PageItem::Page(0), page, pages),
+            KeyCode::End => Paginator::target(PageItem::Page(pages.saturating_sub(1)), page, pages),
+            _ => None,
+        }
+    }
+
+    /// Like [`Paginator::handle_key`], with the key of this frame translated by the
+    /// navigation preset (see [`crate::State::nav_key`])
+    pub fn handle_nav_key(state: &mut crate::State, page: usize, pages: usize) -> Option<usize> {
+        Paginator::handle_key(state.nav_key()?, page, pages)
+    }
+
+    /// Draw the paginator
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, _)
+    /// * `page` - current page (0 based)
+    /// * `pages` - number of pages
+    pub fn render(&mut self, rect: RectBoundary, page: usize, pages: usize) -> DrawingResult {
+        let mut width = 0;
+
+        // items are wider on some pages, erase what a longer list left behind
+        self.buffer
+            .clear_rect(&RectBoundary::new(rect.pos, (rect.size.0, 1)))?;
+
+        for (item, item_rect) in self.item_rects(&rect, page, pages) {
+            if item_rect.right() > rect.right() {
+                break;
+            }
+
+            let style = if item == PageItem::Page(page) {
+                self.current_style
+            } else {
+                self.style
+            };
+
+            self.buffer
+                .write_str_styled(item_rect.pos, &item.text(page), style)?;
+            width = item_rect.right() - rect.pos.0;
+        }
+
+        // done
+        Ok((
+            RectBoundary::new(rect.pos, (width, 1)),
+            self.buffer.get_changes(),
+        ))
+    }
+}
@@ -0,0 +1,179 @@
+//! Widget plugins
+//!
+//! [`Widget`] is an object safe interface for widgets from other crates: a crate
+//! ships a [`WidgetPack`] which adds its widgets to a [`WidgetRegistry`], and the
+//! application creates them by name and draws them with [`draw_widget`], which
+//! handles focus, theming and events the same way for every widget.
+use std::collections::BTreeMap;
+use std::io::Result as IOResult;
+
+use crossterm::event::KeyEvent;
+
+use crate::buffer::PseudoBuffer;
+use crate::drawing::{DrawingResult, Handled, KeyHandler, RectBoundary};
+use crate::event::{ClickEvent, ScrollEvent};
+use crate::hint::Hint;
+use crate::theme::Theme;
+use crate::State;
+
+/// Widget which can be drawn through a `dyn Widget` (everything but `render` is optional)
+pub trait Widget {
+    /// Draw the widget
+    ///
+    /// ## Arguments:
+    /// * `buffer` - buffer to draw into
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `theme` - theme of the application
+    /// * `focused` - if the widget has keyboard focus
+    fn render(
+        &mut self,
+        buffer: PseudoBuffer,
+        rect: RectBoundary,
+        theme: &Theme,
+        focused: bool,
+    ) -> DrawingResult;
+
+    /// If the widget can get keyboard focus (and keys)
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    /// Handle a key while the widget is focused
+    fn handle_key(&mut self, _event: KeyEvent, _state: &mut State) -> Handled {
+        Handled::No
+    }
+
+    /// Handle a click inside of the widget ([`ClickEvent::local`] is relative to its rect)
+    fn on_click(&mut self, _event: &ClickEvent, _state: &mut State) {}
+
+    /// Handle scrolling inside of the widget ([`ScrollEvent::local`] is relative to its rect)
+    fn on_scroll(&mut self, _event: &ScrollEvent, _state: &mut State) {}
+
+    /// Shortcuts of the widget, shown in a [`crate::hint::HintBar`] while it's focused
+    fn hints(&self) -> Vec<Hint> {
+        Vec::new()
+    }
+}
+
+/// Options a widget is created with (like a table from a config file)
+pub type WidgetOptions = serde_json::Value;
+
+/// Creates a widget from its options
+pub type WidgetFactory = dyn Fn(&WidgetOptions) -> Box<dyn Widget>;
+
+/// Collection of widgets shipped by a crate
+pub trait WidgetPack {
+    /// Name of the pack (also the prefix of its widgets, like `charts`)
+    fn name(&self) -> &str;
+
+    /// Add every widget of the pack to `registry`
+    fn register(&self, registry: &mut WidgetRegistry);
+}
+
+/// Widgets which can be created by name
+#[derive(Default)]
+pub struct WidgetRegistry {
+    factories: BTreeMap<String, Box<WidgetFactory>>,
+    packs: Vec<String>,
+}
+
+impl WidgetRegistry {
+    pub fn new() -> WidgetRegistry {
+        WidgetRegistry::default()
+    }
+
+    /// Add every widget of a pack
+    pub fn add_pack(&mut self, pack: &dyn WidgetPack) {
+        pack.register(self);
+        self.packs.push(pack.name().to_string());
+    }
+
+    /// Add a widget (replacing any other widget with the same name)
+    ///
+    /// ## Arguments:
+    /// * `name` - name of the widget (like `charts.sparkline`)
+    /// * `factory` - creates the widget from its options
+    pub fn register(
+        &mut self,
+        name: &str,
+        factory: impl Fn(&WidgetOptions) -> Box<dyn Widget> + 'static,
+    ) {
+        self.factories.insert(name.to_string(), Box::new(factory));
+    }
+
+    /// Create a widget (`None` if there is no widget called `name`)
+    pub fn create(&self, name: &str, options: &WidgetOptions) -> Option<Box<dyn Widget>> {
+        self.factories.get(name).map(|factory| factory(options))
+    }
+
+    /// Check if there is a widget called `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    /// Get the names of every widget (sorted)
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(|k| k.as_str())
+    }
+
+    /// Get the names of the added packs
+    pub fn packs(&self) -> &[String] {
+        &self.packs
+    }
+}
+
+/// Passes keys from [`State::dispatch_key`] to a widget
+struct WidgetKeys<'a>(&'a mut dyn Widget);
+
+impl KeyHandler for WidgetKeys<'_> {
+    fn handle_key(&mut self, event: KeyEvent, state: &mut State) -> Handled {
+        self.0.handle_key(event, state)
+    }
+}
+
+/// Draw a widget during the draw function. Focusable widgets are registered in the
+/// focus ring (clicking them focuses them) and get the key while they're focused.
+/// Clicks and scrolling inside of `rect` go to the widget (in its own coordinates), and the hints of the focused
+/// widget are added to [`State::hints`].
+///
+/// ## Arguments:
+/// * `widget`
+/// * `id` - id of the widget (for focus)
+/// * `state`
+/// * `buffer` - buffer to draw into
+/// * `rect` - pos(x, y), size(x, y)
+pub fn draw_widget(
+    widget: &mut dyn Widget,
+    id: &str,
+    state: &mut State,
+    buffer: &mut PseudoBuffer,
+    rect: RectBoundary,
+) -> IOResult<RectBoundary> {
+    let focusable = widget.focusable();
+
+    // events
+    if let Some(event) = state.click_in(&rect) {
+        if focusable {
+            state.focus.focus(id);
+        }
+
+        widget.on_click(&event, state);
+    }
+
+    if let Some(event) = state.scroll_in(&rect) {
+        widget.on_scroll(&event, state);
+    }
+
+    let focused = focusable && state.focus.register(id, rect.clone());
+
+    if focused {
+        state.dispatch_key(id, &mut WidgetKeys(widget));
+        state.hints.extend(widget.hints());
+    }
+
+    // draw
+    let (drawn, changes) = widget.render(buffer.clone(), rect, &state.theme, focused)?;
+    buffer.set_changes(changes);
+
+    Ok(drawn)
+}
@@ -0,0 +1,235 @@
+//! Rect geometry
+use crate::drawing::{RectBoundary, Vec2};
+
+impl RectBoundary {
+    /// Create a new [`RectBoundary`]
+    pub fn new(pos: Vec2, size: Vec2) -> RectBoundary {
+        RectBoundary { pos, size }
+    }
+
+    /// X position just past the right edge
+    pub fn right(&self) -> u16 {
+        self.pos.0.saturating_add(self.size.0)
+    }
+
+    /// Y position just past the bottom edge
+    pub fn bottom(&self) -> u16 {
+        self.pos.1.saturating_add(self.size.1)
+    }
+
+    /// Check if the rect has no area
+    pub fn is_empty(&self) -> bool {
+        (self.size.0 == 0) | (self.size.1 == 0)
+    }
+
+    /// Check if a position is inside of the rect
+    pub fn contains(&self, pos: Vec2) -> bool {
+        (self.pos.0..self.right()).contains(&pos.0) && (self.pos.1..self.bottom()).contains(&pos.1)
+    }
+
+    /// Translate a position into the rect (relative to its top left corner).
+    /// `None` if it's outside of the rect.
+    pub fn to_local(&self, pos: Vec2) -> Option<Vec2> {
+        self.contains(pos)
+            .then(|| (pos.0 - self.pos.0, pos.1 - self.pos.1))
+    }
+
+    /// Check if `other` is entirely inside of the rect
+    pub fn contains_rect(&self, other: &RectBoundary) -> bool {
+        (other.pos.0 >= self.pos.0)
+            && (other.pos.1 >= self.pos.1)
+            && (other.right() <= self.right())
+            && (other.bottom() <= self.bottom())
+    }
+
+    /// Check if the rect shares any cells with `other`
+    pub fn intersects(&self, other: &RectBoundary) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Get the area shared by both rects
+    pub fn intersection(&self, other: &RectBoundary) -> Option<RectBoundary> {
+        let x = self.pos.0.max(other.pos.0);
+        let y = self.pos.1.max(other.pos.1);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        if (x >= right) | (y >= bottom) {
+            return None;
+        }
+
+        Some(RectBoundary {
+            pos: (x, y),
+            size: (right - x, bottom - y),
+        })
+    }
+
+    /// Get the smallest rect containing both rects
+    pub fn union(&self, other: &RectBoundary) -> RectBoundary {
+        if self.is_empty() {
+            return other.clone();
+        } else if other.is_empty() {
+            return self.clone();
+        }
+
+        let x = self.pos.0.min(other.pos.0);
+        let y = self.pos.1.min(other.pos.1);
+
+        RectBoundary {
+            pos: (x, y),
+            size: (
+                self.right().max(other.right()) - x,
+                self.bottom().max(other.bottom()) - y,
+            ),
+        }
+    }
+
+    /// Shrink the rect by `margin` cells on every side
+    pub fn inset(&self, margin: u16) -> RectBoundary {
+        RectBoundary {
+            pos: (
+                self.pos.0.saturating_add(margin),
+                self.pos.1.saturating_add(margin),
+            ),
+            size: (
+                self.size.0.saturating_sub(margin * 2),
+                self.size.1.saturating_sub(margin * 2),
+            ),
+        }
+    }
+
+    /// Split the rect into a left and right part, `at` cells from the left edge
+    pub fn split_h(&self, at: u16) -> (RectBoundary, RectBoundary) {
+        let at = at.min(self.size.0);
+
+        (
+            RectBoundary {
+                pos: self.pos,
+                size: (at, self.size.1),
+            },
+            RectBoundary {
+                pos: (self.pos.0 + at, self.pos.1),
+                size: (self.size.0 - at, self.size.1),
+            },
+        )
+    }
+
+    /// Split the rect into a top and bottom part, `at` cells from the top edge
+    pub fn split_v(&self, at: u16) -> (RectBoundary, RectBoundary) {
+        let at = at.min(self.size.1);
+
+        (
+            RectBoundary {
+                pos: self.pos,
+                size: (self.size.0, at),
+            },
+            RectBoundary {
+                pos: (self.pos.0, self.pos.1 + at),
+                size: (self.size.0, self.size.1 - at),
+            },
+        )
+    }
+
+    /// Split the rect into `count` columns of (almost) equal width.
+    /// Leftover cells are given to the first columns.
+    pub fn columns(&self, count: u16) -> Vec<RectBoundary> {
+        let mut rects = Vec::new();
+
+        if count == 0 {
+            return rects;
+        }
+
+        let mut x = self.pos.0;
+
+        for i in 0..count {
+            let width = (self.size.0 / count) + u16::from(i < (self.size.0 % count));
+
+            rects.push(RectBoundary {
+                pos: (x, self.pos.1),
+                size: (width, self.size.1),
+            });
+
+            x += width;
+        }
+
+        rects
+    }
+
+    /// Split the rect into `count` rows of (almost) equal height.
+    /// Leftover cells are given to the first rows.
+    pub fn rows(&self, count: u16) -> Vec<RectBoundary> {
+        let mut rects = Vec::new();
+
+        if count == 0 {
+            return rects;
+        }
+
+        let mut y = self.pos.1;
+
+        for i in 0..count {
+            let height = (self.size.1 / count) + u16::from(i < (self.size.1 % count));
+
+            rects.push(RectBoundary {
+                pos: (self.pos.0, y),
+                size: (self.size.0, height),
+            });
+
+            y += height;
+        }
+
+        rects
+    }
+
+    /// Split the rect into columns. `Some(width)` columns get that width (as far as
+    /// there's room left), and the `None` ones share what's left like [`RectBoundary::columns`].
+    pub fn columns_sized(&self, widths: &[Option<u16>]) -> Vec<RectBoundary> {
+        split_sized(self.size.0, widths)
+            .into_iter()
+            .map(|(offset, width)| RectBoundary {
+                pos: (self.pos.0 + offset, self.pos.1),
+                size: (width, self.size.1),
+            })
+            .collect()
+    }
+
+    /// Split the rect into rows. `Some(height)` rows get that height (as far as
+    /// there's room left), and the `None` ones share what's left like [`RectBoundary::rows`].
+    pub fn rows_sized(&self, heights: &[Option<u16>]) -> Vec<RectBoundary> {
+        split_sized(self.size.1, heights)
+            .into_iter()
+            .map(|(offset, height)| RectBoundary {
+                pos: (self.pos.0, self.pos.1 + offset),
+                size: (self.size.0, height),
+            })
+            .collect()
+    }
+}
+
+/// Get `(offset, length)` of every part of `total` cells (see [`RectBoundary::columns_sized`])
+fn split_sized(total: u16, sizes: &[Option<u16>]) -> Vec<(u16, u16)> {
+    let fixed: u16 = sizes.iter().flatten().fold(0, |a, b| a.saturating_add(*b));
+    let flexible = sizes.iter().filter(|s| s.is_none()).count() as u16;
+    let left = total.saturating_sub(fixed);
+
+    let mut parts = Vec::new();
+    let mut offset = 0;
+    let mut i = 0;
+
+    for size in sizes {
+        let length = match size {
+            Some(size) => *size,
+            None => {
+                // leftover cells are given to the first parts
+                let length = (left / flexible) + u16::from(i < (left % flexible));
+                i += 1;
+                length
+            }
+        }
+        .min(total - offset);
+
+        parts.push((offset, length));
+        offset += length;
+    }
+
+    parts
+}
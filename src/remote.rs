@@ -0,0 +1,704 @@
+//! Remote rendering
+//!
+//! A [`RemoteServer`] sends the cells which changed every frame to any number of
+//! connected clients (newline separated JSON [`RemoteMessage`]s). [`RemoteClient`]
+//! draws them in its own terminal.
+//!
+//! Clients send [`ClientMessage`]s back: every client has its own cursor (shown
+//! to the others, it doesn't touch the app), and one client at a time can be
+//! given input control (see [`RemoteServer::set_control`]), for pair debugging.
+//!
+//! Every client is written to on its own thread, so a slow client can't stall the
+//! app. Clients which fall more than [`MAX_QUEUED`] messages behind are dropped.
+use crossterm::event::{
+    poll, read, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers,
+    MouseEventKind,
+};
+use crossterm::{cursor, terminal, QueueableCommand};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Result as IOResult, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::Path;
+
+use crate::buffer::{BufCell, Buffer, BufferChange, BufferWrite, Row};
+use crate::drawing::{TextAttribute, TextBackgroundColor, TextColor, Vec2};
+use crate::style::Style;
+use crate::web::{encode_event, parse_input};
+
+/// Message sent from a [`RemoteServer`] to its clients
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum RemoteMessage {
+    /// The screen size changed (the screen should be cleared)
+    Resize(Vec2),
+    /// Cells which changed
+    Changes(Vec<BufferChange>),
+    /// Id of the client receiving it (sent once after connecting)
+    Welcome(usize),
+    /// Cursors of every client
+    Cursors(Vec<RemoteCursor>),
+    /// Client which has input control (if any)
+    Control(Option<usize>),
+}
+
+/// Message sent from a [`RemoteClient`] to its server
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// The mouse of the client moved
+    Cursor(Vec2),
+    /// Ask for input control (see [`RemoteServer::allow_control_requests`])
+    RequestControl,
+    /// Give input control back
+    ReleaseControl,
+    /// Input as the terminal of the client sent it (dropped unless the client
+    /// has input control, see [`crate::web::encode_event`])
+    Input(String),
+}
+
+/// Where the mouse of a client is
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RemoteCursor {
+    pub client: usize,
+    pub pos: Vec2,
+}
+
+/// Messages a client can fall behind before it's dropped
+pub const MAX_QUEUED: usize = 64;
+
+/// Encode a message as a line of JSON
+fn encode(message: &impl Serialize) -> IOResult<Vec<u8>> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+/// Write a message as a line of JSON
+fn send(writer: &mut dyn Write, message: &impl Serialize) -> IOResult<()> {
+    writer.write_all(&encode(message)?)?;
+    writer.flush()
+}
+
+/// Writing half of the connection to a client
+trait Connection: Write + Send {
+    /// Close both halves of the connection (which also ends the reader thread)
+    fn close(&self);
+}
+
+impl Connection for TcpStream {
+    fn close(&self) {
+        let _ = self.shutdown(Shutdown::Both);
+    }
+}
+
+#[cfg(unix)]
+impl Connection for UnixStream {
+    fn close(&self) {
+        let _ = self.shutdown(Shutdown::Both);
+    }
+}
+
+/// Write the lines sent to the returned queue to a client on another thread.
+/// The connection is closed once the queue is dropped or a write fails.
+fn spawn_writer(mut connection: Box<dyn Connection>) -> mpsc::SyncSender<Vec<u8>> {
+    let (outbox, queue) = mpsc::sync_channel::<Vec<u8>>(MAX_QUEUED);
+
+    std::thread::spawn(move || {
+        while let Ok(line) = queue.recv() {
+            if connection
+                .write_all(&line)
+                .and_then(|_| connection.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+
+        connection.close();
+    });
+
+    outbox
+}
+
+/// What the reader thread of a client got
+enum Inbox {
+    Message(ClientMessage),
+    /// The client disconnected
+    Closed,
+}
+
+/// A connected client
+struct Client {
+    id: usize,
+    /// Lines for the writer thread of the client
+    outbox: mpsc::SyncSender<Vec<u8>>,
+    cursor: Option<Vec2>,
+}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+/// Sends frames to remote viewers. Attach it to a frame with
+/// [`crate::Frame::set_remote`], or call [`RemoteServer::push`] yourself (headless apps).
+pub struct RemoteServer {
+    listener: Listener,
+    clients: Vec<Client>,
+    size: Vec2,
+    /// What the clients have on screen
+    screen: Vec<Row>,
+    /// Id of the next client
+    next_id: usize,
+    /// Messages from the reader threads of the clients
+    inbox: mpsc::Receiver<(usize, Inbox)>,
+    inbox_sender: mpsc::Sender<(usize, Inbox)>,
+    /// Client with input control
+    control: Option<usize>,
+    /// Events from the client with input control, not handled yet
+    input: Vec<Event>,
+    /// Let clients take input control themselves while nobody has it
+    pub allow_control_requests: bool,
+}
+
+impl RemoteServer {
+    fn new(listener: Listener) -> RemoteServer {
+        let (inbox_sender, inbox) = mpsc::channel();
+
+        RemoteServer {
+            listener,
+            clients: Vec::new(),
+            size: (0, 0),
+            screen: Vec::new(),
+            next_id: 0,
+            inbox,
+            inbox_sender,
+            control: None,
+            input: Vec::new(),
+            allow_control_requests: false,
+        }
+    }
+
+    /// Listen for clients on a TCP address
+    pub fn bind_tcp(addr: impl ToSocketAddrs) -> IOResult<RemoteServer> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(RemoteServer::new(Listener::Tcp(listener)))
+    }
+
+    /// Listen for clients on a Unix socket
+    #[cfg(unix)]
+    pub fn bind_unix(path: impl AsRef<Path>) -> IOResult<RemoteServer> {
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        Ok(RemoteServer::new(Listener::Unix(listener)))
+    }
+
+    /// Get the address of a TCP server (to find the port after binding port 0)
+    pub fn local_addr(&self) -> IOResult<SocketAddr> {
+        match &self.listener {
+            Listener::Tcp(l) => l.local_addr(),
+            #[cfg(unix)]
+            Listener::Unix(_) => Err(ErrorKind::Unsupported.into()),
+        }
+    }
+
+    /// Get the number of connected clients
+    pub fn clients(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Get the ids of the connected clients
+    pub fn client_ids(&self) -> Vec<usize> {
+        self.clients.iter().map(|c| c.id).collect()
+    }
+
+    /// Get the client which has input control
+    pub fn control(&self) -> Option<usize> {
+        self.control
+    }
+
+    /// Give input control to a client (`None` to take it away)
+    pub fn set_control(&mut self, client: Option<usize>) {
+        if client != self.control {
+            self.control = client;
+            self.broadcast(&RemoteMessage::Control(client));
+        }
+    }
+
+    /// Get the events sent by the client with input control since the last call.
+    /// [`crate::Frame::poll_events`] handles them like local input.
+    pub fn take_input(&mut self) -> IOResult<Vec<Event>> {
+        self.accept_pending()?;
+        self.receive();
+        Ok(std::mem::take(&mut self.input))
+    }
+
+    /// Apply the messages sent by the clients
+    fn receive(&mut self) {
+        let mut cursors_changed = false;
+        let control = self.control;
+
+        while let Ok((id, inbox)) = self.inbox.try_recv() {
+            match inbox {
+                Inbox::Message(ClientMessage::Cursor(pos)) => {
+                    if let Some(client) = self.clients.iter_mut().find(|c| c.id == id) {
+                        client.cursor = Some(pos);
+                        cursors_changed = true;
+                    }
+                }
+                Inbox::Message(ClientMessage::RequestControl) => {
+                    if self.allow_control_requests && self.control.is_none() {
+                        self.control = Some(id);
+                    }
+                }
+                Inbox::Message(ClientMessage::ReleaseControl) => {
+                    if self.control == Some(id) {
+                        self.control = None;
+                    }
+                }
+                Inbox::Message(ClientMessage::Input(input)) => {
+                    if self.control == Some(id) {
+                        self.input.extend(parse_input(&input));
+                    }
+                }
+                Inbox::Closed => {
+                    self.clients.retain(|c| c.id != id);
+                    cursors_changed = true;
+                }
+            }
+        }
+
+        if self.clients.iter().all(|c| Some(c.id) != self.control) {
+            self.control = None; // gone
+        }
+
+        if self.control != control {
+            self.broadcast(&RemoteMessage::Control(self.control));
+        }
+
+        if cursors_changed {
+            self.broadcast(&RemoteMessage::Cursors(self.cursors()));
+        }
+    }
+
+    /// Get the cursors of every client
+    fn cursors(&self) -> Vec<RemoteCursor> {
+        self.clients
+            .iter()
+            .filter_map(|c| c.cursor.map(|pos| RemoteCursor { client: c.id, pos }))
+            .collect()
+    }
+
+    /// Accept every waiting client, and send it the whole screen
+    fn accept_pending(&mut self) -> IOResult<()> {
+        loop {
+            type Halves = (Box<dyn Connection>, Box<dyn Read + Send>);
+
+            let accepted: IOResult<Halves> = match &self.listener {
+                Listener::Tcp(l) => l.accept().and_then(|(s, _)| {
+                    s.set_nonblocking(false)?;
+                    s.set_nodelay(true)?;
+                    let reader = s.try_clone()?;
+                    Ok((Box::new(s) as _, Box::new(reader) as _))
+                }),
+                #[cfg(unix)]
+                Listener::Unix(l) => l.accept().and_then(|(s, _)| {
+                    s.set_nonblocking(false)?;
+                    let reader = s.try_clone()?;
+                    Ok((Box::new(s) as _, Box::new(reader) as _))
+                }),
+            };
+
+            let (connection, reader) = match accepted {
+                Ok(c) => c,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(e),
+            };
+
+            let id = self.next_id;
+            self.next_id += 1;
+
+            // catch the client up
+            let mut snapshot = Vec::new();
+
+            for (y, row) in self.screen.iter().enumerate() {
+                for (x, cell) in row.iter().enumerate() {
+                    if !cell.empty {
+                        snapshot.push(BufferChange {
+                            loc: (x as u16, y as u16),
+                            cell: cell.clone(),
+                        });
+                    }
+                }
+            }
+
+            let outbox = spawn_writer(connection);
+            let sent: IOResult<()> = [
+                RemoteMessage::Welcome(id),
+                RemoteMessage::Resize(self.size),
+                RemoteMessage::Changes(snapshot),
+                RemoteMessage::Control(self.control),
+                RemoteMessage::Cursors(self.cursors()),
+            ]
+            .iter()
+            .try_for_each(|message| {
+                outbox
+                    .try_send(encode(message)?)
+                    .map_err(|_| ErrorKind::BrokenPipe.into())
+            });
+
+            if sent.is_err() {
+                continue;
+            }
+
+            // read what the client sends on its own thread
+            let sender = self.inbox_sender.clone();
+
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(reader);
+                let mut line = String::new();
+
+                while let Ok(1..) = reader.read_line(&mut line) {
+                    if let Ok(message) = serde_json::from_str(&line) {
+                        if sender.send((id, Inbox::Message(message))).is_err() {
+                            return; // the server is gone
+                        }
+                    }
+
+                    line.clear();
+                }
+
+                let _ = sender.send((id, Inbox::Closed));
+            });
+
+            self.clients.push(Client {
+                id,
+                outbox,
+                cursor: None,
+            });
+        }
+    }
+
+    /// Queue a message for every client (clients which are gone, or more than
+    /// [`MAX_QUEUED`] messages behind, are dropped)
+    fn broadcast(&mut self, message: &RemoteMessage) {
+        let line = match encode(message) {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+
+        self.clients
+            .retain(|client| client.outbox.try_send(line.clone()).is_ok());
+    }
+
+    /// Send a drawn frame to the clients. Only cells which changed since the
+    /// last frame are sent.
+    ///
+    /// ## Arguments:
+    /// * `size` - screen size
+    /// * `changes` - changes of the frame (from [`crate::buffer::PseudoBuffer::get_changes`])
+    pub fn push(&mut self, size: Vec2, changes: &[BufferChange]) -> IOResult<()> {
+        self.accept_pending()?;
+        self.receive();
+
+        if size != self.size {
+            self.size = size;
+            self.screen = vec![BufCell::as_row(size.0); size.1 as usize];
+            self.broadcast(&RemoteMessage::Resize(size));
+        }
+
+        let mut diff = Vec::new();
+
+        for change in changes {
+            let cell = match self
+                .screen
+                .get_mut(change.loc.1 as usize)
+                .and_then(|row| row.get_mut(change.loc.0 as usize))
+            {
+                Some(cell) => cell,
+                None => continue, // off screen
+            };
+
+            if *cell != change.cell {
+                *cell = change.cell.clone();
+                diff.push(change.clone());
+            }
+        }
+
+        if !diff.is_empty() {
+            self.broadcast(&RemoteMessage::Changes(diff));
+        }
+
+        Ok(())
+    }
+}
+
+/// Draws what a [`RemoteServer`] sends in the local terminal.
+/// The cursors of the other clients are drawn on top, and F2 asks for input control
+/// (or gives it back). While in control, keys and the mouse go to the remote app.
+pub struct RemoteClient {
+    reader: BufReader<Box<dyn Read + Send>>,
+    writer: Box<dyn Write + Send>,
+}
+
+/// What a [`RemoteClient`] knows about the session
+struct View {
+    /// Id of this client
+    id: Option<usize>,
+    /// Client with input control
+    control: Option<usize>,
+    /// Cursors of the other clients
+    cursors: Vec<RemoteCursor>,
+    /// Cells of the remote screen (without cursors)
+    screen: Vec<Row>,
+}
+
+impl View {
+    /// Check if this client has input control
+    fn in_control(&self) -> bool {
+        self.id.is_some() && (self.id == self.control)
+    }
+
+    /// Draw the cell of the remote screen at `pos` (`cursor` for a cursor on top)
+    fn draw_cell(&self, buffer: &mut Buffer, pos: Vec2, cursor: Option<usize>) -> IOResult<()> {
+        if (pos.0 >= buffer.size.0) || (pos.1 >= buffer.size.1) {
+            return Ok(());
+        }
+
+        let cell = self
+            .screen
+            .get(pos.1 as usize)
+            .and_then(|row| row.get(pos.0 as usize))
+            .cloned()
+            .unwrap_or(BufCell::EMPTY);
+
+        let style = match cursor {
+            // the cursor of the client in control stands out
+            Some(client) if Some(client) == self.control => Style::DEFAULT
+                .fg(TextColor::Black)
+                .bg(TextBackgroundColor::Yellow),
+            Some(_) => Style::DEFAULT.attr(TextAttribute::Swap),
+            None if cell.empty => {
+                buffer.erase(pos)?;
+                return Ok(());
+            }
+            None => {
+                buffer.write_cell(pos, cell)?;
+                return Ok(());
+            }
+        };
+
+        let char = if cell.empty { ' ' } else { cell.char };
+        buffer.write_cell(pos, BufCell::styled(char, style))?;
+        Ok(())
+    }
+
+    /// Draw the whole remote screen (that fits), with the cursors on top
+    fn draw_screen(&self, buffer: &mut Buffer) -> IOResult<()> {
+        for (y, row) in self.screen.iter().enumerate() {
+            for x in 0..row.len() {
+                self.draw_cell(buffer, (x as u16, y as u16), None)?;
+            }
+        }
+
+        self.draw_cursors(buffer)
+    }
+
+    /// Draw the cursors of the other clients
+    fn draw_cursors(&self, buffer: &mut Buffer) -> IOResult<()> {
+        for cursor in &self.cursors {
+            if Some(cursor.client) != self.id {
+                self.draw_cell(buffer, cursor.pos, Some(cursor.client))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl RemoteClient {
+    /// Connect to a server over TCP
+    pub fn connect_tcp(addr: impl ToSocketAddrs) -> IOResult<RemoteClient> {
+        let stream = TcpStream::connect(addr)?;
+        let writer: Box<dyn Write + Send> = Box::new(stream.try_clone()?);
+        let stream: Box<dyn Read + Send> = Box::new(stream);
+        Ok(RemoteClient {
+            reader: BufReader::new(stream),
+            writer,
+        })
+    }
+
+    /// Connect to a server over a Unix socket
+    #[cfg(unix)]
+    pub fn connect_unix(path: impl AsRef<Path>) -> IOResult<RemoteClient> {
+        let stream = UnixStream::connect(path)?;
+        let writer: Box<dyn Write + Send> = Box::new(stream.try_clone()?);
+        let stream: Box<dyn Read + Send> = Box::new(stream);
+        Ok(RemoteClient {
+            reader: BufReader::new(stream),
+            writer,
+        })
+    }
+
+    /// Show the remote screen until the server disconnects or Ctrl+C (or `q`
+    /// without input control) is pressed
+    pub fn run(self) -> IOResult<()> {
+        let mut stdout = std::io::stdout();
+        let mut buffer = Buffer::new(std::io::stdout(), terminal::size()?);
+
+        // read messages on another thread, so we can still handle keys
+        let (sender, receiver) = mpsc::channel::<RemoteMessage>();
+        let mut reader = self.reader;
+        let mut writer = self.writer;
+
+        std::thread::spawn(move || {
+            let mut line = String::new();
+
+            while let Ok(1..) = reader.read_line(&mut line) {
+                if let Ok(message) = serde_json::from_str(&line) {
+                    if sender.send(message).is_err() {
+                        break;
+                    }
+                }
+
+                line.clear();
+            }
+        });
+
+        // open env
+        terminal::enable_raw_mode()?;
+        stdout.queue(terminal::EnterAlternateScreen)?;
+        stdout.queue(EnableMouseCapture)?;
+        stdout.queue(cursor::Hide)?;
+        stdout.flush()?;
+
+        let res = RemoteClient::view(&mut buffer, &receiver, &mut writer);
+
+        // close env
+        stdout.queue(cursor::Show)?;
+        stdout.queue(DisableMouseCapture)?;
+        stdout.queue(terminal::LeaveAlternateScreen)?;
+        stdout.flush()?;
+        terminal::disable_raw_mode()?;
+
+        res
+    }
+
+    /// Send input to the server (if it can be encoded)
+    fn send_input(writer: &mut dyn Write, event: &Event) -> IOResult<()> {
+        match encode_event(event) {
+            Some(input) => send(writer, &ClientMessage::Input(input)),
+            None => Ok(()),
+        }
+    }
+
+    /// Apply messages until we're told to stop
+    fn view(
+        buffer: &mut Buffer,
+        receiver: &mpsc::Receiver<RemoteMessage>,
+        writer: &mut dyn Write,
+    ) -> IOResult<()> {
+        let mut view = View {
+            id: None,
+            control: None,
+            cursors: Vec::new(),
+            screen: Vec::new(),
+        };
+
+        loop {
+            // input
+            if poll(std::time::Duration::from_millis(10))? {
+                let event = read()?;
+
+                match &event {
+                    Event::Key(key) => {
+                        let ctrl_c = (key.code == KeyCode::Char('c'))
+                            && key.modifiers.contains(KeyModifiers::CONTROL);
+
+                        if ctrl_c || ((key.code == KeyCode::Char('q')) && !view.in_control()) {
+                            return Ok(());
+                        }
+
+                        if key.code == KeyCode::F(2) {
+                            let message = if view.in_control() {
+                                ClientMessage::ReleaseControl
+                            } else {
+                                ClientMessage::RequestControl
+                            };
+                            send(writer, &message)?;
+                        } else if view.in_control() {
+                            RemoteClient::send_input(writer, &event)?;
+                        }
+                    }
+                    Event::Mouse(mouse) => {
+                        if matches!(mouse.kind, MouseEventKind::Moved | MouseEventKind::Drag(_)) {
+                            send(writer, &ClientMessage::Cursor((mouse.column, mouse.row)))?;
+                        }
+
+                        if view.in_control() {
+                            RemoteClient::send_input(writer, &event)?;
+                        }
+                    }
+                    Event::Resize(width, height) => {
+                        // draw what we have again, the server only sends changes
+                        buffer.resize((*width, *height))?;
+                        buffer.invalidate()?;
+                        view.draw_screen(buffer)?;
+                        buffer.commit()?;
+                    }
+                    _ => (),
+                }
+            }
+
+            // messages
+            loop {
+                match receiver.try_recv() {
+                    Ok(RemoteMessage::Resize(size)) => {
+                        // the remote app redraws after resizing, start over
+                        view.screen = vec![BufCell::as_row(size.0); size.1 as usize];
+                        buffer.invalidate()?;
+                    }
+                    Ok(RemoteMessage::Changes(changes)) => {
+                        for change in changes {
+                            if let Some(cell) = view
+                                .screen
+                                .get_mut(change.loc.1 as usize)
+                                .and_then(|row| row.get_mut(change.loc.0 as usize))
+                            {
+                                *cell = change.cell;
+                            }
+
+                            // our terminal might be smaller than the remote one
+                            view.draw_cell(buffer, change.loc, None)?;
+                        }
+
+                        view.draw_cursors(buffer)?;
+                        buffer.commit()?;
+                    }
+                    Ok(RemoteMessage::Welcome(id)) => view.id = Some(id),
+                    Ok(RemoteMessage::Cursors(cursors)) => {
+                        // put back what was under the old cursors
+                        for cursor in std::mem::replace(&mut view.cursors, cursors) {
+                            view.draw_cell(buffer, cursor.pos, None)?;
+                        }
+
+                        view.draw_cursors(buffer)?;
+                        buffer.commit()?;
+                    }
+                    Ok(RemoteMessage::Control(control)) => {
+                        view.control = control;
+                        view.draw_cursors(buffer)?;
+                        buffer.commit()?;
+                    }
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    Err(mpsc::TryRecvError::Disconnected) => return Ok(()),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Render thread
+//!
+//! The [`Buffer`] lives on its own thread and receives [`RenderCommand`]s over a
+//! bounded channel, so slow draw functions can't stall writing to the terminal.
+//! Enable it with [`crate::Frame::spawn_render_thread`].
+use crossterm::cursor;
+use std::io::{Error, ErrorKind, Result as IOResult, Write};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread::JoinHandle;
+
+use crate::buffer::{Buffer, BufferChange, Row};
+use crate::drawing::{RectBoundary, Vec2};
+
+/// Command sent to a [`RenderThread`]
+#[derive(Clone, Debug)]
+pub enum RenderCommand {
+    /// Cells to draw (committed once the queue is empty)
+    Changes(Vec<BufferChange>),
+    /// The terminal was resized
+    Resize(Vec2),
+    /// Clear the screen and draw everything again (see [`Buffer::invalidate`])
+    Invalidate,
+    /// Lock a region (see [`Buffer::lock_region`])
+    LockRegion(RectBoundary),
+    /// Unlock a region (see [`Buffer::unlock_region`])
+    UnlockRegion(RectBoundary),
+    /// Move the cursor after committing
+    MoveCursor(Vec2),
+    /// Show or hide the cursor
+    ShowCursor(bool),
+    /// Save the cursor position (after committing and moving the cursor as queued before this)
+    SaveCursor,
+    /// Restore the saved cursor position (after committing everything before this,
+    /// cursor moves queued before this are dropped)
+    RestoreCursor,
+    /// Write an escape sequence which doesn't draw anything (after committing everything before this)
+    Write(String),
+    /// Send back what's on screen (after committing everything before this)
+    Snapshot(mpsc::Sender<Vec<Row>>),
+    /// Stop the thread
+    Stop,
+}
+
+/// Handle to a thread which owns the terminal [`Buffer`]
+pub struct RenderThread {
+    sender: SyncSender<RenderCommand>,
+    handle: Option<JoinHandle<IOResult<()>>>,
+}
+
+impl RenderThread {
+    /// Start a render thread writing to stdout
+    ///
+    /// ## Arguments:
+    /// * `size` - screen size
+    /// * `capacity` - number of commands which can be queued before [`RenderThread::send`] blocks
+    pub fn spawn(size: Vec2, capacity: usize) -> RenderThread {
+        RenderThread::with_output(std::io::stdout(), size, capacity)
+    }
+
+    /// Start a render thread writing to anything (like the output of the frame, see
+    /// [`Buffer::share_output`])
+    ///
+    /// ## Arguments:
+    /// * `output`
+    /// * `size` - screen size
+    /// * `capacity` - number of commands which can be queued before [`RenderThread::send`] blocks
+    pub fn with_output(
+        output: impl Write + Send + 'static,
+        size: Vec2,
+        capacity: usize,
+    ) -> RenderThread {
+        let (sender, receiver) = mpsc::sync_channel(capacity.max(1));
+        let buffer = Buffer::with_output(output, size);
+
+        RenderThread {
+            sender,
+            handle: Some(std::thread::spawn(move || {
+                RenderThread::run(buffer, receiver)
+            })),
+        }
+    }
+
+    /// Render loop
+    fn run(mut buffer: Buffer, receiver: Receiver<RenderCommand>) -> IOResult<()> {
+        while let Ok(command) = receiver.recv() {
+            let mut cursor_pos = None;
+            let mut next = Some(command);
+
+            // apply everything which is queued, then commit once
+            while let Some(command) = next {
+                match command {
+                    RenderCommand::Changes(changes) => {
+                        buffer.consume_changes(changes)?;
+                    }
+                    RenderCommand::Resize(size) => {
+                        buffer.resize(size)?;
+                    }
+                    RenderCommand::Invalidate => {
+                        buffer.invalidate()?;
+                    }
+                    RenderCommand::LockRegion(rect) => buffer.lock_region(rect),
+                    RenderCommand::UnlockRegion(rect) => {
+                        buffer.unlock_region(&rect)?;
+                    }
+                    RenderCommand::MoveCursor(pos) => cursor_pos = Some(pos),
+                    RenderCommand::ShowCursor(visible) => {
+                        if visible {
+                            buffer.queue(cursor::Show)?;
+                        } else {
+                            buffer.queue(cursor::Hide)?;
+                        }
+
+                        buffer.flush()?;
+                    }
+                    RenderCommand::SaveCursor => {
+                        buffer.commit()?;
+
+                        if let Some(pos) = cursor_pos.take() {
+                            buffer.queue(cursor::MoveTo(pos.0, pos.1))?;
+                        }
+
+                        buffer.queue(cursor::SavePosition)?;
+                        buffer.flush()?;
+                    }
+                    RenderCommand::RestoreCursor => {
+                        buffer.commit()?;
+                        cursor_pos = None;
+                        buffer.queue(cursor::RestorePosition)?;
+                        buffer.flush()?;
+                    }
+                    RenderCommand::Write(text) => {
+                        buffer.commit()?;
+                        buffer.queue(crossterm::style::Print(text))?;
+                        buffer.flush()?;
+                    }
+                    RenderCommand::Snapshot(sender) => {
+                        buffer.commit()?;
+                        let _ = sender.send(buffer.screen_vec.to_rows());
+                    }
+                    RenderCommand::Stop => {
+                        buffer.commit()?;
+                        return Ok(());
+                    }
+                }
+
+                next = receiver.try_recv().ok();
+            }
+
+            buffer.commit()?;
+
+            if let Some(pos) = cursor_pos {
+                buffer.queue(cursor::MoveTo(pos.0, pos.1))?;
+                buffer.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the error the thread stopped with
+    fn stopped(&mut self) -> Error {
+        match self.handle.take().map(|h| h.join()) {
+            Some(Ok(Err(e))) => e,
+            _ => Error::new(ErrorKind::BrokenPipe, "Render thread stopped."),
+        }
+    }
+
+    /// Send a command. Blocks while the queue is full (backpressure).
+    pub fn send(&mut self, command: RenderCommand) -> IOResult<()> {
+        match self.sender.send(command) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(self.stopped()),
+        }
+    }
+
+    /// Send a command if the queue isn't full. Returns `false` if it is
+    /// (the caller can drop the frame and draw again later).
+    pub fn try_send(&mut self, command: RenderCommand) -> IOResult<bool> {
+        match self.sender.try_send(command) {
+            Ok(_) => Ok(true),
+            Err(TrySendError::Full(_)) => Ok(false),
+            Err(TrySendError::Disconnected(_)) => Err(self.stopped()),
+        }
+    }
+
+    /// Stop the thread once everything queued is drawn
+    pub fn stop(mut self) -> IOResult<()> {
+        let _ = self.sender.send(RenderCommand::Stop);
+
+        match self.handle.take().map(|h| h.join()) {
+            Some(Ok(res)) => res,
+            Some(Err(_)) => Err(Error::other("Render thread panicked.")),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for RenderThread {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = self.sender.send(RenderCommand::Stop);
+            let _ = handle.join();
+        }
+    }
+}
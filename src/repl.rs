@@ -0,0 +1,236 @@
+//! Prompt with scrollback
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{Creatable, DrawingResult, Line, RectBoundary, TextColor, TextLeaf, Vec2};
+use crate::input::{TextInput, TextInputState};
+use crate::style::Style;
+
+/// State of a [`Repl`], kept by the application between frames
+#[derive(Clone, Debug)]
+pub struct ReplState {
+    /// The line being typed
+    pub input: TextInputState,
+    /// Submitted lines and command output (oldest first)
+    pub scrollback: Vec<Line>,
+    /// Submitted commands (oldest first)
+    pub history: Vec<String>,
+    /// Index into `history` while recalling commands
+    pub history_pos: Option<usize>,
+    /// Number of lines scrolled up from the bottom of the scrollback
+    pub scroll: usize,
+    /// Maximum number of lines kept in the scrollback (oldest are dropped first)
+    pub max_lines: usize,
+    /// Prompt shown before the input (and before submitted lines in the scrollback)
+    pub prompt: String,
+    /// Line typed before recalling history (restored when going past the newest entry)
+    draft: String,
+}
+
+impl Default for ReplState {
+    fn default() -> Self {
+        ReplState::new("> ")
+    }
+}
+
+impl ReplState {
+    /// Create a new [`ReplState`]
+    pub fn new(prompt: &str) -> Self {
+        ReplState {
+            input: TextInputState::default(),
+            scrollback: Vec::new(),
+            history: Vec::new(),
+            history_pos: None,
+            scroll: 0,
+            max_lines: 1000,
+            prompt: prompt.to_string(),
+            draft: String::new(),
+        }
+    }
+
+    /// Add a line of output to the scrollback
+    pub fn print(&mut self, line: impl Into<Line>) {
+        self.scrollback.push(line.into());
+
+        if self.scrollback.len() > self.max_lines {
+            let extra = self.scrollback.len() - self.max_lines;
+            self.scrollback.drain(..extra);
+        }
+    }
+
+    /// Remove everything from the scrollback
+    pub fn clear(&mut self) {
+        self.scrollback.clear();
+        self.scroll = 0;
+    }
+
+    /// Submit the current input: it's added to the scrollback and history,
+    /// and `on_command` is called with it. Lines returned by `on_command` are
+    /// added to the scrollback as output.
+    pub fn submit(&mut self, on_command: &mut dyn FnMut(&str) -> Vec<Line>) {
+        let command = std::mem::take(&mut self.input.text);
+        self.input = TextInputState::default();
+        self.history_pos = None;
+        self.scroll = 0;
+
+        self.print(Line::from(TextLeaf::styled(
+            &format!("{}{command}", self.prompt),
+            Style::DEFAULT.fg(TextColor::BrightBlack),
+        )));
+
+        if !command.is_empty() && (self.history.last() != Some(&command)) {
+            self.history.push(command.clone());
+        }
+
+        for line in on_command(&command) {
+            self.print(line);
+        }
+    }
+
+    /// Recall the previous command from history
+    pub fn history_prev(&mut self) {
+        if self.history.is_empty() {
+            return;
+        }
+
+        let pos = match self.history_pos {
+            Some(pos) => pos.saturating_sub(1),
+            None => {
+                self.draft = self.input.text.clone();
+                self.history.len() - 1
+            }
+        };
+
+        self.history_pos = Some(pos);
+        self.input = TextInputState::new(&self.history[pos]);
+    }
+
+    /// Recall the next command from history (or the line typed before recalling)
+    pub fn history_next(&mut self) {
+        let Some(pos) = self.history_pos else {
+            return;
+        };
+
+        if (pos + 1) < self.history.len() {
+            self.history_pos = Some(pos + 1);
+            self.input = TextInputState::new(&self.history[pos + 1]);
+        } else {
+            self.history_pos = None;
+            self.input = TextInputState::new(&std::mem::take(&mut self.draft));
+        }
+    }
+
+    /// Handle a key event. Returns `true` if the key was used.
+    ///
+    /// ## Arguments:
+    /// * `event`
+    /// * `page` - number of lines scrolled by Page Up/Page Down
+    /// * `on_command` - called with submitted commands, returns output lines
+    pub fn handle_key(
+        &mut self,
+        event: KeyEvent,
+        page: usize,
+        on_command: &mut dyn FnMut(&str) -> Vec<Line>,
+    ) -> bool {
+        match event.code {
+            KeyCode::Enter if self.input.preedit.is_none() => self.submit(on_command),
+            KeyCode::Up => self.history_prev(),
+            KeyCode::Down => self.history_next(),
+            KeyCode::PageUp => {
+                self.scroll = (self.scroll + page.max(1)).min(self.scrollback.len());
+            }
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(page.max(1)),
+            KeyCode::Char('l') if event.modifiers.contains(KeyModifiers::CONTROL) => self.clear(),
+            _ => return self.input.handle_key(event),
+        }
+
+        true
+    }
+}
+
+/// Prompt pinned to the bottom of a rect, with the scrollback above it
+pub struct Repl {
+    pub buffer: PseudoBuffer,
+    /// Style of the prompt
+    pub prompt_style: Style,
+    /// Place the hardware cursor in the input (see [`TextInput::cursor`])
+    pub cursor: bool,
+}
+
+impl Creatable for Repl {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Repl {
+            prompt_style: buffer.theme_style(|t| t.accent, Style::DEFAULT.fg(TextColor::Cyan)),
+            cursor: false,
+            buffer,
+        }
+    }
+}
+
+impl Repl {
+    /// Get the rect of the input line (after the prompt) of a repl at `rect`
+    pub fn input_rect(rect: &RectBoundary, repl: &ReplState) -> RectBoundary {
+        let prompt = (repl.prompt.chars().count() as u16).min(rect.size.0);
+
+        RectBoundary {
+            pos: (
+                rect.pos.0 + prompt,
+                rect.pos.1 + rect.size.1.saturating_sub(1),
+            ),
+            size: (rect.size.0 - prompt, 1),
+        }
+    }
+
+    /// Get the screen position of the cursor of a repl at `rect`
+    pub fn cursor_pos(rect: &RectBoundary, repl: &ReplState) -> Vec2 {
+        TextInput::cursor_pos(&Repl::input_rect(rect, repl), &repl.input)
+    }
+
+    /// Draw a repl
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `repl` - [`ReplState`]
+    pub fn render(&mut self, rect: RectBoundary, repl: &mut ReplState) -> DrawingResult {
+        if rect.size.1 == 0 {
+            return Ok((rect, self.buffer.get_changes()));
+        }
+
+        // scrollback (newest line right above the prompt)
+        let height = (rect.size.1 - 1) as usize;
+        repl.scroll = repl
+            .scroll
+            .min(repl.scrollback.len().saturating_sub(height));
+
+        let end = repl.scrollback.len() - repl.scroll;
+        let start = end.saturating_sub(height);
+        let top = rect.pos.1 + (height - (end - start)) as u16;
+
+        // rows are padded to the width (the lines shown there last frame might be longer)
+        self.buffer
+            .clear_rect(&RectBoundary::new(rect.pos, (rect.size.0, height as u16)))?;
+
+        for (i, line) in repl.scrollback[start..end].iter().enumerate() {
+            self.buffer
+                .write_line((rect.pos.0, top + i as u16), &line.truncate(rect.size.0))?;
+        }
+
+        // prompt
+        let input_rect = Repl::input_rect(&rect, repl);
+        let prompt = Line::from(TextLeaf::styled(&repl.prompt, self.prompt_style));
+
+        self.buffer.write_line(
+            (rect.pos.0, input_rect.pos.1),
+            &prompt.truncate(rect.size.0),
+        )?;
+
+        // input
+        let mut input = TextInput::new(self.buffer.clone());
+        input.cursor = self.cursor;
+        let (_, changes) = input.render(input_rect, &mut repl.input)?;
+        self.buffer.set_changes(changes);
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
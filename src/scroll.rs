@@ -0,0 +1,341 @@
+//! Scrolling widgets
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::canvas::Canvas;
+use crate::drawing::{
+    Anchor, Creatable, DrawingResult, Line, RectBoundary, StatusSegment, TextBackgroundColor,
+    TextColor,
+};
+use crate::i18n::{self, Locale};
+use crate::style::Style;
+
+/// How a [`ScrollIndicator`] shows the position
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ScrollFormat {
+    /// `12/340` (first visible line / total lines)
+    #[default]
+    Position,
+    /// `42%`, or `Top`/`Bot`/`All` at the ends
+    Percent,
+}
+
+/// Slim scroll position indicator for scrollable widgets
+pub struct ScrollIndicator {
+    pub buffer: PseudoBuffer,
+    pub format: ScrollFormat,
+    pub style: Style,
+    /// Labels at the ends in [`ScrollFormat::Percent`]: all, top and bottom
+    /// (from the locale of the buffer, see [`ScrollIndicator::localize`])
+    pub labels: [String; 3],
+}
+
+impl Creatable for ScrollIndicator {
+    fn new(buffer: PseudoBuffer) -> Self {
+        ScrollIndicator {
+            format: ScrollFormat::Position,
+            style: buffer.theme_style(
+                |t| t.status,
+                Style::DEFAULT
+                    .fg(TextColor::Black)
+                    .bg(TextBackgroundColor::White),
+            ),
+            labels: ScrollIndicator::labels(buffer.locale()),
+            buffer,
+        }
+    }
+}
+
+impl ScrollIndicator {
+    /// Get the labels at the ends in `locale`
+    fn labels(locale: &dyn Locale) -> [String; 3] {
+        [
+            i18n::text(locale, i18n::SCROLL_ALL),
+            i18n::text(locale, i18n::SCROLL_TOP),
+            i18n::text(locale, i18n::SCROLL_BOTTOM),
+        ]
+    }
+
+    /// Take the labels from another locale than the one of the buffer
+    pub fn localize(&mut self, locale: &dyn Locale) {
+        self.labels = ScrollIndicator::labels(locale);
+    }
+
+    /// Get the indicator text
+    ///
+    /// ## Arguments:
+    /// * `offset` - number of lines scrolled past
+    /// * `viewport` - number of visible lines
+    /// * `total` - number of lines
+    pub fn text(&self, offset: usize, viewport: usize, total: usize) -> String {
+        match self.format {
+            ScrollFormat::Position => format!("{}/{}", (offset + 1).min(total), total),
+            ScrollFormat::Percent => {
+                let max = total.saturating_sub(viewport);
+
+                if max == 0 {
+                    self.labels[0].clone()
+                } else if offset == 0 {
+                    self.labels[1].clone()
+                } else if offset >= max {
+                    self.labels[2].clone()
+                } else {
+                    format!("{}%", (offset * 100) / max)
+                }
+            }
+        }
+    }
+
+    /// Get the indicator as a [`StatusSegment`] (to show it in the status line)
+    pub fn segment(&self, offset: usize, viewport: usize, total: usize) -> StatusSegment {
+        StatusSegment::Text(Line::from(self.text(offset, viewport, total).as_str()))
+    }
+
+    /// Draw the indicator in a corner (or on an edge) of the scrollable widget's rect
+    ///
+    /// ## Arguments:
+    /// * `rect` - rect of the scrollable widget
+    /// * `anchor` - where in `rect` the indicator goes
+    /// * `offset` - number of lines scrolled past
+    /// * `viewport` - number of visible lines
+    /// * `total` - number of lines
+    pub fn render(
+        &mut self,
+        rect: RectBoundary,
+        anchor: Anchor,
+        offset: usize,
+        viewport: usize,
+        total: usize,
+    ) -> DrawingResult {
+        let text = format!(" {} ", self.text(offset, viewport, total));
+        let text: String = text.chars().take(rect.size.0 as usize).collect();
+
+        let own = RectBoundary::new((0, 0), (text.chars().count() as u16, 1)).anchor(
+            &rect,
+            anchor,
+            (0, 0),
+        );
+
+        self.buffer.write_str_styled(own.pos, &text, self.style)?;
+
+        // done
+        Ok((own, self.buffer.get_changes()))
+    }
+}
+
+// minimap
+/// Compressed (braille) overview of long content, with the visible part highlighted
+pub struct Minimap {
+    pub buffer: PseudoBuffer,
+    pub style: Style,
+    /// Style of the rows which are currently visible in the viewport
+    pub viewport_style: Style,
+}
+
+impl Creatable for Minimap {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Minimap {
+            style: buffer.theme_style(|t| t.muted, Style::DEFAULT.fg(TextColor::BrightBlack)),
+            viewport_style: Style::DEFAULT
+                .fg(TextColor::White)
+                .bg(TextBackgroundColor::BrightBlack),
+            buffer,
+        }
+    }
+}
+
+impl Minimap {
+    /// Get the content line shown by a row of dots
+    fn line_for_dot_row(rect: &RectBoundary, total: usize, dot_row: usize) -> usize {
+        let dot_rows = (rect.size.1 as usize * 4).max(1);
+
+        if total <= dot_rows {
+            dot_row // one line per dot row
+        } else {
+            (dot_row * total) / dot_rows
+        }
+    }
+
+    /// Get the content line at a screen position inside of the minimap
+    pub fn line_at(rect: &RectBoundary, total: usize, pos: (u16, u16)) -> Option<usize> {
+        if !rect.contains(pos) || (total == 0) {
+            return None;
+        }
+
+        let dot_row = (pos.1 - rect.pos.1) as usize * 4;
+        Some(Minimap::line_for_dot_row(rect, total, dot_row).min(total - 1))
+    }
+
+    /// Handle a click on the minimap. Returns the new scroll offset, which puts
+    /// the clicked line in the middle of the viewport.
+    pub fn handle_click(
+        state: &crate::State,
+        rect: &RectBoundary,
+        total: usize,
+        viewport: usize,
+    ) -> Option<usize> {
+        let click = state.click.as_ref()?;
+        let line = Minimap::line_at(rect, total, click.pos)?;
+
+        Some(
+            line.saturating_sub(viewport / 2)
+                .min(total.saturating_sub(viewport)),
+        )
+    }
+
+    /// Draw the minimap
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `lines` - content
+    /// * `offset` - first visible line of the content
+    /// * `viewport` - number of visible lines of the content
+    pub fn render<S: AsRef<str>>(
+        &mut self,
+        rect: RectBoundary,
+        lines: &[S],
+        offset: usize,
+        viewport: usize,
+    ) -> DrawingResult {
+        let total = lines.len();
+        let longest = lines
+            .iter()
+            .map(|l| l.as_ref().chars().count())
+            .max()
+            .unwrap_or(0);
+
+        // how many characters one dot column covers
+        let dot_cols = (rect.size.0 as usize * 2).max(1);
+        let chars_per_dot = longest.div_ceil(dot_cols).max(1);
+
+        // a dot is set if any character it covers isn't whitespace
+        let mut canvas = Canvas::new(rect.size);
+        let (dot_width, dot_height) = canvas.dot_size();
+
+        for y in 0..dot_height {
+            let line = match lines.get(Minimap::line_for_dot_row(&rect, total, y)) {
+                Some(l) => l.as_ref(),
+                None => continue,
+            };
+
+            for x in 0..dot_width {
+                if line
+                    .chars()
+                    .skip(x * chars_per_dot)
+                    .take(chars_per_dot)
+                    .any(|c| !c.is_whitespace())
+                {
+                    canvas.set(x, y, Style::DEFAULT);
+                }
+            }
+        }
+
+        for row in 0..rect.size.1 {
+            let first_line = Minimap::line_for_dot_row(&rect, total, row as usize * 4);
+            let last_line = Minimap::line_for_dot_row(&rect, total, (row as usize + 1) * 4);
+
+            // highlight rows showing lines which are in the viewport
+            let visible =
+                (first_line < offset + viewport) && (last_line.max(first_line + 1) > offset);
+            let style = if visible {
+                self.viewport_style
+            } else {
+                self.style
+            };
+
+            self.buffer.write_str_styled(
+                (rect.pos.0, rect.pos.1 + row),
+                &canvas.row(row),
+                style,
+            )?;
+        }
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
+
+// gutter
+/// How a [`Gutter`] numbers lines
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineNumbers {
+    /// Line numbers counted from the first line (starting at 1)
+    #[default]
+    Absolute,
+    /// Distance from the current line (which shows its absolute number)
+    Relative,
+}
+
+/// Right-aligned line numbers next to a scrolled view (like a [`crate::drawing::Paragraph`])
+pub struct Gutter {
+    pub buffer: PseudoBuffer,
+    pub numbers: LineNumbers,
+    pub style: Style,
+    /// Style of the current line's number
+    pub current_style: Style,
+}
+
+impl Creatable for Gutter {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Gutter {
+            numbers: LineNumbers::Absolute,
+            style: buffer.theme_style(|t| t.muted, Style::DEFAULT.fg(TextColor::BrightBlack)),
+            current_style: Style::DEFAULT.fg(TextColor::Yellow),
+            buffer,
+        }
+    }
+}
+
+impl Gutter {
+    /// Get the width a gutter needs for `total` lines (the widest number plus a space)
+    pub fn width(total: usize) -> u16 {
+        total.max(1).to_string().len() as u16 + 1
+    }
+
+    /// Draw the gutter
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `total` - number of lines of the content
+    /// * `offset` - first visible line of the content (the view's scroll offset)
+    /// * `current` - line the cursor is on
+    pub fn render(
+        &mut self,
+        rect: RectBoundary,
+        total: usize,
+        offset: usize,
+        current: Option<usize>,
+    ) -> DrawingResult {
+        // numbers end one cell before the edge
+        let width = rect.size.0.saturating_sub(1) as usize;
+
+        for row in 0..rect.size.1 {
+            let line = offset + row as usize;
+
+            if line >= total {
+                break;
+            }
+
+            let number = match (self.numbers, current) {
+                (LineNumbers::Relative, Some(current)) if line != current => line.abs_diff(current),
+                _ => line + 1,
+            };
+
+            let style = if Some(line) == current {
+                self.current_style
+            } else {
+                self.style
+            };
+
+            let text = format!("{number:>width$}");
+            let text: String = text
+                .chars()
+                .skip(text.len().saturating_sub(width))
+                .collect();
+
+            self.buffer
+                .write_str_styled((rect.pos.0, rect.pos.1 + row), &text, style)?;
+        }
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
@@ -0,0 +1,168 @@
+//! Seven-segment display
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{Creatable, DrawingResult, RectBoundary, TextColor, Vec2};
+use crate::style::Style;
+
+// segments
+const A: u8 = 1; // top
+const B: u8 = 1 << 1; // top right
+const C: u8 = 1 << 2; // bottom right
+const D: u8 = 1 << 3; // bottom
+const E: u8 = 1 << 4; // bottom left
+const F: u8 = 1 << 5; // top left
+const G: u8 = 1 << 6; // middle
+
+/// Large digits (and a few letters) in seven-segment style, for clocks, timers and counters.
+/// Every character is 3 cells wide and 5 tall, `:` and `.` are 1 cell wide.
+/// Letters are shown in whichever case can be told apart from the digits (`b` and `d`).
+pub struct SegmentDisplay {
+    pub buffer: PseudoBuffer,
+    /// Style of lit segments
+    pub style: Style,
+    /// Style of unlit segments (`None` to leave them empty)
+    pub off_style: Option<Style>,
+    /// Character segments are drawn with
+    pub fill: char,
+    /// Cells between characters
+    pub gap: u16,
+}
+
+impl Creatable for SegmentDisplay {
+    fn new(buffer: PseudoBuffer) -> Self {
+        SegmentDisplay {
+            buffer,
+            style: Style::DEFAULT.fg(TextColor::BrightRed),
+            off_style: None,
+            fill: '█',
+            gap: 1,
+        }
+    }
+}
+
+impl SegmentDisplay {
+    /// Height of the display in cells
+    pub const HEIGHT: u16 = 5;
+
+    /// Get the lit segments of a character (`None` if it can't be shown)
+    pub fn segments(char: char) -> Option<u8> {
+        Some(match char.to_ascii_uppercase() {
+            '0' | 'O' => A | B | C | D | E | F,
+            '1' => B | C,
+            '2' | 'Z' => A | B | D | E | G,
+            '3' => A | B | C | D | G,
+            '4' => B | C | F | G,
+            '5' | 'S' => A | C | D | F | G,
+            '6' => A | C | D | E | F | G,
+            '7' => A | B | C,
+            '8' => A | B | C | D | E | F | G,
+            '9' => A | B | C | D | F | G,
+            'A' => A | B | C | E | F | G,
+            'B' => C | D | E | F | G, // b, an uppercase B would look like 8
+            'C' => A | D | E | F,
+            'D' => B | C | D | E | G, // d, an uppercase D would look like 0
+            'E' => A | D | E | F | G,
+            'F' => A | E | F | G,
+            'H' => B | C | E | F | G,
+            'L' => D | E | F,
+            'P' => A | B | E | F | G,
+            'U' => B | C | D | E | F,
+            'R' => E | G,
+            'N' => C | E | G,
+            '-' => G,
+            '_' => D,
+            ' ' => 0,
+            _ => return None,
+        })
+    }
+
+    /// Get the width of `text` in cells
+    pub fn width(&self, text: &str) -> u16 {
+        let widths: Vec<u16> = text
+            .chars()
+            .map(|c| match c {
+                ':' | '.' => 1,
+                c if SegmentDisplay::segments(c).is_some() => 3,
+                _ => 0,
+            })
+            .filter(|w| *w > 0)
+            .collect();
+
+        widths.iter().sum::<u16>() + (widths.len().saturating_sub(1) as u16 * self.gap)
+    }
+
+    /// Get which of the 3x5 cells of a character are lit (and which belong to a segment at all)
+    fn cells(segments: u8) -> [[(bool, bool); 3]; 5] {
+        let lit = |mask: u8| ((segments & mask) != 0, true);
+
+        [
+            [lit(A | F), lit(A), lit(A | B)],
+            [lit(F), (false, false), lit(B)],
+            [lit(F | E | G), lit(G), lit(B | C | G)],
+            [lit(E), (false, false), lit(C)],
+            [lit(E | D), lit(D), lit(C | D)],
+        ]
+    }
+
+    /// Draw one cell (unlit cells without an `off_style` are erased, so the last text doesn't stay lit)
+    fn draw_cell(&mut self, pos: Vec2, lit: bool) -> std::io::Result<()> {
+        let style = match (lit, self.off_style) {
+            (true, _) => self.style,
+            (false, Some(off)) => off,
+            (false, None) => {
+                self.buffer.erase(pos)?;
+                return Ok(());
+            }
+        };
+
+        self.buffer
+            .write_str_styled(pos, &self.fill.to_string(), style)?;
+        Ok(())
+    }
+
+    /// Draw text (characters which can't be shown are skipped)
+    ///
+    /// ## Arguments:
+    /// * `pos` - top left corner
+    /// * `text`
+    pub fn render(&mut self, pos: Vec2, text: &str) -> DrawingResult {
+        let mut x = pos.0;
+
+        for char in text.chars() {
+            match char {
+                ':' => {
+                    self.draw_cell((x, pos.1 + 1), true)?;
+                    self.draw_cell((x, pos.1 + 3), true)?;
+                    x += 1 + self.gap;
+                }
+                '.' => {
+                    self.draw_cell((x, pos.1 + 4), true)?;
+                    x += 1 + self.gap;
+                }
+                c => {
+                    let Some(segments) = SegmentDisplay::segments(c) else {
+                        continue;
+                    };
+
+                    for (dy, row) in SegmentDisplay::cells(segments).iter().enumerate() {
+                        for (dx, (lit, part)) in row.iter().enumerate() {
+                            if *part {
+                                self.draw_cell((x + dx as u16, pos.1 + dy as u16), *lit)?;
+                            }
+                        }
+                    }
+
+                    x += 3 + self.gap;
+                }
+            }
+        }
+
+        // done
+        Ok((
+            RectBoundary {
+                pos,
+                size: (self.width(text), SegmentDisplay::HEIGHT),
+            },
+            self.buffer.get_changes(),
+        ))
+    }
+}
@@ -0,0 +1,283 @@
+//! Selection of list-like widgets
+//!
+//! A [`SelectionModel`] keeps the cursor and the selected items of a list, a table
+//! (see [`crate::table::TableState::selection`]) or anything else showing items with ids,
+//! and handles the keys and clicks which change them the same way everywhere.
+use std::collections::BTreeSet;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// How many items a [`SelectionModel`] can select
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum SelectionMode {
+    /// Nothing is selected, only the cursor moves
+    None,
+    /// The item under the cursor is selected
+    #[default]
+    Single,
+    /// Space toggles the item under the cursor, Shift+arrows select ranges
+    Multiple,
+}
+
+/// Items which were selected or deselected by one change, passed to the `on_change` callback
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SelectionChange {
+    pub added: Vec<usize>,
+    pub removed: Vec<usize>,
+}
+
+/// Cursor and selected items (by id) of a list-like widget
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SelectionModel {
+    pub mode: SelectionMode,
+    /// Id of the item under the cursor
+    pub cursor: Option<usize>,
+    /// Number of items moved by Page Up/Page Down
+    pub page: usize,
+    selected: BTreeSet<usize>,
+    /// Where a Shift range starts
+    anchor: Option<usize>,
+}
+
+impl Default for SelectionModel {
+    fn default() -> Self {
+        SelectionModel::new(SelectionMode::Single)
+    }
+}
+
+impl SelectionModel {
+    /// Create a new [`SelectionModel`] with nothing selected
+    pub fn new(mode: SelectionMode) -> Self {
+        SelectionModel {
+            mode,
+            cursor: None,
+            page: 10,
+            selected: BTreeSet::new(),
+            anchor: None,
+        }
+    }
+
+    /// Check if the item `id` is selected
+    pub fn is_selected(&self, id: usize) -> bool {
+        self.selected.contains(&id)
+    }
+
+    /// Get the ids of the selected items (sorted)
+    pub fn selected(&self) -> impl Iterator<Item = usize> + '_ {
+        self.selected.iter().copied()
+    }
+
+    /// Get the number of selected items
+    pub fn len(&self) -> usize {
+        self.selected.len()
+    }
+
+    /// Check if nothing is selected
+    pub fn is_empty(&self) -> bool {
+        self.selected.is_empty()
+    }
+
+    /// Replace the selection, telling `on_change` what changed
+    fn set(&mut self, selected: BTreeSet<usize>, on_change: &mut dyn FnMut(SelectionChange)) {
+        let selected = match self.mode {
+            SelectionMode::None => BTreeSet::new(),
+            SelectionMode::Single => selected.into_iter().take(1).collect(),
+            SelectionMode::Multiple => selected,
+        };
+
+        let change = SelectionChange {
+            added: selected.difference(&self.selected).copied().collect(),
+            removed: self.selected.difference(&selected).copied().collect(),
+        };
+
+        self.selected = selected;
+
+        if !change.added.is_empty() || !change.removed.is_empty() {
+            on_change(change);
+        }
+    }
+
+    /// Select only the item `id` (and put the cursor on it)
+    pub fn select(&mut self, id: usize, on_change: &mut dyn FnMut(SelectionChange)) {
+        self.cursor = Some(id);
+        self.anchor = Some(id);
+        self.set(BTreeSet::from([id]), on_change);
+    }
+
+    /// Select or deselect the item `id` (only selects it in single mode)
+    pub fn toggle(&mut self, id: usize, on_change: &mut dyn FnMut(SelectionChange)) {
+        self.cursor = Some(id);
+        self.anchor = Some(id);
+
+        let mut selected = self.selected.clone();
+
+        match self.mode {
+            SelectionMode::Multiple if !selected.insert(id) => {
+                selected.remove(&id);
+            }
+            SelectionMode::Multiple => (),
+            _ => selected = BTreeSet::from([id]),
+        }
+
+        self.set(selected, on_change);
+    }
+
+    /// Select the items from the anchor (the last item selected on its own) to `id`
+    ///
+    /// ## Arguments:
+    /// * `id`
+    /// * `ids` - ids of the items in the order they are shown
+    /// * `on_change`
+    pub fn select_range(
+        &mut self,
+        id: usize,
+        ids: &[usize],
+        on_change: &mut dyn FnMut(SelectionChange),
+    ) {
+        let anchor = self.anchor.or(self.cursor).unwrap_or(id);
+        self.cursor = Some(id);
+
+        let (Some(a), Some(b)) = (
+            ids.iter().position(|i| *i == anchor),
+            ids.iter().position(|i| *i == id),
+        ) else {
+            return self.select(id, on_change);
+        };
+
+        self.anchor = Some(anchor);
+        self.set(
+            ids[a.min(b)..=a.max(b)].iter().copied().collect(),
+            on_change,
+        );
+    }
+
+    /// Select every item (multiple mode)
+    pub fn select_all(&mut self, ids: &[usize], on_change: &mut dyn FnMut(SelectionChange)) {
+        if self.mode == SelectionMode::Multiple {
+            self.set(ids.iter().copied().collect(), on_change);
+        }
+    }
+
+    /// Deselect everything
+    pub fn clear(&mut self, on_change: &mut dyn FnMut(SelectionChange)) {
+        self.set(BTreeSet::new(), on_change);
+    }
+
+    /// Drop selected items which aren't in `ids` anymore (after items were removed)
+    pub fn retain(&mut self, ids: &[usize], on_change: &mut dyn FnMut(SelectionChange)) {
+        let selected = self
+            .selected
+            .iter()
+            .copied()
+            .filter(|id| ids.contains(id))
+            .collect();
+
+        if self.cursor.is_some_and(|c| !ids.contains(&c)) {
+            self.cursor = None;
+        }
+
+        self.set(selected, on_change);
+    }
+
+    /// Move the cursor by `by` items. In single mode the selection follows the cursor.
+    ///
+    /// ## Arguments:
+    /// * `by`
+    /// * `ids` - ids of the items in the order they are shown
+    /// * `extend` - select the range from the anchor (Shift, multiple mode)
+    /// * `on_change`
+    pub fn move_cursor(
+        &mut self,
+        by: isize,
+        ids: &[usize],
+        extend: bool,
+        on_change: &mut dyn FnMut(SelectionChange),
+    ) {
+        if ids.is_empty() {
+            self.cursor = None;
+            return;
+        }
+
+        let i = match self.cursor.and_then(|c| ids.iter().position(|i| *i == c)) {
+            Some(i) => i.saturating_add_signed(by).min(ids.len() - 1),
+            None => 0,
+        };
+        let id = ids[i];
+
+        match self.mode {
+            SelectionMode::None => self.cursor = Some(id),
+            SelectionMode::Single => self.select(id, on_change),
+            SelectionMode::Multiple if extend => self.select_range(id, ids, on_change),
+            SelectionMode::Multiple => {
+                self.cursor = Some(id);
+                self.anchor = Some(id);
+            }
+        }
+    }
+
+    /// Handle a key event. Returns `true` if the key was used.
+    ///
+    /// * Up/Down/Page Up/Page Down/Home/End move the cursor (with Shift: select the range)
+    /// * Space toggles the item under the cursor
+    /// * Ctrl+A selects every item
+    ///
+    /// ## Arguments:
+    /// * `event`
+    /// * `ids` - ids of the items in the order they are shown
+    /// * `on_change`
+    pub fn handle_key(
+        &mut self,
+        event: KeyEvent,
+        ids: &[usize],
+        on_change: &mut dyn FnMut(SelectionChange),
+    ) -> bool {
+        let extend = event.modifiers.contains(KeyModifiers::SHIFT);
+        let page = self.page.max(1) as isize;
+
+        match event.code {
+            KeyCode::Up => self.move_cursor(-1, ids, extend, on_change),
+            KeyCode::Down => self.move_cursor(1, ids, extend, on_change),
+            KeyCode::PageUp => self.move_cursor(-page, ids, extend, on_change),
+            KeyCode::PageDown => self.move_cursor(page, ids, extend, on_change),
+            KeyCode::Home => self.move_cursor(isize::MIN / 2, ids, extend, on_change),
+            KeyCode::End => self.move_cursor(isize::MAX / 2, ids, extend, on_change),
+            KeyCode::Char(' ') if self.mode != SelectionMode::None => match self.cursor {
+                Some(id) => self.toggle(id, on_change),
+                None => return false,
+            },
+            KeyCode::Char('a')
+                if event.modifiers.contains(KeyModifiers::CONTROL)
+                    && (self.mode == SelectionMode::Multiple) =>
+            {
+                self.select_all(ids, on_change)
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Handle a click on the item `id` (Ctrl toggles it, Shift selects the range)
+    ///
+    /// ## Arguments:
+    /// * `id`
+    /// * `modifiers` - modifiers of the click
+    /// * `ids` - ids of the items in the order they are shown
+    /// * `on_change`
+    pub fn handle_click(
+        &mut self,
+        id: usize,
+        modifiers: KeyModifiers,
+        ids: &[usize],
+        on_change: &mut dyn FnMut(SelectionChange),
+    ) {
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            self.select_range(id, ids, on_change);
+        } else if modifiers.contains(KeyModifiers::CONTROL) {
+            self.toggle(id, on_change);
+        } else {
+            self.select(id, on_change);
+        }
+    }
+}
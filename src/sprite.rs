@@ -0,0 +1,117 @@
+//! Sprites
+//!
+//! Small grids of cells with transparency, drawn with [`crate::buffer::PseudoBuffer::blit`].
+//! For simple games and animations.
+use crate::buffer::BufCell;
+use crate::drawing::Vec2;
+use crate::style::Style;
+
+/// A grid of cells where `None` cells are transparent (whatever is below them stays).
+/// Every cell holds one narrow character.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sprite {
+    width: u16,
+    height: u16,
+    /// Cells row by row
+    cells: Vec<Option<BufCell>>,
+}
+
+impl Sprite {
+    /// Create a new fully transparent [`Sprite`]
+    pub fn new(size: Vec2) -> Self {
+        Sprite {
+            width: size.0,
+            height: size.1,
+            cells: vec![None; size.0 as usize * size.1 as usize],
+        }
+    }
+
+    /// Create a [`Sprite`] from rows of text, every character getting `style`.
+    /// Characters equal to `transparent` are left transparent, and short rows are padded with them.
+    ///
+    /// ## Arguments:
+    /// * `rows`
+    /// * `style`
+    /// * `transparent` - character which marks transparent cells (like `' '` or `'.'`)
+    pub fn from_rows(rows: &[&str], style: Style, transparent: char) -> Self {
+        let width = rows.iter().map(|r| r.chars().count()).max().unwrap_or(0) as u16;
+        let mut sprite = Sprite::new((width, rows.len() as u16));
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, char) in row.chars().enumerate() {
+                if char != transparent {
+                    sprite.set((x as u16, y as u16), Some(BufCell::styled(char, style)));
+                }
+            }
+        }
+
+        sprite
+    }
+
+    /// Get the size as (width, height)
+    pub fn size(&self) -> Vec2 {
+        (self.width, self.height)
+    }
+
+    /// Get the index of a cell (`None` if it's outside of the sprite)
+    fn index(&self, pos: Vec2) -> Option<usize> {
+        ((pos.0 < self.width) && (pos.1 < self.height))
+            .then_some(pos.1 as usize * self.width as usize + pos.0 as usize)
+    }
+
+    /// Get a cell (`None` if it's transparent or outside of the sprite)
+    pub fn get(&self, pos: Vec2) -> Option<&BufCell> {
+        self.cells[self.index(pos)?].as_ref()
+    }
+
+    /// Set a cell (`None` to make it transparent). Positions outside of the sprite are ignored.
+    pub fn set(&mut self, pos: Vec2, cell: Option<BufCell>) {
+        if let Some(i) = self.index(pos) {
+            self.cells[i] = cell;
+        }
+    }
+
+    /// Build a new sprite of `size` where every cell comes from `source(x, y)` of this one
+    fn remap(&self, size: Vec2, source: impl Fn(u16, u16) -> Vec2) -> Self {
+        let mut sprite = Sprite::new(size);
+
+        for y in 0..size.1 {
+            for x in 0..size.0 {
+                sprite.set((x, y), self.get(source(x, y)).cloned());
+            }
+        }
+
+        sprite
+    }
+
+    /// Mirror left to right (characters themselves aren't mirrored)
+    pub fn flip_horizontal(&self) -> Self {
+        self.remap(self.size(), |x, y| (self.width - 1 - x, y))
+    }
+
+    /// Mirror top to bottom (characters themselves aren't mirrored)
+    pub fn flip_vertical(&self) -> Self {
+        self.remap(self.size(), |x, y| (x, self.height - 1 - y))
+    }
+
+    /// Rotate by 90 degrees clockwise (width and height swap).
+    /// Keep in mind cells are about twice as tall as wide, so the result looks stretched.
+    pub fn rotate_cw(&self) -> Self {
+        self.remap((self.height, self.width), |x, y| (y, self.height - 1 - x))
+    }
+
+    /// Rotate by 90 degrees counterclockwise (width and height swap)
+    pub fn rotate_ccw(&self) -> Self {
+        self.remap((self.height, self.width), |x, y| (self.width - 1 - y, x))
+    }
+
+    /// Get every opaque cell with its position inside of the sprite
+    pub fn cells(&self) -> impl Iterator<Item = (Vec2, &BufCell)> {
+        let width = self.width.max(1) as usize;
+
+        self.cells.iter().enumerate().filter_map(move |(i, cell)| {
+            cell.as_ref()
+                .map(|cell| (((i % width) as u16, (i / width) as u16), cell))
+        })
+    }
+}
@@ -0,0 +1,199 @@
+//! SSH sessions
+//!
+//! Serve a TUI over SSH: every connection gets a [`SshSession`] and its own
+//! [`crate::Frame`] (see [`crate::Frame::with_output`]) which writes into the session.
+//! The session keeps track of the size of the PTY and turns channel data into events.
+//! It doesn't depend on an SSH library, with russh the handler looks like this:
+//!
+//! ```ignore
+//! async fn pty_request(&mut self, channel: ChannelId, term: &str, cols: u32, rows: u32, ..) {
+//!     self.session.pty_request(cols, rows);
+//! }
+//!
+//! async fn shell_request(&mut self, channel: ChannelId, session: &mut Session) {
+//!     self.frame = Some(Frame::with_output(self.session.output(), self.session.size(), draw));
+//!     self.session.begin()?;
+//!     self.step_and_send(channel, session)?;
+//! }
+//!
+//! async fn data(&mut self, channel: ChannelId, data: &[u8], session: &mut Session) {
+//!     for event in self.session.input(data) {
+//!         self.frame.handle_event(event)?;
+//!     }
+//!     session.data(channel, self.session.take_output().into());
+//! }
+//!
+//! // every few milliseconds while no data arrives
+//! fn tick(&mut self, channel: ChannelId, session: &mut Session) {
+//!     for event in self.session.expired_input() {
+//!         self.frame.handle_event(event)?;
+//!     }
+//!     session.data(channel, self.session.take_output().into());
+//! }
+//!
+//! async fn window_change_request(&mut self, channel: ChannelId, cols: u32, rows: u32, ..) {
+//!     self.frame.handle_event(self.session.window_change(cols, rows))?;
+//! }
+//! ```
+use std::io::{Result as IOResult, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{
+    DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture, Event,
+};
+use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, Command};
+
+use crate::drawing::Vec2;
+use crate::web::{parse_input, parse_input_partial, WebOutput};
+
+/// State of one SSH connection
+pub struct SshSession {
+    output: WebOutput,
+    size: Vec2,
+    /// Start of a character or escape sequence which was cut off at the end of the last data
+    pending: Vec<u8>,
+    /// When the pending data became a lone escape
+    escape_since: Option<Instant>,
+}
+
+impl Default for SshSession {
+    fn default() -> Self {
+        SshSession::new()
+    }
+}
+
+impl SshSession {
+    /// How long an escape at the end of the data waits for the rest of a sequence
+    /// before it's the escape key (see [`SshSession::expired_input`])
+    pub const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+
+    /// Create a new [`SshSession`] (80x24 until the client asks for a PTY)
+    pub fn new() -> SshSession {
+        SshSession {
+            output: WebOutput::new(),
+            size: (80, 24),
+            pending: Vec::new(),
+            escape_since: None,
+        }
+    }
+
+    /// Get the size of the PTY
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Get the output to create the [`crate::Frame`] of the connection with
+    pub fn output(&self) -> WebOutput {
+        self.output.clone()
+    }
+
+    /// Take everything written since the last call (to send it over the channel)
+    pub fn take_output(&self) -> Vec<u8> {
+        self.output.take().into_bytes()
+    }
+
+    /// The client asked for a PTY (before the frame is created)
+    pub fn pty_request(&mut self, cols: u32, rows: u32) {
+        self.size = (clamp(cols), clamp(rows));
+    }
+
+    /// The window of the client was resized. Returns the event to pass to the frame.
+    pub fn window_change(&mut self, cols: u32, rows: u32) -> Event {
+        self.pty_request(cols, rows);
+        Event::Resize(self.size.0, self.size.1)
+    }
+
+    /// Turn data from the client into events (see [`crate::web::parse_input`]).
+    /// Characters and escape sequences cut off at the end are kept for the next data
+    /// (a lone escape until [`SshSession::expired_input`] gives it up).
+    pub fn input(&mut self, data: &[u8]) -> Vec<Event> {
+        self.pending.extend_from_slice(data);
+
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            // an incomplete character at the end waits for the rest
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            // invalid bytes are replaced
+            Err(_) => self.pending.len(),
+        };
+
+        let rest = self.pending.split_off(valid);
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+
+        // an incomplete escape sequence waits for the rest too
+        let (events, parsed) = parse_input_partial(&text);
+        self.pending = text.as_bytes()[parsed..].to_vec();
+        self.pending.extend(rest);
+
+        self.escape_since = if self.pending == b"\x1b" {
+            Some(Instant::now())
+        } else {
+            None
+        };
+
+        events
+    }
+
+    /// Get the escape key if an escape at the end of the last data waited for
+    /// [`SshSession::ESCAPE_TIMEOUT`] without the rest of a sequence (call it
+    /// regularly while no data arrives)
+    pub fn expired_input(&mut self) -> Vec<Event> {
+        match self.escape_since {
+            Some(since) if since.elapsed() >= SshSession::ESCAPE_TIMEOUT => {
+                self.escape_since = None;
+                let text = String::from_utf8_lossy(&std::mem::take(&mut self.pending)).into_owned();
+                parse_input(&text)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Switch the client to the alternate screen and turn on mouse and focus reporting
+    /// (like [`crate::Frame::open_env`] does for the local terminal)
+    pub fn begin(&self) -> IOResult<()> {
+        self.write_commands(&[
+            &EnterAlternateScreen,
+            &cursor::MoveTo(0, 0),
+            &EnableMouseCapture,
+            &EnableFocusChange,
+        ])
+    }
+
+    /// Undo [`SshSession::begin`] (before closing the channel)
+    pub fn end(&self) -> IOResult<()> {
+        self.write_commands(&[
+            &DisableFocusChange,
+            &DisableMouseCapture,
+            &LeaveAlternateScreen,
+            &cursor::Show,
+        ])
+    }
+
+    fn write_commands(&self, commands: &[&dyn AnsiCommand]) -> IOResult<()> {
+        let mut ansi = String::new();
+
+        for command in commands {
+            command.ansi(&mut ansi)?;
+        }
+
+        self.output.clone().write_all(ansi.as_bytes())
+    }
+}
+
+/// [`Command`] as an object (only the escape sequence is needed, never the WinAPI)
+trait AnsiCommand {
+    fn ansi(&self, out: &mut String) -> IOResult<()>;
+}
+
+impl<C: Command> AnsiCommand for C {
+    fn ansi(&self, out: &mut String) -> IOResult<()> {
+        self.write_ansi(out)
+            .map_err(|_| std::io::Error::other("failed to format command"))
+    }
+}
+
+/// Clamp a size from the client to what fits in a cell position
+fn clamp(n: u32) -> u16 {
+    n.clamp(1, u16::MAX as u32) as u16
+}
@@ -0,0 +1,85 @@
+//! Piped input
+//!
+//! For pipelines like `tail -f app.log | myapp`: stdin is the pipe, so keys are
+//! read from the controlling terminal (`/dev/tty`) instead, and the lines coming
+//! through the pipe are sent to the frame as [`UserEvent`]s carrying a [`StdinEvent`].
+use std::io::{BufRead, IsTerminal};
+use std::sync::mpsc::Sender;
+use std::thread::JoinHandle;
+
+use crate::event::UserEvent;
+
+/// Payload of the [`UserEvent`]s sent by a [`StdinSource`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum StdinEvent {
+    /// A line was read (without the line ending)
+    Line(String),
+    /// The other end of the pipe was closed (or reading failed)
+    Closed,
+}
+
+/// Check if stdin is a pipe or file instead of the terminal
+pub fn is_piped() -> bool {
+    !std::io::stdin().is_terminal()
+}
+
+/// Open the controlling terminal (used for keys while stdin is piped).
+/// Fails if the app has no terminal at all (like when started by a service).
+pub fn controlling_terminal() -> std::io::Result<std::fs::File> {
+    #[cfg(windows)]
+    let path = "CONIN$";
+    #[cfg(not(windows))]
+    let path = "/dev/tty";
+
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+}
+
+/// Reads lines from piped stdin on its own thread
+pub struct StdinSource {
+    handle: JoinHandle<()>,
+}
+
+impl StdinSource {
+    /// Start reading stdin. Returns `None` if stdin is the terminal (nothing is
+    /// piped in). Fails if there is no controlling terminal to read keys from.
+    ///
+    /// ## Arguments:
+    /// * `sender` - where lines go (see [`crate::Frame::event_sender`])
+    /// * `name` - name of the events
+    pub fn spawn(sender: Sender<UserEvent>, name: &str) -> std::io::Result<Option<Self>> {
+        if !is_piped() {
+            return Ok(None);
+        }
+
+        // keys come from here now (crossterm opens it on its own, this makes sure it exists)
+        controlling_terminal()?;
+
+        let name = name.to_string();
+        let handle = std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+
+                if sender
+                    .send(UserEvent::new(&name, StdinEvent::Line(line)))
+                    .is_err()
+                {
+                    return; // the frame is gone
+                }
+            }
+
+            let _ = sender.send(UserEvent::new(&name, StdinEvent::Closed));
+        });
+
+        Ok(Some(StdinSource { handle }))
+    }
+
+    /// Check if the pipe was closed
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
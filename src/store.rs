@@ -0,0 +1,195 @@
+//! Widget state store
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+/// Turns a stored value back into JSON (see [`StateStore::get_persistent`])
+type SaveFn = fn(&dyn Any) -> Option<serde_json::Value>;
+
+/// Saved values of a [`StateStore`] (see [`crate::Frame::save_session`])
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Id of the focused widget
+    pub focused: Option<String>,
+    /// Screen the application showed (see [`crate::State::screen`])
+    pub screen: Option<String>,
+    pub values: Vec<SessionValue>,
+}
+
+/// A saved value of a [`Session`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionValue {
+    pub id: String,
+    /// Name of the type of the value (values of other types with the same id are kept apart)
+    pub type_name: String,
+    pub value: serde_json::Value,
+}
+
+/// Per-widget state kept between frames, keyed by component id and type
+/// (the same id can hold a [`crate::input::TextInputState`] and a scroll offset, for example).
+///
+/// ```ignore
+/// let input = state.store.get_or_default::<TextInputState>("name");
+/// ```
+#[derive(Default)]
+pub struct StateStore {
+    values: HashMap<(String, TypeId), Box<dyn Any>>,
+    /// Values which are saved in sessions, with their type name
+    persistent: HashMap<(String, TypeId), (&'static str, SaveFn)>,
+    /// Values of a loaded session which weren't asked for yet, by (id, type name)
+    saved: HashMap<(String, String), serde_json::Value>,
+}
+
+impl StateStore {
+    /// Get the `T` of `id`, inserting `T::default()` if there is none
+    pub fn get_or_default<T: Default + 'static>(&mut self, id: &str) -> &mut T {
+        self.get_or_insert_with(id, T::default)
+    }
+
+    /// Get the `T` of `id`, inserting `init()` if there is none
+    pub fn get_or_insert_with<T: 'static>(&mut self, id: &str, init: impl FnOnce() -> T) -> &mut T {
+        self.values
+            .entry((id.to_string(), TypeId::of::<T>()))
+            .or_insert_with(|| Box::new(init()))
+            .downcast_mut()
+            .expect("value is stored under its own type")
+    }
+
+    /// Get the `T` of `id`
+    pub fn get<T: 'static>(&self, id: &str) -> Option<&T> {
+        self.values
+            .get(&(id.to_string(), TypeId::of::<T>()))
+            .and_then(|v| v.downcast_ref())
+    }
+
+    /// Get the `T` of `id` mutably
+    pub fn get_mut<T: 'static>(&mut self, id: &str) -> Option<&mut T> {
+        self.values
+            .get_mut(&(id.to_string(), TypeId::of::<T>()))
+            .and_then(|v| v.downcast_mut())
+    }
+
+    /// Set the `T` of `id`, returning the old one
+    pub fn insert<T: 'static>(&mut self, id: &str, value: T) -> Option<T> {
+        self.values
+            .insert((id.to_string(), TypeId::of::<T>()), Box::new(value))
+            .and_then(|v| v.downcast().ok())
+            .map(|v| *v)
+    }
+
+    /// Remove the `T` of `id`
+    pub fn remove<T: 'static>(&mut self, id: &str) -> Option<T> {
+        self.values
+            .remove(&(id.to_string(), TypeId::of::<T>()))
+            .and_then(|v| v.downcast().ok())
+            .map(|v| *v)
+    }
+
+    /// Remove every value of `id` (when the widget is gone for good)
+    pub fn remove_all(&mut self, id: &str) {
+        self.values.retain(|(key, _), _| key != id);
+        self.persistent.retain(|(key, _), _| key != id);
+        self.saved.retain(|(key, _), _| key != id);
+    }
+
+    /// Remove everything
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.persistent.clear();
+        self.saved.clear();
+    }
+
+    /// Like [`StateStore::get_or_default`], but the value is saved in sessions
+    /// (see [`StateStore::to_session`]). After a session was loaded, its value is
+    /// used instead of `T::default()` (or the value there was before loading).
+    ///
+    /// The states of the built-in widgets (like [`crate::input::TextInputState`],
+    /// [`crate::table::TableState`], [`crate::editor::EditorState`] and
+    /// [`crate::wizard::WizardState`]) are made persistent by keeping them here:
+    ///
+    /// ```ignore
+    /// let files = state.store.get_persistent::<TableState>("files");
+    /// ```
+    pub fn get_persistent<T>(&mut self, id: &str) -> &mut T
+    where
+        T: Default + Serialize + DeserializeOwned + 'static,
+    {
+        let key = (id.to_string(), TypeId::of::<T>());
+        let type_name = std::any::type_name::<T>();
+
+        self.persistent
+            .entry(key.clone())
+            .or_insert((type_name, |value| {
+                serde_json::to_value(value.downcast_ref::<T>()?).ok()
+            }));
+
+        if !self.values.contains_key(&key) {
+            let value: T = self
+                .saved
+                .remove(&(id.to_string(), type_name.to_string()))
+                .and_then(|v| serde_json::from_value(v).ok())
+                .unwrap_or_default();
+
+            self.values.insert(key.clone(), Box::new(value));
+        }
+
+        self.values
+            .get_mut(&key)
+            .and_then(|v| v.downcast_mut())
+            .expect("value is stored under its own type")
+    }
+
+    /// Save every value which was asked for with [`StateStore::get_persistent`]
+    /// (and the ones of a loaded session which weren't asked for yet)
+    pub fn to_session(&self) -> Session {
+        let mut values: Vec<SessionValue> = self
+            .saved
+            .iter()
+            .map(|((id, type_name), value)| SessionValue {
+                id: id.clone(),
+                type_name: type_name.clone(),
+                value: value.clone(),
+            })
+            .collect();
+
+        for (key, (type_name, save)) in &self.persistent {
+            if let Some(value) = self.values.get(key).and_then(|v| save(v.as_ref())) {
+                values.push(SessionValue {
+                    id: key.0.clone(),
+                    type_name: type_name.to_string(),
+                    value,
+                });
+            }
+        }
+
+        // same order every time
+        values.sort_by(|a, b| (&a.id, &a.type_name).cmp(&(&b.id, &b.type_name)));
+
+        Session {
+            focused: None,
+            screen: None,
+            values,
+        }
+    }
+
+    /// Load the values of a session. Persistent values are replaced by the saved
+    /// ones the next time they're asked for.
+    pub fn load_session(&mut self, session: Session) {
+        self.saved = session
+            .values
+            .into_iter()
+            .map(|v| ((v.id, v.type_name), v.value))
+            .collect();
+
+        for (key, (type_name, _)) in &self.persistent {
+            if self
+                .saved
+                .contains_key(&(key.0.clone(), type_name.to_string()))
+            {
+                self.values.remove(key);
+            }
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! Cell styling
+use serde::{Deserialize, Serialize};
+
+use crate::drawing::{TextAttribute, TextBackgroundColor, TextColor, TextCommand};
+
+/// Set of [`TextAttribute`]s (serialized as a list of attribute names)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "Vec<TextAttribute>", from = "Vec<TextAttribute>")]
+pub struct Attributes(u16);
+
+impl Attributes {
+    pub const NONE: Attributes = Attributes(0);
+
+    /// Every attribute, in the order they're emitted
+    const ALL: [TextAttribute; 8] = [
+        TextAttribute::Bold,
+        TextAttribute::Dim,
+        TextAttribute::Italic,
+        TextAttribute::Underline,
+        TextAttribute::Blink,
+        TextAttribute::Swap,
+        TextAttribute::Hidden,
+        TextAttribute::Strikethrough,
+    ];
+
+    /// Get a copy of the set with `attr` added
+    pub const fn with(self, attr: TextAttribute) -> Attributes {
+        Attributes(self.0 | (1 << attr as u8))
+    }
+
+    /// Add an attribute
+    pub fn insert(&mut self, attr: TextAttribute) {
+        self.0 |= 1 << attr as u8;
+    }
+
+    /// Remove an attribute
+    pub fn remove(&mut self, attr: TextAttribute) {
+        self.0 &= !(1 << attr as u8);
+    }
+
+    /// Check if an attribute is in the set
+    pub fn contains(&self, attr: TextAttribute) -> bool {
+        (self.0 & (1 << attr as u8)) != 0
+    }
+
+    /// Check if the set has no attributes
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterate over every attribute in the set
+    pub fn iter(&self) -> impl Iterator<Item = TextAttribute> + '_ {
+        Attributes::ALL.into_iter().filter(|a| self.contains(*a))
+    }
+}
+
+impl From<Attributes> for Vec<TextAttribute> {
+    fn from(value: Attributes) -> Self {
+        value.iter().collect()
+    }
+}
+
+impl From<Vec<TextAttribute>> for Attributes {
+    fn from(value: Vec<TextAttribute>) -> Self {
+        value.into_iter().fold(Attributes::NONE, Attributes::with)
+    }
+}
+
+impl From<TextAttribute> for Attributes {
+    fn from(value: TextAttribute) -> Self {
+        Attributes::NONE.with(value)
+    }
+}
+
+/// Extended underline styles (`CSI 4:n m`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UnderlineStyle {
+    Single = 1,
+    Double = 2,
+    Curly = 3,
+    Dotted = 4,
+    Dashed = 5,
+}
+
+/// Check if the terminal (probably) supports extended underline styles and colors.
+/// Terminals which don't would show garbage or nothing, so we fall back to a plain underline.
+pub fn supports_extended_underline() -> bool {
+    let term = std::env::var("TERM").unwrap_or_default();
+    let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+
+    ["kitty", "wezterm", "foot", "alacritty", "contour"]
+        .iter()
+        .any(|t| term.contains(t) | term_program.to_lowercase().contains(t))
+        | std::env::var("VTE_VERSION").is_ok()
+}
+
+/// Get the 256 color palette index of a [`TextColor`]
+fn palette_index(color: TextColor) -> u8 {
+    let code = color as u8;
+
+    if code >= 90 {
+        code - 90 + 8 // bright
+    } else {
+        code - 30
+    }
+}
+
+/// Style of a cell (every field is optional when deserializing)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Style {
+    /// Text color (terminal default if `None`)
+    pub fg: Option<TextColor>,
+    /// Background color (terminal default if `None`)
+    pub bg: Option<TextBackgroundColor>,
+    pub attributes: Attributes,
+    /// Underline style (in addition to [`TextAttribute::Underline`], which is always single)
+    pub underline: Option<UnderlineStyle>,
+    /// Underline color (terminal default if `None`)
+    pub underline_color: Option<TextColor>,
+}
+
+impl Style {
+    /// Terminal default style
+    pub const DEFAULT: Style = Style {
+        fg: None,
+        bg: None,
+        attributes: Attributes::NONE,
+        underline: None,
+        underline_color: None,
+    };
+
+    /// Set the text color
+    pub fn fg(mut self, color: TextColor) -> Style {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Set the background color
+    pub fn bg(mut self, color: TextBackgroundColor) -> Style {
+        self.bg = Some(color);
+        self
+    }
+
+    /// Add an attribute
+    pub fn attr(mut self, attr: TextAttribute) -> Style {
+        self.attributes.insert(attr);
+        self
+    }
+
+    /// Set the underline style
+    pub fn underline(mut self, underline: UnderlineStyle) -> Style {
+        self.underline = Some(underline);
+        self
+    }
+
+    /// Set the underline color
+    pub fn underline_color(mut self, color: TextColor) -> Style {
+        self.underline_color = Some(color);
+        self
+    }
+
+    /// Get the SGR escape sequence which switches to this style (from any other style)
+    pub fn to_ansi(&self) -> String {
+        self.to_ansi_with(true)
+    }
+
+    /// Like [`Style::to_ansi`], but extended underlines can be disabled
+    /// (they become a plain underline, and the underline color is dropped)
+    pub fn to_ansi_with(&self, extended_underline: bool) -> String {
+        let mut codes = vec![(TextCommand::Reset as u8).to_string()];
+
+        for attr in self.attributes.iter() {
+            codes.push((attr as u8).to_string());
+        }
+
+        if let Some(underline) = self.underline {
+            if extended_underline {
+                codes.push(format!("4:{}", underline as u8));
+            } else if !self.attributes.contains(TextAttribute::Underline) {
+                codes.push((TextAttribute::Underline as u8).to_string());
+            }
+        }
+
+        if let (Some(color), true) = (self.underline_color, extended_underline) {
+            codes.push(format!("58;5;{}", palette_index(color)));
+        }
+
+        if let Some(fg) = self.fg {
+            codes.push((fg as u8).to_string());
+        }
+
+        if let Some(bg) = self.bg {
+            codes.push((bg as u8).to_string());
+        }
+
+        format!("\x1b[{}m", codes.join(";"))
+    }
+}
+
+/// How an overlay recolors the cells beneath it (see [`Style::blend`])
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Blend {
+    /// Bright colors become normal, normal colors become (bright) black, and text is dimmed
+    Darken,
+    /// Every color becomes a shade of gray
+    Desaturate,
+}
+
+/// Get the gray of a [`TextColor`] with about the same brightness
+fn gray(color: TextColor) -> TextColor {
+    match color {
+        TextColor::Black => TextColor::Black,
+        TextColor::BrightWhite | TextColor::BrightYellow | TextColor::BrightCyan => {
+            TextColor::BrightWhite
+        }
+        TextColor::White | TextColor::Yellow | TextColor::Cyan | TextColor::BrightGreen => {
+            TextColor::White
+        }
+        _ => TextColor::BrightBlack,
+    }
+}
+
+/// Get the darker version of a [`TextColor`]
+fn darker(color: TextColor) -> TextColor {
+    match color {
+        TextColor::BrightRed => TextColor::Red,
+        TextColor::BrightGreen => TextColor::Green,
+        TextColor::BrightYellow => TextColor::Yellow,
+        TextColor::BrightBlue => TextColor::Blue,
+        TextColor::BrightMagenta => TextColor::Magenta,
+        TextColor::BrightCyan => TextColor::Cyan,
+        TextColor::BrightWhite => TextColor::White,
+        TextColor::Black => TextColor::Black,
+        _ => TextColor::BrightBlack,
+    }
+}
+
+/// Get the [`TextColor`] of a [`TextBackgroundColor`] (codes are 10 apart)
+fn bg_as_fg(color: TextBackgroundColor) -> TextColor {
+    match color {
+        TextBackgroundColor::Black => TextColor::Black,
+        TextBackgroundColor::Red => TextColor::Red,
+        TextBackgroundColor::Green => TextColor::Green,
+        TextBackgroundColor::Yellow => TextColor::Yellow,
+        TextBackgroundColor::Blue => TextColor::Blue,
+        TextBackgroundColor::Magenta => TextColor::Magenta,
+        TextBackgroundColor::Cyan => TextColor::Cyan,
+        TextBackgroundColor::White => TextColor::White,
+        TextBackgroundColor::BrightBlack => TextColor::BrightBlack,
+        TextBackgroundColor::BrightRed => TextColor::BrightRed,
+        TextBackgroundColor::BrightGreen => TextColor::BrightGreen,
+        TextBackgroundColor::BrightYellow => TextColor::BrightYellow,
+        TextBackgroundColor::BrightBlue => TextColor::BrightBlue,
+        TextBackgroundColor::BrightMagenta => TextColor::BrightMagenta,
+        TextBackgroundColor::BrightCyan => TextColor::BrightCyan,
+        TextBackgroundColor::BrightWhite => TextColor::BrightWhite,
+    }
+}
+
+/// Get the [`TextBackgroundColor`] of a [`TextColor`]
+fn fg_as_bg(color: TextColor) -> TextBackgroundColor {
+    match color {
+        TextColor::Black => TextBackgroundColor::Black,
+        TextColor::Red => TextBackgroundColor::Red,
+        TextColor::Green => TextBackgroundColor::Green,
+        TextColor::Yellow => TextBackgroundColor::Yellow,
+        TextColor::Blue => TextBackgroundColor::Blue,
+        TextColor::Magenta => TextBackgroundColor::Magenta,
+        TextColor::Cyan => TextBackgroundColor::Cyan,
+        TextColor::White => TextBackgroundColor::White,
+        TextColor::BrightBlack => TextBackgroundColor::BrightBlack,
+        TextColor::BrightRed => TextBackgroundColor::BrightRed,
+        TextColor::BrightGreen => TextBackgroundColor::BrightGreen,
+        TextColor::BrightYellow => TextBackgroundColor::BrightYellow,
+        TextColor::BrightBlue => TextBackgroundColor::BrightBlue,
+        TextColor::BrightMagenta => TextBackgroundColor::BrightMagenta,
+        TextColor::BrightCyan => TextBackgroundColor::BrightCyan,
+        TextColor::BrightWhite => TextBackgroundColor::BrightWhite,
+    }
+}
+
+impl Style {
+    /// Get this style as it looks under an overlay which blends with `blend`
+    pub fn blend(self, blend: Blend) -> Style {
+        let recolor = match blend {
+            Blend::Darken => darker,
+            Blend::Desaturate => gray,
+        };
+
+        let recolor_bg = |bg: TextBackgroundColor| match (blend, recolor(bg_as_fg(bg))) {
+            // gray would lighten dark backgrounds
+            (Blend::Darken, TextColor::BrightBlack) => TextBackgroundColor::Black,
+            (_, color) => fg_as_bg(color),
+        };
+
+        let mut style = Style {
+            fg: self.fg.map(recolor),
+            bg: self.bg.map(recolor_bg),
+            underline_color: self.underline_color.map(recolor),
+            ..self
+        };
+
+        if blend == Blend::Darken {
+            // text in the terminal's default color can only be dimmed
+            style.fg = Some(style.fg.unwrap_or(TextColor::BrightBlack));
+            style.attributes.remove(TextAttribute::Bold);
+            style.attributes.insert(TextAttribute::Dim);
+        }
+
+        style
+    }
+}
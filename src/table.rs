@@ -0,0 +1,594 @@
+//! Table widget
+use crossterm::event::{KeyCode, KeyEvent};
+use std::cmp::Ordering;
+
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{Creatable, DrawingResult, RectBoundary, TextAttribute, Vec2};
+use crate::filter::{Highlight, Searchable};
+use crate::grid::GridCursor;
+use crate::input::TextInputState;
+use crate::selection::{SelectionChange, SelectionModel};
+use crate::style::Style;
+use crate::text::{display_width, truncate_to_width, TruncateAt};
+use crate::State;
+
+/// Column of a [`Table`]
+#[derive(Clone, Debug)]
+pub struct TableColumn {
+    pub title: String,
+    /// Width in cells
+    pub width: u16,
+}
+
+impl TableColumn {
+    pub fn new(title: &str, width: u16) -> Self {
+        TableColumn {
+            title: title.to_string(),
+            width,
+        }
+    }
+}
+
+/// Row of a [`Table`]
+#[derive(Clone, Debug)]
+pub struct TableRow {
+    /// Stable id of the row (selection follows the id, not the position)
+    pub id: usize,
+    pub cells: Vec<String>,
+}
+
+impl TableRow {
+    pub fn new(id: usize, cells: Vec<String>) -> Self {
+        TableRow { id, cells }
+    }
+}
+
+/// Direction a [`Table`] is sorted in
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// State of a [`Table`], kept by the application between frames
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct TableState {
+    /// Column index and direction the rows are sorted by
+    pub sort: Option<(usize, SortDirection)>,
+    /// Only rows with a cell containing this (case insensitive) are shown
+    pub filter: String,
+    /// Id of the selected row
+    pub selected: Option<usize>,
+    /// Number of visible rows scrolled past
+    pub scroll: usize,
+    /// Rows Page Up/Page Down move by (the height of the table when it was last drawn)
+    pub page: usize,
+    /// Index of the selected column (the cell which gets edited)
+    pub column: usize,
+    /// Value of the cell being edited (`None` if we're not editing)
+    pub editing: Option<TextInputState>,
+    /// Selected rows (see [`TableState::handle_selection_key`]), its cursor is `selected`
+    pub selection: SelectionModel,
+}
+
+impl TableState {
+    /// Sort by `column`, or flip the direction if we're already sorted by it
+    pub fn toggle_sort(&mut self, column: usize) {
+        self.sort = match self.sort {
+            Some((c, SortDirection::Ascending)) if c == column => {
+                Some((column, SortDirection::Descending))
+            }
+            _ => Some((column, SortDirection::Ascending)),
+        };
+    }
+
+    /// Compare two cells (as numbers if both are numbers)
+    fn compare_cells(a: &str, b: &str) -> Ordering {
+        match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            _ => a.to_lowercase().cmp(&b.to_lowercase()),
+        }
+    }
+
+    /// Get the rows which pass the filter, in sorted order
+    pub fn visible_rows<'a>(&self, rows: &'a [TableRow]) -> Vec<&'a TableRow> {
+        let filter = self.filter.to_lowercase();
+
+        let mut visible: Vec<&TableRow> = rows
+            .iter()
+            .filter(|r| {
+                filter.is_empty() || r.cells.iter().any(|c| c.to_lowercase().contains(&filter))
+            })
+            .collect();
+
+        if let Some((column, direction)) = self.sort {
+            // stable, so equal rows keep their original order
+            visible.sort_by(|a, b| {
+                let ordering = TableState::compare_cells(
+                    a.cells.get(column).map(|c| c.as_str()).unwrap_or(""),
+                    b.cells.get(column).map(|c| c.as_str()).unwrap_or(""),
+                );
+
+                match direction {
+                    SortDirection::Ascending => ordering,
+                    SortDirection::Descending => ordering.reverse(),
+                }
+            });
+        }
+
+        visible
+    }
+
+    /// Index of the selected row in the visible rows
+    fn selected_index(&self, visible: &[&TableRow]) -> Option<usize> {
+        let selected = self.selected?;
+        visible.iter().position(|r| r.id == selected)
+    }
+
+    /// Move the selection by `by` visible rows
+    pub fn move_selection(&mut self, rows: &[TableRow], by: isize) {
+        let visible = self.visible_rows(rows);
+
+        if visible.is_empty() {
+            self.selected = None;
+            return;
+        }
+
+        let i = match self.selected_index(&visible) {
+            Some(i) => (i as isize + by).clamp(0, visible.len() as isize - 1) as usize,
+            None => 0,
+        };
+
+        self.selected = Some(visible[i].id);
+    }
+
+    /// Handle a key event (Up/Down move the selection, Page Up/Page Down by a page,
+    /// Home/End to the first/last row). Returns `true` if the key was used.
+    pub fn handle_key(&mut self, event: KeyEvent, rows: &[TableRow]) -> bool {
+        if self.editing.is_some() {
+            return false;
+        }
+
+        match event.code {
+            KeyCode::Up => self.move_selection(rows, -1),
+            KeyCode::Down => self.move_selection(rows, 1),
+            KeyCode::PageUp => self.move_selection(rows, -(self.page.max(1) as isize)),
+            KeyCode::PageDown => self.move_selection(rows, self.page.max(1) as isize),
+            KeyCode::Home => self.move_selection(rows, isize::MIN / 2),
+            KeyCode::End => self.move_selection(rows, isize::MAX / 2),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Like [`TableState::handle_key`], with the key of this frame translated by the
+    /// navigation preset (see [`State::nav_key`]). Returns `true` if the key was used.
+    pub fn handle_nav_key(&mut self, state: &mut State, rows: &[TableRow]) -> bool {
+        match state.nav_key() {
+            Some(event) => self.handle_key(event, rows),
+            None => false,
+        }
+    }
+
+    /// Get the ids of the rows which pass the filter, in sorted order
+    pub fn visible_ids(&self, rows: &[TableRow]) -> Vec<usize> {
+        self.visible_rows(rows).iter().map(|r| r.id).collect()
+    }
+
+    /// Handle a key event with the [`SelectionModel`] of the table (Space toggles rows,
+    /// Shift+Up/Down selects ranges, depending on its mode). Returns `true` if the key was used.
+    ///
+    /// ## Arguments:
+    /// * `event`
+    /// * `rows`
+    /// * `on_change` - called when rows are selected or deselected
+    pub fn handle_selection_key(
+        &mut self,
+        event: KeyEvent,
+        rows: &[TableRow],
+        on_change: &mut dyn FnMut(SelectionChange),
+    ) -> bool {
+        if self.editing.is_some() {
+            return false;
+        }
+
+        self.selection.cursor = self.selected;
+        let used = self
+            .selection
+            .handle_key(event, &self.visible_ids(rows), on_change);
+        self.selected = self.selection.cursor;
+
+        used
+    }
+
+    /// Select the clicked row with the [`SelectionModel`] of the table (Ctrl+click toggles it,
+    /// Shift+click selects the range). Returns `true` if a row was clicked.
+    ///
+    /// ## Arguments:
+    /// * `state`
+    /// * `rect` - rect the table was drawn at
+    /// * `rows`
+    /// * `on_change` - called when rows are selected or deselected
+    pub fn handle_selection_click(
+        &mut self,
+        state: &State,
+        rect: &RectBoundary,
+        rows: &[TableRow],
+        on_change: &mut dyn FnMut(SelectionChange),
+    ) -> bool {
+        let Some(click) = state.click_in(rect) else {
+            return false;
+        };
+
+        let ids = self.visible_ids(rows);
+        let i = click.local.1 as usize;
+
+        // (the header isn't a row)
+        let Some(id) = i.checked_sub(1).and_then(|i| ids.get(self.scroll + i)) else {
+            return false;
+        };
+
+        self.selection.cursor = self.selected;
+        self.selection
+            .handle_click(*id, click.modifiers, &ids, on_change);
+        self.selected = self.selection.cursor;
+
+        true
+    }
+
+    /// Get the value of the selected cell
+    pub fn selected_cell<'a>(&self, rows: &'a [TableRow]) -> Option<&'a str> {
+        let selected = self.selected?;
+        let row = rows.iter().find(|r| r.id == selected)?;
+        Some(row.cells.get(self.column).map(|c| c.as_str()).unwrap_or(""))
+    }
+
+    /// Start editing the selected cell. Returns `false` if no row is selected.
+    pub fn start_edit(&mut self, rows: &[TableRow]) -> bool {
+        match self.selected_cell(rows) {
+            Some(value) => {
+                self.editing = Some(TextInputState::new(value));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop editing without changing the cell
+    pub fn cancel_edit(&mut self) {
+        self.editing = None;
+    }
+
+    /// Stop editing and pass the new value to `on_commit` (row id, column, value).
+    /// The table doesn't own the rows, so it's up to the callback to store the value.
+    pub fn commit_edit(&mut self, on_commit: &mut dyn FnMut(usize, usize, String)) {
+        if let (Some(input), Some(selected)) = (self.editing.take(), self.selected) {
+            on_commit(selected, self.column, input.text);
+        }
+    }
+
+    /// Handle a key event with cell editing. Left/Right select the column,
+    /// Enter (or F2) starts editing, then Enter commits and Esc cancels.
+    /// Every other key goes to [`TableState::handle_key`] (or the cell while editing).
+    /// Returns `true` if the key was used.
+    ///
+    /// ## Arguments:
+    /// * `event`
+    /// * `columns`
+    /// * `rows`
+    /// * `on_commit` - called with (row id, column, value) when an edit is committed
+    pub fn handle_edit_key(
+        &mut self,
+        event: KeyEvent,
+        columns: &[TableColumn],
+        rows: &[TableRow],
+        on_commit: &mut dyn FnMut(usize, usize, String),
+    ) -> bool {
+        if let Some(input) = &mut self.editing {
+            return match event.code {
+                KeyCode::Enter => {
+                    self.commit_edit(on_commit);
+                    true
+                }
+                KeyCode::Esc => {
+                    self.cancel_edit();
+                    true
+                }
+                _ => input.handle_key(event),
+            };
+        }
+
+        match event.code {
+            KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down => {
+                let visible = self.visible_rows(rows);
+
+                // with no selection, the first key only selects the first row
+                let Some(row) = self.selected_index(&visible) else {
+                    self.selected = visible.first().map(|r| r.id);
+                    return true;
+                };
+
+                let mut cursor = GridCursor::new(visible.len(), columns.len());
+                cursor.row = row;
+                cursor.col = self.column;
+                cursor.handle_key(event);
+
+                self.selected = visible.get(cursor.row).map(|r| r.id);
+                self.column = cursor.col;
+            }
+            KeyCode::Enter | KeyCode::F(2) => return self.start_edit(rows),
+            _ => return self.handle_key(event, rows),
+        }
+
+        true
+    }
+
+    /// Handle a click on a [`Table`] rendered at `rect`: clicking a header sorts
+    /// by that column, clicking a row selects it (and the clicked cell),
+    /// double clicking a cell starts editing it. Returns `true` if the click was used.
+    pub fn handle_click(
+        &mut self,
+        state: &State,
+        rect: &RectBoundary,
+        columns: &[TableColumn],
+        rows: &[TableRow],
+    ) -> bool {
+        let (pos, count) = match &state.click {
+            Some(click) if rect.contains(click.pos) => (click.pos, click.count),
+            _ => return false,
+        };
+
+        if pos.1 == rect.pos.1 {
+            // header
+            match Table::column_at(rect, columns, pos.0) {
+                Some(column) => self.toggle_sort(column),
+                None => return false,
+            }
+        } else {
+            // row
+            let visible = self.visible_rows(rows);
+            let i = self.scroll + (pos.1 - rect.pos.1 - 1) as usize;
+
+            let id = match visible.get(i) {
+                Some(row) => row.id,
+                None => return false,
+            };
+
+            let column = Table::column_at(rect, columns, pos.0);
+
+            if (self.selected != Some(id)) | (column.is_some_and(|c| c != self.column)) {
+                // clicking another cell drops the edit
+                self.cancel_edit();
+            }
+
+            self.selected = Some(id);
+            self.column = column.unwrap_or(self.column);
+
+            if (count >= 2) & column.is_some() {
+                self.start_edit(rows);
+            }
+        }
+
+        true
+    }
+}
+
+/// Table with sortable columns, filtering, and row selection
+pub struct Table {
+    pub buffer: PseudoBuffer,
+    pub header_style: Style,
+    pub selected_style: Style,
+    /// Style of the rows selected in [`TableState::selection`] (other than the cursor row)
+    pub marked_style: Style,
+    /// Style of the cell being edited
+    pub editing_style: Style,
+    /// Matches to highlight in the cells (see [`crate::filter::Filterable`])
+    pub highlight: Option<Highlight>,
+}
+
+impl Creatable for Table {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Table {
+            header_style: buffer
+                .theme_style(|t| t.header, Style::DEFAULT.attr(TextAttribute::Bold)),
+            selected_style: buffer
+                .theme_style(|t| t.selected, Style::DEFAULT.attr(TextAttribute::Swap)),
+            marked_style: Style::DEFAULT.attr(TextAttribute::Bold),
+            editing_style: Style::DEFAULT.attr(TextAttribute::Underline),
+            highlight: None,
+            buffer,
+        }
+    }
+}
+
+impl Table {
+    /// Get the rect of every column (columns are separated by one cell)
+    pub fn column_rects(rect: &RectBoundary, columns: &[TableColumn]) -> Vec<RectBoundary> {
+        let mut x = rect.pos.0;
+        let mut rects = Vec::new();
+
+        for column in columns {
+            let width = column.width.min(rect.right().saturating_sub(x));
+            rects.push(RectBoundary::new((x, rect.pos.1), (width, rect.size.1)));
+            x = x.saturating_add(column.width + 1);
+        }
+
+        rects
+    }
+
+    /// Get the index of the column at screen column `x`
+    pub fn column_at(rect: &RectBoundary, columns: &[TableColumn], x: u16) -> Option<usize> {
+        Table::column_rects(rect, columns)
+            .iter()
+            .position(|r| (r.pos.0..r.right()).contains(&x))
+    }
+
+    /// Get the screen position of the cursor in the cell being edited
+    /// (`None` if we're not editing, or the row isn't visible)
+    pub fn edit_cursor_pos(
+        rect: &RectBoundary,
+        columns: &[TableColumn],
+        rows: &[TableRow],
+        table: &TableState,
+    ) -> Option<Vec2> {
+        let input = table.editing.as_ref()?;
+        let column_rect = Table::column_rects(rect, columns)
+            .get(table.column)?
+            .clone();
+
+        let visible = table.visible_rows(rows);
+        let y = table.selected_index(&visible)?.checked_sub(table.scroll)?;
+
+        if y >= rect.size.1.saturating_sub(1) as usize {
+            return None;
+        }
+
+        let x = input.cursor.saturating_sub(input.scroll) as u16;
+        Some((
+            column_rect.pos.0 + x.min(column_rect.size.0.saturating_sub(1)),
+            rect.pos.1 + 1 + y as u16,
+        ))
+    }
+
+    /// Pad or cut `text` to exactly `width` cells (cut text ends with `…`)
+    fn fit(text: &str, width: u16) -> String {
+        let text = truncate_to_width(text, width as usize, TruncateAt::End);
+        let padding = (width as usize).saturating_sub(display_width(&text));
+        format!("{text}{}", " ".repeat(padding))
+    }
+
+    /// Draw a table. The first row of `rect` is the header.
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `columns`
+    /// * `rows`
+    /// * `table` - [`TableState`] (scroll is updated so the selection stays visible,
+    ///   the cell being edited is drawn with its current value)
+    pub fn render(
+        &mut self,
+        rect: RectBoundary,
+        columns: &[TableColumn],
+        rows: &[TableRow],
+        table: &mut TableState,
+    ) -> DrawingResult {
+        let column_rects = Table::column_rects(&rect, columns);
+
+        // header
+        for (i, (column, column_rect)) in columns.iter().zip(&column_rects).enumerate() {
+            let indicator = match table.sort {
+                Some((c, SortDirection::Ascending)) if c == i => " ▲",
+                Some((c, SortDirection::Descending)) if c == i => " ▼",
+                _ => "",
+            };
+
+            let title = format!("{}{indicator}", column.title);
+            self.buffer.write_str_styled(
+                column_rect.pos,
+                &Table::fit(&title, column_rect.size.0),
+                self.header_style,
+            )?;
+        }
+
+        // rows
+        let visible = table.visible_rows(rows);
+        let height = rect.size.1.saturating_sub(1) as usize;
+        table.page = height;
+
+        if let Some(i) = table.selected_index(&visible) {
+            // keep selection on screen
+            if i < table.scroll {
+                table.scroll = i;
+            } else if i >= table.scroll + height {
+                table.scroll = i + 1 - height;
+            }
+        }
+
+        table.scroll = table.scroll.min(visible.len().saturating_sub(height));
+        let shown = visible.len().saturating_sub(table.scroll).min(height);
+
+        for (y, row) in visible.iter().skip(table.scroll).take(height).enumerate() {
+            let style = if table.selected == Some(row.id) {
+                self.selected_style
+            } else if table.selection.is_selected(row.id) {
+                self.marked_style
+            } else {
+                Style::DEFAULT
+            };
+
+            for (i, column_rect) in column_rects.iter().enumerate() {
+                let pos = (column_rect.pos.0, rect.pos.1 + 1 + y as u16);
+
+                match &mut table.editing {
+                    Some(input) if (table.selected == Some(row.id)) & (table.column == i) => {
+                        // inline text input
+                        input.scroll_to_cursor(column_rect.size.0 as usize, 0);
+                        let text: String = input.text.chars().skip(input.scroll).collect();
+
+                        self.buffer.write_str_styled(
+                            pos,
+                            &Table::fit(&text, column_rect.size.0),
+                            self.editing_style,
+                        )?;
+                    }
+                    _ => {
+                        let cell = row.cells.get(i).map(|c| c.as_str()).unwrap_or("");
+                        let text = Table::fit(cell, column_rect.size.0);
+
+                        match &self.highlight {
+                            Some(highlight) => {
+                                self.buffer.write_line(pos, &highlight.line(&text, style))?;
+                            }
+                            None => {
+                                self.buffer.write_str_styled(pos, &text, style)?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // erase the rows below (the filter or the data might have less rows than last frame)
+        self.buffer.clear_rect(&RectBoundary::new(
+            (rect.pos.0, rect.pos.1 + 1 + shown as u16),
+            (rect.size.0, (height - shown) as u16),
+        ))?;
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
+
+/// [`Table`] with its data, to put it in a [`crate::filter::Filterable`]
+pub struct TableView<'a> {
+    pub table: Table,
+    pub columns: &'a [TableColumn],
+    pub rows: &'a [TableRow],
+    pub state: &'a mut TableState,
+}
+
+impl Searchable for TableView<'_> {
+    fn item_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn item_text(&self, index: usize) -> String {
+        self.rows[index].cells.join(" ")
+    }
+
+    fn render_items(
+        &mut self,
+        buffer: PseudoBuffer,
+        rect: RectBoundary,
+        visible: &[usize],
+        highlight: &Highlight,
+    ) -> DrawingResult {
+        let rows: Vec<TableRow> = visible.iter().map(|i| self.rows[*i].clone()).collect();
+
+        self.table.buffer = buffer;
+        self.table.highlight = Some(highlight.clone());
+        self.table.render(rect, self.columns, &rows, self.state)
+    }
+}
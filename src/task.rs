@@ -0,0 +1,299 @@
+//! Background tasks
+//!
+//! Work started with [`crate::Frame::spawn_task`] runs on its own thread, and its
+//! result comes back as a [`UserEvent`] named after the task, so the UI doesn't
+//! freeze while it runs.
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::event::UserEvent;
+
+// progress
+/// How far along a task is
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Progress {
+    /// From `0.0` to `1.0`
+    pub fraction: f64,
+    /// What the task is doing right now
+    pub status: String,
+}
+
+/// Progress of every running task (see [`crate::State::progress`])
+#[derive(Clone, Debug, Default)]
+pub struct ProgressTracker {
+    /// Progress by task id, with the name of the task (in the order the tasks were started)
+    tasks: Arc<Mutex<Vec<(u64, String, Progress)>>>,
+    /// Id of the next task (tasks can have the same name)
+    next_id: Arc<AtomicU64>,
+    /// If anything changed since the frame last looked
+    dirty: Arc<AtomicBool>,
+}
+
+impl ProgressTracker {
+    /// Lock the tasks (a panicked task doesn't poison the others)
+    fn lock(&self) -> MutexGuard<'_, Vec<(u64, String, Progress)>> {
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start tracking the task `name` (at 0%)
+    pub fn handle(&self, name: &str) -> ProgressHandle {
+        let handle = ProgressHandle {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            name: name.to_string(),
+            tracker: self.clone(),
+        };
+
+        handle.set(0.0, "");
+        handle
+    }
+
+    /// Get the progress of the task `name` (the first one started, if there are more)
+    pub fn get(&self, name: &str) -> Option<Progress> {
+        self.lock()
+            .iter()
+            .find(|(_, n, _)| n == name)
+            .map(|(_, _, p)| p.clone())
+    }
+
+    /// Get the progress of every running task
+    pub fn tasks(&self) -> Vec<(String, Progress)> {
+        self.lock()
+            .iter()
+            .map(|(_, n, p)| (n.clone(), p.clone()))
+            .collect()
+    }
+
+    /// Get the combined progress of every running task (the average, with
+    /// the number of running tasks as status). `None` if nothing is running.
+    pub fn total(&self) -> Option<Progress> {
+        let tasks = self.lock();
+
+        if tasks.is_empty() {
+            return None;
+        }
+
+        let fraction = tasks.iter().map(|(_, _, p)| p.fraction).sum::<f64>() / tasks.len() as f64;
+        let status = match tasks.len() {
+            1 => tasks[0].2.status.clone(),
+            n => format!("{n} tasks"),
+        };
+
+        Some(Progress { fraction, status })
+    }
+
+    /// Check (and reset) if anything changed
+    pub(crate) fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::Relaxed)
+    }
+
+    /// Stop tracking the task `id`
+    fn remove(&self, id: u64) {
+        self.lock().retain(|(i, _, _)| *i != id);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Used by a task to report its progress
+#[derive(Clone, Debug)]
+pub struct ProgressHandle {
+    id: u64,
+    name: String,
+    tracker: ProgressTracker,
+}
+
+impl ProgressHandle {
+    /// Set the progress
+    ///
+    /// ## Arguments:
+    /// * `fraction` - from `0.0` to `1.0`
+    /// * `status` - what the task is doing right now
+    pub fn set(&self, fraction: f64, status: &str) {
+        let progress = Progress {
+            fraction: fraction.clamp(0.0, 1.0),
+            status: status.to_string(),
+        };
+
+        {
+            let mut tasks = self.tracker.lock();
+
+            match tasks.iter_mut().find(|(i, _, _)| *i == self.id) {
+                Some((_, _, p)) => *p = progress,
+                None => tasks.push((self.id, self.name.clone(), progress)),
+            }
+        }
+
+        self.tracker.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Set the progress, keeping the status
+    pub fn set_fraction(&self, fraction: f64) {
+        let status = self
+            .tracker
+            .lock()
+            .iter()
+            .find(|(i, _, _)| *i == self.id)
+            .map(|(_, _, p)| p.status.clone())
+            .unwrap_or_default();
+
+        self.set(fraction, &status);
+    }
+
+    /// Stop reporting (the task disappears from the tracker)
+    pub fn finish(self) {
+        self.tracker.remove(self.id);
+    }
+}
+
+/// Finishes the progress of a task when it's dropped, so tasks which panic
+/// or are aborted don't stay in the tracker
+struct FinishOnDrop(ProgressHandle);
+
+impl Drop for FinishOnDrop {
+    fn drop(&mut self) {
+        self.0.tracker.remove(self.0.id);
+    }
+}
+
+// tasks
+/// Given to a running task to check for cancellation and report progress
+#[derive(Clone, Debug)]
+pub struct TaskContext {
+    cancelled: Arc<AtomicBool>,
+    progress: ProgressHandle,
+}
+
+impl TaskContext {
+    /// Check if the task was cancelled (long tasks should check this now and then and stop early)
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Get the progress of the task (shown by [`crate::drawing::ProgressBar::render_task`])
+    pub fn progress(&self) -> &ProgressHandle {
+        &self.progress
+    }
+}
+
+/// What runs the task
+enum TaskInner {
+    Thread(std::thread::JoinHandle<()>),
+    #[cfg(feature = "async")]
+    Tokio(tokio::task::JoinHandle<()>),
+}
+
+/// Handle to a running task
+pub struct TaskHandle {
+    /// Name of the task (and of its result event)
+    pub name: String,
+    cancelled: Arc<AtomicBool>,
+    inner: TaskInner,
+}
+
+impl TaskHandle {
+    /// Cancel the task. Its result is dropped instead of being sent
+    /// (async tasks are also aborted at their next `.await`).
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+
+        #[cfg(feature = "async")]
+        if let TaskInner::Tokio(handle) = &self.inner {
+            handle.abort();
+        }
+    }
+
+    /// Check if the task was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Check if the task is done (finished, cancelled or panicked)
+    pub fn is_finished(&self) -> bool {
+        match &self.inner {
+            TaskInner::Thread(handle) => handle.is_finished(),
+            #[cfg(feature = "async")]
+            TaskInner::Tokio(handle) => handle.is_finished(),
+        }
+    }
+}
+
+/// Run `work` on a new thread and send its result to `sender` as a [`UserEvent`] named `name`
+///
+/// ## Arguments:
+/// * `sender` - where the result goes (see [`crate::Frame::event_sender`])
+/// * `progress` - where the task reports its progress (until it's done)
+/// * `name` - name of the task
+/// * `work`
+pub fn spawn<T: Send + 'static>(
+    sender: Sender<UserEvent>,
+    progress: &ProgressTracker,
+    name: &str,
+    work: impl FnOnce(&TaskContext) -> T + Send + 'static,
+) -> TaskHandle {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let context = TaskContext {
+        cancelled: cancelled.clone(),
+        progress: progress.handle(name),
+    };
+    let event_name = name.to_string();
+    let finish = FinishOnDrop(context.progress.clone());
+
+    let handle = std::thread::spawn(move || {
+        let result = work(&context);
+        drop(finish);
+
+        if !context.is_cancelled() {
+            let _ = sender.send(UserEvent::new(&event_name, result));
+        }
+    });
+
+    TaskHandle {
+        name: name.to_string(),
+        cancelled,
+        inner: TaskInner::Thread(handle),
+    }
+}
+
+/// Run a future on the current tokio runtime and send its output to `sender`
+/// as a [`UserEvent`] named `name`. Must be called from inside of a runtime.
+///
+/// ## Arguments:
+/// * `sender` - where the result goes (see [`crate::Frame::event_sender`])
+/// * `progress` - where the task reports its progress (until it's done)
+/// * `name` - name of the task
+/// * `work` - gets a [`TaskContext`] and returns the future
+#[cfg(feature = "async")]
+pub fn spawn_async<T, F>(
+    sender: Sender<UserEvent>,
+    progress: &ProgressTracker,
+    name: &str,
+    work: impl FnOnce(TaskContext) -> F,
+) -> TaskHandle
+where
+    T: Send + 'static,
+    F: std::future::Future<Output = T> + Send + 'static,
+{
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let context = TaskContext {
+        cancelled: cancelled.clone(),
+        progress: progress.handle(name),
+    };
+    let event_name = name.to_string();
+    let future = work(context.clone());
+    let finish = FinishOnDrop(context.progress.clone());
+
+    let handle = tokio::spawn(async move {
+        let result = future.await;
+        drop(finish);
+
+        if !context.is_cancelled() {
+            let _ = sender.send(UserEvent::new(&event_name, result));
+        }
+    });
+
+    TaskHandle {
+        name: name.to_string(),
+        cancelled,
+        inner: TaskInner::Tokio(handle),
+    }
+}
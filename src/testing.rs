@@ -0,0 +1,262 @@
+//! Testing helpers
+//!
+//! [`TestBackend`] stands in for the terminal: a [`crate::buffer::Buffer`] writes to it
+//! (see [`crate::buffer::Buffer::with_output`]), and it plays the written escape
+//! sequences back onto a grid of cells, so tests can check what a real terminal would show.
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use crate::buffer::{BufCell, Buffer, Row};
+use crate::drawing::{TextAttribute, TextBackgroundColor, TextColor, Vec2};
+use crate::style::{Style, UnderlineStyle};
+use crate::text::char_width;
+
+/// Every [`TextColor`] (to look them up by code)
+const COLORS: [TextColor; 16] = [
+    TextColor::Black,
+    TextColor::Red,
+    TextColor::Green,
+    TextColor::Yellow,
+    TextColor::Blue,
+    TextColor::Magenta,
+    TextColor::Cyan,
+    TextColor::White,
+    TextColor::BrightBlack,
+    TextColor::BrightRed,
+    TextColor::BrightGreen,
+    TextColor::BrightYellow,
+    TextColor::BrightBlue,
+    TextColor::BrightMagenta,
+    TextColor::BrightCyan,
+    TextColor::BrightWhite,
+];
+
+/// Every [`TextBackgroundColor`] (to look them up by code)
+const BACKGROUNDS: [TextBackgroundColor; 16] = [
+    TextBackgroundColor::Black,
+    TextBackgroundColor::Red,
+    TextBackgroundColor::Green,
+    TextBackgroundColor::Yellow,
+    TextBackgroundColor::Blue,
+    TextBackgroundColor::Magenta,
+    TextBackgroundColor::Cyan,
+    TextBackgroundColor::White,
+    TextBackgroundColor::BrightBlack,
+    TextBackgroundColor::BrightRed,
+    TextBackgroundColor::BrightGreen,
+    TextBackgroundColor::BrightYellow,
+    TextBackgroundColor::BrightBlue,
+    TextBackgroundColor::BrightMagenta,
+    TextBackgroundColor::BrightCyan,
+    TextBackgroundColor::BrightWhite,
+];
+
+/// Every [`TextAttribute`] (to look them up by code)
+const ATTRIBUTES: [TextAttribute; 8] = [
+    TextAttribute::Bold,
+    TextAttribute::Dim,
+    TextAttribute::Italic,
+    TextAttribute::Underline,
+    TextAttribute::Blink,
+    TextAttribute::Swap,
+    TextAttribute::Hidden,
+    TextAttribute::Strikethrough,
+];
+
+/// A fake terminal which keeps everything written to it (clones share the output)
+#[derive(Clone, Default)]
+pub struct TestBackend {
+    output: Arc<Mutex<Vec<u8>>>,
+}
+
+impl TestBackend {
+    pub fn new() -> TestBackend {
+        TestBackend::default()
+    }
+
+    /// Create a [`Buffer`] which writes to a new [`TestBackend`]
+    pub fn buffer(size: Vec2) -> (Buffer, TestBackend) {
+        let backend = TestBackend::new();
+        let mut buffer = Buffer::with_output(backend.clone(), size);
+        buffer.extended_underline = true; // don't depend on the terminal running the tests
+
+        (buffer, backend)
+    }
+
+    /// Get everything written so far
+    pub fn output(&self) -> Vec<u8> {
+        self.output
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Forget everything written so far
+    pub fn clear(&self) {
+        self.output
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// Play everything written so far onto an empty screen of `size` and get its rows
+    pub fn screen(&self, size: Vec2) -> Vec<Row> {
+        let mut terminal = Terminal::new(size);
+        terminal.feed(&String::from_utf8_lossy(&self.output()));
+        terminal.rows
+    }
+
+    /// Like [`TestBackend::screen`], but as text (see [`Buffer::screen_text`])
+    pub fn screen_text(&self, size: Vec2) -> String {
+        Buffer::screen_text(&self.screen(size))
+    }
+
+    /// Like [`TestBackend::screen`], but as text with styles (see [`Buffer::screen_ansi`])
+    pub fn screen_ansi(&self, size: Vec2) -> String {
+        Buffer::screen_ansi(&self.screen(size))
+    }
+}
+
+impl Write for TestBackend {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.output
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Just enough of a terminal to understand what a [`Buffer`] writes
+struct Terminal {
+    size: Vec2,
+    rows: Vec<Row>,
+    cursor: Vec2,
+    style: Style,
+}
+
+impl Terminal {
+    fn new(size: Vec2) -> Terminal {
+        Terminal {
+            size,
+            rows: vec![BufCell::as_row(size.0); size.1 as usize],
+            cursor: (0, 0),
+            style: Style::DEFAULT,
+        }
+    }
+
+    /// Apply written text
+    fn feed(&mut self, text: &str) {
+        let mut chars = text.chars().peekable();
+
+        while let Some(char) = chars.next() {
+            if char != '\x1b' {
+                self.print(char);
+                continue;
+            }
+
+            // only CSI sequences are understood, anything else is skipped
+            if chars.next_if_eq(&'[').is_none() {
+                chars.next();
+                continue;
+            }
+
+            let mut params = String::new();
+
+            for c in chars.by_ref() {
+                if ('\x40'..='\x7e').contains(&c) {
+                    self.csi(&params, c);
+                    break;
+                }
+
+                params.push(c);
+            }
+        }
+    }
+
+    /// Print a character at the cursor
+    fn print(&mut self, char: char) {
+        let width = char_width(char).max(1) as u16;
+
+        if self.cursor.0 + width > self.size.0 {
+            // wrap
+            self.cursor = (0, self.cursor.1 + 1);
+        }
+
+        if let Some(row) = self.rows.get_mut(self.cursor.1 as usize) {
+            row[self.cursor.0 as usize] = BufCell::styled(char, self.style);
+
+            if width > 1 {
+                row[self.cursor.0 as usize + 1] = BufCell::EMPTY;
+            }
+        }
+
+        self.cursor.0 += width;
+    }
+
+    /// Apply a CSI sequence
+    fn csi(&mut self, params: &str, command: char) {
+        match command {
+            'H' => {
+                let mut numbers = params.split(';').map(|n| n.parse::<u16>().unwrap_or(1));
+                let y = numbers.next().unwrap_or(1).max(1) - 1;
+                let x = numbers.next().unwrap_or(1).max(1) - 1;
+                self.cursor = (x, y);
+            }
+            'J' if params == "2" => {
+                self.rows = vec![BufCell::as_row(self.size.0); self.size.1 as usize];
+            }
+            'm' => self.sgr(params),
+            _ => {}
+        }
+    }
+
+    /// Apply an SGR sequence (the ones [`Style::to_ansi`] writes)
+    fn sgr(&mut self, params: &str) {
+        let mut codes = params.split(';');
+
+        while let Some(code) = codes.next() {
+            if let Some(underline) = code.strip_prefix("4:") {
+                self.style.underline = match underline {
+                    "1" => Some(UnderlineStyle::Single),
+                    "2" => Some(UnderlineStyle::Double),
+                    "3" => Some(UnderlineStyle::Curly),
+                    "4" => Some(UnderlineStyle::Dotted),
+                    "5" => Some(UnderlineStyle::Dashed),
+                    _ => None,
+                };
+                continue;
+            }
+
+            let Ok(code) = code.parse::<u8>() else {
+                continue;
+            };
+
+            match code {
+                0 => self.style = Style::DEFAULT,
+                58 => {
+                    // 58;5;n
+                    let index = codes.nth(1).and_then(|n| n.parse::<u8>().ok());
+                    self.style.underline_color = index.and_then(|i| {
+                        COLORS
+                            .into_iter()
+                            .find(|c| (*c as u8) == if i >= 8 { i - 8 + 90 } else { i + 30 })
+                    });
+                }
+                _ => {
+                    if let Some(attr) = ATTRIBUTES.into_iter().find(|a| (*a as u8) == code) {
+                        self.style.attributes.insert(attr);
+                    } else if let Some(color) = COLORS.into_iter().find(|c| (*c as u8) == code) {
+                        self.style.fg = Some(color);
+                    } else if let Some(bg) = BACKGROUNDS.into_iter().find(|c| (*c as u8) == code) {
+                        self.style.bg = Some(bg);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Text helpers
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::style::Style;
+
+/// Where [`truncate_to_width`] cuts text which is too wide
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TruncateAt {
+    /// `Long te…`
+    #[default]
+    End,
+    /// `Long…text`
+    Middle,
+    /// `…ng text`
+    Start,
+}
+
+/// Get the width of `text` in terminal cells (wide characters take two)
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Get the width of a character in terminal cells
+pub fn char_width(char: char) -> usize {
+    char.width().unwrap_or(0)
+}
+
+/// Take cells from `cells` while they fit in `width`
+fn fit<'a>(cells: impl Iterator<Item = &'a (char, Style)>, width: usize) -> Vec<(char, Style)> {
+    let mut used = 0;
+    let mut fitting = Vec::new();
+
+    for cell in cells {
+        used += char_width(cell.0);
+
+        if used > width {
+            break;
+        }
+
+        fitting.push(*cell);
+    }
+
+    fitting
+}
+
+/// Like [`truncate_to_width`], but with styled characters.
+/// The `…` gets the style of the characters next to it.
+pub fn truncate_cells(cells: &[(char, Style)], width: usize, at: TruncateAt) -> Vec<(char, Style)> {
+    let total: usize = cells.iter().map(|c| char_width(c.0)).sum();
+
+    if total <= width {
+        return cells.to_vec();
+    }
+
+    if width == 0 {
+        return Vec::new();
+    }
+
+    // room for the text next to the ellipsis
+    let room = width - 1;
+
+    match at {
+        TruncateAt::End => {
+            let mut res = fit(cells.iter(), room);
+            let style = cells[res.len()].1;
+            res.push(('…', style));
+            res
+        }
+        TruncateAt::Start => {
+            let mut end = fit(cells.iter().rev(), room);
+            let style = cells[cells.len() - end.len() - 1].1;
+            end.push(('…', style));
+            end.reverse();
+            end
+        }
+        TruncateAt::Middle => {
+            let mut res = fit(cells.iter(), room.div_ceil(2));
+            let style = cells[res.len()].1;
+            let used: usize = res.iter().map(|c| char_width(c.0)).sum();
+            let mut end = fit(cells.iter().rev(), room - used);
+            end.reverse();
+
+            res.push(('…', style));
+            res.extend(end);
+            res
+        }
+    }
+}
+
+/// Cut `text` down to `width` terminal cells, marking the cut with `…`
+///
+/// ## Arguments:
+/// * `text`
+/// * `width` - in cells (wide characters take two)
+/// * `at` - [`TruncateAt`]
+pub fn truncate_to_width(text: &str, width: usize, at: TruncateAt) -> String {
+    let cells: Vec<(char, Style)> = text.chars().map(|c| (c, Style::DEFAULT)).collect();
+
+    truncate_cells(&cells, width, at)
+        .into_iter()
+        .map(|(c, _)| c)
+        .collect()
+}
+
+// bidi
+/// Get the logical (character) index of each character of `text` in visual order,
+/// so right-to-left runs (Arabic, Hebrew) are drawn the way they're read.
+///
+/// Without the `bidi` feature, the text is always drawn left-to-right.
+#[cfg(feature = "bidi")]
+pub fn visual_map(text: &str) -> Vec<usize> {
+    use unicode_bidi::BidiInfo;
+
+    // byte index -> character index
+    let mut char_index = vec![0; text.len()];
+
+    for (i, (byte, _)) in text.char_indices().enumerate() {
+        char_index[byte] = i;
+    }
+
+    let info = BidiInfo::new(text, None);
+    let mut map = Vec::with_capacity(char_index.len());
+
+    for paragraph in &info.paragraphs {
+        let (levels, runs) = info.visual_runs(paragraph, paragraph.range.clone());
+
+        for run in runs {
+            let chars = text[run.clone()]
+                .char_indices()
+                .map(|(byte, _)| char_index[run.start + byte]);
+
+            if levels[run.start].is_rtl() {
+                map.extend(chars.rev());
+            } else {
+                map.extend(chars);
+            }
+        }
+    }
+
+    map
+}
+
+/// Get the logical (character) index of each character of `text` in visual order,
+/// so right-to-left runs (Arabic, Hebrew) are drawn the way they're read.
+///
+/// Without the `bidi` feature, the text is always drawn left-to-right.
+#[cfg(not(feature = "bidi"))]
+pub fn visual_map(text: &str) -> Vec<usize> {
+    (0..text.chars().count()).collect()
+}
+
+/// Get `text` with its characters in visual order (see [`visual_map`])
+pub fn visual_order(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    visual_map(text).into_iter().map(|i| chars[i]).collect()
+}
+
+/// Get the visual position of the cursor at logical character index `cursor`.
+/// A cursor after the last character stays at the end.
+pub fn logical_to_visual(text: &str, cursor: usize) -> usize {
+    visual_map(text)
+        .iter()
+        .position(|i| *i == cursor)
+        .unwrap_or(cursor)
+}
+
+/// Get the logical character index of the character at visual position `pos`
+/// (for turning clicks into cursor positions)
+pub fn visual_to_logical(text: &str, pos: usize) -> usize {
+    visual_map(text).get(pos).copied().unwrap_or(pos)
+}
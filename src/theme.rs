@@ -0,0 +1,88 @@
+//! Themes
+use serde::{Deserialize, Serialize};
+
+use crate::drawing::{TextAttribute, TextBackgroundColor, TextColor};
+use crate::style::Style;
+
+/// Palette of styles for an application. Built-in components drawn by a frame take
+/// their default styles from it (see [`crate::buffer::PseudoBuffer::theme_style`]), and
+/// the application can pick styles from here for everything else.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Normal text
+    pub text: Style,
+    /// Less important text (hints, placeholders)
+    pub muted: Style,
+    /// Highlighted text (titles, links)
+    pub accent: Style,
+    /// Selected items
+    pub selected: Style,
+    /// Table headers
+    pub header: Style,
+    /// Status line
+    pub status: Style,
+    /// Box borders
+    pub border: Style,
+    /// Errors
+    pub error: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    /// Theme for terminals with a dark background
+    pub fn dark() -> Theme {
+        Theme {
+            text: Style::DEFAULT.fg(TextColor::White),
+            muted: Style::DEFAULT.fg(TextColor::BrightBlack),
+            accent: Style::DEFAULT.fg(TextColor::BrightCyan),
+            selected: Style::DEFAULT
+                .fg(TextColor::Black)
+                .bg(TextBackgroundColor::Cyan),
+            header: Style::DEFAULT
+                .fg(TextColor::BrightWhite)
+                .attr(TextAttribute::Bold),
+            status: Style::DEFAULT
+                .fg(TextColor::Black)
+                .bg(TextBackgroundColor::White),
+            border: Style::DEFAULT.fg(TextColor::BrightBlack),
+            error: Style::DEFAULT.fg(TextColor::BrightRed),
+        }
+    }
+
+    /// Theme for terminals with a light background
+    pub fn light() -> Theme {
+        Theme {
+            text: Style::DEFAULT.fg(TextColor::Black),
+            muted: Style::DEFAULT.fg(TextColor::BrightBlack),
+            accent: Style::DEFAULT.fg(TextColor::Blue),
+            selected: Style::DEFAULT
+                .fg(TextColor::BrightWhite)
+                .bg(TextBackgroundColor::Blue),
+            header: Style::DEFAULT
+                .fg(TextColor::Black)
+                .attr(TextAttribute::Bold),
+            status: Style::DEFAULT
+                .fg(TextColor::BrightWhite)
+                .bg(TextBackgroundColor::BrightBlack),
+            border: Style::DEFAULT.fg(TextColor::White),
+            error: Style::DEFAULT.fg(TextColor::Red),
+        }
+    }
+
+    /// Get the light theme if this is the dark theme, or the dark theme otherwise
+    /// (for a dark/light toggle: bind an action like `toggle_theme` in the
+    /// [`crate::keymap::Keymap`] and pass this to [`crate::Frame::set_theme`])
+    pub fn toggled(&self) -> Theme {
+        if *self == Theme::dark() {
+            Theme::light()
+        } else {
+            Theme::dark()
+        }
+    }
+}
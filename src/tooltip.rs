@@ -0,0 +1,74 @@
+//! Tooltips
+//!
+//! Components register a tooltip for their rect with [`crate::State::tooltip`]. Once the
+//! mouse rests over the rect for [`crate::State::tooltip_delay`], the frame draws the
+//! tooltip next to the pointer on top of everything, and moving the mouse hides it.
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{
+    Creatable, DrawingResult, RectBoundary, TextBackgroundColor, TextColor, Vec2,
+};
+use crate::style::Style;
+use crate::text::{display_width, truncate_to_width, TruncateAt};
+
+/// One line of text next to the pointer: ` Save the file `
+pub struct Tooltip {
+    pub buffer: PseudoBuffer,
+    pub style: Style,
+    /// Longer texts are cut off
+    pub max_width: u16,
+}
+
+impl Creatable for Tooltip {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Tooltip {
+            buffer,
+            style: Style::DEFAULT
+                .fg(TextColor::Black)
+                .bg(TextBackgroundColor::BrightYellow),
+            max_width: 40,
+        }
+    }
+}
+
+impl Tooltip {
+    /// Get the rect of a tooltip for the pointer at `pointer` (below and right of it,
+    /// moved to stay inside of the window)
+    pub fn rect(&self, window_size: Vec2, pointer: Vec2, text: &str) -> RectBoundary {
+        let width = (display_width(text) as u16 + 2)
+            .min(self.max_width)
+            .min(window_size.0);
+
+        let x = (pointer.0 + 1).min(window_size.0.saturating_sub(width));
+        let y = if pointer.1 + 1 < window_size.1 {
+            pointer.1 + 1
+        } else {
+            pointer.1.saturating_sub(1)
+        };
+
+        RectBoundary::new((x, y), (width, 1))
+    }
+
+    /// Draw the tooltip for the pointer at `pointer`
+    ///
+    /// ## Arguments:
+    /// * `window_size`
+    /// * `pointer` - where the mouse is
+    /// * `text`
+    pub fn render(&mut self, window_size: Vec2, pointer: Vec2, text: &str) -> DrawingResult {
+        let rect = self.rect(window_size, pointer, text);
+
+        if rect.is_empty() {
+            return Ok((rect, self.buffer.get_changes()));
+        }
+
+        let width = rect.size.0.saturating_sub(2) as usize;
+        let text = truncate_to_width(text, width, TruncateAt::End);
+        let pad = " ".repeat(width.saturating_sub(display_width(&text)));
+        let line = format!(" {text}{pad} ");
+
+        self.buffer.write_str_styled(rect.pos, &line, self.style)?;
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
@@ -0,0 +1,87 @@
+//! File watching
+//!
+//! With the `watch` feature, changes to files are sent as [`UserEvent`]s
+//! carrying a [`FileChange`], so log viewers and apps which reload their
+//! config redraw when files change. Start one with [`crate::Frame::watch_files`].
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+
+use crate::event::UserEvent;
+
+/// What happened to the files of a [`FileChange`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileChangeKind {
+    Created,
+    Modified,
+    Removed,
+    /// Anything else (access, metadata, ...)
+    Other,
+}
+
+/// Payload of the [`UserEvent`]s sent by a [`FileWatcher`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileChange {
+    pub kind: FileChangeKind,
+    pub paths: Vec<PathBuf>,
+}
+
+/// Watches files and sends their changes to the frame (stops when dropped)
+pub struct FileWatcher {
+    watcher: RecommendedWatcher,
+}
+
+impl FileWatcher {
+    /// Create a new [`FileWatcher`] which isn't watching anything yet
+    ///
+    /// ## Arguments:
+    /// * `sender` - where changes go (see [`crate::Frame::event_sender`])
+    /// * `name` - name of the events
+    pub fn new(sender: Sender<UserEvent>, name: &str) -> notify::Result<Self> {
+        let name = name.to_string();
+
+        let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+
+            let kind = match event.kind {
+                EventKind::Create(_) => FileChangeKind::Created,
+                EventKind::Modify(_) => FileChangeKind::Modified,
+                EventKind::Remove(_) => FileChangeKind::Removed,
+                EventKind::Access(_) => return, // reading doesn't change anything
+                _ => FileChangeKind::Other,
+            };
+
+            let _ = sender.send(UserEvent::new(
+                &name,
+                FileChange {
+                    kind,
+                    paths: event.paths,
+                },
+            ));
+        })?;
+
+        Ok(FileWatcher { watcher })
+    }
+
+    /// Start watching a file or directory
+    ///
+    /// ## Arguments:
+    /// * `path`
+    /// * `recursive` - also watch everything inside of a directory
+    pub fn watch(&mut self, path: impl AsRef<Path>, recursive: bool) -> notify::Result<()> {
+        let mode = if recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+
+        self.watcher.watch(path.as_ref(), mode)
+    }
+
+    /// Stop watching a file or directory
+    pub fn unwatch(&mut self, path: impl AsRef<Path>) -> notify::Result<()> {
+        self.watcher.unwatch(path.as_ref())
+    }
+}
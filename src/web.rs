@@ -0,0 +1,419 @@
+//! Web terminals
+//!
+//! Pieces for running a [`crate::Frame`] inside of a web page with xterm.js (see
+//! `web/hamui.js`): the frame writes into a [`WebOutput`] (see [`crate::Frame::with_output`])
+//! which is handed to `term.write`, and what xterm.js sends back from `term.onData`
+//! is turned into events with [`parse_input`] and passed to [`crate::Frame::handle_event`].
+//!
+//! The exports the shim calls are up to the application (with `wasm_bindgen`, for example):
+//!
+//! ```ignore
+//! #[wasm_bindgen]
+//! pub fn input(data: &str) {
+//!     with_frame(|frame| {
+//!         for event in hamui::web::parse_input(data) {
+//!             frame.handle_event(event).unwrap();
+//!         }
+//!     });
+//! }
+//!
+//! #[wasm_bindgen]
+//! pub fn resize(cols: u16, rows: u16) {
+//!     with_frame(|frame| frame.handle_event(hamui::web::resize_event(cols, rows)).unwrap());
+//! }
+//!
+//! #[wasm_bindgen]
+//! pub fn preedit(text: &str) {
+//!     with_frame(|frame| frame.handle_user_event(hamui::web::preedit_event(text)).unwrap());
+//! }
+//!
+//! #[wasm_bindgen]
+//! pub fn take_output() -> String {
+//!     OUTPUT.with(|output| output.take())
+//! }
+//! ```
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+
+use crate::event::UserEvent;
+
+/// Name of the [`UserEvent`]s made by [`preedit_event`]
+pub const PREEDIT: &str = "preedit";
+
+/// Text being composed by the input method of the page (empty once composition ends),
+/// the payload of [`preedit_event`]s
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preedit(pub String);
+
+/// Output of a frame running in a web page (clones share the output)
+#[derive(Clone, Default)]
+pub struct WebOutput {
+    output: Arc<Mutex<Vec<u8>>>,
+}
+
+impl WebOutput {
+    pub fn new() -> WebOutput {
+        WebOutput::default()
+    }
+
+    /// Take everything written since the last call (for `term.write`)
+    pub fn take(&self) -> String {
+        let bytes = std::mem::take(&mut *self.output.lock().unwrap_or_else(|e| e.into_inner()));
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+impl Write for WebOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.output
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Get the event of the terminal being resized to `cols` x `rows` (from `term.onResize`)
+pub fn resize_event(cols: u16, rows: u16) -> Event {
+    Event::Resize(cols, rows)
+}
+
+/// Get the event of the input method of the page composing `text` (from the
+/// `compositionupdate` and `compositionend` events of `term.textarea`). Pass it to
+/// [`crate::Frame::handle_user_event`], text inputs pick it up with
+/// [`crate::input::TextInputState::handle_preedit`]. The composed text itself
+/// still arrives through `term.onData` once composition ends.
+pub fn preedit_event(text: &str) -> UserEvent {
+    UserEvent::new(PREEDIT, Preedit(text.to_string()))
+}
+
+/// Turn what xterm.js sends (from `term.onData`) into events: keys, mouse
+/// reports (SGR encoding) and focus changes. Unknown (and cut off) sequences are dropped,
+/// an escape at the end is the escape key.
+pub fn parse_input(data: &str) -> Vec<Event> {
+    let (mut events, parsed) = parse_input_partial(data);
+
+    if &data[parsed..] == "\x1b" {
+        events.push(key(KeyCode::Esc, KeyModifiers::NONE));
+    }
+
+    events
+}
+
+/// Like [`parse_input`], but an escape sequence which is cut off at the end (or an
+/// escape at the end, which could be the start of one) is left alone. Returns the events
+/// and the length of `data` which was parsed (the rest goes in front of the next data,
+/// see [`crate::ssh::SshSession::input`]).
+pub fn parse_input_partial(data: &str) -> (Vec<Event>, usize) {
+    let chars: Vec<char> = data.chars().collect();
+    let mut events = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let char = chars[i];
+        let sequence_start = i;
+        i += 1;
+
+        if char != '\x1b' {
+            events.extend(char_key(char, KeyModifiers::NONE));
+            continue;
+        }
+
+        match chars.get(i) {
+            // escape, or the start of a sequence which was cut off
+            None => return (events, byte_len(&chars[..sequence_start])),
+            // CSI
+            Some('[') => {
+                let start = i + 1;
+                let Some(end) =
+                    (start..chars.len()).find(|&j| ('\x40'..='\x7e').contains(&chars[j]))
+                else {
+                    // cut off
+                    return (events, byte_len(&chars[..sequence_start]));
+                };
+
+                let params: String = chars[start..end].iter().collect();
+                events.extend(csi(&params, chars[end]));
+                i = end + 1;
+            }
+            // SS3 (arrows and F1-F4 in application mode)
+            Some('O') => {
+                let Some(command) = chars.get(i + 1) else {
+                    // cut off
+                    return (events, byte_len(&chars[..sequence_start]));
+                };
+
+                events.extend(csi("", *command));
+                i += 2;
+            }
+            // Alt + key
+            Some(&char) => {
+                events.extend(char_key(char, KeyModifiers::ALT));
+                i += 1;
+            }
+        }
+    }
+
+    (events, data.len())
+}
+
+/// Get the length of `chars` in UTF-8
+fn byte_len(chars: &[char]) -> usize {
+    chars.iter().map(|c| c.len_utf8()).sum()
+}
+
+/// Turn an event back into what a terminal sends for it (the opposite of [`parse_input`]).
+/// Returns `None` for events terminals don't send (resizes, key releases).
+pub fn encode_event(event: &Event) -> Option<String> {
+    match event {
+        Event::Key(key) if key.kind != KeyEventKind::Release => encode_key(key),
+        Event::Mouse(mouse) => Some(encode_mouse(mouse)),
+        Event::FocusGained => Some("\x1b[I".to_string()),
+        Event::FocusLost => Some("\x1b[O".to_string()),
+        Event::Paste(text) => Some(text.clone()),
+        _ => None,
+    }
+}
+
+/// Get the xterm modifier parameter of `modifiers` (`1 + bits`)
+fn modifier_param(modifiers: KeyModifiers) -> u8 {
+    1 + u8::from(modifiers.contains(KeyModifiers::SHIFT))
+        + (u8::from(modifiers.contains(KeyModifiers::ALT)) * 2)
+        + (u8::from(modifiers.contains(KeyModifiers::CONTROL)) * 4)
+}
+
+fn encode_key(key: &KeyEvent) -> Option<String> {
+    let modifiers = modifier_param(key.modifiers);
+    let alt = if key.modifiers.contains(KeyModifiers::ALT) {
+        "\x1b"
+    } else {
+        ""
+    };
+
+    // CSI sequences with the modifiers in them
+    let csi = |param: u8, command: char| {
+        if modifiers > 1 {
+            format!("\x1b[{param};{modifiers}{command}")
+        } else if param == 1 {
+            format!("\x1b[{command}")
+        } else {
+            format!("\x1b[{param}{command}")
+        }
+    };
+
+    Some(match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            let control = match c {
+                ' ' => '\x00',
+                c if c.is_ascii_alphabetic() => (c.to_ascii_lowercase() as u8 - b'a' + 1) as char,
+                c => c,
+            };
+            format!("{alt}{control}")
+        }
+        KeyCode::Char(c) => format!("{alt}{c}"),
+        KeyCode::Enter => format!("{alt}\r"),
+        KeyCode::Tab => format!("{alt}\t"),
+        KeyCode::Backspace => format!("{alt}\x7f"),
+        KeyCode::Esc => format!("{alt}\x1b"),
+        KeyCode::BackTab => "\x1b[Z".to_string(),
+        KeyCode::Up => csi(1, 'A'),
+        KeyCode::Down => csi(1, 'B'),
+        KeyCode::Right => csi(1, 'C'),
+        KeyCode::Left => csi(1, 'D'),
+        KeyCode::Home => csi(1, 'H'),
+        KeyCode::End => csi(1, 'F'),
+        KeyCode::Insert => csi(2, '~'),
+        KeyCode::Delete => csi(3, '~'),
+        KeyCode::PageUp => csi(5, '~'),
+        KeyCode::PageDown => csi(6, '~'),
+        KeyCode::F(n @ 1..=4) => csi(1, (b'P' + n - 1) as char),
+        KeyCode::F(n @ 5..=12) => csi([15, 17, 18, 19, 20, 21, 23, 24][n as usize - 5], '~'),
+        _ => return None,
+    })
+}
+
+fn encode_mouse(mouse: &MouseEvent) -> String {
+    let button = |button: MouseButton| match button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    };
+
+    let (mut code, release) = match mouse.kind {
+        MouseEventKind::Down(b) => (button(b), false),
+        MouseEventKind::Up(b) => (button(b), true),
+        MouseEventKind::Drag(b) => (button(b) + 32, false),
+        MouseEventKind::Moved => (35, false),
+        MouseEventKind::ScrollUp => (64, false),
+        MouseEventKind::ScrollDown => (65, false),
+        MouseEventKind::ScrollLeft => (66, false),
+        MouseEventKind::ScrollRight => (67, false),
+    };
+
+    if mouse.modifiers.contains(KeyModifiers::SHIFT) {
+        code += 4;
+    }
+
+    if mouse.modifiers.contains(KeyModifiers::ALT) {
+        code += 8;
+    }
+
+    if mouse.modifiers.contains(KeyModifiers::CONTROL) {
+        code += 16;
+    }
+
+    format!(
+        "\x1b[<{code};{};{}{}",
+        mouse.column + 1,
+        mouse.row + 1,
+        if release { 'm' } else { 'M' }
+    )
+}
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+    Event::Key(KeyEvent::new(code, modifiers))
+}
+
+/// Get the key of a single character
+fn char_key(char: char, modifiers: KeyModifiers) -> Option<Event> {
+    Some(match char {
+        '\r' | '\n' => key(KeyCode::Enter, modifiers),
+        '\t' => key(KeyCode::Tab, modifiers),
+        '\x7f' | '\x08' => key(KeyCode::Backspace, modifiers),
+        '\x1b' => key(KeyCode::Esc, modifiers),
+        '\x00' => key(KeyCode::Char(' '), modifiers | KeyModifiers::CONTROL),
+        // Ctrl + letter
+        '\x01'..='\x1a' => key(
+            KeyCode::Char((b'a' + (char as u8) - 1) as char),
+            modifiers | KeyModifiers::CONTROL,
+        ),
+        c if c.is_control() => return None,
+        c if c.is_uppercase() => key(KeyCode::Char(c), modifiers | KeyModifiers::SHIFT),
+        c => key(KeyCode::Char(c), modifiers),
+    })
+}
+
+/// Get the modifiers of an xterm modifier parameter (`1 + bits`)
+fn modifiers(param: Option<&str>) -> KeyModifiers {
+    let bits = param
+        .and_then(|p| p.parse::<u8>().ok())
+        .unwrap_or(1)
+        .saturating_sub(1);
+    let mut modifiers = KeyModifiers::NONE;
+
+    if bits & 1 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+
+    if bits & 2 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+
+    if bits & 4 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+
+    modifiers
+}
+
+/// Get the event of a CSI sequence
+fn csi(params: &str, command: char) -> Option<Event> {
+    if let Some(mouse) = params.strip_prefix('<') {
+        return sgr_mouse(mouse, command == 'm');
+    }
+
+    let mut parts = params.split(';');
+    let first = parts.next().filter(|p| !p.is_empty());
+    let modifiers = modifiers(parts.next());
+
+    let code = match command {
+        'A' => KeyCode::Up,
+        'B' => KeyCode::Down,
+        'C' => KeyCode::Right,
+        'D' => KeyCode::Left,
+        'H' => KeyCode::Home,
+        'F' => KeyCode::End,
+        'P' => KeyCode::F(1),
+        'Q' => KeyCode::F(2),
+        'R' => KeyCode::F(3),
+        'S' => KeyCode::F(4),
+        'Z' => return Some(key(KeyCode::BackTab, KeyModifiers::SHIFT)),
+        'I' => return Some(Event::FocusGained),
+        'O' => return Some(Event::FocusLost),
+        '~' => match first?.parse::<u8>().ok()? {
+            1 | 7 => KeyCode::Home,
+            2 => KeyCode::Insert,
+            3 => KeyCode::Delete,
+            4 | 8 => KeyCode::End,
+            5 => KeyCode::PageUp,
+            6 => KeyCode::PageDown,
+            n @ 11..=15 => KeyCode::F(n - 10),
+            n @ 17..=21 => KeyCode::F(n - 11),
+            n @ 23..=24 => KeyCode::F(n - 12),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(key(code, modifiers))
+}
+
+/// Get the event of an SGR mouse report (`button;x;y`)
+fn sgr_mouse(params: &str, release: bool) -> Option<Event> {
+    let mut numbers = params.split(';').map(|n| n.parse::<u16>().ok());
+    let (code, x, y) = (numbers.next()??, numbers.next()??, numbers.next()??);
+
+    let button = match code & 3 {
+        0 => MouseButton::Left,
+        1 => MouseButton::Middle,
+        _ => MouseButton::Right,
+    };
+
+    let kind = if code & 64 != 0 {
+        match code & 3 {
+            0 => MouseEventKind::ScrollUp,
+            1 => MouseEventKind::ScrollDown,
+            2 => MouseEventKind::ScrollLeft,
+            _ => MouseEventKind::ScrollRight,
+        }
+    } else if code & 32 != 0 {
+        if code & 3 == 3 {
+            MouseEventKind::Moved
+        } else {
+            MouseEventKind::Drag(button)
+        }
+    } else if release {
+        MouseEventKind::Up(button)
+    } else {
+        MouseEventKind::Down(button)
+    };
+
+    let mut modifiers = KeyModifiers::NONE;
+
+    if code & 4 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+
+    if code & 8 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+
+    if code & 16 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+
+    Some(Event::Mouse(MouseEvent {
+        kind,
+        column: x.saturating_sub(1),
+        row: y.saturating_sub(1),
+        modifiers,
+    }))
+}
@@ -0,0 +1,291 @@
+//! Wizard
+//!
+//! A [`Wizard`] walks the user through a sequence of [`WizardStep`]s (form screens) with
+//! Back/Next/Finish controls. A step can't be left forward until it validates, and the
+//! results of every step are collected when the wizard finishes.
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::buffer::{BufferWrite, PseudoBuffer};
+use crate::drawing::{
+    Creatable, DrawingResult, RectBoundary, Text, TextAttribute, TextColor, TextLeaf,
+};
+use crate::i18n;
+use crate::style::Style;
+use crate::text::{display_width, truncate_to_width, TruncateAt};
+use crate::State;
+
+/// Screen of a [`Wizard`]
+pub trait WizardStep {
+    /// Title of the step (in the step indicator, and the key of its result)
+    fn title(&self) -> String;
+
+    /// Draw the step
+    ///
+    /// ## Arguments:
+    /// * `buffer` - buffer to draw into
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `state` - for the events and focus of the widgets of the step
+    fn render(
+        &mut self,
+        buffer: PseudoBuffer,
+        rect: RectBoundary,
+        state: &mut State,
+    ) -> DrawingResult;
+
+    /// Check if the step can be left forward (`Err` is shown to the user)
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Result of the step (collected when the wizard finishes)
+    fn result(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+}
+
+/// What a control of a [`Wizard`] did
+#[derive(Clone, Debug, PartialEq)]
+pub enum WizardEvent {
+    /// Moved to the step at the index
+    Moved(usize),
+    /// The current step didn't validate (the message is shown under it)
+    Rejected(String),
+    /// Every step validated: the results of the steps by title
+    Finished(serde_json::Value),
+}
+
+/// Controls of a [`Wizard`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WizardControl {
+    Back,
+    /// Next (Finish on the last step)
+    Next,
+}
+
+/// Progress of a [`Wizard`], kept by the application between frames
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct WizardState {
+    /// Index of the current step
+    pub step: usize,
+    /// Why the current step didn't validate
+    pub error: Option<String>,
+    /// If the wizard was finished
+    pub finished: bool,
+}
+
+impl WizardState {
+    /// Go to the previous step
+    pub fn back(&mut self) -> Option<WizardEvent> {
+        self.step = self.step.checked_sub(1)?;
+        self.error = None;
+        Some(WizardEvent::Moved(self.step))
+    }
+
+    /// Validate the current step, then go to the next one (or finish on the last one)
+    pub fn next(&mut self, steps: &[Box<dyn WizardStep>]) -> Option<WizardEvent> {
+        let step = steps.get(self.step)?;
+
+        if let Err(e) = step.validate() {
+            self.error = Some(e.clone());
+            return Some(WizardEvent::Rejected(e));
+        }
+
+        self.error = None;
+
+        if self.step + 1 < steps.len() {
+            self.step += 1;
+            Some(WizardEvent::Moved(self.step))
+        } else {
+            self.finished = true;
+            Some(WizardEvent::Finished(WizardState::result(steps)))
+        }
+    }
+
+    /// Collect the results of every step (an object with the titles as keys)
+    pub fn result(steps: &[Box<dyn WizardStep>]) -> serde_json::Value {
+        serde_json::Value::Object(steps.iter().map(|s| (s.title(), s.result())).collect())
+    }
+}
+
+/// Step indicator, the current step, and Back/Next/Finish controls
+pub struct Wizard {
+    pub buffer: PseudoBuffer,
+    /// Style of the steps which aren't reached yet
+    pub step_style: Style,
+    /// Style of the current step in the indicator
+    pub current_style: Style,
+    /// Style of the steps which are done
+    pub done_style: Style,
+    /// Style of the validation error
+    pub error_style: Style,
+}
+
+impl Creatable for Wizard {
+    fn new(buffer: PseudoBuffer) -> Self {
+        Wizard {
+            step_style: buffer.theme_style(|t| t.muted, Style::DEFAULT.attr(TextAttribute::Dim)),
+            current_style: Style::DEFAULT
+                .attr(TextAttribute::Bold)
+                .attr(TextAttribute::Underline),
+            done_style: Style::DEFAULT,
+            error_style: buffer.theme_style(|t| t.error, Style::DEFAULT.fg(TextColor::Red)),
+            buffer,
+        }
+    }
+}
+
+impl Wizard {
+    /// Get the rect the current step is drawn in (between the indicator and the controls)
+    pub fn step_rect(rect: &RectBoundary) -> RectBoundary {
+        RectBoundary::new(
+            (rect.pos.0, rect.pos.1 + 2),
+            (rect.size.0, rect.size.1.saturating_sub(4)),
+        )
+    }
+
+    /// Get the controls with their labels and rects (on the last row)
+    pub fn controls(
+        rect: &RectBoundary,
+        wizard: &WizardState,
+        steps: usize,
+        state: &State,
+    ) -> Vec<(WizardControl, String, RectBoundary)> {
+        let y = rect.pos.1 + rect.size.1.saturating_sub(1);
+        let mut controls = Vec::new();
+
+        if wizard.step > 0 {
+            let label = state.text(i18n::BACK);
+            let width = display_width(&label) as u16 + 2;
+            controls.push((
+                WizardControl::Back,
+                label,
+                RectBoundary::new((rect.pos.0, y), (width, 1)),
+            ));
+        }
+
+        let label = if wizard.step + 1 >= steps {
+            state.text(i18n::FINISH)
+        } else {
+            state.text(i18n::NEXT)
+        };
+        let width = display_width(&label) as u16 + 2;
+        controls.push((
+            WizardControl::Next,
+            label,
+            RectBoundary::new((rect.right().saturating_sub(width), y), (width, 1)),
+        ));
+
+        controls
+    }
+
+    /// Handle a click on the controls
+    ///
+    /// ## Arguments:
+    /// * `state`
+    /// * `rect` - rect the wizard was drawn at
+    /// * `steps`
+    /// * `wizard` - [`WizardState`]
+    pub fn handle_click(
+        state: &State,
+        rect: &RectBoundary,
+        steps: &[Box<dyn WizardStep>],
+        wizard: &mut WizardState,
+    ) -> Option<WizardEvent> {
+        let click = state.click.as_ref()?;
+
+        let (control, _, _) = Wizard::controls(rect, wizard, steps.len(), state)
+            .into_iter()
+            .find(|(_, _, r)| r.contains(click.pos))?;
+
+        match control {
+            WizardControl::Back => wizard.back(),
+            WizardControl::Next => wizard.next(steps),
+        }
+    }
+
+    /// Handle a key event (Alt+Left goes back, Alt+Right goes next)
+    pub fn handle_key(
+        event: KeyEvent,
+        steps: &[Box<dyn WizardStep>],
+        wizard: &mut WizardState,
+    ) -> Option<WizardEvent> {
+        if !event.modifiers.contains(KeyModifiers::ALT) {
+            return None;
+        }
+
+        match event.code {
+            KeyCode::Left => wizard.back(),
+            KeyCode::Right => wizard.next(steps),
+            _ => None,
+        }
+    }
+
+    /// Draw the wizard
+    ///
+    /// ## Arguments:
+    /// * `rect` - pos(x, y), size(x, y)
+    /// * `steps`
+    /// * `wizard` - [`WizardState`]
+    /// * `state`
+    pub fn render(
+        &mut self,
+        rect: RectBoundary,
+        steps: &mut [Box<dyn WizardStep>],
+        wizard: &mut WizardState,
+        state: &mut State,
+    ) -> DrawingResult {
+        wizard.step = wizard.step.min(steps.len().saturating_sub(1));
+
+        // step indicator
+        let mut x = rect.pos.0;
+
+        for (i, step) in steps.iter().enumerate() {
+            let style = match i.cmp(&wizard.step) {
+                std::cmp::Ordering::Less => self.done_style,
+                std::cmp::Ordering::Equal => self.current_style,
+                std::cmp::Ordering::Greater => self.step_style,
+            };
+
+            let room = rect.right().saturating_sub(x) as usize;
+            let text = truncate_to_width(
+                &format!("{}. {}", i + 1, step.title()),
+                room,
+                TruncateAt::End,
+            );
+
+            if text.is_empty() {
+                break;
+            }
+
+            self.buffer
+                .write_str_styled((x, rect.pos.1), &text, style)?;
+            x = x.saturating_add(display_width(&text) as u16 + 2);
+        }
+
+        // current step
+        if let Some(step) = steps.get_mut(wizard.step) {
+            let (_, changes) = step.render(self.buffer.clone(), Wizard::step_rect(&rect), state)?;
+            self.buffer.set_changes(changes);
+        }
+
+        // error
+        if let Some(error) = &wizard.error {
+            self.buffer.write_str_styled(
+                (rect.pos.0, rect.pos.1 + rect.size.1.saturating_sub(2)),
+                &truncate_to_width(error, rect.size.0 as usize, TruncateAt::End),
+                self.error_style,
+            )?;
+        }
+
+        // controls
+        for (_, label, control_rect) in Wizard::controls(&rect, wizard, steps.len(), state) {
+            let (_, changes) = Text::new(self.buffer.clone())
+                .render_button(TextLeaf::styled(&label, Style::DEFAULT), control_rect.pos)?;
+            self.buffer.set_changes(changes);
+        }
+
+        // done
+        Ok((rect, self.buffer.get_changes()))
+    }
+}
@@ -0,0 +1,329 @@
+use hamui::buffer::{BufCell, Buffer, BufferWrite, PseudoBuffer};
+
+fn buffer(size: (u16, u16)) -> Buffer {
+    Buffer::new(std::io::stdout(), size)
+}
+
+#[test]
+fn write_cell_rejects_out_of_bounds() {
+    let mut buf = buffer((4, 2));
+
+    assert!(buf.write_cell((3, 1), BufCell::from_char('a')).is_ok());
+    assert!(buf.write_cell((4, 0), BufCell::from_char('a')).is_err()); // exactly the width
+    assert!(buf.write_cell((0, 2), BufCell::from_char('a')).is_err()); // exactly the height
+    assert!(buf
+        .write_cell((u16::MAX, u16::MAX), BufCell::from_char('a'))
+        .is_err());
+}
+
+#[test]
+fn write_str_stops_at_the_edge() {
+    let mut buf = buffer((4, 1));
+
+    assert!(buf.write_str((2, 0), "abc").is_err());
+    assert_eq!(buf.take_pending().len(), 2); // what fit is still written
+}
+
+#[test]
+fn fill_range_checks_the_range() {
+    let mut buf = buffer((4, 2));
+
+    assert!(buf.fill_range(0, 4, 1, BufCell::from_char('a')).is_ok());
+    assert!(buf.fill_range(0, 5, 0, BufCell::from_char('a')).is_err());
+    assert!(buf.fill_range(3, 2, 0, BufCell::from_char('a')).is_err());
+    assert!(buf.fill_range(0, 1, 2, BufCell::from_char('a')).is_err());
+    assert_eq!(buf.take_pending().len(), 4); // failed fills write nothing
+}
+
+#[test]
+fn pseudo_buffer_clips_to_the_window() {
+    let mut pseudo = PseudoBuffer::new((4, 2));
+
+    pseudo.write_str((2, 1), "abc").unwrap();
+    pseudo.write_cell((0, 2), BufCell::from_char('a')).unwrap();
+
+    let locs: Vec<_> = pseudo.get_changes().iter().map(|c| c.loc).collect();
+    assert_eq!(locs, vec![(2, 1), (3, 1)]);
+}
+
+#[test]
+fn consume_changes_accepts_clipped_changes() {
+    let mut pseudo = PseudoBuffer::new((4, 2));
+    pseudo.write_str((0, 0), "too long for it").unwrap();
+
+    let mut buf = buffer((4, 2));
+    assert!(buf.consume_changes(pseudo.get_changes()).is_ok());
+}
+
+#[test]
+fn get_cell_rejects_out_of_bounds() {
+    let mut buf = buffer((4, 2));
+
+    assert!(buf.get_cell((3, 1)).is_ok());
+    assert!(buf.get_cell((4, 1)).is_err());
+    assert!(buf.get_cell((0, 2)).is_err());
+}
+
+// commit
+use hamui::buffer::{Cells, ResizePolicy};
+use hamui::drawing::{TextBackgroundColor, TextColor};
+use hamui::style::Style;
+use hamui::testing::TestBackend;
+
+/// Draw `cells` on a fresh buffer in one commit (what the screen should look like)
+fn full_repaint(cells: &Cells) -> String {
+    let (mut buf, backend) = TestBackend::buffer(cells.size());
+
+    for (y, row) in cells.rows().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            buf.write_cell((x as u16, y as u16), cell.clone()).unwrap();
+        }
+    }
+
+    buf.commit().unwrap();
+    backend.screen_ansi(cells.size())
+}
+
+/// Text written at a position with a style
+type Write<'a> = ((u16, u16), &'a str, Style);
+
+/// Commit frames one after another, checking the terminal against a full repaint after each
+fn check_frames(size: (u16, u16), frames: &[&[Write]]) {
+    let (mut buf, backend) = TestBackend::buffer(size);
+
+    for frame in frames {
+        for (pos, text, style) in frame.iter() {
+            buf.write_str_styled(*pos, text, *style).unwrap();
+        }
+
+        buf.commit().unwrap();
+
+        assert_eq!(backend.screen_ansi(size), full_repaint(&buf.screen_vec));
+    }
+}
+
+#[test]
+fn commit_writes_rows_with_content() {
+    let (mut buf, backend) = TestBackend::buffer((6, 3));
+
+    buf.write_str((0, 0), "top").unwrap();
+    buf.write_str((2, 2), "end").unwrap();
+    let stats = buf.commit().unwrap();
+
+    assert_eq!(stats.cells_changed, 6);
+    assert_eq!(stats.rows_touched, 2);
+    assert_eq!(backend.screen_text((6, 3)), "top\n\n  end\n");
+}
+
+#[test]
+fn commit_skips_rows_without_changes() {
+    let (mut buf, backend) = TestBackend::buffer((6, 2));
+
+    buf.write_str((0, 0), "same").unwrap();
+    buf.commit().unwrap();
+    backend.clear();
+
+    buf.write_str((0, 0), "same").unwrap();
+    buf.write_str((0, 1), "new").unwrap();
+    let stats = buf.commit().unwrap();
+
+    assert_eq!(stats.rows_touched, 1);
+    assert!(!String::from_utf8_lossy(&backend.output()).contains("same"));
+}
+
+#[test]
+fn commit_matches_full_repaint() {
+    let red = Style::DEFAULT.fg(TextColor::Red);
+    let blue = Style::DEFAULT.bg(TextBackgroundColor::Blue);
+
+    check_frames(
+        (8, 3),
+        &[
+            &[((0, 0), "hello", Style::DEFAULT), ((1, 2), "abc", red)],
+            &[((2, 0), "LL", blue)],
+            &[
+                ((0, 0), "  ", Style::DEFAULT),
+                ((0, 2), "xyz", Style::DEFAULT),
+            ],
+            &[((3, 1), "界", red), ((6, 1), "ab", blue)],
+            &[((4, 1), "q", Style::DEFAULT)], // cover half of the wide character
+            &[],
+        ],
+    );
+}
+
+#[test]
+fn commit_erases_empty_cells() {
+    let (mut buf, backend) = TestBackend::buffer((5, 1));
+
+    buf.write_str((0, 0), "hello").unwrap();
+    buf.commit().unwrap();
+
+    buf.erase((1, 0)).unwrap();
+    buf.clear_rect(&hamui::drawing::RectBoundary::new((3, 0), (2, 1)))
+        .unwrap();
+    buf.commit().unwrap();
+
+    assert_eq!(backend.screen_text((5, 1)), "h l\n");
+}
+
+#[test]
+fn commit_after_resize_repaints() {
+    for (policy, expected) in [
+        (ResizePolicy::AnchorTopLeft, "firs\nab\n"),
+        (ResizePolicy::AnchorBottom, "\nabir\n"),
+        (ResizePolicy::Reflow, "thir\nab\n"),
+    ] {
+        let (mut buf, backend) = TestBackend::buffer((6, 3));
+        buf.resize_policy = policy;
+
+        buf.write_str((0, 0), "first").unwrap();
+        buf.write_str((0, 2), "third").unwrap();
+        buf.commit().unwrap();
+
+        buf.resize((4, 2)).unwrap();
+        buf.write_str((0, 1), "ab").unwrap();
+        buf.commit().unwrap();
+
+        // the terminal was cleared, what was kept is drawn again under the new cells
+        assert_eq!(backend.screen_text((4, 2)), expected, "{policy:?}");
+        assert_eq!(backend.screen_ansi((4, 2)), full_repaint(&buf.screen_vec));
+    }
+}
+
+#[test]
+fn resize_keeps_content_on_screen() {
+    for (policy, expected) in [
+        (ResizePolicy::AnchorBottom, "two\nthree\n"),
+        (ResizePolicy::Reflow, "three\n!\n"),
+    ] {
+        let (mut buf, backend) = TestBackend::buffer((6, 3));
+        buf.resize_policy = policy;
+
+        buf.write_str((0, 0), "one").unwrap();
+        buf.write_str((0, 1), "two").unwrap();
+        buf.write_str((0, 2), "three!").unwrap();
+        buf.commit().unwrap();
+
+        let loss = buf.resize((5, 2)).unwrap();
+        buf.commit().unwrap(); // nothing new drawn
+
+        assert_eq!(backend.screen_text((5, 2)), expected, "{policy:?}");
+        assert!(!loss.is_empty());
+    }
+}
+
+#[test]
+fn commit_leaves_locked_cells_alone() {
+    let (mut buf, backend) = TestBackend::buffer((6, 1));
+    buf.lock_region(hamui::drawing::RectBoundary::new((2, 0), (2, 1)));
+
+    buf.write_str((0, 0), "abcdef").unwrap();
+    buf.commit().unwrap();
+
+    assert_eq!(backend.screen_text((6, 1)), "ab  ef\n");
+    assert!(buf.is_locked((3, 0)));
+}
+
+#[test]
+fn components_take_styles_from_the_theme() {
+    use hamui::drawing::{Creatable, StatusLine};
+    use hamui::theme::Theme;
+
+    let mut pseudo = PseudoBuffer::new((4, 2));
+    let unthemed = StatusLine::new(pseudo.clone()).style;
+
+    pseudo.set_theme(Some(std::sync::Arc::new(Theme::light())));
+    assert_eq!(StatusLine::new(pseudo.clone()).style, Theme::light().status);
+    assert_ne!(unthemed, Theme::light().status);
+}
+
+#[test]
+fn components_take_strings_from_the_locale() {
+    use hamui::drawing::Creatable;
+    use hamui::i18n::{self, Translations};
+    use hamui::scroll::{ScrollFormat, ScrollIndicator};
+
+    let mut pseudo = PseudoBuffer::new((4, 2));
+    pseudo.set_locale(Some(std::sync::Arc::new(
+        Translations::new().with(i18n::SCROLL_TOP, "Haut"),
+    )));
+
+    let mut indicator = ScrollIndicator::new(pseudo);
+    indicator.format = ScrollFormat::Percent;
+    assert_eq!(indicator.text(0, 10, 100), "Haut");
+}
+
+#[test]
+fn cached_components_keep_the_theme_and_the_cursor() {
+    use hamui::cache::RenderCache;
+    use hamui::drawing::{Component, Creatable, RectBoundary, StatusLine};
+    use hamui::theme::Theme;
+
+    let mut cache = RenderCache::default();
+    let rect = RectBoundary::new((0, 0), (4, 1));
+    let draw = |cache: &mut RenderCache, pseudo: &mut PseudoBuffer| {
+        cache
+            .render(pseudo, "status", "same", rect.clone(), |buffer, rect| {
+                buffer.set_cursor(Some((2, 0)));
+                let mut status = StatusLine::new(buffer);
+                assert_eq!(status.style, Theme::light().status);
+                status.render((4, 2), rect)
+            })
+            .unwrap();
+    };
+
+    for _ in 0..2 {
+        // the second frame replays the changes
+        let mut pseudo = PseudoBuffer::new((4, 2));
+        pseudo.set_theme(Some(std::sync::Arc::new(Theme::light())));
+        draw(&mut cache, &mut pseudo);
+        assert_eq!(pseudo.cursor(), Some((2, 0)));
+    }
+}
+
+#[test]
+fn cell_grids_keep_the_theme_and_the_cursor() {
+    use hamui::buffer::CellGrid;
+    use hamui::drawing::{Component, Creatable, StatusLine};
+    use hamui::theme::Theme;
+
+    let mut pseudo = PseudoBuffer::new((8, 4));
+    pseudo.set_theme(Some(std::sync::Arc::new(Theme::light())));
+
+    let mut grid = CellGrid::new();
+    let render = |buffer: PseudoBuffer, rect| {
+        buffer.set_cursor(Some((1, 0)));
+        let mut status = StatusLine::new(buffer);
+        assert_eq!(status.style, Theme::light().status);
+        status.render((4, 1), rect)
+    };
+    assert!(grid.render(&pseudo, (4, 1), "same", render).unwrap());
+    assert!(!grid.render(&pseudo, (4, 1), "same", render).unwrap());
+
+    pseudo.blit_grid(&grid, (2, 3));
+    assert_eq!(pseudo.cursor(), Some((3, 3)));
+
+    // a new theme renders again
+    pseudo.set_theme(Some(std::sync::Arc::new(Theme::dark())));
+    assert!(grid
+        .render(&pseudo, (4, 1), "same", |buffer, rect| {
+            StatusLine::new(buffer).render((4, 1), rect)
+        })
+        .unwrap());
+}
+
+#[test]
+fn quick_row_layout_saturates_on_wide_components() {
+    use hamui::drawing::{Align, Creatable, QuickRow, RectBoundary};
+
+    let mut row = QuickRow::new(PseudoBuffer::new((10, 1)));
+    row.gap = 1;
+    row.align = Align::Center;
+
+    let rects = row.layout(
+        &RectBoundary::new((0, 0), (10, 1)),
+        &[(u16::MAX, 1), (u16::MAX, 1)],
+    );
+    assert_eq!(rects[1].pos.0, u16::MAX);
+}
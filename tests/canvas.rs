@@ -0,0 +1,15 @@
+use hamui::canvas::Canvas;
+use hamui::style::Style;
+
+#[test]
+fn dots_outside_of_the_canvas_are_ignored() {
+    let mut canvas = Canvas::new((2, 1));
+    assert_eq!(canvas.dot_size(), (4, 4));
+
+    canvas.line_v(0, 0, 3, Style::DEFAULT);
+    canvas.set(3, 0, Style::DEFAULT);
+    canvas.set(4, 0, Style::DEFAULT);
+    canvas.set(0, 4, Style::DEFAULT);
+
+    assert_eq!(canvas.row(0), "⡇⠈");
+}
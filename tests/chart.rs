@@ -0,0 +1,20 @@
+use hamui::chart::Axis;
+
+#[test]
+fn ticks_always_include_min_and_max() {
+    assert_eq!(Axis::new(0.0, 9.0).ticks(2), vec![0.0, 9.0]);
+    assert_eq!(Axis::new(1.0, 9.0).ticks(2), vec![1.0, 9.0]);
+    assert_eq!(Axis::new(0.0, 9.0).ticks(3), vec![0.0, 5.0, 9.0]);
+}
+
+#[test]
+fn ticks_between_are_round_and_at_most_count() {
+    assert_eq!(Axis::new(0.0, 100.0).ticks(5), vec![0.0, 50.0, 100.0]);
+    assert_eq!(
+        Axis::new(0.0, 100.0).ticks(6),
+        vec![0.0, 20.0, 40.0, 60.0, 80.0, 100.0]
+    );
+
+    // no tick right next to the ends
+    assert_eq!(Axis::new(0.5, 9.8).ticks(4), vec![0.5, 5.0, 9.8]);
+}
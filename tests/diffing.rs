@@ -0,0 +1,410 @@
+//! Random sequences of writes, erases, resizes and commits. After every commit, the
+//! terminal (played back by the [`TestBackend`]) must show exactly what the buffer
+//! says is on screen, and that must match a grid the test keeps on its own.
+use crossterm::event::{Event, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use hamui::buffer::{BufCell, Buffer, BufferChange, BufferWrite, PseudoBuffer, ResizePolicy};
+use hamui::drawing::{RectBoundary, TextBackgroundColor, TextColor};
+use hamui::style::Style;
+use hamui::testing::TestBackend;
+use hamui::{Frame, State};
+
+/// Small deterministic random number generator (xorshift), so failures can be replayed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n.max(1)
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len() as u64) as usize]
+    }
+}
+
+const CHARS: [char; 8] = ['a', 'b', 'x', '#', '─', ' ', 'é', '9'];
+
+fn random_style(rng: &mut Rng) -> Style {
+    let mut style = Style::DEFAULT;
+
+    if rng.below(2) == 0 {
+        style = style.fg(rng.pick(&[TextColor::Red, TextColor::BrightBlue, TextColor::White]));
+    }
+
+    if rng.below(3) == 0 {
+        style = style.bg(rng.pick(&[TextBackgroundColor::Black, TextBackgroundColor::Green]));
+    }
+
+    style
+}
+
+/// What the screen should show: (char, style) for every cell, row by row
+struct Model {
+    size: (u16, u16),
+    cells: Vec<(char, Style)>,
+}
+
+impl Model {
+    fn new(size: (u16, u16)) -> Model {
+        Model {
+            size,
+            cells: vec![(' ', Style::DEFAULT); size.0 as usize * size.1 as usize],
+        }
+    }
+
+    fn set(&mut self, pos: (u16, u16), char: char, style: Style) {
+        if (pos.0 < self.size.0) && (pos.1 < self.size.1) {
+            self.cells[pos.1 as usize * self.size.0 as usize + pos.0 as usize] = (char, style);
+        }
+    }
+
+    /// Same format as [`Buffer::screen_ansi`]
+    fn ansi(&self) -> String {
+        let rows = (0..self.size.1 as usize)
+            .map(|y| {
+                (0..self.size.0 as usize)
+                    .map(|x| {
+                        let (char, style) = self.cells[y * self.size.0 as usize + x];
+                        BufCell::styled(char, style)
+                    })
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+
+        Buffer::screen_ansi(&rows)
+    }
+}
+
+fn run(seed: u64, policy: ResizePolicy) {
+    let mut rng = Rng(seed);
+    let mut size = (1 + rng.below(12) as u16, 1 + rng.below(6) as u16);
+    let (mut buf, backend) = TestBackend::buffer(size);
+    buf.resize_policy = policy;
+
+    let mut model = Model::new(size);
+
+    for step in 0..60 {
+        match rng.below(10) {
+            // write a cell
+            0..=5 => {
+                let pos = (
+                    rng.below(size.0 as u64) as u16,
+                    rng.below(size.1 as u64) as u16,
+                );
+                let char = rng.pick(&CHARS);
+                let style = random_style(&mut rng);
+
+                buf.write_cell(pos, BufCell::styled(char, style)).unwrap();
+                model.set(pos, char, style);
+            }
+            // erase a cell
+            6 => {
+                let pos = (
+                    rng.below(size.0 as u64) as u16,
+                    rng.below(size.1 as u64) as u16,
+                );
+
+                buf.erase(pos).unwrap();
+                model.set(pos, ' ', Style::DEFAULT);
+            }
+            // resize (the screen is cleared, pending cells are kept where the policy puts them)
+            7 => {
+                size = (1 + rng.below(12) as u16, 1 + rng.below(6) as u16);
+                buf.resize(size).unwrap();
+
+                model = Model::new(size);
+                for change in buf.take_pending() {
+                    model.set(change.loc, change.cell.char, change.cell.style);
+                    buf.write_cell(change.loc, change.cell).unwrap();
+                }
+            }
+            // commit
+            _ => {
+                buf.commit().unwrap();
+
+                let context = format!("seed {seed}, step {step}, {policy:?}");
+                assert_eq!(
+                    backend.screen_ansi(size),
+                    Buffer::screen_ansi(&buf.screen_vec.to_rows()),
+                    "terminal and buffer differ ({context})"
+                );
+                assert_eq!(
+                    backend.screen_ansi(size),
+                    model.ansi(),
+                    "terminal and model differ ({context})"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn random_frames_reach_the_terminal() {
+    for seed in 1..200 {
+        for policy in [
+            ResizePolicy::AnchorTopLeft,
+            ResizePolicy::AnchorBottom,
+            ResizePolicy::Reflow,
+        ] {
+            run(seed * 7919, policy);
+        }
+    }
+}
+
+fn mouse(kind: MouseEventKind, pos: (u16, u16)) -> Event {
+    Event::Mouse(MouseEvent {
+        kind,
+        column: pos.0,
+        row: pos.1,
+        modifiers: KeyModifiers::NONE,
+    })
+}
+
+#[test]
+fn hidden_tooltips_are_erased() {
+    let size = (12, 3);
+    let mut draw = |state: &mut State, buffer| {
+        state.tooltip_delay = std::time::Duration::ZERO;
+        state.tooltip(&RectBoundary::new((0, 0), (3, 1)), "tip");
+        buffer
+    };
+
+    let backend = TestBackend::new();
+    let mut frame = Frame::with_output(backend.clone(), size, &mut draw);
+    frame.step().unwrap();
+
+    // rest on the widget until the tooltip shows
+    frame
+        .handle_event(mouse(MouseEventKind::Moved, (1, 0)))
+        .unwrap();
+    frame
+        .wait_for_event(Some(std::time::Duration::ZERO))
+        .unwrap();
+    assert_ne!(backend.screen_ansi(size), Model::new(size).ansi());
+
+    frame
+        .handle_event(mouse(MouseEventKind::Moved, (10, 2)))
+        .unwrap();
+    assert_eq!(backend.screen_ansi(size), Model::new(size).ansi());
+}
+
+#[test]
+fn drag_ghosts_are_erased() {
+    let size = (12, 3);
+    let mut draw = |state: &mut State, buffer| {
+        state.start_drag(&RectBoundary::new((0, 0), (3, 1)), "item", "item");
+        buffer
+    };
+
+    let backend = TestBackend::new();
+    let mut frame = Frame::with_output(backend.clone(), size, &mut draw);
+    frame.step().unwrap();
+
+    frame
+        .handle_event(mouse(MouseEventKind::Down(MouseButton::Left), (1, 0)))
+        .unwrap();
+    frame
+        .handle_event(mouse(MouseEventKind::Drag(MouseButton::Left), (2, 1)))
+        .unwrap();
+    frame
+        .handle_event(mouse(MouseEventKind::Drag(MouseButton::Left), (4, 2)))
+        .unwrap();
+    assert!(backend
+        .screen_text(size)
+        .lines()
+        .nth(2)
+        .unwrap()
+        .contains("item"));
+    assert!(!backend
+        .screen_text(size)
+        .lines()
+        .nth(1)
+        .unwrap()
+        .contains("item"));
+
+    frame
+        .handle_event(mouse(MouseEventKind::Up(MouseButton::Left), (4, 2)))
+        .unwrap();
+    assert_eq!(backend.screen_ansi(size), Model::new(size).ansi());
+}
+
+/// Commit every frame to the same terminal and get what it shows. Drawing a frame over
+/// others must look the same as drawing it alone, so components erase what they drew
+/// last frame but not this one.
+fn screen_after(size: (u16, u16), frames: Vec<Vec<BufferChange>>) -> String {
+    let (mut buf, backend) = TestBackend::buffer(size);
+
+    for changes in frames {
+        buf.consume_changes(changes).unwrap();
+        buf.commit().unwrap();
+    }
+
+    backend.screen_text(size)
+}
+
+#[test]
+fn narrowing_a_table_filter_erases_rows() {
+    use hamui::drawing::Creatable;
+    use hamui::filter::{FilterState, Filterable};
+    use hamui::input::TextInputState;
+    use hamui::table::{Table, TableColumn, TableRow, TableState, TableView};
+
+    let size = (20, 6);
+    let columns = [TableColumn::new("Name", 10)];
+    let rows: Vec<TableRow> = ["alpha", "beta", "gamma", "delta"]
+        .iter()
+        .enumerate()
+        .map(|(id, name)| TableRow::new(id, vec![name.to_string()]))
+        .collect();
+
+    let frame = |query: &str| {
+        let mut state = TableState::default();
+        let mut filter = FilterState {
+            input: TextInputState::new(query),
+            editing: true,
+        };
+        let view = TableView {
+            table: Table::new(PseudoBuffer::new(size)),
+            columns: &columns,
+            rows: &rows,
+            state: &mut state,
+        };
+
+        Filterable::new(PseudoBuffer::new(size), view)
+            .render(RectBoundary::new((0, 0), size), &mut filter)
+            .unwrap()
+            .1
+    };
+
+    let narrowed = screen_after(size, vec![frame("a"), frame("gam")]);
+    assert_eq!(narrowed, screen_after(size, vec![frame("gam")]));
+    assert!(!narrowed.contains("delta"));
+}
+
+#[test]
+fn panning_a_candlestick_chart_erases_old_candles() {
+    use hamui::chart::{Candle, Candlestick};
+    use hamui::drawing::Creatable;
+
+    let size = (20, 6);
+    let candles = [
+        Candle::new(0.0, 9.0, 0.0, 9.0),
+        Candle::new(9.0, 9.5, 4.0, 5.0),
+        Candle::new(5.0, 6.0, 4.5, 5.5),
+        Candle::new(5.5, 7.0, 5.0, 6.5),
+    ];
+
+    let frame = |offset: usize| {
+        Candlestick::new(PseudoBuffer::new(size))
+            .render(RectBoundary::new((0, 0), size), &candles, offset)
+            .unwrap()
+            .1
+    };
+
+    assert_eq!(
+        screen_after(size, vec![frame(0), frame(1)]),
+        screen_after(size, vec![frame(1)])
+    );
+}
+
+#[test]
+fn shorter_editor_text_erases_the_old_text() {
+    use hamui::drawing::Creatable;
+    use hamui::editor::{Editor, EditorState};
+
+    let size = (20, 4);
+    let frame = |text: &str| {
+        let mut editor = Editor::new(PseudoBuffer::new(size));
+        editor.gutter = true;
+
+        editor
+            .render(RectBoundary::new((0, 0), size), &mut EditorState::new(text))
+            .unwrap()
+            .1
+    };
+
+    assert_eq!(
+        screen_after(size, vec![frame("hello world\nsecond"), frame("second")]),
+        screen_after(size, vec![frame("second")])
+    );
+}
+
+#[test]
+fn scrolling_a_repl_erases_longer_lines() {
+    use hamui::drawing::Creatable;
+    use hamui::repl::{Repl, ReplState};
+
+    let size = (20, 3);
+    let mut repl = ReplState::new("> ");
+    repl.print("a much longer line");
+    repl.print("short");
+    repl.print("tiny");
+
+    let mut frame = |scroll: usize| {
+        repl.scroll = scroll;
+        Repl::new(PseudoBuffer::new(size))
+            .render(RectBoundary::new((0, 0), size), &mut repl)
+            .unwrap()
+            .1
+    };
+
+    let (scrolled, bottom) = (frame(1), frame(0));
+    assert_eq!(
+        screen_after(size, vec![scrolled, bottom.clone()]),
+        screen_after(size, vec![bottom])
+    );
+}
+
+#[test]
+fn unlit_segments_are_erased() {
+    use hamui::drawing::Creatable;
+    use hamui::segment::SegmentDisplay;
+
+    let size = (6, 6);
+    let draw = |text: &str| {
+        let (_, changes) = SegmentDisplay::new(PseudoBuffer::new(size))
+            .render((0, 0), text)
+            .unwrap();
+        changes
+    };
+
+    let one = draw("1");
+    assert_eq!(
+        screen_after(size, vec![draw("8"), one.clone()]),
+        screen_after(size, vec![one])
+    );
+}
+
+#[test]
+fn segment_letters_differ_from_digits() {
+    use hamui::segment::SegmentDisplay;
+
+    assert_ne!(SegmentDisplay::segments('B'), SegmentDisplay::segments('8'));
+    assert_ne!(SegmentDisplay::segments('D'), SegmentDisplay::segments('0'));
+}
+
+#[test]
+fn shorter_paginators_erase_the_old_items() {
+    use hamui::drawing::Creatable;
+    use hamui::pagination::Paginator;
+
+    let size = (30, 1);
+    let draw = |page: usize, pages: usize| {
+        let (_, changes) = Paginator::new(PseudoBuffer::new(size))
+            .render(RectBoundary::new((0, 0), (30, 1)), page, pages)
+            .unwrap();
+        changes
+    };
+
+    let short = draw(0, 2);
+    assert_eq!(
+        screen_after(size, vec![draw(4, 10), short.clone()]),
+        screen_after(size, vec![short])
+    );
+}
@@ -0,0 +1,240 @@
+use std::sync::{Arc, Mutex};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use hamui::buffer::{BufferWrite, PseudoBuffer};
+use hamui::drawing::{Handled, KeyHandler, RectBoundary};
+use hamui::testing::TestBackend;
+use hamui::{Frame, State};
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+/// Button which activates on Enter (read from `state.key`) or on a click
+#[test]
+fn enter_activates_the_focused_widget_once() {
+    let activations = Arc::new(Mutex::new(0));
+    let counter = activations.clone();
+
+    let mut draw = move |state: &mut State, buffer| {
+        let rect = RectBoundary::new((0, 0), (4, 1));
+        state.focus.register("button", rect.clone());
+
+        let key = state.key.is_some_and(|k| k.code == KeyCode::Enter);
+        let click = state.click.as_ref().is_some_and(|c| rect.contains(c.pos));
+
+        if key || click {
+            *counter.lock().unwrap() += 1;
+        }
+
+        buffer
+    };
+
+    let mut frame = Frame::with_output(TestBackend::new(), (10, 3), &mut draw);
+    frame.step().unwrap(); // register the widget
+    frame.handle_event(key(KeyCode::Tab)).unwrap();
+    frame.handle_event(key(KeyCode::Enter)).unwrap();
+
+    assert_eq!(*activations.lock().unwrap(), 1);
+}
+
+/// Widget which handles Enter itself
+struct Submit(Arc<Mutex<(usize, usize)>>);
+
+impl KeyHandler for Submit {
+    fn handle_key(&mut self, event: KeyEvent, _state: &mut State) -> Handled {
+        if event.code != KeyCode::Enter {
+            return Handled::No;
+        }
+
+        self.0.lock().unwrap().0 += 1;
+        Handled::Yes
+    }
+}
+
+#[test]
+fn handled_keys_dont_click_too() {
+    let counts = Arc::new(Mutex::new((0, 0))); // (keys, clicks)
+    let submit_counts = counts.clone();
+
+    let mut draw = move |state: &mut State, buffer| {
+        state
+            .focus
+            .register("field", RectBoundary::new((0, 0), (4, 1)));
+        state.dispatch_key("field", &mut Submit(submit_counts.clone()));
+
+        if state.click.is_some() {
+            submit_counts.lock().unwrap().1 += 1;
+        }
+
+        buffer
+    };
+
+    let mut frame = Frame::with_output(TestBackend::new(), (10, 3), &mut draw);
+    frame.step().unwrap(); // register the widget
+    frame.handle_event(key(KeyCode::Tab)).unwrap();
+    frame.handle_event(key(KeyCode::Enter)).unwrap();
+
+    assert_eq!(*counts.lock().unwrap(), (1, 0));
+}
+
+#[test]
+fn render_thread_writes_to_the_frame_output() {
+    let mut draw = |_: &mut State, mut buffer: PseudoBuffer| {
+        buffer.write_str((0, 0), "hi").unwrap();
+        buffer
+    };
+
+    let backend = TestBackend::new();
+    let mut frame = Frame::with_output(backend.clone(), (10, 3), &mut draw);
+    frame.spawn_render_thread(4);
+    frame.step().unwrap();
+
+    // (a screenshot waits for the thread to commit)
+    let path = std::env::temp_dir().join("hamui-render-thread-output.txt");
+    frame.screenshot(&path).unwrap();
+    let _ = std::fs::remove_file(path);
+
+    assert!(backend.screen_text((10, 3)).starts_with("hi"));
+}
+
+#[test]
+fn set_cursor_leaves_the_pointer() {
+    let pointer = Arc::new(Mutex::new(None));
+    let seen = pointer.clone();
+
+    let mut draw = move |state: &mut State, buffer| {
+        *seen.lock().unwrap() = Some(state.cursor_pos);
+        buffer
+    };
+
+    let mut frame = Frame::with_output(TestBackend::new(), (10, 3), &mut draw);
+    frame.set_cursor((5, 2), true).unwrap();
+    frame.step().unwrap();
+
+    assert_eq!(*pointer.lock().unwrap(), Some((0, 0)));
+}
+
+#[test]
+fn text_inputs_take_the_preedit_of_the_host() {
+    use hamui::input::TextInputState;
+    use hamui::web::preedit_event;
+
+    let input = Arc::new(Mutex::new(TextInputState::new("ab")));
+    let drawn = input.clone();
+
+    let mut draw = move |state: &mut State, buffer| {
+        drawn.lock().unwrap().handle_preedit(state);
+        buffer
+    };
+
+    let mut frame = Frame::with_output(TestBackend::new(), (10, 3), &mut draw);
+    frame.handle_user_event(preedit_event("にほ")).unwrap();
+    assert_eq!(input.lock().unwrap().preedit.as_deref(), Some("にほ"));
+
+    frame.handle_user_event(preedit_event("")).unwrap();
+    assert_eq!(input.lock().unwrap().preedit, None);
+    assert_eq!(input.lock().unwrap().text, "ab");
+}
+
+#[test]
+fn cursor_commands_keep_their_place_among_render_thread_frames() {
+    let mut draw = |_: &mut State, mut buffer: PseudoBuffer| {
+        buffer.write_str((0, 0), "hi").unwrap();
+        buffer
+    };
+
+    let backend = TestBackend::new();
+    let mut frame = Frame::with_output(backend.clone(), (10, 3), &mut draw);
+    frame.spawn_render_thread(4);
+    frame.save_cursor().unwrap();
+    frame.step().unwrap();
+    frame.restore_cursor().unwrap();
+
+    // (a screenshot waits for the thread to commit)
+    let path = std::env::temp_dir().join("hamui-render-thread-cursor.txt");
+    frame.screenshot(&path).unwrap();
+    let _ = std::fs::remove_file(path);
+
+    let output = String::from_utf8_lossy(&backend.output()).into_owned();
+    let save = output.find("\x1b7").unwrap();
+    let drawn = output.find("hi").unwrap();
+    let restore = output.rfind("\x1b8").unwrap();
+    assert!((save < drawn) && (drawn < restore), "{output:?}");
+}
+
+#[test]
+fn mouse_moves_place_the_cursor_after_render_thread_frames() {
+    use crossterm::event::{MouseEvent, MouseEventKind};
+
+    let mut draw = |_: &mut State, mut buffer: PseudoBuffer| {
+        buffer.write_str((0, 0), "hi").unwrap();
+        buffer
+    };
+
+    let backend = TestBackend::new();
+    let mut frame = Frame::with_output(backend.clone(), (10, 3), &mut draw);
+    frame.spawn_render_thread(4);
+    frame.step().unwrap();
+    frame
+        .handle_event(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Moved,
+            column: 5,
+            row: 2,
+            modifiers: KeyModifiers::NONE,
+        }))
+        .unwrap();
+
+    // (a screenshot waits for the thread to commit)
+    let path = std::env::temp_dir().join("hamui-render-thread-mouse.txt");
+    frame.screenshot(&path).unwrap();
+    let _ = std::fs::remove_file(path);
+
+    let output = String::from_utf8_lossy(&backend.output()).into_owned();
+    let last_move = output.rfind("\x1b[").map(|i| &output[i..]).unwrap();
+    assert!(last_move.starts_with("\x1b[3;6H"), "{output:?}");
+}
+
+#[test]
+fn cleared_shared_cells_are_erased() {
+    let mut draw = |_: &mut State, mut buffer: PseudoBuffer| {
+        buffer.write_str((0, 0), "hi").unwrap();
+        buffer
+    };
+
+    let backend = TestBackend::new();
+    let mut frame = Frame::with_output(backend.clone(), (10, 3), &mut draw);
+    let mut shared = frame.shared_buffer();
+    shared.write_str((0, 0), "log").unwrap();
+    shared.write_str((0, 1), "log").unwrap();
+    frame.step().unwrap();
+    assert!(backend.screen_text((10, 3)).starts_with("log"));
+
+    // the application is drawn again where the cells covered it
+    shared.clear();
+    frame.step().unwrap();
+    let screen = backend.screen_text((10, 3));
+    assert_eq!(screen.lines().take(2).collect::<Vec<_>>(), ["hi", ""]);
+}
+
+#[test]
+fn requested_redraws_happen_without_events() {
+    let draws = Arc::new(Mutex::new(0));
+    let counter = draws.clone();
+    let mut draw = move |state: &mut State, buffer: PseudoBuffer| {
+        *counter.lock().unwrap() += 1;
+        state.redraw_in(std::time::Duration::from_millis(10));
+        buffer
+    };
+
+    let mut frame = Frame::with_output(TestBackend::new(), (10, 3), &mut draw);
+    frame.step().unwrap();
+    assert_eq!(*draws.lock().unwrap(), 1);
+
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    assert!(frame
+        .wait_for_event(Some(std::time::Duration::ZERO))
+        .unwrap());
+    assert_eq!(*draws.lock().unwrap(), 2);
+}
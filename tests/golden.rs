@@ -0,0 +1,548 @@
+//! Golden snapshots of the built-in components.
+//!
+//! Every component is drawn through a [`TestBackend`] and the screen is compared with
+//! `tests/golden/<name>.txt`. Missing snapshots fail the test; run with `UPDATE_GOLDEN=1`
+//! to write new ones, or all of them again after an intended change (and check the diff).
+use std::path::Path;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use hamui::badge::{Badge, Chip, Chips};
+use hamui::breadcrumb::Breadcrumb;
+use hamui::buffer::{BufferChange, BufferWrite, PseudoBuffer};
+use hamui::button::{AsyncButton, AsyncButtonState, AsyncStatus};
+use hamui::chart::{Candle, Candlestick, Heatmap, Legend, Timeline, TimelineBar, TimelineView};
+use hamui::collapsible::{Collapsible, CollapsibleState};
+use hamui::dialog::{Dialog, DialogAnswer, DialogState};
+use hamui::drawing::{
+    Anchor, Component, Creatable, Line, Paragraph, ProgressBar, QuickBox, QuickRow, RectBoundary,
+    Stack, StatusLine, Text, TextLeaf, Vec2,
+};
+use hamui::editor::{Editor, EditorState};
+use hamui::filter::{FilterState, Filterable};
+use hamui::hint::{Hint, HintBar};
+use hamui::i18n::English;
+use hamui::input::{NumberInput, NumberInputState, TextInput, TextInputState};
+use hamui::keymap::Keymap;
+use hamui::pagination::Paginator;
+use hamui::repl::{Repl, ReplState};
+use hamui::scroll::{Gutter, Minimap, ScrollIndicator};
+use hamui::segment::SegmentDisplay;
+use hamui::style::Style;
+use hamui::table::{Table, TableColumn, TableRow, TableState, TableView};
+use hamui::task::Progress;
+use hamui::testing::TestBackend;
+use hamui::text::TruncateAt;
+use hamui::tooltip::Tooltip;
+
+const SIZE: Vec2 = (30, 8);
+
+fn buffer() -> PseudoBuffer {
+    PseudoBuffer::new(SIZE)
+}
+
+fn rect() -> RectBoundary {
+    RectBoundary::new((1, 1), (26, 6))
+}
+
+/// Draw changes and compare the screen with the snapshot
+fn golden(name: &str, changes: Vec<BufferChange>) {
+    let (mut buf, backend) = TestBackend::buffer(SIZE);
+    buf.consume_changes(changes).unwrap();
+    buf.commit().unwrap();
+
+    let actual = backend.screen_text(SIZE);
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.txt"));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "{name} has no snapshot at {} (run with UPDATE_GOLDEN=1 to write it)",
+            path.display()
+        )
+    });
+    assert_eq!(actual, expected, "{name} doesn't match {}", path.display());
+}
+
+#[test]
+fn quick_box() {
+    let mut quick_box = QuickBox::new(buffer());
+    quick_box.title = Some("Title".to_string());
+    quick_box.shadow = true;
+
+    let (_, changes) = quick_box.render(SIZE, rect()).unwrap();
+    golden("quick_box", changes);
+}
+
+#[test]
+fn quick_box_fits_below_its_position() {
+    let (drawn, changes) = QuickBox::new(buffer())
+        .render(SIZE, RectBoundary::new((1, 5), (10, 6)))
+        .unwrap();
+
+    assert_eq!(drawn.size, (10, 2));
+    golden("quick_box_fits_below_its_position", changes);
+}
+
+#[test]
+fn text() {
+    let mut text = Text::new(buffer());
+    text.render(TextLeaf::styled("plain", Style::DEFAULT), (0, 0))
+        .unwrap();
+    text.render_truncated(
+        TextLeaf::styled("cut off at the end", Style::DEFAULT),
+        (0, 1),
+        10,
+        TruncateAt::End,
+    )
+    .unwrap();
+    text.render_button(TextLeaf::styled("Button", Style::DEFAULT), (0, 3))
+        .unwrap();
+
+    // wide characters take two cells
+    let (drawn, _) = text.render_line(&Line::from("幅 wide"), (12, 0)).unwrap();
+    assert_eq!(drawn.size, (7, 1));
+
+    let (_, changes) = text
+        .render_aligned(
+            &Line::from("centered"),
+            (0, 6),
+            SIZE.0,
+            hamui::drawing::Align::Center,
+        )
+        .unwrap();
+    golden("text", changes);
+}
+
+#[test]
+fn status_line() {
+    let mut status = StatusLine::new(buffer());
+
+    let (_, changes) = status
+        .render_line(
+            RectBoundary::new((0, 7), (SIZE.0, 1)),
+            &Line::from(" NORMAL  main.rs"),
+        )
+        .unwrap();
+    golden("status_line", changes);
+}
+
+#[test]
+fn status_line_wide() {
+    let mut status = StatusLine::new(buffer());
+
+    // wide characters take two cells, so the bar still ends at the edge of the rect
+    let (drawn, changes) = status
+        .render_line(
+            RectBoundary::new((0, 7), (20, 1)),
+            &Line::from(" 編集中 ファイル名前が長すぎる"),
+        )
+        .unwrap();
+    assert_eq!(drawn.size.0, 20);
+    golden("status_line_wide", changes);
+}
+
+#[test]
+fn quick_row() {
+    let mut row = QuickRow::new(buffer());
+
+    let (_, changes) = row
+        .render(
+            rect(),
+            vec![
+                (TextLeaf::styled("one", Style::DEFAULT), (5, 1)),
+                (TextLeaf::styled("two", Style::DEFAULT), (5, 1)),
+                (TextLeaf::styled("three", Style::DEFAULT), (5, 1)),
+            ],
+        )
+        .unwrap();
+    golden("quick_row", changes);
+}
+
+#[test]
+fn stack() {
+    let mut bottom = QuickBox::new(buffer());
+    let mut top = QuickBox::new(buffer());
+    top.title = Some("top".to_string());
+
+    let mut stack = Stack::new(buffer());
+    let (_, changes) = stack
+        .render(SIZE, rect(), vec![&mut bottom, &mut top])
+        .unwrap();
+    golden("stack", changes);
+}
+
+#[test]
+fn paragraph() {
+    let mut paragraph = Paragraph::new(buffer());
+
+    let (_, changes) = paragraph
+        .render(
+            rect(),
+            &[
+                Line::from("A paragraph of lines,"),
+                Line::from("each one cut to the width of the rect it's drawn in."),
+                Line::from(""),
+                Line::from("The end."),
+            ],
+        )
+        .unwrap();
+    golden("paragraph", changes);
+}
+
+#[test]
+fn progress_bar() {
+    let mut bar = ProgressBar::new(buffer());
+    bar.label = true;
+
+    let progress = Progress {
+        fraction: 0.4,
+        status: "copying".to_string(),
+    };
+
+    let (_, changes) = bar
+        .render(RectBoundary::new((1, 1), (26, 1)), &progress)
+        .unwrap();
+    golden("progress_bar", changes);
+}
+
+#[test]
+fn text_input() {
+    let mut input = TextInput::new(buffer());
+    let mut state = TextInputState::new("hello world");
+
+    let (_, changes) = input
+        .render(RectBoundary::new((1, 1), (20, 1)), &mut state)
+        .unwrap();
+    golden("text_input", changes);
+}
+
+#[test]
+fn number_input() {
+    let mut input = NumberInput::new(buffer());
+    let state = NumberInputState::new(42.0, 0.0, 100.0, 1.0);
+
+    let (_, changes) = input
+        .render(RectBoundary::new((1, 1), (12, 1)), &state)
+        .unwrap();
+    golden("number_input", changes);
+}
+
+#[test]
+fn table() {
+    let mut table = Table::new(buffer());
+    let columns = [TableColumn::new("Name", 10), TableColumn::new("Size", 6)];
+    let rows = [
+        TableRow::new(0, vec!["a.txt".to_string(), "12".to_string()]),
+        TableRow::new(1, vec!["b.txt".to_string(), "340".to_string()]),
+    ];
+
+    let (_, changes) = table
+        .render(rect(), &columns, &rows, &mut TableState::default())
+        .unwrap();
+    golden("table", changes);
+}
+
+#[test]
+fn filterable_table() {
+    let columns = [TableColumn::new("Name", 10), TableColumn::new("Size", 6)];
+    let rows = [
+        TableRow::new(0, vec!["a.txt".to_string(), "12".to_string()]),
+        TableRow::new(1, vec!["b.rs".to_string(), "340".to_string()]),
+        TableRow::new(2, vec!["c.txt".to_string(), "7".to_string()]),
+    ];
+    let mut state = TableState::default();
+    let mut filter = FilterState {
+        input: TextInputState::new("txt"),
+        editing: true,
+    };
+
+    let view = TableView {
+        table: Table::new(buffer()),
+        columns: &columns,
+        rows: &rows,
+        state: &mut state,
+    };
+    let mut filterable = Filterable::new(buffer(), view);
+
+    assert_eq!(filterable.visible(&filter), vec![0, 2]);
+
+    let (_, changes) = filterable.render(rect(), &mut filter).unwrap();
+    golden("filterable_table", changes);
+}
+
+#[test]
+fn paginator() {
+    let mut paginator = Paginator::new(buffer());
+
+    let (_, changes) = paginator
+        .render(RectBoundary::new((1, 1), (26, 1)), 4, 10)
+        .unwrap();
+    golden("paginator", changes);
+}
+
+#[test]
+fn breadcrumb() {
+    let mut breadcrumb = Breadcrumb::new(buffer());
+    let levels = ["Home", "Settings", "Network", "Wi-Fi", "Advanced"];
+
+    let (_, changes) = breadcrumb
+        .render(RectBoundary::new((1, 1), (26, 1)), &levels)
+        .unwrap();
+    golden("breadcrumb", changes);
+}
+
+#[test]
+fn collapsible() {
+    let mut collapsible = Collapsible::new(buffer());
+    let mut section = CollapsibleState::new(false);
+    section.toggle();
+
+    // halfway open
+    collapsible.ticks = 2;
+    let (_, changes) = collapsible
+        .render(rect(), "Details", &mut section, 4, |mut buffer, rect| {
+            for y in 0..rect.size.1 {
+                buffer.write_str((rect.pos.0 + 2, rect.pos.1 + y), &format!("line {y}"))?;
+            }
+
+            Ok((rect, buffer.get_changes()))
+        })
+        .unwrap();
+
+    assert!(section.is_animating());
+    golden("collapsible", changes);
+}
+
+#[test]
+fn chips() {
+    let mut badge = Badge::new(buffer());
+    let (_, changes) = badge.render((1, 1), "done").unwrap();
+
+    let mut chips = Chips::new(PseudoBuffer::new(SIZE));
+    chips.buffer.set_changes(changes);
+    let tags = [
+        Chip::new("rust").closable(),
+        Chip::new("terminal").closable(),
+        Chip::new("ui"),
+        Chip::new("widgets").closable(),
+    ];
+
+    let (used, changes) = chips
+        .render(RectBoundary::new((1, 3), (26, 3)), &tags)
+        .unwrap();
+
+    assert_eq!(used.size.1, 2);
+    golden("chips", changes);
+}
+
+#[test]
+fn hint_bar() {
+    let keymap = Keymap::default();
+    let hints = [
+        Hint::from_keymap(&keymap, "quit", "quit").unwrap(),
+        Hint::new("/", "filter"),
+        Hint::from_keymap(&keymap, "undo", "undo").unwrap(),
+        Hint::new("?", "help"),
+    ];
+
+    let mut bar = HintBar::new(buffer());
+    let (used, changes) = bar
+        .render(RectBoundary::new((1, 1), (28, 1)), &hints)
+        .unwrap();
+
+    assert!(used.size.0 <= 28);
+    golden("hint_bar", changes);
+}
+
+#[test]
+fn async_button() {
+    let mut idle = AsyncButtonState::default();
+    let mut running = AsyncButtonState::default();
+    running.status = AsyncStatus::Running;
+    let mut failed = AsyncButtonState::default();
+    failed.status = AsyncStatus::Failed("timeout".to_string());
+
+    let mut button = AsyncButton::new(buffer());
+    button.render((1, 1), "Save", &mut idle).unwrap();
+    button.render((1, 2), "Save", &mut running).unwrap();
+    let (_, changes) = button.render((1, 3), "Save", &mut failed).unwrap();
+
+    assert!(running.is_running());
+    golden("async_button", changes);
+}
+
+#[test]
+fn tooltip() {
+    let mut tooltip = Tooltip::new(buffer());
+    tooltip.render(SIZE, (2, 1), "Save the file").unwrap();
+
+    // moved inside of the window at the edges
+    let (_, changes) = tooltip.render(SIZE, (27, SIZE.1 - 1), "Close").unwrap();
+    golden("tooltip", changes);
+}
+
+#[test]
+fn dialog() {
+    let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+    let mut state = DialogState::prompt("Name", "");
+    for c in "ham".chars() {
+        assert_eq!(state.handle_key(key(KeyCode::Char(c))), None);
+    }
+    assert_eq!(state.handle_key(key(KeyCode::Tab)), None);
+    assert!(state.cancel_selected);
+
+    let mut dialog = Dialog::new(buffer());
+    dialog.backdrop = None;
+    let (rect, changes) = dialog.render(SIZE, &mut state, &English).unwrap();
+
+    let [accept, _] = Dialog::button_rects(&rect, &state.labels(&English));
+    assert_eq!(
+        Dialog::handle_click(accept.pos, SIZE, &state, &English),
+        Some(DialogAnswer::Accept)
+    );
+    assert_eq!(
+        state.handle_key(key(KeyCode::Enter)),
+        Some(DialogAnswer::Cancel)
+    );
+    assert_eq!(state.value(), "ham");
+    golden("dialog", changes);
+}
+
+#[test]
+fn scroll_indicator() {
+    let mut indicator = ScrollIndicator::new(buffer());
+
+    let (_, changes) = indicator
+        .render(rect(), Anchor::BottomRight, 10, 5, 40)
+        .unwrap();
+    golden("scroll_indicator", changes);
+}
+
+#[test]
+fn minimap() {
+    let mut minimap = Minimap::new(buffer());
+    let lines: Vec<String> = (0..20).map(|i| "x".repeat(i % 7 * 3)).collect();
+
+    let (_, changes) = minimap
+        .render(RectBoundary::new((1, 1), (4, 6)), &lines, 2, 5)
+        .unwrap();
+    golden("minimap", changes);
+}
+
+#[test]
+fn gutter() {
+    let mut gutter = Gutter::new(buffer());
+
+    let (_, changes) = gutter
+        .render(RectBoundary::new((0, 0), (4, 6)), 12, 3, Some(5))
+        .unwrap();
+    golden("gutter", changes);
+}
+
+#[test]
+fn editor() {
+    let mut editor = Editor::new(buffer());
+    let mut state = EditorState::new("fn main() {\n    println!(\"hi\");\n}");
+
+    let (_, changes) = editor.render(rect(), &mut state).unwrap();
+    golden("editor", changes);
+}
+
+#[test]
+fn repl() {
+    let mut repl = Repl::new(buffer());
+    let mut state = ReplState::new("> ");
+    state.print(Line::from("welcome"));
+
+    let (_, changes) = repl.render(rect(), &mut state).unwrap();
+    golden("repl", changes);
+}
+
+#[test]
+fn segment_display() {
+    let mut display = SegmentDisplay::new(buffer());
+
+    let (_, changes) = display.render((1, 1), "12:34").unwrap();
+    golden("segment_display", changes);
+}
+
+#[test]
+fn legend() {
+    let mut legend = Legend::new(buffer());
+
+    let (_, changes) = legend
+        .render(
+            rect(),
+            &[("cpu", Style::DEFAULT), ("memory", Style::DEFAULT)],
+        )
+        .unwrap();
+    golden("legend", changes);
+}
+
+#[test]
+fn heatmap() {
+    let mut heatmap = Heatmap::new(buffer());
+    let values = vec![vec![0.0, 0.5, 1.0], vec![1.0, 0.25, 0.0]];
+
+    let (_, changes) = heatmap
+        .render(rect(), &values, &["a", "b"], &["x", "y", "z"], None)
+        .unwrap();
+    golden("heatmap", changes);
+}
+
+#[test]
+fn heatmap_legend() {
+    let mut heatmap = Heatmap::new(buffer());
+    heatmap.legend = true;
+    let values = vec![vec![0.0, 0.5, 1.0], vec![1.0, 0.25, 0.0]];
+
+    let (_, changes) = heatmap
+        .render(rect(), &values, &["a", "b"], &["x", "y", "z"], None)
+        .unwrap();
+    golden("heatmap_legend", changes);
+}
+
+#[test]
+fn heatmap_without_levels() {
+    let mut heatmap = Heatmap::new(buffer());
+    heatmap.levels.clear();
+
+    let (drawn, _) = heatmap
+        .render(rect(), &[vec![0.0, 1.0]], &["a"], &["x", "y"], None)
+        .unwrap();
+    assert!(!drawn.is_empty());
+}
+
+#[test]
+fn candlestick() {
+    let mut chart = Candlestick::new(buffer());
+    let candles = [
+        Candle::new(10.0, 14.0, 9.0, 13.0),
+        Candle::new(13.0, 15.0, 11.0, 12.0),
+        Candle::new(12.0, 12.5, 8.0, 9.0),
+    ];
+
+    let (_, changes) = chart.render(rect(), &candles, 0).unwrap();
+    golden("candlestick", changes);
+}
+
+#[test]
+fn timeline() {
+    let mut timeline = Timeline::new(buffer());
+    let bars = [
+        TimelineBar::new("build", 0.0, 4.0),
+        TimelineBar::new("test", 3.0, 9.0),
+    ];
+    let mut view = TimelineView::fit(&bars);
+
+    let (_, changes) = timeline.render(rect(), &bars, &mut view, None).unwrap();
+    golden("timeline", changes);
+}
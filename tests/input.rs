@@ -0,0 +1,69 @@
+use crossterm::event::{Event, KeyCode, MouseEventKind};
+use hamui::input::NumberInputState;
+use hamui::ssh::SshSession;
+use hamui::web::parse_input;
+
+fn keys(events: &[Event]) -> Vec<KeyCode> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            Event::Key(key) => Some(key.code),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn parse_input_reads_keys_and_mouse() {
+    let events = parse_input("a\x1b[A\x1b[<0;5;3M");
+
+    assert_eq!(keys(&events), vec![KeyCode::Char('a'), KeyCode::Up]);
+    assert!(matches!(
+        events.last(),
+        Some(Event::Mouse(m)) if (m.kind, m.column, m.row) == (MouseEventKind::Down(crossterm::event::MouseButton::Left), 4, 2)
+    ));
+}
+
+#[test]
+fn ssh_input_waits_for_split_sequences() {
+    let mut session = SshSession::new();
+
+    // mouse report split across two packets
+    assert_eq!(session.input(b"x\x1b[<0;5").len(), 1);
+    let events = session.input(b";3M");
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0], Event::Mouse(_)));
+
+    // SS3 and a character split in the middle
+    assert!(session.input(b"\x1bO").is_empty());
+    assert_eq!(keys(&session.input(b"A\xc3")), vec![KeyCode::Up]);
+    assert_eq!(keys(&session.input(b"\xa9")), vec![KeyCode::Char('é')]);
+
+    // an arrow split after the escape
+    assert!(session.input(b"\x1b").is_empty());
+    assert_eq!(keys(&session.input(b"[A")), vec![KeyCode::Up]);
+
+    // a lone escape is the escape key once nothing follows it for a while
+    assert!(session.input(b"\x1b").is_empty());
+    assert!(session.expired_input().is_empty());
+    std::thread::sleep(SshSession::ESCAPE_TIMEOUT);
+    assert_eq!(keys(&session.expired_input()), vec![KeyCode::Esc]);
+    assert!(session.expired_input().is_empty());
+}
+
+#[test]
+fn number_input_undo_skips_unchanged_snapshots() {
+    let mut input = NumberInputState::new(5.0, 0.0, 10.0, 1.0);
+
+    // nothing to undo right after creation (the field isn't blanked)
+    input.undo();
+    assert_eq!(input.text, "5");
+
+    input.increment();
+    input.commit();
+    assert_eq!(input.text, "6");
+
+    // committing an unchanged value isn't an edit
+    input.undo();
+    assert_eq!(input.text, "5");
+}
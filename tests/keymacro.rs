@@ -0,0 +1,93 @@
+use std::sync::{Arc, Mutex};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use hamui::keymacro::{Macros, MAX_COUNT};
+use hamui::testing::TestBackend;
+use hamui::{Frame, State};
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+fn alt(c: char) -> Event {
+    Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT))
+}
+
+/// Presses of `x` seen by the draw function, and if `x` is held after the last draw
+#[derive(Default)]
+struct Seen {
+    presses: usize,
+    held: bool,
+}
+
+fn run(events: &[Event]) -> Seen {
+    let seen = Arc::new(Mutex::new(Seen::default()));
+    let counter = seen.clone();
+
+    let mut draw = move |state: &mut State, buffer| {
+        let mut seen = counter.lock().unwrap();
+        seen.presses += state.key.is_some_and(|k| k.code == KeyCode::Char('x')) as usize;
+        seen.held = state.keys_down.is_down(KeyCode::Char('x'));
+        buffer
+    };
+
+    let mut frame = Frame::with_output(TestBackend::new(), (10, 3), &mut draw);
+    frame.add_middleware(Macros::default());
+    frame.step().unwrap();
+    // (typed keys only reach the draw function in keyboard mode)
+    frame.handle_event(key(KeyCode::Esc)).unwrap();
+
+    for event in events {
+        frame.handle_event(event.clone()).unwrap();
+    }
+
+    drop(frame);
+    let seen = std::mem::take(&mut *seen.lock().unwrap());
+    seen
+}
+
+#[test]
+fn play_count_is_clamped() {
+    let mut events = vec![
+        alt('q'),
+        key(KeyCode::Char('a')),
+        key(KeyCode::Char('x')),
+        alt('q'),
+    ];
+    events.push(alt('@'));
+    events.extend(
+        "99999999999999999999a"
+            .chars()
+            .map(|c| key(KeyCode::Char(c))),
+    );
+
+    assert_eq!(run(&events).presses, 1 + MAX_COUNT);
+}
+
+#[test]
+fn played_keys_are_released() {
+    let release = Event::Key(KeyEvent {
+        kind: KeyEventKind::Release,
+        ..KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE)
+    });
+
+    // the terminal reports releases, so held keys are exact
+    let events = [
+        release,
+        alt('q'),
+        key(KeyCode::Char('a')),
+        key(KeyCode::Char('x')),
+        alt('q'),
+        Event::Key(KeyEvent {
+            kind: KeyEventKind::Release,
+            ..KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)
+        }),
+        alt('@'),
+        key(KeyCode::Char('a')),
+    ];
+
+    let seen = run(&events);
+    assert_eq!(seen.presses, 2);
+    assert!(!seen.held);
+}
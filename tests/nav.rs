@@ -0,0 +1,62 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use hamui::nav::{NavPreset, Navigation};
+use hamui::table::{TableColumn, TableRow, TableState};
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+#[test]
+fn table_pages_through_rows() {
+    let rows: Vec<TableRow> = (0..10)
+        .map(|i| TableRow::new(i, vec![i.to_string()]))
+        .collect();
+
+    let mut table = TableState {
+        selected: Some(0),
+        page: 4,
+        ..TableState::default()
+    };
+
+    assert!(table.handle_key(key(KeyCode::PageDown), &rows));
+    assert_eq!(table.selected, Some(4));
+
+    assert!(table.handle_key(key(KeyCode::PageDown), &rows));
+    assert!(table.handle_key(key(KeyCode::PageDown), &rows));
+    assert_eq!(table.selected, Some(9));
+
+    assert!(table.handle_key(key(KeyCode::PageUp), &rows));
+    assert_eq!(table.selected, Some(5));
+}
+
+#[test]
+fn vi_keys_translate_to_page_keys() {
+    let mut nav = Navigation::new(NavPreset::Vi);
+    let ctrl_f = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+
+    assert_eq!(
+        nav.translate(ctrl_f).map(|k| k.code),
+        Some(KeyCode::PageDown)
+    );
+    assert_eq!(nav.translate(key(KeyCode::Char('g'))), None); // starts `gg`
+    assert_eq!(
+        nav.translate(key(KeyCode::Char('g'))),
+        Some(KeyEvent::new(KeyCode::Home, KeyModifiers::CONTROL))
+    );
+}
+
+#[test]
+fn first_cell_key_selects_the_first_row() {
+    let rows: Vec<TableRow> = (0..3)
+        .map(|i| TableRow::new(i, vec![i.to_string()]))
+        .collect();
+    let columns = [TableColumn::new("n", 4)];
+    let mut table = TableState::default();
+
+    assert!(table.handle_edit_key(key(KeyCode::Down), &columns, &rows, &mut |_, _, _| ()));
+    assert_eq!(table.selected, Some(0));
+
+    assert!(table.handle_edit_key(key(KeyCode::Down), &columns, &rows, &mut |_, _, _| ()));
+    assert_eq!(table.selected, Some(1));
+}
@@ -0,0 +1,54 @@
+use hamui::buffer::{BufCell, BufferChange};
+use hamui::remote::{RemoteServer, MAX_QUEUED};
+use std::io::{BufRead, BufReader};
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Every cell of a `size` screen, set to `char`
+fn frame(size: (u16, u16), char: char) -> Vec<BufferChange> {
+    (0..size.1)
+        .flat_map(|y| (0..size.0).map(move |x| (x, y)))
+        .map(|loc| BufferChange {
+            loc,
+            cell: BufCell::from_char(char),
+        })
+        .collect()
+}
+
+#[test]
+fn clients_get_the_screen_after_connecting() {
+    let mut server = RemoteServer::bind_tcp("127.0.0.1:0").unwrap();
+    let client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+
+    server.push((4, 2), &frame((4, 2), 'a')).unwrap();
+    assert_eq!(server.clients(), 1);
+
+    let mut lines = BufReader::new(client).lines();
+    assert!(lines.next().unwrap().unwrap().contains("Welcome"));
+}
+
+#[test]
+fn slow_clients_are_dropped_instead_of_blocking() {
+    let mut server = RemoteServer::bind_tcp("127.0.0.1:0").unwrap();
+    let _client = TcpStream::connect(server.local_addr().unwrap()).unwrap(); // never reads
+    let (done, finished) = mpsc::channel();
+
+    std::thread::spawn(move || {
+        let size = (100, 50);
+
+        for i in 0..MAX_QUEUED * 4 {
+            let char = if i % 2 == 0 { 'a' } else { 'b' };
+            server.push(size, &frame(size, char)).unwrap();
+
+            if server.clients() == 0 {
+                break;
+            }
+        }
+
+        done.send(server.clients()).unwrap();
+    });
+
+    let clients = finished.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(clients, 0);
+}
@@ -0,0 +1,110 @@
+use std::sync::{Arc, Mutex};
+
+use hamui::input::TextInputState;
+use hamui::table::TableState;
+use hamui::testing::TestBackend;
+use hamui::wizard::WizardState;
+use hamui::{Frame, State};
+
+fn session_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("hamui-session-{name}-{}.json", std::process::id()))
+}
+
+/// Values read back by the draw function
+#[derive(Debug, Default, PartialEq)]
+struct Restored {
+    screen: Option<String>,
+    selected: Option<usize>,
+    step: usize,
+    name: String,
+    scratch: Option<usize>,
+}
+
+#[test]
+fn session_round_trips_widget_states() {
+    let path = session_path("widgets");
+
+    let mut save = |state: &mut State, buffer| {
+        state.screen = Some("settings".to_string());
+        state.store.get_persistent::<TableState>("files").selected = Some(3);
+        state.store.get_persistent::<WizardState>("setup").step = 2;
+        state.store.get_persistent::<TextInputState>("name").text = "ham".to_string();
+        // (not persistent, so not saved)
+        state.store.insert("scratch", 7usize);
+        buffer
+    };
+
+    let mut frame = Frame::with_output(TestBackend::new(), (10, 3), &mut save);
+    frame.step().unwrap();
+    frame.save_session(&path).unwrap();
+    drop(frame);
+
+    let restored = Arc::new(Mutex::new(Restored::default()));
+    let read = restored.clone();
+
+    let mut load = move |state: &mut State, buffer| {
+        *read.lock().unwrap() = Restored {
+            screen: state.screen.clone(),
+            selected: state.store.get_persistent::<TableState>("files").selected,
+            step: state.store.get_persistent::<WizardState>("setup").step,
+            name: state
+                .store
+                .get_persistent::<TextInputState>("name")
+                .text
+                .clone(),
+            scratch: state.store.get::<usize>("scratch").copied(),
+        };
+        buffer
+    };
+
+    let mut frame = Frame::with_output(TestBackend::new(), (10, 3), &mut load);
+    frame.load_session(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    frame.step().unwrap();
+
+    assert_eq!(
+        *restored.lock().unwrap(),
+        Restored {
+            screen: Some("settings".to_string()),
+            selected: Some(3),
+            step: 2,
+            name: "ham".to_string(),
+            scratch: None,
+        }
+    );
+}
+
+#[test]
+fn loading_replaces_values_asked_for_before() {
+    let path = session_path("replace");
+    let scroll = Arc::new(Mutex::new(5));
+    let set = scroll.clone();
+
+    // the draw function sets the scroll offset, or reads it back while it is 0
+    let mut draw = move |state: &mut State, buffer| {
+        let table = state.store.get_persistent::<TableState>("files");
+        let mut scroll = set.lock().unwrap();
+
+        match *scroll {
+            0 => *scroll = table.scroll,
+            value => table.scroll = value,
+        }
+
+        buffer
+    };
+
+    let mut frame = Frame::with_output(TestBackend::new(), (10, 3), &mut draw);
+    frame.step().unwrap();
+    frame.save_session(&path).unwrap();
+
+    // change it after saving
+    *scroll.lock().unwrap() = 9;
+    frame.step().unwrap();
+
+    *scroll.lock().unwrap() = 0;
+    frame.load_session(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    frame.step().unwrap();
+
+    assert_eq!(*scroll.lock().unwrap(), 5);
+}
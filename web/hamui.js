@@ -0,0 +1,40 @@
+// Connects an xterm.js terminal to a hamui app compiled to WASM (see `hamui::web`).
+//
+// `app` is the module exported by the app with `input(data)`, `resize(cols, rows)`,
+// `preedit(text)` and `take_output()`.
+export function attach(term, app) {
+  const flush = () => {
+    const output = app.take_output();
+
+    if (output.length > 0) {
+      term.write(output);
+    }
+  };
+
+  term.onData((data) => {
+    app.input(data);
+    flush();
+  });
+
+  // input method composition (the result arrives through onData)
+  term.textarea.addEventListener("compositionupdate", (event) => {
+    app.preedit(event.data);
+    flush();
+  });
+
+  term.textarea.addEventListener("compositionend", () => {
+    app.preedit("");
+    flush();
+  });
+
+  term.onResize(({ cols, rows }) => {
+    app.resize(cols, rows);
+    flush();
+  });
+
+  app.resize(term.cols, term.rows);
+  flush();
+
+  // for apps which draw on their own (timers, tasks)
+  return { flush };
+}